@@ -1,6 +1,11 @@
 use std::collections::HashMap;
+use std::io::{BufRead, IsTerminal, Write};
+use std::time::Duration;
 
 use clap::{Parser, Subcommand};
+use oct_cloud::aws::types::InstanceType;
+
+mod output;
 
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
@@ -19,19 +24,98 @@ struct Cli {
     /// Context path
     #[clap(long, default_value = ".")]
     context_path: String,
+
+    /// Path to the config file, `-` reads the config from stdin.
+    /// Repeat to merge overlays, later files override earlier ones
+    #[clap(long, default_value = "oct.toml")]
+    config: Vec<String>,
+
+    /// Time limit for each deployment step, e.g. `90s`, `30m` or `1h`
+    #[clap(long, default_value = "30m", value_parser = parse_duration)]
+    timeout: Duration,
+
+    /// Cloud region, overrides the `AWS_REGION` env and the config value
+    #[clap(long)]
+    region: Option<String>,
+
+    /// Log verbosity, `-v` info, `-vv` debug and `-vvv` trace,
+    /// `RUST_LOG` env overrides it
+    #[clap(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Disable colored output, also disabled by the `NO_COLOR` env and
+    /// when the output is not a terminal
+    #[clap(long)]
+    no_color: bool,
 }
 
 #[derive(Subcommand)]
 enum Commands {
+    /// Write a starter config file
+    Init {
+        /// Project name
+        #[clap(long, default_value = "my-project")]
+        name: String,
+
+        /// Overwrite the existing config file
+        #[clap(long)]
+        force: bool,
+    },
     /// Run Genesis step
-    Genesis,
+    Genesis {
+        /// Deploy only the shared networking without VMs when no services
+        /// are configured
+        #[clap(long)]
+        allow_no_services: bool,
+
+        /// Build the service images locally before provisioning any infra,
+        /// fails on the first broken build
+        #[clap(long)]
+        build_check: bool,
+
+        /// Leader VM instance type, e.g. `t3.small`, instead of the one
+        /// selected from the service resources
+        #[clap(long, value_parser = parse_instance_type)]
+        instance_type: Option<InstanceType>,
+    },
+    /// Show the VMs genesis would deploy and their estimated monthly cost
+    Plan,
+    /// Check the local prerequisites: a container manager, the AWS CLI,
+    /// AWS credentials and network access to AWS
+    Doctor,
     /// Apply the application
-    Apply,
+    Apply {
+        /// Recreate all services even if their configuration is unchanged
+        #[clap(long)]
+        force_recreate: bool,
+
+        /// Keep running and re-apply the services when the config file or
+        /// a service Dockerfile directory changes
+        #[clap(long)]
+        watch: bool,
+
+        /// Apply only this service and the services it depends on, keeping
+        /// the other running services as is
+        #[clap(long, conflicts_with = "watch")]
+        target: Option<String>,
+    },
     /// Destroy the application
     Destroy {
         /// Path to the state file (skips oct.toml when provided)
         #[clap(long)]
         state_path: Option<String>,
+
+        /// Stop only this service, keeping the infrastructure and other services
+        #[clap(long)]
+        service: Option<String>,
+
+        /// Skip the interactive confirmation, required when stdin is not a terminal
+        #[clap(long)]
+        yes: bool,
+
+        /// Destroy independent resources concurrently
+        #[clap(long)]
+        parallel_destroy: bool,
     },
     /// Deploy a single container in one step (genesis + apply)
     Run {
@@ -67,6 +151,154 @@ enum Commands {
         #[clap(long, default_value = "./oct-run-state.json")]
         state_path: String,
     },
+    /// Print JSON Schema of the config file
+    Schema,
+    /// Inspect the deployed state
+    State {
+        #[clap(subcommand)]
+        command: StateCommands,
+    },
+    /// Add a resource created outside of oct to the infra state, so the next
+    /// deployments manage it
+    Import {
+        /// Resource type, `vpc` or `subnet`
+        resource_type: String,
+
+        /// Cloud resource ID, e.g. `vpc-0123`
+        id: String,
+
+        /// Name of a resource in the state the imported one depends on,
+        /// e.g. `vpc.vpc-0123` (repeatable)
+        #[clap(long = "depends-on")]
+        dependencies: Vec<String>,
+    },
+    /// Run a one-off command in a service container
+    Exec {
+        /// Service name
+        service: String,
+
+        /// Replica index, required for a service with multiple replicas
+        #[clap(long)]
+        replica: Option<u32>,
+
+        /// Command and its arguments, passed after `--`
+        #[clap(last = true, required = true)]
+        command: Vec<String>,
+    },
+    /// Print the logs of a service container
+    Logs {
+        /// Service name
+        service: String,
+
+        /// Replica index, required for a service with multiple replicas
+        #[clap(long)]
+        replica: Option<u32>,
+
+        /// Keep streaming the new output
+        #[clap(short, long)]
+        follow: bool,
+
+        /// Show only the logs newer than this, e.g. `90s`, `30m` or `1h`
+        #[clap(long, value_parser = parse_duration)]
+        since: Option<Duration>,
+
+        /// Show only the last lines, 1000 by default without `--follow`
+        #[clap(long)]
+        tail: Option<u32>,
+    },
+    /// Open a shell on a deployed instance
+    Ssh {
+        /// Instance index, IP or IP prefix, may be omitted when only one
+        /// instance is deployed
+        instance: Option<String>,
+
+        /// Remote user
+        #[clap(long, default_value = "ubuntu")]
+        user: String,
+
+        /// Path to the private key
+        #[clap(long)]
+        key: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum StateCommands {
+    /// Print the current infra and user state
+    Show {
+        /// Print only the infra state
+        #[clap(long, conflicts_with = "user")]
+        infra: bool,
+
+        /// Print only the user state
+        #[clap(long)]
+        user: bool,
+    },
+    /// Remove a resource from the infra state without destroying it
+    Rm {
+        /// Resource name, e.g. `vpc.vpc-0123`
+        name: String,
+
+        /// Remove the resource even if other resources depend on it
+        #[clap(long)]
+        force: bool,
+    },
+}
+
+/// Parses a positive duration in `<number>[s|m|h]` format, seconds are used
+/// when no unit is given
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let (number, multiplier) = match value.chars().last() {
+        Some('s') => (&value[..value.len() - 1], 1),
+        Some('m') => (&value[..value.len() - 1], 60),
+        Some('h') => (&value[..value.len() - 1], 60 * 60),
+        _ => (value, 1),
+    };
+
+    number
+        .parse::<u64>()
+        .ok()
+        .filter(|number| *number > 0)
+        .and_then(|number| number.checked_mul(multiplier))
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("Invalid duration '{value}', expected e.g. 90s, 30m or 1h"))
+}
+
+/// Parses a known AWS instance type name, e.g. `t3.small`
+fn parse_instance_type(value: &str) -> Result<InstanceType, String> {
+    InstanceType::try_from(value).map_err(|e| e.to_string())
+}
+
+/// Asks the user to type the project name before destroying it
+fn confirm_destroy(
+    project_name: &str,
+    yes: bool,
+    is_terminal: bool,
+    input: &mut impl BufRead,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if yes {
+        return Ok(());
+    }
+
+    if !is_terminal {
+        return Err(
+            "Destroy confirmation requires an interactive terminal, pass --yes to skip it".into(),
+        );
+    }
+
+    write!(
+        std::io::stderr(),
+        "Type the project name '{project_name}' to confirm destruction: "
+    )?;
+
+    let mut answer = String::new();
+    input.read_line(&mut answer)?;
+
+    if answer.trim() == project_name {
+        Ok(())
+    } else {
+        Err("Destroy cancelled, the project name does not match".into())
+    }
 }
 
 /// Builds a [`oct_config::Config`] from inline CLI arguments instead of reading
@@ -94,14 +326,12 @@ fn build_inline_config(
     let service = oct_config::Service {
         name: name.to_string(),
         image: image.to_string(),
-        dockerfile_path: None,
-        command: None,
         internal_port: Some(internal_port),
         external_port: Some(external_port),
         cpus,
         memory,
-        depends_on: vec![],
         envs: env_map,
+        ..Default::default()
     };
 
     let user_state_path = state_path.replace(".json", "-user.json");
@@ -116,7 +346,7 @@ fn build_inline_config(
                 path: user_state_path,
             },
             services: vec![service],
-            domain: None,
+            ..Default::default()
         },
     })
 }
@@ -135,35 +365,258 @@ fn build_destroy_config(state_path: &str) -> oct_config::Config {
             user_state_backend: oct_config::StateBackend::Local {
                 path: user_state_path,
             },
-            services: vec![],
-            domain: None,
+            ..Default::default()
         },
     }
 }
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+/// Returns a minimal valid config with a single example service,
+/// optional settings are commented out
+fn build_starter_config(name: &str) -> String {
+    format!(
+        r#"[project]
+name = "{name}"
+# domain = "example.com"
+
+[project.state_backend.local]
+path = "./state.json"
+
+# Store the state in S3 instead of the local file
+# [project.state_backend.s3]
+# region = "us-west-2"
+# bucket = "{name}-state"
+# key = "state.json"
+
+[project.user_state_backend.local]
+path = "./user_state.json"
+
+[[project.services]]
+name = "app"
+image = "nginx:latest"
+internal_port = 80
+external_port = 80
+cpus = 250
+memory = 64
+"#
+    )
+}
+
+/// Writes the starter config, an existing file is kept unless `force` is set
+fn write_starter_config(
+    path: &str,
+    name: &str,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if path == "-" {
+        return Err("Config file path is required, stdin cannot be initialized".into());
+    }
+
+    if !force && std::path::Path::new(path).exists() {
+        return Err(
+            format!("Config file {path} already exists, pass --force to overwrite it").into(),
+        );
+    }
+
+    std::fs::write(path, build_starter_config(name))
+        .map_err(|e| format!("Failed to write config file {path}: {e}"))?;
+
+    Ok(())
+}
+
+/// Builds `ssh` arguments for connecting to the instance
+fn build_ssh_args(user: &str, key: Option<&str>, public_ip: &str) -> Vec<String> {
+    let mut args = vec![];
+
+    if let Some(key) = key {
+        args.push(String::from("-i"));
+        args.push(key.to_string());
+    }
+
+    args.push(format!("{user}@{public_ip}"));
 
+    args
+}
+
+/// Sets the project region using the flag > `AWS_REGION` env > config precedence
+fn override_region(config: &mut oct_config::Config, region: Option<&str>) {
+    let env_region = std::env::var("AWS_REGION").ok();
+
+    config.project.region = Some(oct_orchestrator::resolve_region(
+        region,
+        env_region.as_deref(),
+        config.project.region.as_deref(),
+    ));
+}
+
+/// Returns `env_logger` filter for the `-v` flags count
+fn get_log_filter(verbose: u8) -> &'static str {
+    match verbose {
+        0 | 1 => "info",
+        2 => "debug",
+        _ => "trace",
+    }
+}
+
+/// Loads the config files with the region overridden by the flag or env
+fn load_config(
+    paths: &[String],
+    region: Option<&str>,
+) -> Result<oct_config::Config, Box<dyn std::error::Error + Send + Sync>> {
+    let mut config = oct_config::Config::from_paths(paths)?;
+    override_region(&mut config, region);
+
+    Ok(config)
+}
+
+#[tokio::main]
+async fn main() -> Result<std::process::ExitCode, Box<dyn std::error::Error + Send + Sync>> {
     let cli = Cli::parse();
 
+    let no_color_env = std::env::var_os("NO_COLOR");
+    let log_color = output::should_color(
+        cli.no_color,
+        no_color_env.as_deref(),
+        std::io::stderr().is_terminal(),
+    );
+    let output = output::Output::new(output::should_color(
+        cli.no_color,
+        no_color_env.as_deref(),
+        std::io::stdout().is_terminal(),
+    ));
+
+    env_logger::Builder::from_env(
+        env_logger::Env::default().default_filter_or(get_log_filter(cli.verbose)),
+    )
+    .write_style(if log_color {
+        env_logger::WriteStyle::Always
+    } else {
+        env_logger::WriteStyle::Never
+    })
+    .init();
+
     let orchestrator = oct_orchestrator::OrchestratorWithGraph;
 
     match cli.command {
-        Commands::Genesis => {
-            let config = oct_config::Config::new(None)?;
-            orchestrator.genesis(&config).await?;
+        Commands::Init { name, force } => {
+            let [config_path] = cli.config.as_slice() else {
+                return Err("Init writes a single config file, pass one --config".into());
+            };
+
+            write_starter_config(config_path, &name, force)?;
+
+            writeln!(
+                std::io::stdout().lock(),
+                "{}",
+                output.success(&format!("Created {config_path}"))
+            )?;
+        }
+        Commands::Genesis {
+            allow_no_services,
+            build_check,
+            instance_type,
+        } => {
+            let config = load_config(&cli.config, cli.region.as_deref())?;
+
+            let options = oct_orchestrator::GenesisOptions {
+                timeout: cli.timeout,
+                allow_no_services,
+                build_check,
+                config_dir: oct_orchestrator::get_config_dir(&cli.config).to_path_buf(),
+                instance_type,
+            };
+
+            orchestrator.genesis_with_options(&config, options).await?;
+        }
+        Commands::Plan => {
+            let config = load_config(&cli.config, cli.region.as_deref())?;
+
+            write!(std::io::stdout().lock(), "{}", orchestrator.plan(&config)?)?;
+        }
+        Commands::Doctor => {
+            // The config is optional here, doctor runs before a project exists
+            let config_region = match oct_config::Config::from_paths(&cli.config) {
+                Ok(config) => config.project.region,
+                Err(e) => {
+                    log::warn!("Failed to load config, its region is not used: {e}");
+
+                    None
+                }
+            };
+            let env_region = std::env::var("AWS_REGION").ok();
+            let region = oct_orchestrator::resolve_region(
+                cli.region.as_deref(),
+                env_region.as_deref(),
+                config_region.as_deref(),
+            );
+
+            let report = orchestrator.doctor(&region).await;
+            write!(std::io::stdout().lock(), "{}", report.checklist)?;
+
+            if !report.passed {
+                return Ok(std::process::ExitCode::FAILURE);
+            }
         }
-        Commands::Apply => {
-            let config = oct_config::Config::new(None)?;
-            orchestrator.apply(&config).await?;
+        Commands::Apply {
+            force_recreate,
+            watch,
+            target,
+        } => {
+            let options = oct_orchestrator::ApplyOptions {
+                timeout: cli.timeout,
+                force_recreate,
+                partial: target.is_some(),
+            };
+
+            if watch {
+                orchestrator
+                    .watch_apply(&cli.config, options, || {
+                        load_config(&cli.config, cli.region.as_deref())
+                    })
+                    .await?;
+            } else {
+                let mut config = load_config(&cli.config, cli.region.as_deref())?;
+
+                if let Some(target) = target {
+                    let target_services = config.get_target_services(&target)?;
+                    config
+                        .project
+                        .services
+                        .retain(|service| target_services.contains(&service.name));
+                }
+
+                orchestrator.apply_with_options(&config, options).await?;
+            }
         }
-        Commands::Destroy { state_path } => {
-            let config = match state_path {
+        Commands::Destroy {
+            state_path,
+            service,
+            yes,
+            parallel_destroy,
+        } => {
+            let mut config = match state_path {
                 Some(path) => build_destroy_config(&path),
-                None => oct_config::Config::new(None)?,
+                None => oct_config::Config::from_paths(&cli.config)?,
             };
-            orchestrator.destroy(&config).await?;
+            override_region(&mut config, cli.region.as_deref());
+
+            let stdin = std::io::stdin();
+            confirm_destroy(
+                &config.project.name,
+                yes,
+                stdin.is_terminal(),
+                &mut stdin.lock(),
+            )?;
+
+            match service {
+                Some(service_name) => orchestrator.destroy_service(&config, &service_name).await?,
+                None => {
+                    let options = oct_orchestrator::DestroyOptions {
+                        parallel: parallel_destroy,
+                    };
+
+                    orchestrator.destroy_with_options(&config, options).await?;
+                }
+            }
         }
         Commands::Run {
             image,
@@ -175,7 +628,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
             envs,
             state_path,
         } => {
-            let config = build_inline_config(
+            let mut config = build_inline_config(
                 &name,
                 &image,
                 cpus,
@@ -185,13 +638,124 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
                 &envs,
                 &state_path,
             )?;
+            override_region(&mut config, cli.region.as_deref());
+
+            orchestrator
+                .genesis_with_timeout(&config, cli.timeout)
+                .await?;
+            orchestrator
+                .apply_with_timeout(&config, cli.timeout)
+                .await?;
+        }
+        Commands::Schema => {
+            writeln!(
+                std::io::stdout().lock(),
+                "{}",
+                oct_config::Config::json_schema()?
+            )?;
+        }
+        Commands::State {
+            command: StateCommands::Show { infra, user },
+        } => {
+            let config = load_config(&cli.config, cli.region.as_deref())?;
+
+            let scope = match (infra, user) {
+                (true, _) => oct_orchestrator::StateScope::Infra,
+                (_, true) => oct_orchestrator::StateScope::User,
+                _ => oct_orchestrator::StateScope::All,
+            };
+
+            let state = orchestrator.show_state(&config, scope).await?;
+
+            write!(std::io::stdout().lock(), "{state}")?;
+        }
+        Commands::State {
+            command: StateCommands::Rm { name, force },
+        } => {
+            let config = load_config(&cli.config, cli.region.as_deref())?;
+
+            orchestrator
+                .remove_state_resource(&config, &name, force)
+                .await?;
+        }
+        Commands::Import {
+            resource_type,
+            id,
+            dependencies,
+        } => {
+            let config = load_config(&cli.config, cli.region.as_deref())?;
+
+            let name = orchestrator
+                .import_state_resource(&config, &resource_type, &id, dependencies)
+                .await?;
+
+            writeln!(std::io::stdout().lock(), "Imported {name}")?;
+        }
+        Commands::Exec {
+            service,
+            replica,
+            command,
+        } => {
+            let config = load_config(&cli.config, cli.region.as_deref())?;
+
+            let exec_output = orchestrator
+                .exec_service(&config, &service, replica, &command)
+                .await?;
 
-            orchestrator.genesis(&config).await?;
-            orchestrator.apply(&config).await?;
+            write!(std::io::stdout().lock(), "{}", exec_output.stdout)?;
+            write!(std::io::stderr().lock(), "{}", exec_output.stderr)?;
+
+            if exec_output.exit_code != 0 {
+                // A command terminated by a signal reports -1
+                return Ok(std::process::ExitCode::from(
+                    u8::try_from(exec_output.exit_code).unwrap_or(1),
+                ));
+            }
+        }
+        Commands::Logs {
+            service,
+            replica,
+            follow,
+            since,
+            tail,
+        } => {
+            let config = load_config(&cli.config, cli.region.as_deref())?;
+
+            orchestrator
+                .service_logs(
+                    &config,
+                    &service,
+                    replica,
+                    since,
+                    tail,
+                    follow,
+                    &mut std::io::stdout(),
+                )
+                .await?;
+        }
+        Commands::Ssh {
+            instance,
+            user,
+            key,
+        } => {
+            let config = load_config(&cli.config, cli.region.as_deref())?;
+
+            let public_ip = orchestrator
+                .resolve_instance_ip(&config, instance.as_deref())
+                .await?;
+
+            let status = std::process::Command::new("ssh")
+                .args(build_ssh_args(&user, key.as_deref(), &public_ip))
+                .status()
+                .map_err(|e| format!("Failed to run ssh: {e}"))?;
+
+            if !status.success() {
+                return Err(format!("ssh exited with {status}").into());
+            }
         }
     }
 
-    Ok(())
+    Ok(std::process::ExitCode::SUCCESS)
 }
 
 #[cfg(test)]
@@ -209,6 +773,324 @@ mod tests {
         assert_eq!(cli.user_state_file_path, "./user_state.json");
         assert_eq!(cli.dockerfile_path, ".");
         assert_eq!(cli.context_path, ".");
+        assert_eq!(cli.config, vec!["oct.toml"]);
+        assert_eq!(cli.timeout, Duration::from_secs(30 * 60));
+        assert_eq!(cli.region, None);
+        assert!(!cli.no_color);
+    }
+
+    #[test]
+    fn test_cli_no_color_flag() {
+        // Arrange
+        let cli = Cli::parse_from(["app", "--no-color", "apply"]);
+
+        // Assert
+        assert!(cli.no_color);
+    }
+
+    #[test]
+    fn test_cli_verbose_flag_count() {
+        // Arrange
+        let quiet_cli = Cli::parse_from(["app", "apply"]);
+        let cli = Cli::parse_from(["app", "-vv", "apply"]);
+        let long_cli = Cli::parse_from(["app", "--verbose", "-v", "-v", "apply"]);
+
+        // Assert
+        assert_eq!(quiet_cli.verbose, 0);
+        assert_eq!(cli.verbose, 2);
+        assert_eq!(long_cli.verbose, 3);
+    }
+
+    #[test]
+    fn test_get_log_filter() {
+        // Act & Assert
+        assert_eq!(get_log_filter(0), "info");
+        assert_eq!(get_log_filter(1), "info");
+        assert_eq!(get_log_filter(2), "debug");
+        assert_eq!(get_log_filter(3), "trace");
+        assert_eq!(get_log_filter(10), "trace");
+    }
+
+    #[test]
+    fn test_cli_config_from_stdin() {
+        // Arrange
+        let cli = Cli::parse_from(["app", "--config", "-", "apply"]);
+
+        // Assert
+        assert_eq!(cli.config, vec!["-"]);
+    }
+
+    #[test]
+    fn test_cli_config_overlays() {
+        // Arrange
+        let cli = Cli::parse_from([
+            "app",
+            "--config",
+            "base.toml",
+            "--config",
+            "prod.toml",
+            "apply",
+        ]);
+
+        // Assert
+        assert_eq!(cli.config, vec!["base.toml", "prod.toml"]);
+    }
+
+    #[test]
+    fn test_cli_timeout_flag() {
+        // Arrange
+        let cli = Cli::parse_from(["app", "genesis", "--timeout", "90s"]);
+
+        // Assert
+        assert_eq!(cli.timeout, Duration::from_secs(90));
+    }
+
+    #[test]
+    fn test_genesis_allow_no_services_flag() {
+        // Arrange
+        let cli = Cli::parse_from(["app", "genesis", "--allow-no-services"]);
+
+        // Assert
+        match cli.command {
+            Commands::Genesis {
+                allow_no_services,
+                build_check,
+                instance_type,
+            } => {
+                assert!(allow_no_services);
+                assert!(!build_check);
+                assert_eq!(instance_type, None);
+            }
+            _ => panic!("Expected Commands::Genesis"),
+        }
+    }
+
+    #[test]
+    fn test_genesis_build_check_flag() {
+        // Arrange
+        let cli = Cli::parse_from(["app", "genesis", "--build-check"]);
+
+        // Assert
+        match cli.command {
+            Commands::Genesis { build_check, .. } => assert!(build_check),
+            _ => panic!("Expected Commands::Genesis"),
+        }
+    }
+
+    #[test]
+    fn test_genesis_instance_type_flag() {
+        // Arrange
+        let cli = Cli::parse_from(["app", "genesis", "--instance-type", "t3.small"]);
+        let unknown_cli = Cli::try_parse_from(["app", "genesis", "--instance-type", "t3.huge"]);
+
+        // Assert
+        match cli.command {
+            Commands::Genesis { instance_type, .. } => {
+                assert_eq!(instance_type, Some(InstanceType::T3Small));
+            }
+            _ => panic!("Expected Commands::Genesis"),
+        }
+        assert!(
+            unknown_cli
+                .err()
+                .expect("Expected unknown instance type error")
+                .to_string()
+                .contains("Unknown instance type: t3.huge")
+        );
+    }
+
+    #[test]
+    fn test_cli_region_flag() {
+        // Arrange
+        let cli = Cli::parse_from(["app", "--region", "eu-central-1", "apply"]);
+        let mut config = build_destroy_config("./state.json");
+
+        // Act
+        override_region(&mut config, cli.region.as_deref());
+
+        // Assert
+        assert_eq!(cli.region.as_deref(), Some("eu-central-1"));
+        assert_eq!(config.project.region.as_deref(), Some("eu-central-1"));
+    }
+
+    #[test]
+    fn test_exec_parses_command() {
+        // Arrange
+        let cli = Cli::parse_from(["app", "exec", "app_1", "--", "ls", "-la", "/"]);
+
+        // Assert
+        match cli.command {
+            Commands::Exec {
+                service,
+                replica,
+                command,
+            } => {
+                assert_eq!(service, "app_1");
+                assert_eq!(replica, None);
+                assert_eq!(command, vec!["ls", "-la", "/"]);
+            }
+            _ => panic!("Expected Commands::Exec"),
+        }
+    }
+
+    #[test]
+    fn test_exec_requires_command() {
+        // Act
+        let result = Cli::try_parse_from(["app", "exec", "app_1"]);
+
+        // Assert
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_logs_parses_since_and_follow() {
+        // Arrange
+        let cli = Cli::parse_from([
+            "app",
+            "logs",
+            "app_1",
+            "--replica",
+            "1",
+            "--since",
+            "5m",
+            "--follow",
+        ]);
+
+        // Assert
+        match cli.command {
+            Commands::Logs {
+                service,
+                replica,
+                follow,
+                since,
+                tail,
+            } => {
+                assert_eq!(service, "app_1");
+                assert_eq!(replica, Some(1));
+                assert!(follow);
+                assert_eq!(since, Some(Duration::from_secs(5 * 60)));
+                assert_eq!(tail, None);
+            }
+            _ => panic!("Expected Commands::Logs"),
+        }
+    }
+
+    #[test]
+    fn test_build_ssh_args() {
+        // Arrange
+        let cli = Cli::parse_from(["app", "ssh", "1", "--key", "~/.ssh/oct.pem"]);
+
+        // Act & Assert
+        match cli.command {
+            Commands::Ssh {
+                instance,
+                user,
+                key,
+            } => {
+                assert_eq!(instance.as_deref(), Some("1"));
+                assert_eq!(
+                    build_ssh_args(&user, key.as_deref(), "10.0.1.5"),
+                    vec!["-i", "~/.ssh/oct.pem", "ubuntu@10.0.1.5"]
+                );
+            }
+            _ => panic!("Expected Commands::Ssh"),
+        }
+    }
+
+    #[test]
+    fn test_state_show_conflicting_flags() {
+        // Act
+        let result = Cli::try_parse_from(["app", "state", "show", "--infra", "--user"]);
+
+        // Assert
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_state_rm_parses() {
+        // Arrange
+        let cli = Cli::parse_from(["app", "state", "rm", "vpc.vpc-1", "--force"]);
+
+        // Assert
+        match cli.command {
+            Commands::State {
+                command: StateCommands::Rm { name, force },
+            } => {
+                assert_eq!(name, "vpc.vpc-1");
+                assert!(force);
+            }
+            _ => panic!("Expected Commands::State"),
+        }
+    }
+
+    #[test]
+    fn test_import_parses() {
+        // Arrange
+        let cli = Cli::parse_from([
+            "app",
+            "import",
+            "subnet",
+            "subnet-0123",
+            "--depends-on",
+            "vpc.vpc-0123",
+        ]);
+
+        // Assert
+        match cli.command {
+            Commands::Import {
+                resource_type,
+                id,
+                dependencies,
+            } => {
+                assert_eq!(resource_type, "subnet");
+                assert_eq!(id, "subnet-0123");
+                assert_eq!(dependencies, vec![String::from("vpc.vpc-0123")]);
+            }
+            _ => panic!("Expected Commands::Import"),
+        }
+    }
+
+    #[test]
+    fn test_build_starter_config_parses() {
+        // Act
+        let config = oct_config::Config::from_reader(build_starter_config("demo").as_bytes())
+            .expect("Failed to parse starter config");
+
+        // Assert
+        assert_eq!(config.project.name, "demo");
+        assert_eq!(config.project.services.len(), 1);
+        assert_eq!(config.project.services[0].name, "app");
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("45"), Ok(Duration::from_secs(45)));
+        assert_eq!(parse_duration("45s"), Ok(Duration::from_secs(45)));
+        assert_eq!(parse_duration("30m"), Ok(Duration::from_secs(30 * 60)));
+        assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(2 * 60 * 60)));
+        assert!(parse_duration("soon").is_err());
+        assert!(parse_duration("").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration_overflow() {
+        assert_eq!(
+            parse_duration("99999999999999999h"),
+            Err(String::from(
+                "Invalid duration '99999999999999999h', expected e.g. 90s, 30m or 1h"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_duration_zero() {
+        assert_eq!(
+            parse_duration("0s"),
+            Err(String::from(
+                "Invalid duration '0s', expected e.g. 90s, 30m or 1h"
+            ))
+        );
+        assert!(parse_duration("0").is_err());
     }
 
     #[test]
@@ -292,6 +1174,82 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_plan() {
+        // Arrange
+        let cli = Cli::parse_from(["app", "plan"]);
+
+        // Assert
+        assert!(matches!(cli.command, Commands::Plan));
+    }
+
+    #[test]
+    fn test_doctor() {
+        // Arrange
+        let cli = Cli::parse_from(["app", "doctor"]);
+
+        // Assert
+        assert!(matches!(cli.command, Commands::Doctor));
+    }
+
+    #[test]
+    fn test_apply_force_recreate() {
+        // Arrange
+        let cli = Cli::parse_from(["app", "apply", "--force-recreate"]);
+
+        // Assert
+        match cli.command {
+            Commands::Apply { force_recreate, .. } => assert!(force_recreate),
+            _ => panic!("Expected Commands::Apply"),
+        }
+    }
+
+    #[test]
+    fn test_apply_without_force_recreate() {
+        // Arrange
+        let cli = Cli::parse_from(["app", "apply"]);
+
+        // Assert
+        match cli.command {
+            Commands::Apply {
+                force_recreate,
+                watch,
+                target,
+            } => {
+                assert!(!force_recreate);
+                assert!(!watch);
+                assert_eq!(target, None);
+            }
+            _ => panic!("Expected Commands::Apply"),
+        }
+    }
+
+    #[test]
+    fn test_apply_watch() {
+        // Arrange
+        let cli = Cli::parse_from(["app", "apply", "--watch"]);
+
+        // Assert
+        match cli.command {
+            Commands::Apply { watch, .. } => assert!(watch),
+            _ => panic!("Expected Commands::Apply"),
+        }
+    }
+
+    #[test]
+    fn test_apply_target() {
+        // Arrange
+        let cli = Cli::parse_from(["app", "apply", "--target", "web"]);
+        let watch_cli = Cli::try_parse_from(["app", "apply", "--target", "web", "--watch"]);
+
+        // Assert
+        match cli.command {
+            Commands::Apply { target, .. } => assert_eq!(target, Some(String::from("web"))),
+            _ => panic!("Expected Commands::Apply"),
+        }
+        assert!(watch_cli.is_err());
+    }
+
     #[test]
     fn test_destroy_with_state_path() {
         // Arrange
@@ -299,8 +1257,13 @@ mod tests {
 
         // Assert
         match cli.command {
-            Commands::Destroy { state_path } => {
+            Commands::Destroy {
+                state_path,
+                service,
+                ..
+            } => {
                 assert_eq!(state_path, Some("s.json".to_string()));
+                assert_eq!(service, None);
             }
             _ => panic!("Expected Commands::Destroy"),
         }
@@ -313,13 +1276,86 @@ mod tests {
 
         // Assert
         match cli.command {
-            Commands::Destroy { state_path } => {
+            Commands::Destroy {
+                state_path,
+                service,
+                ..
+            } => {
+                assert_eq!(state_path, None);
+                assert_eq!(service, None);
+            }
+            _ => panic!("Expected Commands::Destroy"),
+        }
+    }
+
+    #[test]
+    fn test_destroy_with_service() {
+        // Arrange
+        let cli = Cli::parse_from(["app", "destroy", "--service", "app_1"]);
+
+        // Assert
+        match cli.command {
+            Commands::Destroy {
+                state_path,
+                service,
+                ..
+            } => {
                 assert_eq!(state_path, None);
+                assert_eq!(service, Some("app_1".to_string()));
             }
             _ => panic!("Expected Commands::Destroy"),
         }
     }
 
+    #[test]
+    fn test_destroy_with_yes() {
+        // Arrange
+        let cli = Cli::parse_from(["app", "destroy", "--yes"]);
+
+        // Assert
+        match cli.command {
+            Commands::Destroy { yes, .. } => assert!(yes),
+            _ => panic!("Expected Commands::Destroy"),
+        }
+    }
+
+    #[test]
+    fn test_confirm_destroy_yes_bypass() {
+        // Act
+        let result = confirm_destroy("example", true, false, &mut std::io::empty());
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_confirm_destroy_not_terminal_without_yes() {
+        // Act
+        let result = confirm_destroy("example", false, false, &mut std::io::empty());
+
+        // Assert
+        assert_eq!(
+            result.expect_err("Expected confirmation error").to_string(),
+            "Destroy confirmation requires an interactive terminal, pass --yes to skip it"
+        );
+    }
+
+    #[test]
+    fn test_confirm_destroy_project_name_typed() {
+        // Act
+        let matching_result = confirm_destroy("example", false, true, &mut "example\n".as_bytes());
+        let mismatching_result = confirm_destroy("example", false, true, &mut "other\n".as_bytes());
+
+        // Assert
+        assert!(matching_result.is_ok());
+        assert_eq!(
+            mismatching_result
+                .expect_err("Expected cancellation error")
+                .to_string(),
+            "Destroy cancelled, the project name does not match"
+        );
+    }
+
     #[test]
     fn test_build_inline_config_defaults() {
         // Arrange / Act
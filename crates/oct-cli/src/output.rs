@@ -0,0 +1,92 @@
+use std::ffi::OsStr;
+
+/// Decides whether the output is colored, `--no-color` and a non-empty
+/// `NO_COLOR` env (<https://no-color.org>) disable colors, otherwise the
+/// output is colored only when it is attached to a terminal
+pub(crate) fn should_color(
+    no_color: bool,
+    no_color_env: Option<&OsStr>,
+    is_terminal: bool,
+) -> bool {
+    if no_color {
+        return false;
+    }
+
+    if no_color_env.is_some_and(|value| !value.is_empty()) {
+        return false;
+    }
+
+    is_terminal
+}
+
+/// Formats the CLI output, the text is left as is when colors are disabled
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Output {
+    color: bool,
+}
+
+impl Output {
+    pub(crate) fn new(color: bool) -> Self {
+        Self { color }
+    }
+
+    /// Highlights a successfully finished step
+    pub(crate) fn success(self, text: &str) -> String {
+        self.paint(text, "32")
+    }
+
+    fn paint(self, text: &str, ansi_code: &str) -> String {
+        if !self.color {
+            return text.to_string();
+        }
+
+        format!("\x1b[{ansi_code}m{text}\x1b[0m")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_color_terminal() {
+        // Act & Assert
+        assert!(should_color(false, None, true));
+        assert!(!should_color(false, None, false));
+    }
+
+    #[test]
+    fn test_should_color_no_color_flag() {
+        // Act & Assert
+        assert!(!should_color(true, None, true));
+        assert!(!should_color(true, None, false));
+    }
+
+    #[test]
+    fn test_should_color_no_color_env() {
+        // Act & Assert
+        assert!(!should_color(false, Some(OsStr::new("1")), true));
+        assert!(!should_color(true, Some(OsStr::new("1")), true));
+        assert!(!should_color(false, Some(OsStr::new("1")), false));
+    }
+
+    #[test]
+    fn test_should_color_empty_no_color_env() {
+        // Act & Assert
+        assert!(should_color(false, Some(OsStr::new("")), true));
+        assert!(!should_color(false, Some(OsStr::new("")), false));
+    }
+
+    #[test]
+    fn test_output_success() {
+        // Act & Assert
+        assert_eq!(
+            Output::new(true).success("Created oct.toml"),
+            "\x1b[32mCreated oct.toml\x1b[0m"
+        );
+        assert_eq!(
+            Output::new(false).success("Created oct.toml"),
+            "Created oct.toml"
+        );
+    }
+}
@@ -28,3 +28,195 @@ async fn test_destroy_no_oct_toml() {
         "Failed to read config file oct.toml",
     ));
 }
+
+#[tokio::test]
+async fn test_apply_invalid_config_from_stdin() {
+    // Arrange
+    let mut oct_cli_bin = Command::new(assert_cmd::cargo::cargo_bin!());
+
+    // Act
+    let cmd = oct_cli_bin
+        .args(["--config", "-", "apply"])
+        .write_stdin("[project");
+
+    // Assert
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("TOML parse error"));
+}
+
+#[tokio::test]
+async fn test_destroy_not_terminal_without_yes() {
+    // Arrange
+    let mut oct_cli_bin = Command::new(assert_cmd::cargo::cargo_bin!());
+
+    // Act
+    let cmd = oct_cli_bin
+        .args(["destroy", "--state-path", "./missing-oct-state.json"])
+        .write_stdin("");
+
+    // Assert
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "Destroy confirmation requires an interactive terminal, pass --yes to skip it",
+    ));
+}
+
+#[tokio::test]
+async fn test_destroy_with_yes_skips_confirmation() {
+    // Arrange
+    let mut oct_cli_bin = Command::new(assert_cmd::cargo::cargo_bin!());
+
+    // Act
+    let cmd = oct_cli_bin
+        .args([
+            "destroy",
+            "--yes",
+            "--state-path",
+            "./missing-oct-state.json",
+        ])
+        .write_stdin("");
+
+    // Assert
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Infra state is not found"));
+}
+
+#[tokio::test]
+async fn test_state_show_local_state() {
+    // Arrange
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let state_path = dir.path().join("state.json");
+    let user_state_path = dir.path().join("user_state.json");
+    let config_path = dir.path().join("oct.toml");
+
+    std::fs::write(
+        &state_path,
+        r#"{
+  "resources": [
+    {
+      "name": "vpc.vpc-1",
+      "resource": {"Vpc": {"id": "vpc-1", "region": "us-west-2", "cidr_block": "10.0.0.0/16", "name": "vpc"}},
+      "dependencies": []
+    },
+    {
+      "name": "vm.i-1",
+      "resource": {"Vm": {"id": "i-1", "public_ip": "1.2.3.4", "instance_type": "T3Micro", "ami": "ami-1", "user_data": ""}},
+      "dependencies": ["vpc.vpc-1"]
+    }
+  ]
+}"#,
+    )
+    .expect("Failed to write state");
+    std::fs::write(
+        &user_state_path,
+        r#"{
+  "instances": {
+    "1.2.3.4": {
+      "cpus": 2000,
+      "memory": 1024,
+      "services": {
+        "app_1": {"name": "app_1", "image": "nginx:latest", "dockerfile_path": null, "command": null, "internal_port": 80, "external_port": 80, "cpus": 250, "memory": 64}
+      }
+    }
+  }
+}"#,
+    )
+    .expect("Failed to write user state");
+    std::fs::write(
+        &config_path,
+        format!(
+            r#"
+[project]
+name = "example"
+services = []
+
+[project.state_backend.local]
+path = "{}"
+
+[project.user_state_backend.local]
+path = "{}"
+"#,
+            state_path.display(),
+            user_state_path.display()
+        ),
+    )
+    .expect("Failed to write config");
+
+    let mut oct_cli_bin = Command::new(assert_cmd::cargo::cargo_bin!());
+
+    // Act
+    let cmd = oct_cli_bin.args([
+        "--config",
+        &config_path.display().to_string(),
+        "state",
+        "show",
+    ]);
+
+    // Assert
+    cmd.assert().success().stdout(
+        predicate::str::contains("VPC vpc-1 (us-west-2, 10.0.0.0/16)")
+            .and(predicate::str::contains("VM i-1 1.2.3.4 t3.micro"))
+            .and(predicate::str::contains(
+                "Instance 1.2.3.4 (2000 millicores, 1024 MB)",
+            ))
+            .and(predicate::str::contains(
+                "app_1 nginx:latest (250 millicores, 64 MB)",
+            )),
+    );
+}
+
+#[tokio::test]
+async fn test_schema_without_oct_toml() {
+    // Arrange
+    let mut oct_cli_bin = Command::new(assert_cmd::cargo::cargo_bin!());
+
+    // Act
+    let cmd = oct_cli_bin.arg("schema");
+
+    // Assert
+    cmd.assert().success().stdout(
+        predicate::str::contains(r#""title": "Config""#)
+            .and(predicate::str::contains(r#""services""#)),
+    );
+}
+
+#[tokio::test]
+async fn test_init_creates_parsable_config() {
+    // Arrange
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let config_path = dir.path().join("oct.toml").display().to_string();
+
+    let mut oct_cli_bin = Command::new(assert_cmd::cargo::cargo_bin!());
+
+    // Act
+    let cmd = oct_cli_bin.args(["--config", &config_path, "init", "--name", "demo"]);
+
+    // Assert
+    cmd.assert().success();
+
+    let config = oct_config::Config::new(Some(&config_path)).expect("Failed to parse config");
+    assert_eq!(config.project.name, "demo");
+}
+
+#[tokio::test]
+async fn test_init_keeps_existing_config() {
+    // Arrange
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let config_path = dir.path().join("oct.toml");
+    std::fs::write(&config_path, "existing").expect("Failed to write config");
+
+    let mut oct_cli_bin = Command::new(assert_cmd::cargo::cargo_bin!());
+
+    // Act
+    let cmd = oct_cli_bin.args(["--config", &config_path.display().to_string(), "init"]);
+
+    // Assert
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("already exists, pass --force"));
+    assert_eq!(
+        std::fs::read_to_string(&config_path).expect("Failed to read config"),
+        "existing"
+    );
+}
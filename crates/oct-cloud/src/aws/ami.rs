@@ -0,0 +1,79 @@
+use crate::aws::client::Ssm;
+
+/// Public SSM parameter with the current Canonical Ubuntu 24.04 LTS amd64
+/// image, published in every commercial region
+const UBUNTU_AMI_PARAMETER: &str =
+    "/aws/service/canonical/ubuntu/server/24.04/stable/current/amd64/hvm/ebs-gp3/ami-id";
+
+/// Resolves the image of the Leader VM in the region, AMI IDs differ between
+/// regions so none is hardcoded
+pub struct AmiResolver {
+    ssm: Ssm,
+}
+
+impl AmiResolver {
+    pub async fn new(region: String) -> Self {
+        let region_provider = aws_sdk_ssm::config::Region::new(region);
+        let config = aws_config::defaults(aws_config::BehaviorVersion::latest())
+            .region(region_provider)
+            .load()
+            .await;
+
+        Self {
+            ssm: Ssm::new(aws_sdk_ssm::Client::new(&config)),
+        }
+    }
+
+    /// Returns the Ubuntu AMI ID of the region
+    pub async fn resolve(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        self.ssm
+            .get_parameter(UBUNTU_AMI_PARAMETER)
+            .await
+            .map_err(|e| format!("Failed to resolve the Ubuntu AMI of the region: {e}").into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockall::predicate::eq;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_ami_resolver_resolve() {
+        // Arrange
+        let mut ssm_mock = Ssm::default();
+        ssm_mock
+            .expect_get_parameter()
+            .with(eq(String::from(UBUNTU_AMI_PARAMETER)))
+            .return_once(|_| Ok(String::from("ami-0123456789abcdef0")));
+
+        let resolver = AmiResolver { ssm: ssm_mock };
+
+        // Act
+        let ami = resolver.resolve().await;
+
+        // Assert
+        assert_eq!(ami.expect("Failed to resolve AMI"), "ami-0123456789abcdef0");
+    }
+
+    #[tokio::test]
+    async fn test_ami_resolver_resolve_error() {
+        // Arrange
+        let mut ssm_mock = Ssm::default();
+        ssm_mock
+            .expect_get_parameter()
+            .return_once(|_| Err("ParameterNotFound".into()));
+
+        let resolver = AmiResolver { ssm: ssm_mock };
+
+        // Act
+        let ami = resolver.resolve().await;
+
+        // Assert
+        assert_eq!(
+            ami.expect_err("Expected AMI error").to_string(),
+            "Failed to resolve the Ubuntu AMI of the region: ParameterNotFound"
+        );
+    }
+}
@@ -1,4 +1,8 @@
 /// AWS service clients implementation
+use std::future::Future;
+use std::time::Duration;
+
+use aws_sdk_ec2::error::{ErrorMetadata, ProvideErrorMetadata};
 use aws_sdk_ec2::operation::run_instances::RunInstancesOutput;
 use aws_sdk_ec2::types::{AttributeBooleanValue, IpPermission, IpRange};
 use aws_sdk_route53::types::ChangeAction;
@@ -7,6 +11,133 @@ use mockall::automock;
 use uuid::Uuid;
 
 use crate::aws::types::{InstanceType, RecordType};
+use crate::resource::{Backoff, DEFAULT_BASE_DELAY, DEFAULT_MAX_ATTEMPTS};
+
+/// AWS error codes returned when API requests are throttled
+const THROTTLING_ERROR_CODES: [&str; 3] =
+    ["RequestLimitExceeded", "Throttling", "ThrottlingException"];
+
+/// Checks whether an AWS error is caused by request throttling
+fn is_throttling_error<E: ProvideErrorMetadata>(error: &E) -> bool {
+    error
+        .code()
+        .is_some_and(|code| THROTTLING_ERROR_CODES.contains(&code))
+}
+
+/// AWS error codes of a resource that does not exist, besides the EC2 codes
+/// ending with `.NotFound`, e.g. `InvalidSubnetID.NotFound`
+const NOT_FOUND_ERROR_CODES: [&str; 6] = [
+    "NoSuchHostedZone",
+    "NoSuchEntity",
+    "RepositoryNotFoundException",
+    "LoadBalancerNotFound",
+    "TargetGroupNotFound",
+    "ListenerNotFound",
+];
+
+/// Checks whether an AWS error is caused by a resource that does not exist
+fn is_not_found_error<E: ProvideErrorMetadata>(error: &E) -> bool {
+    error
+        .code()
+        .is_some_and(|code| code.ends_with(".NotFound") || NOT_FOUND_ERROR_CODES.contains(&code))
+}
+
+/// Treats a not found error of a delete request as success, the resource
+/// was already deleted out-of-band
+fn ignore_not_found<T, E: ProvideErrorMetadata>(result: Result<T, E>) -> Result<Option<T>, E> {
+    match result {
+        Ok(output) => Ok(Some(output)),
+        Err(error) if is_not_found_error(&error) => {
+            log::warn!(
+                "Resource is already deleted: {}",
+                error.message().unwrap_or_default()
+            );
+
+            Ok(None)
+        }
+        Err(error) => Err(error),
+    }
+}
+
+/// Runs an AWS request, retrying it with exponential backoff
+/// while AWS responds with a throttling error
+async fn retry_on_throttling<T, E, F, Fut>(
+    max_attempts: u32,
+    base_delay: Duration,
+    mut operation: F,
+) -> Result<T, E>
+where
+    E: ProvideErrorMetadata,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut backoff = Backoff::new(max_attempts, base_delay);
+
+    loop {
+        let error = match operation().await {
+            Err(error) if is_throttling_error(&error) => error,
+            result => return result,
+        };
+
+        let Some(delay) = backoff.next_delay("send AWS request", error.code().unwrap_or_default())
+        else {
+            return Err(error);
+        };
+
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Runs an AWS request with the default throttling retry settings
+async fn with_throttling_retry<T, E, F, Fut>(operation: F) -> Result<T, E>
+where
+    E: ProvideErrorMetadata,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    retry_on_throttling(DEFAULT_MAX_ATTEMPTS, DEFAULT_BASE_DELAY, operation).await
+}
+
+/// AWS error keeping the error metadata of the SDK error, so the error
+/// code can still be read once the error is boxed
+#[derive(Debug)]
+pub(crate) struct AwsError {
+    meta: ErrorMetadata,
+    source: Box<dyn std::error::Error + Send + Sync>,
+}
+
+impl AwsError {
+    pub(crate) fn new<E>(error: E) -> Self
+    where
+        E: ProvideErrorMetadata + std::error::Error + Send + Sync + 'static,
+    {
+        Self {
+            meta: error.meta().clone(),
+            source: Box::new(error),
+        }
+    }
+}
+
+impl ProvideErrorMetadata for AwsError {
+    fn meta(&self) -> &ErrorMetadata {
+        &self.meta
+    }
+}
+
+impl std::fmt::Display for AwsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.meta.code() {
+            Some(code) => write!(f, "{code}: {}", self.source),
+            None => write!(f, "{}", self.source),
+        }
+    }
+}
+
+impl std::error::Error for AwsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
 
 pub(super) struct S3Impl {
     inner: aws_sdk_s3::Client,
@@ -42,7 +173,7 @@ impl S3Impl {
                 match sdk_err.into_service_error() {
                     aws_sdk_s3::operation::create_bucket::CreateBucketError::BucketAlreadyOwnedByYou(_) => Ok(()),
                     aws_sdk_s3::operation::create_bucket::CreateBucketError::BucketAlreadyExists(_) => Ok(()),
-                    err => Err(Box::new(err)),
+                    err => Err(Box::new(AwsError::new(err))),
                 }
             }
         }
@@ -52,7 +183,12 @@ impl S3Impl {
         &self,
         name: &str,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        self.inner.delete_bucket().bucket(name).send().await?;
+        self.inner
+            .delete_bucket()
+            .bucket(name)
+            .send()
+            .await
+            .map_err(AwsError::new)?;
 
         Ok(())
     }
@@ -69,7 +205,8 @@ impl S3Impl {
             .key(key)
             .body(data.into())
             .send()
-            .await?;
+            .await
+            .map_err(AwsError::new)?;
 
         Ok(())
     }
@@ -100,7 +237,8 @@ impl S3Impl {
             .bucket(bucket_name)
             .key(key)
             .send()
-            .await?;
+            .await
+            .map_err(AwsError::new)?;
 
         Ok(())
     }
@@ -164,17 +302,51 @@ impl Ec2Impl {
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         log::info!("Deleting VPC");
 
-        self.inner
-            .delete_vpc()
-            .vpc_id(vpc_id.clone())
-            .send()
-            .await?;
+        ignore_not_found(self.inner.delete_vpc().vpc_id(vpc_id.clone()).send().await)?;
 
         log::info!("Deleted VPC: {vpc_id}");
 
         Ok(())
     }
 
+    /// Get VPC state, `None` while the new VPC is not visible to
+    /// `DescribeVpcs` yet
+    pub async fn get_vpc_state(
+        &self,
+        vpc_id: String,
+    ) -> Result<Option<aws_sdk_ec2::types::VpcState>, Box<dyn std::error::Error + Send + Sync>>
+    {
+        let response =
+            with_throttling_retry(|| self.inner.describe_vpcs().vpc_ids(vpc_id.clone()).send())
+                .await;
+
+        match response {
+            Ok(response) => Ok(response.vpcs().first().and_then(|vpc| vpc.state()).cloned()),
+            Err(e) if e.code() == Some("InvalidVpcID.NotFound") => Ok(None),
+            Err(e) => Err(Box::new(e)),
+        }
+    }
+
+    /// CIDR block of the VPC, `None` when the VPC does not exist
+    pub async fn get_vpc_cidr_block(
+        &self,
+        vpc_id: String,
+    ) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let response =
+            with_throttling_retry(|| self.inner.describe_vpcs().vpc_ids(vpc_id.clone()).send())
+                .await;
+
+        match response {
+            Ok(response) => Ok(response
+                .vpcs()
+                .first()
+                .and_then(|vpc| vpc.cidr_block())
+                .map(str::to_string)),
+            Err(e) if e.code() == Some("InvalidVpcID.NotFound") => Ok(None),
+            Err(e) => Err(Box::new(e)),
+        }
+    }
+
     /// Create Security Group
     pub async fn create_security_group(
         &self,
@@ -208,17 +380,20 @@ impl Ec2Impl {
         &self,
         vpc_id: String,
     ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        let response = self
-            .inner
-            .get_security_groups_for_vpc()
-            .vpc_id(vpc_id)
-            .send()
-            .await?;
+        let security_groups = with_throttling_retry(|| {
+            self.inner
+                .get_security_groups_for_vpc()
+                .vpc_id(vpc_id.clone())
+                .into_paginator()
+                .items()
+                .send()
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .await?;
 
-        Ok(response
-            .security_group_for_vpcs()
+        Ok(security_groups
             .first()
-            .expect("Failed to get the default security group as the first element")
+            .ok_or("Failed to get the default security group as the first element")?
             .group_id()
             .ok_or("Failed to get security group id")?
             .to_string())
@@ -231,11 +406,13 @@ impl Ec2Impl {
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         log::info!("Deleting security group");
 
-        self.inner
-            .delete_security_group()
-            .group_id(security_group_id.clone())
-            .send()
-            .await?;
+        ignore_not_found(
+            self.inner
+                .delete_security_group()
+                .group_id(security_group_id.clone())
+                .send()
+                .await,
+        )?;
 
         log::info!("Deleted security group: {security_group_id}");
 
@@ -315,6 +492,58 @@ impl Ec2Impl {
         Ok(subnet_id)
     }
 
+    /// Get subnet state, `None` while the new subnet is not visible to
+    /// `DescribeSubnets` yet
+    pub async fn get_subnet_state(
+        &self,
+        subnet_id: String,
+    ) -> Result<Option<aws_sdk_ec2::types::SubnetState>, Box<dyn std::error::Error + Send + Sync>>
+    {
+        let response = with_throttling_retry(|| {
+            self.inner
+                .describe_subnets()
+                .subnet_ids(subnet_id.clone())
+                .send()
+        })
+        .await;
+
+        match response {
+            Ok(response) => Ok(response
+                .subnets()
+                .first()
+                .and_then(|subnet| subnet.state())
+                .cloned()),
+            Err(e) if e.code() == Some("InvalidSubnetID.NotFound") => Ok(None),
+            Err(e) => Err(Box::new(e)),
+        }
+    }
+
+    /// CIDR block and availability zone of the subnet, `None` when the subnet
+    /// does not exist
+    pub async fn get_subnet_placement(
+        &self,
+        subnet_id: String,
+    ) -> Result<Option<(String, String)>, Box<dyn std::error::Error + Send + Sync>> {
+        let response = with_throttling_retry(|| {
+            self.inner
+                .describe_subnets()
+                .subnet_ids(subnet_id.clone())
+                .send()
+        })
+        .await;
+
+        match response {
+            Ok(response) => Ok(response.subnets().first().and_then(|subnet| {
+                Some((
+                    subnet.cidr_block()?.to_string(),
+                    subnet.availability_zone()?.to_string(),
+                ))
+            })),
+            Err(e) if e.code() == Some("InvalidSubnetID.NotFound") => Ok(None),
+            Err(e) => Err(Box::new(e)),
+        }
+    }
+
     /// Delete Subnet
     pub async fn delete_subnet(
         &self,
@@ -322,11 +551,13 @@ impl Ec2Impl {
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         log::info!("Deleting subnet");
 
-        self.inner
-            .delete_subnet()
-            .subnet_id(subnet_id.clone())
-            .send()
-            .await?;
+        ignore_not_found(
+            self.inner
+                .delete_subnet()
+                .subnet_id(subnet_id.clone())
+                .send()
+                .await,
+        )?;
 
         log::info!("Deleted subnet: {subnet_id}");
 
@@ -370,21 +601,25 @@ impl Ec2Impl {
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         log::info!("Detaching Internet Gateway {internet_gateway_id} from VPC");
 
-        self.inner
-            .detach_internet_gateway()
-            .internet_gateway_id(internet_gateway_id.clone())
-            .vpc_id(vpc_id.clone())
-            .send()
-            .await?;
+        ignore_not_found(
+            self.inner
+                .detach_internet_gateway()
+                .internet_gateway_id(internet_gateway_id.clone())
+                .vpc_id(vpc_id.clone())
+                .send()
+                .await,
+        )?;
 
         log::info!("Detached Internet Gateway {internet_gateway_id} from VPC");
 
         log::info!("Deleting Internet Gateway");
-        self.inner
-            .delete_internet_gateway()
-            .internet_gateway_id(internet_gateway_id.clone())
-            .send()
-            .await?;
+        ignore_not_found(
+            self.inner
+                .delete_internet_gateway()
+                .internet_gateway_id(internet_gateway_id.clone())
+                .send()
+                .await,
+        )?;
 
         log::info!("Deleted Internet Gateway {internet_gateway_id} from VPC");
 
@@ -422,11 +657,13 @@ impl Ec2Impl {
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         log::info!("Deleting Route Table {route_table_id}");
 
-        self.inner
-            .delete_route_table()
-            .route_table_id(route_table_id.clone())
-            .send()
-            .await?;
+        ignore_not_found(
+            self.inner
+                .delete_route_table()
+                .route_table_id(route_table_id.clone())
+                .send()
+                .await,
+        )?;
 
         log::info!("Deleted Route Table {route_table_id}");
 
@@ -481,16 +718,22 @@ impl Ec2Impl {
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         log::info!("Disassociating Route Table {route_table_id} with Subnet {subnet_id}");
 
-        let response = self
-            .inner
-            .describe_route_tables()
-            .route_table_ids(route_table_id.clone())
-            .send()
-            .await?;
+        let route_tables = ignore_not_found(
+            with_throttling_retry(|| {
+                self.inner
+                    .describe_route_tables()
+                    .route_table_ids(route_table_id.clone())
+                    .into_paginator()
+                    .items()
+                    .send()
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .await,
+        )?
+        .unwrap_or_default();
 
         // Extract association IDs
-        let associations: Vec<String> = response
-            .route_tables()
+        let associations: Vec<String> = route_tables
             .iter()
             .flat_map(|rt| rt.associations().iter())
             .filter_map(|assoc| assoc.route_table_association_id().map(str::to_string))
@@ -505,14 +748,16 @@ impl Ec2Impl {
         // Disassociate each found Route Table Association
         for association_id in associations {
             log::info!("Disassociating Route Table {route_table_id} from {association_id}");
-            self.inner
-                .disassociate_route_table()
-                .association_id(association_id.clone())
-                .send()
-                .await?;
+            ignore_not_found(
+                self.inner
+                    .disassociate_route_table()
+                    .association_id(association_id.clone())
+                    .send()
+                    .await,
+            )?;
         }
 
-        for route_table in response.route_tables() {
+        for route_table in &route_tables {
             for route in route_table.routes() {
                 if let Some(destination) = route.destination_cidr_block() {
                     if destination == "local" || destination.starts_with("10.0.0.") {
@@ -523,12 +768,14 @@ impl Ec2Impl {
                     }
 
                     log::info!("Deleting route {destination} from Route Table {route_table_id}");
-                    self.inner
-                        .delete_route()
-                        .route_table_id(route_table_id.clone())
-                        .destination_cidr_block(destination)
-                        .send()
-                        .await?;
+                    ignore_not_found(
+                        self.inner
+                            .delete_route()
+                            .route_table_id(route_table_id.clone())
+                            .destination_cidr_block(destination)
+                            .send()
+                            .await,
+                    )?;
                 }
             }
         }
@@ -562,19 +809,25 @@ impl Ec2Impl {
         &self,
         instance_id: String,
     ) -> Result<aws_sdk_ec2::types::Instance, Box<dyn std::error::Error + Send + Sync>> {
-        let response = self
-            .inner
-            .describe_instances()
-            .instance_ids(instance_id)
-            .send()
-            .await?;
+        let reservations = with_throttling_retry(|| {
+            self.inner
+                .describe_instances()
+                .instance_ids(instance_id.clone())
+                .into_paginator()
+                .items()
+                .send()
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .await?;
 
-        let instance = response
-            .reservations()
-            .first()
-            .ok_or("No reservations")?
-            .instances()
-            .first()
+        if reservations.is_empty() {
+            return Err("No reservations".into());
+        }
+
+        let instance = reservations
+            .iter()
+            .flat_map(|reservation| reservation.instances())
+            .next()
             .ok_or("No instances")?;
 
         Ok(instance.clone())
@@ -620,24 +873,29 @@ impl Ec2Impl {
             )
             .security_group_ids(security_group_id);
 
-        let response = request.send().await?;
+        let response = with_throttling_retry(|| request.clone().send()).await?;
 
         log::info!("Created EC2 instance");
 
         Ok(response)
     }
 
+    /// Returns `false` when the instance does not exist
     pub async fn terminate_instance(
         &self,
         instance_id: String,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        self.inner
-            .terminate_instances()
-            .instance_ids(instance_id)
-            .send()
-            .await?;
-
-        Ok(())
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let response = ignore_not_found(
+            with_throttling_retry(|| {
+                self.inner
+                    .terminate_instances()
+                    .instance_ids(instance_id.clone())
+                    .send()
+            })
+            .await,
+        )?;
+
+        Ok(response.is_some())
     }
 }
 
@@ -759,14 +1017,17 @@ impl Route53Impl {
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         log::info!("Deleting Route53 hosted zone {id}");
         // List all record sets
-        let record_sets = self
-            .inner
-            .list_resource_record_sets()
-            .hosted_zone_id(id.clone())
-            .send()
-            .await?
-            .resource_record_sets()
-            .to_vec();
+        let Some(response) = ignore_not_found(
+            self.inner
+                .list_resource_record_sets()
+                .hosted_zone_id(id.clone())
+                .send()
+                .await,
+        )?
+        else {
+            return Ok(());
+        };
+        let record_sets = response.resource_record_sets().to_vec();
 
         // Filter out NS and SOA, and delete the rest
         for record_set in record_sets {
@@ -786,11 +1047,7 @@ impl Route53Impl {
 
         log::info!("Deleted non-default record sets from hosted zone {id}");
 
-        self.inner
-            .delete_hosted_zone()
-            .id(id.clone())
-            .send()
-            .await?;
+        ignore_not_found(self.inner.delete_hosted_zone().id(id.clone()).send().await)?;
 
         log::info!("Deleted Route53 hosted zone {id}");
 
@@ -858,12 +1115,19 @@ impl Route53Impl {
             .changes(change)
             .build()?;
 
-        self.inner
+        let response = self
+            .inner
             .change_resource_record_sets()
             .hosted_zone_id(hosted_zone_id)
             .change_batch(changes)
             .send()
-            .await?;
+            .await;
+
+        if action == ChangeAction::Delete {
+            ignore_not_found(response)?;
+        } else {
+            response?;
+        }
 
         log::info!("Finished to {action} {record_type} record for {domain_name}");
 
@@ -923,40 +1187,88 @@ impl IAMImpl {
         Self { inner }
     }
 
+    /// Creates IAM role for EC2 instance and attaches the policies to it
+    ///
+    /// Returns `false` without attaching the policies when the role already
+    /// exists
     pub async fn create_instance_iam_role(
         &self,
         name: String,
         assume_role_policy: String,
         policy_arns: Vec<String>,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
         // Create IAM role for EC2 instance
         log::info!("Creating IAM role for EC2 instance");
 
-        self.inner
+        let response = self
+            .inner
             .create_role()
             .role_name(name.clone())
             .assume_role_policy_document(assume_role_policy)
             .send()
-            .await?;
+            .await;
 
-        log::info!("Created IAM role for EC2 instance");
+        match response {
+            Ok(_) => {}
+            Err(sdk_err) => match sdk_err.into_service_error() {
+                aws_sdk_iam::operation::create_role::CreateRoleError::EntityAlreadyExistsException(_) => {
+                    log::info!("IAM role {name} already exists");
 
-        for policy_arn in &policy_arns {
-            log::info!("Attaching '{policy_arn}' policy to the role");
+                    return Ok(false);
+                }
+                err => return Err(Box::new(err)),
+            },
+        }
 
-            self.inner
-                .attach_role_policy()
-                .role_name(name.clone())
-                .policy_arn(policy_arn)
-                .send()
-                .await?;
+        log::info!("Created IAM role for EC2 instance");
 
-            log::info!("Attached '{policy_arn}' policy to the role");
+        for policy_arn in policy_arns {
+            self.attach_role_policy(name.clone(), policy_arn).await?;
         }
 
+        Ok(true)
+    }
+
+    pub async fn attach_role_policy(
+        &self,
+        name: String,
+        policy_arn: String,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        log::info!("Attaching '{policy_arn}' policy to the role");
+
+        self.inner
+            .attach_role_policy()
+            .role_name(name)
+            .policy_arn(&policy_arn)
+            .send()
+            .await?;
+
+        log::info!("Attached '{policy_arn}' policy to the role");
+
         Ok(())
     }
 
+    /// Returns ARNs of the managed policies attached to the role
+    pub async fn list_attached_role_policies(
+        &self,
+        name: String,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let response = self
+            .inner
+            .list_attached_role_policies()
+            .role_name(name)
+            .into_paginator()
+            .items()
+            .send()
+            .collect::<Result<Vec<_>, _>>()
+            .await?;
+
+        Ok(response
+            .iter()
+            .filter_map(|policy| policy.policy_arn().map(str::to_string))
+            .collect())
+    }
+
     pub async fn delete_instance_iam_role(
         &self,
         name: String,
@@ -965,63 +1277,119 @@ impl IAMImpl {
         for policy_arn in &policy_arns {
             log::info!("Detaching '{policy_arn}' IAM role from EC2 instance");
 
-            self.inner
-                .detach_role_policy()
-                .role_name(name.clone())
-                .policy_arn(policy_arn)
-                .send()
-                .await?;
+            ignore_not_found(
+                self.inner
+                    .detach_role_policy()
+                    .role_name(name.clone())
+                    .policy_arn(policy_arn)
+                    .send()
+                    .await,
+            )?;
 
             log::info!("Detached '{policy_arn}' IAM role from EC2 instance");
         }
 
         log::info!("Deleting IAM role for EC2 instance");
 
-        self.inner
-            .delete_role()
-            .role_name(name.clone())
-            .send()
-            .await?;
+        ignore_not_found(
+            self.inner
+                .delete_role()
+                .role_name(name.clone())
+                .send()
+                .await,
+        )?;
 
         log::info!("Deleted IAM role for EC2 instance");
 
         Ok(())
     }
 
+    /// Creates IAM instance profile for EC2 instance and adds the roles to it
+    ///
+    /// Returns `false` without adding the roles when the instance profile
+    /// already exists
     pub async fn create_instance_profile(
         &self,
         name: String,
         role_names: Vec<String>,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
         log::info!("Creating IAM instance profile for EC2 instance");
 
-        self.inner
+        let response = self
+            .inner
             .create_instance_profile()
             .instance_profile_name(name.clone())
             .send()
-            .await?;
+            .await;
+
+        match response {
+            Ok(_) => {}
+            Err(sdk_err) => match sdk_err.into_service_error() {
+                aws_sdk_iam::operation::create_instance_profile::CreateInstanceProfileError::EntityAlreadyExistsException(_) => {
+                    log::info!("IAM instance profile {name} already exists");
+
+                    return Ok(false);
+                }
+                err => return Err(Box::new(err)),
+            },
+        }
 
         log::info!("Created IAM instance profile for EC2 instance");
 
         for role_name in role_names {
-            log::info!("Adding '{role_name}' IAM role to instance profile");
-
-            self.inner
-                .add_role_to_instance_profile()
-                .instance_profile_name(name.clone())
-                .role_name(role_name.clone())
-                .send()
+            self.add_role_to_instance_profile(name.clone(), role_name)
                 .await?;
-
-            log::info!("Added '{role_name}' IAM role to instance profile");
         }
 
         log::info!("Waiting for instance profile to be ready");
         tokio::time::sleep(std::time::Duration::from_secs(10)).await;
 
+        Ok(true)
+    }
+
+    pub async fn add_role_to_instance_profile(
+        &self,
+        name: String,
+        role_name: String,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        log::info!("Adding '{role_name}' IAM role to instance profile");
+
+        self.inner
+            .add_role_to_instance_profile()
+            .instance_profile_name(name)
+            .role_name(&role_name)
+            .send()
+            .await?;
+
+        log::info!("Added '{role_name}' IAM role to instance profile");
+
         Ok(())
     }
 
+    /// Returns names of the roles added to the instance profile
+    pub async fn get_instance_profile_role_names(
+        &self,
+        name: String,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let response = self
+            .inner
+            .get_instance_profile()
+            .instance_profile_name(name)
+            .send()
+            .await?;
+
+        Ok(response
+            .instance_profile()
+            .map(|instance_profile| {
+                instance_profile
+                    .roles()
+                    .iter()
+                    .map(|role| role.role_name().to_string())
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
     pub async fn delete_instance_profile(
         &self,
         name: String,
@@ -1030,23 +1398,27 @@ impl IAMImpl {
         for role_name in role_names {
             log::info!("Removing {role_name} IAM role from instance profile");
 
-            self.inner
-                .remove_role_from_instance_profile()
-                .instance_profile_name(name.clone())
-                .role_name(role_name.clone())
-                .send()
-                .await?;
+            ignore_not_found(
+                self.inner
+                    .remove_role_from_instance_profile()
+                    .instance_profile_name(name.clone())
+                    .role_name(role_name.clone())
+                    .send()
+                    .await,
+            )?;
 
             log::info!("Removed {role_name} IAM role from instance profile");
         }
 
         log::info!("Deleting IAM instance profile");
 
-        self.inner
-            .delete_instance_profile()
-            .instance_profile_name(name.clone())
-            .send()
-            .await?;
+        ignore_not_found(
+            self.inner
+                .delete_instance_profile()
+                .instance_profile_name(name.clone())
+                .send()
+                .await,
+        )?;
 
         log::info!("Deleted IAM instance profile");
 
@@ -1100,14 +1472,345 @@ impl ECRImpl {
         name: String,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         log::info!("Deleting ECR repository");
+        ignore_not_found(
+            self.inner
+                .delete_repository()
+                .repository_name(name)
+                .force(true)
+                .send()
+                .await,
+        )?;
+
+        log::info!("Deleted ECR repository");
+
+        Ok(())
+    }
+
+    /// Set repository lifecycle policy
+    pub async fn put_lifecycle_policy(
+        &self,
+        name: String,
+        policy: String,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        log::info!("Putting ECR lifecycle policy");
         self.inner
-            .delete_repository()
+            .put_lifecycle_policy()
             .repository_name(name)
-            .force(true)
+            .lifecycle_policy_text(policy)
             .send()
             .await?;
 
-        log::info!("Deleted ECR repository");
+        Ok(())
+    }
+}
+
+pub(super) struct SecretsManagerImpl {
+    inner: aws_sdk_secretsmanager::Client,
+}
+
+#[cfg_attr(test, allow(dead_code))]
+#[cfg_attr(test, automock)]
+impl SecretsManagerImpl {
+    pub(super) fn new(inner: aws_sdk_secretsmanager::Client) -> Self {
+        Self { inner }
+    }
+
+    /// Get secret string value by secret name or ARN
+    pub(super) async fn get_secret_value(
+        &self,
+        name: &str,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let response = self.inner.get_secret_value().secret_id(name).send().await?;
+
+        response
+            .secret_string()
+            .map(ToString::to_string)
+            .ok_or_else(|| format!("Secret '{name}' has no string value").into())
+    }
+}
+
+pub(super) struct SsmImpl {
+    inner: aws_sdk_ssm::Client,
+}
+
+#[cfg_attr(test, allow(dead_code))]
+#[cfg_attr(test, automock)]
+impl SsmImpl {
+    pub(super) fn new(inner: aws_sdk_ssm::Client) -> Self {
+        Self { inner }
+    }
+
+    /// Get decrypted Parameter Store parameter value
+    pub(super) async fn get_parameter(
+        &self,
+        name: &str,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let response = self
+            .inner
+            .get_parameter()
+            .name(name)
+            .with_decryption(true)
+            .send()
+            .await?;
+
+        response
+            .parameter()
+            .and_then(|parameter| parameter.value())
+            .map(ToString::to_string)
+            .ok_or_else(|| format!("Parameter '{name}' has no value").into())
+    }
+}
+
+/// AWS Elastic Load Balancing v2 client implementation
+#[derive(Debug)]
+pub struct ELBv2Impl {
+    inner: aws_sdk_elasticloadbalancingv2::Client,
+}
+
+// TODO: Add tests using static replay
+#[cfg_attr(test, allow(dead_code))]
+#[cfg_attr(test, automock)]
+impl ELBv2Impl {
+    pub fn new(inner: aws_sdk_elasticloadbalancingv2::Client) -> Self {
+        Self { inner }
+    }
+
+    /// Create HTTP target group of instances, returns its ARN
+    pub async fn create_target_group(
+        &self,
+        name: String,
+        vpc_id: String,
+        port: i32,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        log::info!("Creating target group");
+
+        let response = self
+            .inner
+            .create_target_group()
+            .name(name)
+            .protocol(aws_sdk_elasticloadbalancingv2::types::ProtocolEnum::Http)
+            .port(port)
+            .vpc_id(vpc_id)
+            .target_type(aws_sdk_elasticloadbalancingv2::types::TargetTypeEnum::Instance)
+            .send()
+            .await?;
+
+        let target_group_arn = response
+            .target_groups()
+            .first()
+            .and_then(|target_group| target_group.target_group_arn())
+            .ok_or("Failed to retrieve target group ARN")?;
+
+        log::info!("Created target group: {target_group_arn}");
+
+        Ok(target_group_arn.to_string())
+    }
+
+    pub async fn delete_target_group(
+        &self,
+        target_group_arn: String,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        log::info!("Deleting target group");
+
+        ignore_not_found(
+            self.inner
+                .delete_target_group()
+                .target_group_arn(target_group_arn.clone())
+                .send()
+                .await,
+        )?;
+
+        log::info!("Deleted target group: {target_group_arn}");
+
+        Ok(())
+    }
+
+    /// Create internet-facing application load balancer,
+    /// returns its ARN and DNS name
+    pub async fn create_load_balancer(
+        &self,
+        name: String,
+        subnet_ids: Vec<String>,
+        security_group_ids: Vec<String>,
+    ) -> Result<(String, String), Box<dyn std::error::Error + Send + Sync>> {
+        log::info!("Creating load balancer");
+
+        let response = self
+            .inner
+            .create_load_balancer()
+            .name(name)
+            .r#type(aws_sdk_elasticloadbalancingv2::types::LoadBalancerTypeEnum::Application)
+            .scheme(aws_sdk_elasticloadbalancingv2::types::LoadBalancerSchemeEnum::InternetFacing)
+            .set_subnets(Some(subnet_ids))
+            .set_security_groups(Some(security_group_ids))
+            .send()
+            .await?;
+
+        let load_balancer = response
+            .load_balancers()
+            .first()
+            .ok_or("Failed to create load balancer")?;
+        let load_balancer_arn = load_balancer
+            .load_balancer_arn()
+            .ok_or("Failed to retrieve load balancer ARN")?;
+        let dns_name = load_balancer
+            .dns_name()
+            .ok_or("Failed to retrieve load balancer DNS name")?;
+
+        log::info!("Created load balancer: {load_balancer_arn}");
+
+        Ok((load_balancer_arn.to_string(), dns_name.to_string()))
+    }
+
+    pub async fn delete_load_balancer(
+        &self,
+        load_balancer_arn: String,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        log::info!("Deleting load balancer");
+
+        ignore_not_found(
+            self.inner
+                .delete_load_balancer()
+                .load_balancer_arn(load_balancer_arn.clone())
+                .send()
+                .await,
+        )?;
+
+        log::info!("Deleted load balancer: {load_balancer_arn}");
+
+        Ok(())
+    }
+
+    /// Checks whether the load balancer is gone, AWS deletes load balancers
+    /// asynchronously and their target groups stay in use until then
+    pub async fn is_load_balancer_deleted(
+        &self,
+        load_balancer_arn: String,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let response = self
+            .inner
+            .describe_load_balancers()
+            .load_balancer_arns(load_balancer_arn)
+            .send()
+            .await;
+
+        match response {
+            Ok(response) => Ok(response.load_balancers().is_empty()),
+            Err(e) if is_not_found_error(&e) => Ok(true),
+            Err(e) => Err(Box::new(e)),
+        }
+    }
+
+    /// Create HTTP listener forwarding all requests to the target group,
+    /// returns its ARN
+    pub async fn create_listener(
+        &self,
+        load_balancer_arn: String,
+        target_group_arn: String,
+        port: i32,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        log::info!("Creating listener");
+
+        let forward_action = aws_sdk_elasticloadbalancingv2::types::Action::builder()
+            .r#type(aws_sdk_elasticloadbalancingv2::types::ActionTypeEnum::Forward)
+            .target_group_arn(target_group_arn)
+            .build()?;
+
+        let response = self
+            .inner
+            .create_listener()
+            .load_balancer_arn(load_balancer_arn)
+            .protocol(aws_sdk_elasticloadbalancingv2::types::ProtocolEnum::Http)
+            .port(port)
+            .default_actions(forward_action)
+            .send()
+            .await?;
+
+        let listener_arn = response
+            .listeners()
+            .first()
+            .and_then(|listener| listener.listener_arn())
+            .ok_or("Failed to retrieve listener ARN")?;
+
+        log::info!("Created listener: {listener_arn}");
+
+        Ok(listener_arn.to_string())
+    }
+
+    pub async fn delete_listener(
+        &self,
+        listener_arn: String,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        log::info!("Deleting listener");
+
+        ignore_not_found(
+            self.inner
+                .delete_listener()
+                .listener_arn(listener_arn.clone())
+                .send()
+                .await,
+        )?;
+
+        log::info!("Deleted listener: {listener_arn}");
+
+        Ok(())
+    }
+
+    /// Register instances as targets receiving traffic on the target group port
+    pub async fn register_targets(
+        &self,
+        target_group_arn: String,
+        instance_ids: Vec<String>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        log::info!("Registering targets {instance_ids:?}");
+
+        let targets = instance_ids
+            .into_iter()
+            .map(|instance_id| {
+                aws_sdk_elasticloadbalancingv2::types::TargetDescription::builder()
+                    .id(instance_id)
+                    .build()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.inner
+            .register_targets()
+            .target_group_arn(target_group_arn)
+            .set_targets(Some(targets))
+            .send()
+            .await?;
+
+        log::info!("Registered targets");
+
+        Ok(())
+    }
+
+    /// Deregister instances, the load balancer stops routing new requests
+    /// to them
+    pub async fn deregister_targets(
+        &self,
+        target_group_arn: String,
+        instance_ids: Vec<String>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        log::info!("Deregistering targets {instance_ids:?}");
+
+        let targets = instance_ids
+            .into_iter()
+            .map(|instance_id| {
+                aws_sdk_elasticloadbalancingv2::types::TargetDescription::builder()
+                    .id(instance_id)
+                    .build()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.inner
+            .deregister_targets()
+            .target_group_arn(target_group_arn)
+            .set_targets(Some(targets))
+            .send()
+            .await?;
+
+        log::info!("Deregistered targets");
 
         Ok(())
     }
@@ -1117,12 +1820,16 @@ impl ECRImpl {
 #[cfg(not(test))]
 pub use ECRImpl as ECR;
 #[cfg(not(test))]
+pub use ELBv2Impl as ELBv2;
+#[cfg(not(test))]
 pub use Ec2Impl as Ec2;
 #[cfg(not(test))]
 pub use IAMImpl as IAM;
 #[cfg(test)]
 pub use MockECRImpl as ECR;
 #[cfg(test)]
+pub use MockELBv2Impl as ELBv2;
+#[cfg(test)]
 pub use MockEc2Impl as Ec2;
 #[cfg(test)]
 pub use MockIAMImpl as IAM;
@@ -1130,7 +1837,144 @@ pub use MockIAMImpl as IAM;
 pub use MockRoute53Impl as Route53;
 #[cfg(test)]
 pub(super) use MockS3Impl as S3;
+#[cfg(test)]
+pub(super) use MockSecretsManagerImpl as SecretsManager;
+#[cfg(test)]
+pub(super) use MockSsmImpl as Ssm;
 #[cfg(not(test))]
 pub use Route53Impl as Route53;
 #[cfg(not(test))]
 pub(super) use S3Impl as S3;
+#[cfg(not(test))]
+pub(super) use SecretsManagerImpl as SecretsManager;
+#[cfg(not(test))]
+pub(super) use SsmImpl as Ssm;
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct TestError(ErrorMetadata);
+
+    impl TestError {
+        fn with_code(code: &str) -> Self {
+            Self(ErrorMetadata::builder().code(code).build())
+        }
+    }
+
+    impl ProvideErrorMetadata for TestError {
+        fn meta(&self) -> &ErrorMetadata {
+            &self.0
+        }
+    }
+
+    #[test]
+    fn test_is_throttling_error() {
+        // Arrange
+        let throttled = TestError::with_code("RequestLimitExceeded");
+        let not_found = TestError::with_code("InvalidInstanceID.NotFound");
+
+        // Act & Assert
+        assert!(is_throttling_error(&throttled));
+        assert!(!is_throttling_error(&not_found));
+    }
+
+    #[test]
+    fn test_is_not_found_error() {
+        // Arrange
+        let ec2_not_found = TestError::with_code("InvalidSubnetID.NotFound");
+        let iam_not_found = TestError::with_code("NoSuchEntity");
+        let dependency_violation = TestError::with_code("DependencyViolation");
+        let without_code = TestError(ErrorMetadata::builder().message("NotFound").build());
+
+        // Act & Assert
+        assert!(is_not_found_error(&ec2_not_found));
+        assert!(is_not_found_error(&iam_not_found));
+        assert!(!is_not_found_error(&dependency_violation));
+        assert!(!is_not_found_error(&without_code));
+    }
+
+    #[test]
+    fn test_ignore_not_found() {
+        // Arrange
+        let deleted: Result<&str, TestError> = Ok("deleted");
+        let not_found: Result<&str, TestError> = Err(TestError::with_code("InvalidGroup.NotFound"));
+        let dependency_violation: Result<&str, TestError> =
+            Err(TestError::with_code("DependencyViolation"));
+
+        // Act & Assert
+        assert_eq!(
+            ignore_not_found(deleted).expect("Failed to delete"),
+            Some("deleted")
+        );
+        assert_eq!(
+            ignore_not_found(not_found).expect("Failed to ignore not found"),
+            None
+        );
+        assert!(ignore_not_found(dependency_violation).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_retry_on_throttling_succeeds_after_throttling() {
+        // Arrange
+        let calls = AtomicU32::new(0);
+
+        // Act
+        let result = retry_on_throttling(3, Duration::from_millis(1), || async {
+            if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                Err(TestError::with_code("RequestLimitExceeded"))
+            } else {
+                Ok("i-12345")
+            }
+        })
+        .await;
+
+        // Assert
+        assert_eq!(
+            result.expect("Failed to retry throttled request"),
+            "i-12345"
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_on_throttling_gives_up_after_max_attempts() {
+        // Arrange
+        let calls = AtomicU32::new(0);
+
+        // Act
+        let result: Result<(), TestError> =
+            retry_on_throttling(3, Duration::from_millis(1), || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+
+                Err(TestError::with_code("RequestLimitExceeded"))
+            })
+            .await;
+
+        // Assert
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_on_throttling_does_not_retry_other_errors() {
+        // Arrange
+        let calls = AtomicU32::new(0);
+
+        // Act
+        let result: Result<(), TestError> =
+            retry_on_throttling(3, Duration::from_millis(1), || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+
+                Err(TestError::with_code("InvalidInstanceID.NotFound"))
+            })
+            .await;
+
+        // Assert
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,20 @@
+use aws_sdk_ec2::config::ProvideCredentials;
+
+/// Resolves AWS credentials with the default provider chain (environment,
+/// shared config and credentials files, SSO, instance metadata), returns the
+/// resolved access key ID
+pub async fn resolve_credentials(
+    region: &str,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let config = aws_config::defaults(aws_config::BehaviorVersion::latest())
+        .region(aws_sdk_ec2::config::Region::new(region.to_string()))
+        .load()
+        .await;
+
+    let credentials_provider = config
+        .credentials_provider()
+        .ok_or("No AWS credentials provider is configured")?;
+    let credentials = credentials_provider.provide_credentials().await?;
+
+    Ok(credentials.access_key_id().to_string())
+}
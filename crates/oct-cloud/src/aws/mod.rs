@@ -1,4 +1,7 @@
+pub mod ami;
+pub mod credentials;
 pub mod resource;
+pub mod secrets;
 pub mod types;
 
 pub mod client;
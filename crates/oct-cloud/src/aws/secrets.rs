@@ -0,0 +1,357 @@
+use std::collections::HashMap;
+
+use crate::aws::client::{SecretsManager, Ssm};
+
+const SECRET_REFERENCE_PREFIX: &str = "secret://";
+const SECRETS_MANAGER_PREFIX: &str = "secret://secretsmanager/";
+const SSM_PARAMETER_PREFIX: &str = "ssm://";
+
+/// Reference to a secret value stored outside of the config file
+#[derive(Debug, PartialEq, Eq)]
+pub enum SecretReference {
+    /// `secret://secretsmanager/<name>?key=<json_key>`, `key` selects a field
+    /// of a JSON secret
+    SecretsManager { name: String, key: Option<String> },
+    /// `ssm://<name>`, e.g. `ssm:///my/param` for the `/my/param` parameter
+    SsmParameter { name: String },
+}
+
+impl SecretReference {
+    /// Parses an env value, returns `None` for plain values
+    pub fn parse(value: &str) -> Result<Option<Self>, Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(name) = value.strip_prefix(SSM_PARAMETER_PREFIX) {
+            if name.is_empty() {
+                return Err(format!("Parameter name is missing in '{value}'").into());
+            }
+
+            return Ok(Some(Self::SsmParameter {
+                name: name.to_string(),
+            }));
+        }
+
+        if !value.starts_with(SECRET_REFERENCE_PREFIX) {
+            return Ok(None);
+        }
+
+        let Some(reference) = value.strip_prefix(SECRETS_MANAGER_PREFIX) else {
+            return Err(format!("Unsupported secret reference '{value}'").into());
+        };
+
+        let (name, query) = match reference.split_once('?') {
+            Some((name, query)) => (name, Some(query)),
+            None => (reference, None),
+        };
+
+        if name.is_empty() {
+            return Err(format!("Secret name is missing in '{value}'").into());
+        }
+
+        let key = match query {
+            None => None,
+            Some(query) => match query.strip_prefix("key=") {
+                Some(key) if !key.is_empty() => Some(key.to_string()),
+                _ => {
+                    return Err(format!(
+                        "Invalid secret reference query in '{value}', expected 'key=<json_key>'"
+                    )
+                    .into());
+                }
+            },
+        };
+
+        Ok(Some(Self::SecretsManager {
+            name: name.to_string(),
+            key,
+        }))
+    }
+}
+
+/// Resolves secret references in service envs
+pub struct SecretsResolver {
+    secrets_manager: SecretsManager,
+    ssm: Ssm,
+}
+
+impl SecretsResolver {
+    pub async fn new(region: String) -> Self {
+        let region_provider = aws_sdk_secretsmanager::config::Region::new(region);
+        let config = aws_config::defaults(aws_config::BehaviorVersion::latest())
+            .region(region_provider)
+            .load()
+            .await;
+
+        let secrets_manager_client = aws_sdk_secretsmanager::Client::new(&config);
+        let ssm_client = aws_sdk_ssm::Client::new(&config);
+
+        Self {
+            secrets_manager: SecretsManager::new(secrets_manager_client),
+            ssm: Ssm::new(ssm_client),
+        }
+    }
+
+    /// Replaces secret references with the secret values,
+    /// plain values are kept as is
+    pub async fn resolve_envs(
+        &self,
+        envs: &HashMap<String, String>,
+    ) -> Result<HashMap<String, String>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut resolved_envs = HashMap::with_capacity(envs.len());
+
+        for (name, value) in envs {
+            let resolved_value = match SecretReference::parse(value)? {
+                Some(reference) => self.resolve(&reference).await?,
+                None => value.clone(),
+            };
+
+            resolved_envs.insert(name.clone(), resolved_value);
+        }
+
+        Ok(resolved_envs)
+    }
+
+    async fn resolve(
+        &self,
+        reference: &SecretReference,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        match reference {
+            SecretReference::SecretsManager { name, key } => {
+                let secret = self
+                    .secrets_manager
+                    .get_secret_value(name)
+                    .await
+                    .map_err(|e| format!("Failed to get secret '{name}': {e}"))?;
+
+                let Some(key) = key else {
+                    return Ok(secret);
+                };
+
+                let secret_json: serde_json::Value = serde_json::from_str(&secret)
+                    .map_err(|e| format!("Secret '{name}' is not a valid JSON: {e}"))?;
+
+                match secret_json.get(key) {
+                    Some(serde_json::Value::String(value)) => Ok(value.clone()),
+                    Some(value) => Ok(value.to_string()),
+                    None => Err(format!("Key '{key}' is not found in secret '{name}'").into()),
+                }
+            }
+            SecretReference::SsmParameter { name } => self
+                .ssm
+                .get_parameter(name)
+                .await
+                .map_err(|e| format!("Failed to get SSM parameter '{name}': {e}").into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mockall::predicate::eq;
+
+    use super::*;
+
+    #[test]
+    fn test_secret_reference_parse_plain_value() {
+        // Act
+        let reference = SecretReference::parse("plain-value");
+
+        // Assert
+        assert_eq!(reference.expect("Failed to parse value"), None);
+    }
+
+    #[test]
+    fn test_secret_reference_parse_secrets_manager() {
+        // Act
+        let reference = SecretReference::parse("secret://secretsmanager/prod/db");
+        let reference_with_key =
+            SecretReference::parse("secret://secretsmanager/prod/db?key=password");
+
+        // Assert
+        assert_eq!(
+            reference.expect("Failed to parse reference"),
+            Some(SecretReference::SecretsManager {
+                name: String::from("prod/db"),
+                key: None,
+            })
+        );
+        assert_eq!(
+            reference_with_key.expect("Failed to parse reference"),
+            Some(SecretReference::SecretsManager {
+                name: String::from("prod/db"),
+                key: Some(String::from("password")),
+            })
+        );
+    }
+
+    #[test]
+    fn test_secret_reference_parse_invalid() {
+        // Act & Assert
+        assert_eq!(
+            SecretReference::parse("secret://vault/prod/db")
+                .expect_err("Expected unsupported reference error")
+                .to_string(),
+            "Unsupported secret reference 'secret://vault/prod/db'"
+        );
+        assert_eq!(
+            SecretReference::parse("secret://secretsmanager/")
+                .expect_err("Expected missing name error")
+                .to_string(),
+            "Secret name is missing in 'secret://secretsmanager/'"
+        );
+        assert!(SecretReference::parse("secret://secretsmanager/prod/db?field=password").is_err());
+    }
+
+    #[test]
+    fn test_secret_reference_parse_ssm_parameter() {
+        // Act
+        let reference = SecretReference::parse("ssm:///my/param");
+
+        // Assert
+        assert_eq!(
+            reference.expect("Failed to parse reference"),
+            Some(SecretReference::SsmParameter {
+                name: String::from("/my/param"),
+            })
+        );
+        assert_eq!(
+            SecretReference::parse("ssm://")
+                .expect_err("Expected missing name error")
+                .to_string(),
+            "Parameter name is missing in 'ssm://'"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_secrets_resolver_resolve_envs() {
+        // Arrange
+        let mut secrets_manager_mock = SecretsManager::default();
+        secrets_manager_mock
+            .expect_get_secret_value()
+            .with(eq(String::from("prod/db")))
+            .times(2)
+            .returning(|_| Ok(String::from(r#"{"user": "admin", "password": "s3cr3t"}"#)));
+
+        let resolver = SecretsResolver {
+            secrets_manager: secrets_manager_mock,
+            ssm: Ssm::default(),
+        };
+
+        let envs = HashMap::from([
+            (String::from("LOG_LEVEL"), String::from("info")),
+            (
+                String::from("DB_USER"),
+                String::from("secret://secretsmanager/prod/db?key=user"),
+            ),
+            (
+                String::from("DB_PASSWORD"),
+                String::from("secret://secretsmanager/prod/db?key=password"),
+            ),
+        ]);
+
+        // Act
+        let resolved_envs = resolver.resolve_envs(&envs).await;
+
+        // Assert
+        assert_eq!(
+            resolved_envs.expect("Failed to resolve envs"),
+            HashMap::from([
+                (String::from("LOG_LEVEL"), String::from("info")),
+                (String::from("DB_USER"), String::from("admin")),
+                (String::from("DB_PASSWORD"), String::from("s3cr3t")),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_secrets_resolver_resolve_envs_missing_key() {
+        // Arrange
+        let mut secrets_manager_mock = SecretsManager::default();
+        secrets_manager_mock
+            .expect_get_secret_value()
+            .with(eq(String::from("prod/db")))
+            .return_once(|_| Ok(String::from(r#"{"user": "admin"}"#)));
+
+        let resolver = SecretsResolver {
+            secrets_manager: secrets_manager_mock,
+            ssm: Ssm::default(),
+        };
+
+        let envs = HashMap::from([(
+            String::from("DB_PASSWORD"),
+            String::from("secret://secretsmanager/prod/db?key=password"),
+        )]);
+
+        // Act
+        let resolved_envs = resolver.resolve_envs(&envs).await;
+
+        // Assert
+        assert_eq!(
+            resolved_envs
+                .expect_err("Expected missing key error")
+                .to_string(),
+            "Key 'password' is not found in secret 'prod/db'"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_secrets_resolver_resolve_envs_ssm_parameter() {
+        // Arrange
+        let mut ssm_mock = Ssm::default();
+        ssm_mock
+            .expect_get_parameter()
+            .with(eq(String::from("/my/param")))
+            .return_once(|_| Ok(String::from("param-value")));
+
+        let resolver = SecretsResolver {
+            secrets_manager: SecretsManager::default(),
+            ssm: ssm_mock,
+        };
+
+        let envs = HashMap::from([
+            (String::from("LOG_LEVEL"), String::from("info")),
+            (String::from("MY_PARAM"), String::from("ssm:///my/param")),
+        ]);
+
+        // Act
+        let resolved_envs = resolver.resolve_envs(&envs).await;
+
+        // Assert
+        assert_eq!(
+            resolved_envs.expect("Failed to resolve envs"),
+            HashMap::from([
+                (String::from("LOG_LEVEL"), String::from("info")),
+                (String::from("MY_PARAM"), String::from("param-value")),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_secrets_resolver_resolve_envs_ssm_parameter_failure() {
+        // Arrange
+        let mut ssm_mock = Ssm::default();
+        ssm_mock
+            .expect_get_parameter()
+            .with(eq(String::from("/missing/param")))
+            .return_once(|_| Err("ParameterNotFound".into()));
+
+        let resolver = SecretsResolver {
+            secrets_manager: SecretsManager::default(),
+            ssm: ssm_mock,
+        };
+
+        let envs = HashMap::from([(
+            String::from("MY_PARAM"),
+            String::from("ssm:///missing/param"),
+        )]);
+
+        // Act
+        let resolved_envs = resolver.resolve_envs(&envs).await;
+
+        // Assert
+        assert_eq!(
+            resolved_envs
+                .expect_err("Expected SSM parameter error")
+                .to_string(),
+            "Failed to get SSM parameter '/missing/param': ParameterNotFound"
+        );
+    }
+}
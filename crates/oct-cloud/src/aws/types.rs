@@ -10,6 +10,7 @@ pub enum RecordType {
     NS,
     SOA,
     TXT,
+    CNAME,
 }
 
 impl From<&str> for RecordType {
@@ -19,6 +20,7 @@ impl From<&str> for RecordType {
             "NS" => Self::NS,
             "SOA" => Self::SOA,
             "TXT" => Self::TXT,
+            "CNAME" => Self::CNAME,
             _ => panic!("Invalid record type: {s}"),
         }
     }
@@ -31,6 +33,7 @@ impl From<RrType> for RecordType {
             RrType::Ns => Self::NS,
             RrType::Soa => Self::SOA,
             RrType::Txt => Self::TXT,
+            RrType::Cname => Self::CNAME,
             _ => panic!("Invalid record type: {rr_type}"),
         }
     }
@@ -43,6 +46,7 @@ impl From<RecordType> for RrType {
             RecordType::NS => Self::Ns,
             RecordType::SOA => Self::Soa,
             RecordType::TXT => Self::Txt,
+            RecordType::CNAME => Self::Cname,
         }
     }
 }
@@ -54,6 +58,7 @@ impl RecordType {
             RecordType::NS => "NS",
             RecordType::SOA => "SOA",
             RecordType::TXT => "TXT",
+            RecordType::CNAME => "CNAME",
         }
     }
 }
@@ -67,7 +72,8 @@ impl fmt::Display for RecordType {
 /// Represents an AWS instance type.
 #[derive(Debug, PartialEq, Eq)]
 pub struct InstanceInfo {
-    /// The number of CPUs for the instance type.
+    /// CPU capacity of the instance type in millicores (1 vCPU = 1000),
+    /// the same unit as `Service.cpus`.
     pub cpus: u32,
     /// The amount of memory (in MB) for the instance type.
     pub memory: u64,
@@ -75,6 +81,13 @@ pub struct InstanceInfo {
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum InstanceType {
+    T2Nano,
+    T2Micro,
+    T2Small,
+    T2Medium,
+    T2Large,
+    T2Xlarge,
+    T22xlarge,
     T3Nano,
     T3Micro,
     T3Small,
@@ -82,11 +95,26 @@ pub enum InstanceType {
     T3Large,
     T3Xlarge,
     T32xlarge,
+    M5Large,
+    M5Xlarge,
+    M52xlarge,
+    M54xlarge,
+    C5Large,
+    C5Xlarge,
+    C52xlarge,
+    C54xlarge,
 }
 
 impl InstanceType {
     pub fn as_str(&self) -> &str {
         match self {
+            InstanceType::T2Nano => "t2.nano",
+            InstanceType::T2Micro => "t2.micro",
+            InstanceType::T2Small => "t2.small",
+            InstanceType::T2Medium => "t2.medium",
+            InstanceType::T2Large => "t2.large",
+            InstanceType::T2Xlarge => "t2.xlarge",
+            InstanceType::T22xlarge => "t2.2xlarge",
             InstanceType::T3Nano => "t3.nano",
             InstanceType::T3Micro => "t3.micro",
             InstanceType::T3Small => "t3.small",
@@ -94,24 +122,64 @@ impl InstanceType {
             InstanceType::T3Large => "t3.large",
             InstanceType::T3Xlarge => "t3.xlarge",
             InstanceType::T32xlarge => "t3.2xlarge",
+            InstanceType::M5Large => "m5.large",
+            InstanceType::M5Xlarge => "m5.xlarge",
+            InstanceType::M52xlarge => "m5.2xlarge",
+            InstanceType::M54xlarge => "m5.4xlarge",
+            InstanceType::C5Large => "c5.large",
+            InstanceType::C5Xlarge => "c5.xlarge",
+            InstanceType::C52xlarge => "c5.2xlarge",
+            InstanceType::C54xlarge => "c5.4xlarge",
         }
     }
 
+    /// All known instance types
+    const ALL: [Self; 22] = [
+        Self::T2Nano,
+        Self::T2Micro,
+        Self::T2Small,
+        Self::T2Medium,
+        Self::T2Large,
+        Self::T2Xlarge,
+        Self::T22xlarge,
+        Self::T3Nano,
+        Self::T3Micro,
+        Self::T3Small,
+        Self::T3Medium,
+        Self::T3Large,
+        Self::T3Xlarge,
+        Self::T32xlarge,
+        Self::M5Large,
+        Self::M5Xlarge,
+        Self::M52xlarge,
+        Self::M54xlarge,
+        Self::C5Large,
+        Self::C5Xlarge,
+        Self::C52xlarge,
+        Self::C54xlarge,
+    ];
+
+    /// Instance types considered for the Leader VM sorted by size from
+    /// smallest to largest
+    const SORTED_BY_SIZE: [Self; 7] = [
+        Self::T3Nano,
+        Self::T3Micro,
+        Self::T3Small,
+        Self::T3Medium,
+        Self::T3Large,
+        Self::T3Xlarge,
+        Self::T32xlarge,
+    ];
+
+    /// Returns the largest supported instance type
+    pub fn largest() -> Self {
+        Self::SORTED_BY_SIZE[Self::SORTED_BY_SIZE.len() - 1]
+    }
+
     /// Tries to get the smallest possible instance type for to fit requested
-    /// resources
-    // NOTE: The instances list must be sorted by size from smallest to largest
+    /// resources, `cpus` are millicores
     pub fn from_resources(cpus: u32, memory: u64) -> Option<Self> {
-        let instances = [
-            Self::T3Nano,
-            Self::T3Micro,
-            Self::T3Small,
-            Self::T3Medium,
-            Self::T3Large,
-            Self::T3Xlarge,
-            Self::T32xlarge,
-        ];
-
-        for instance in instances {
+        for instance in Self::SORTED_BY_SIZE {
             let info = instance.get_info();
             if cpus <= info.cpus && memory <= info.memory {
                 return Some(instance);
@@ -121,8 +189,38 @@ impl InstanceType {
         None
     }
 
+    /// CPU and memory of the instance type, burstable `t2`/`t3` types report
+    /// their full vCPU count
     pub fn get_info(&self) -> InstanceInfo {
         match self {
+            Self::T2Nano => InstanceInfo {
+                cpus: 1000,
+                memory: 512,
+            },
+            Self::T2Micro => InstanceInfo {
+                cpus: 1000,
+                memory: 1024,
+            },
+            Self::T2Small => InstanceInfo {
+                cpus: 1000,
+                memory: 2048,
+            },
+            Self::T2Medium => InstanceInfo {
+                cpus: 2000,
+                memory: 4096,
+            },
+            Self::T2Large => InstanceInfo {
+                cpus: 2000,
+                memory: 8192,
+            },
+            Self::T2Xlarge => InstanceInfo {
+                cpus: 4000,
+                memory: 16384,
+            },
+            Self::T22xlarge => InstanceInfo {
+                cpus: 8000,
+                memory: 32768,
+            },
             Self::T3Nano => InstanceInfo {
                 cpus: 2000,
                 memory: 512,
@@ -151,27 +249,51 @@ impl InstanceType {
                 cpus: 8000,
                 memory: 32768,
             },
+            Self::M5Large => InstanceInfo {
+                cpus: 2000,
+                memory: 8192,
+            },
+            Self::M5Xlarge => InstanceInfo {
+                cpus: 4000,
+                memory: 16384,
+            },
+            Self::M52xlarge => InstanceInfo {
+                cpus: 8000,
+                memory: 32768,
+            },
+            Self::M54xlarge => InstanceInfo {
+                cpus: 16000,
+                memory: 65536,
+            },
+            Self::C5Large => InstanceInfo {
+                cpus: 2000,
+                memory: 4096,
+            },
+            Self::C5Xlarge => InstanceInfo {
+                cpus: 4000,
+                memory: 8192,
+            },
+            Self::C52xlarge => InstanceInfo {
+                cpus: 8000,
+                memory: 16384,
+            },
+            Self::C54xlarge => InstanceInfo {
+                cpus: 16000,
+                memory: 32768,
+            },
         }
     }
 }
 
-impl From<&str> for InstanceType {
-    /// Creates an `InstanceType` from a string.
-    ///
-    /// # Panics
-    ///
-    /// Panics if the string is not a valid instance type.
-    fn from(value: &str) -> Self {
-        match value {
-            "t3.nano" => Self::T3Nano,
-            "t3.micro" => Self::T3Micro,
-            "t3.small" => Self::T3Small,
-            "t3.medium" => Self::T3Medium,
-            "t3.large" => Self::T3Large,
-            "t3.xlarge" => Self::T3Xlarge,
-            "t3.2xlarge" => Self::T32xlarge,
-            _ => panic!("Invalid instance type: {value}"),
-        }
+impl TryFrom<&str> for InstanceType {
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    /// Parses the AWS instance type name, e.g. `t3.micro`
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::ALL
+            .into_iter()
+            .find(|instance_type| instance_type.as_str() == value)
+            .ok_or_else(|| format!("Unknown instance type: {value}").into())
     }
 }
 
@@ -203,6 +325,7 @@ mod tests {
         assert_eq!(RecordType::from("NS"), RecordType::NS);
         assert_eq!(RecordType::from("SOA"), RecordType::SOA);
         assert_eq!(RecordType::from("TXT"), RecordType::TXT);
+        assert_eq!(RecordType::from("CNAME"), RecordType::CNAME);
     }
 
     #[test]
@@ -229,6 +352,10 @@ mod tests {
             RecordType::from(aws_sdk_route53::types::RrType::Txt),
             RecordType::TXT
         );
+        assert_eq!(
+            RecordType::from(aws_sdk_route53::types::RrType::Cname),
+            RecordType::CNAME
+        );
     }
     #[test]
     #[should_panic(expected = "Invalid record type: AAAA")]
@@ -242,6 +369,7 @@ mod tests {
         assert_eq!(RecordType::NS.as_str(), "NS");
         assert_eq!(RecordType::SOA.as_str(), "SOA");
         assert_eq!(RecordType::TXT.as_str(), "TXT");
+        assert_eq!(RecordType::CNAME.as_str(), "CNAME");
     }
 
     #[test]
@@ -269,15 +397,73 @@ mod tests {
     }
 
     #[test]
-    fn test_instance_type_from_str() {
-        assert_eq!(InstanceType::from("t3.nano"), InstanceType::T3Nano);
-        assert_eq!(InstanceType::from("t3.2xlarge"), InstanceType::T32xlarge);
+    fn test_instance_type_get_info_other_families() {
+        assert_eq!(
+            InstanceType::T2Micro.get_info(),
+            InstanceInfo {
+                cpus: 1000,
+                memory: 1024
+            }
+        );
+        assert_eq!(
+            InstanceType::M5Xlarge.get_info(),
+            InstanceInfo {
+                cpus: 4000,
+                memory: 16384
+            }
+        );
+        assert_eq!(
+            InstanceType::C54xlarge.get_info(),
+            InstanceInfo {
+                cpus: 16000,
+                memory: 32768
+            }
+        );
+    }
+
+    #[test]
+    fn test_instance_type_try_from_str() {
+        assert_eq!(
+            InstanceType::try_from("t3.nano").expect("Failed to parse instance type"),
+            InstanceType::T3Nano
+        );
+        assert_eq!(
+            InstanceType::try_from("t3.2xlarge").expect("Failed to parse instance type"),
+            InstanceType::T32xlarge
+        );
+        assert_eq!(
+            InstanceType::try_from("t2.medium").expect("Failed to parse instance type"),
+            InstanceType::T2Medium
+        );
+        assert_eq!(
+            InstanceType::try_from("m5.2xlarge").expect("Failed to parse instance type"),
+            InstanceType::M52xlarge
+        );
+        assert_eq!(
+            InstanceType::try_from("c5.large").expect("Failed to parse instance type"),
+            InstanceType::C5Large
+        );
+    }
+
+    #[test]
+    fn test_instance_type_try_from_str_round_trip() {
+        for instance_type in InstanceType::ALL {
+            assert_eq!(
+                InstanceType::try_from(instance_type.as_str())
+                    .expect("Failed to parse instance type"),
+                instance_type
+            );
+        }
     }
 
     #[test]
-    #[should_panic(expected = "Invalid instance type: invalid")]
-    fn test_instance_type_from_str_invalid() {
-        let _ = InstanceType::from("invalid");
+    fn test_instance_type_try_from_str_unknown() {
+        assert_eq!(
+            InstanceType::try_from("x2iedn.32xlarge")
+                .expect_err("Expected unknown instance type error")
+                .to_string(),
+            "Unknown instance type: x2iedn.32xlarge"
+        );
     }
 
     #[test]
@@ -288,6 +474,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_from_resources_fits_t3_nano_millicores_request() {
+        assert_eq!(
+            InstanceType::from_resources(250, 256),
+            Some(InstanceType::T3Nano)
+        );
+    }
+
     #[test]
     fn test_from_resources_fits_t3_nano_exact_request() {
         assert_eq!(
@@ -336,6 +530,11 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_instance_type_largest() {
+        assert_eq!(InstanceType::largest(), InstanceType::T32xlarge);
+    }
+
     #[test]
     fn test_from_resources_no_fit_cpu_overflow() {
         assert_eq!(InstanceType::from_resources(8001, 32768), None);
@@ -0,0 +1,81 @@
+#[cfg(test)]
+use mockall::automock;
+
+/// GCP Compute Engine client implementation
+///
+/// Skeleton for the GCP provider, API calls are not implemented yet
+#[derive(Debug)]
+pub struct ComputeImpl {
+    project_id: String,
+}
+
+#[cfg_attr(test, allow(dead_code))]
+#[cfg_attr(test, automock)]
+impl ComputeImpl {
+    pub fn new(project_id: String) -> Self {
+        Self { project_id }
+    }
+
+    /// Create VPC network
+    pub async fn create_network(
+        &self,
+        name: String,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Err(format!(
+            "Failed to create network '{name}' in project '{}': GCP Compute API is not \
+             implemented yet",
+            self.project_id
+        )
+        .into())
+    }
+
+    /// Delete VPC network
+    pub async fn delete_network(
+        &self,
+        name: String,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        Err(format!(
+            "Failed to delete network '{name}' in project '{}': GCP Compute API is not \
+             implemented yet",
+            self.project_id
+        )
+        .into())
+    }
+
+    /// Create VM instance, returns instance name and public IP
+    pub async fn create_instance(
+        &self,
+        name: String,
+        _network: String,
+        _zone: String,
+        _machine_type: String,
+        _image: String,
+        _startup_script: String,
+    ) -> Result<(String, String), Box<dyn std::error::Error + Send + Sync>> {
+        Err(format!(
+            "Failed to create instance '{name}' in project '{}': GCP Compute API is not \
+             implemented yet",
+            self.project_id
+        )
+        .into())
+    }
+
+    /// Delete VM instance
+    pub async fn delete_instance(
+        &self,
+        name: String,
+        _zone: String,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        Err(format!(
+            "Failed to delete instance '{name}' in project '{}': GCP Compute API is not \
+             implemented yet",
+            self.project_id
+        )
+        .into())
+    }
+}
+
+#[cfg(not(test))]
+pub use ComputeImpl as Compute;
+#[cfg(test)]
+pub use MockComputeImpl as Compute;
@@ -0,0 +1,63 @@
+use petgraph::Graph;
+
+use crate::gcp::resource::{NetworkSpec, ResourceSpecType, SpecNode, VmSpec};
+
+/// Generates spec graph with a single VM in a dedicated network
+pub fn get_spec_graph(
+    zone: &str,
+    machine_type: &str,
+    startup_script: String,
+) -> Graph<SpecNode, String> {
+    let mut deps = Graph::<SpecNode, String>::new();
+    let root = deps.add_node(SpecNode::Root);
+
+    let network_1 = deps.add_node(SpecNode::Resource(ResourceSpecType::Network(NetworkSpec {
+        name: String::from("network-1"),
+    })));
+
+    let vm = deps.add_node(SpecNode::Resource(ResourceSpecType::Vm(VmSpec {
+        name: String::from("vm-1"),
+        zone: zone.to_string(),
+        machine_type: machine_type.to_string(),
+        image: String::from("projects/ubuntu-os-cloud/global/images/family/ubuntu-2404-lts-amd64"),
+        startup_script,
+    })));
+
+    deps.extend_with_edges([
+        (root, network_1, String::new()),
+        (network_1, vm, String::new()),
+    ]);
+
+    deps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::graph::kahn_traverse;
+
+    #[test]
+    fn test_get_spec_graph() {
+        // Act
+        let graph = get_spec_graph("us-central1-a", "e2-medium", String::from("#!/bin/bash"));
+
+        // Assert
+        assert_eq!(graph.node_count(), 3);
+        assert_eq!(graph.edge_count(), 2);
+
+        let sorted_nodes = kahn_traverse(&graph).expect("Failed to traverse graph");
+        let sorted_specs: Vec<&SpecNode> =
+            sorted_nodes.iter().map(|index| &graph[*index]).collect();
+
+        assert!(matches!(sorted_specs[0], SpecNode::Root));
+        assert!(matches!(
+            sorted_specs[1],
+            SpecNode::Resource(ResourceSpecType::Network(_))
+        ));
+        assert!(matches!(
+            sorted_specs[2],
+            SpecNode::Resource(ResourceSpecType::Vm(VmSpec { machine_type, .. }))
+                if machine_type == "e2-medium"
+        ));
+    }
+}
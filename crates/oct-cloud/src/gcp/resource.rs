@@ -0,0 +1,256 @@
+use serde::{Deserialize, Serialize};
+
+use crate::gcp::client;
+use crate::infra::resource::Manager;
+
+#[derive(Debug)]
+pub struct NetworkSpec {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Network {
+    pub id: String,
+    pub name: String,
+}
+
+pub struct NetworkManager<'a> {
+    pub client: &'a client::Compute,
+}
+
+impl Manager<'_, NetworkSpec, Network, Node> for NetworkManager<'_> {
+    async fn create(
+        &self,
+        input: &'_ NetworkSpec,
+        _parents: Vec<&'_ Node>,
+    ) -> Result<Network, Box<dyn std::error::Error + Send + Sync>> {
+        let id = self.client.create_network(input.name.clone()).await?;
+
+        Ok(Network {
+            id,
+            name: input.name.clone(),
+        })
+    }
+
+    async fn destroy(
+        &self,
+        input: &'_ Network,
+        _parents: Vec<&'_ Node>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.client.delete_network(input.name.clone()).await
+    }
+}
+
+#[derive(Debug)]
+pub struct VmSpec {
+    pub name: String,
+    pub zone: String,
+    pub machine_type: String,
+    pub image: String,
+    pub startup_script: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Vm {
+    pub name: String,
+    pub zone: String,
+    pub public_ip: String,
+    pub machine_type: String,
+    pub image: String,
+}
+
+pub struct VmManager<'a> {
+    pub client: &'a client::Compute,
+}
+
+impl Manager<'_, VmSpec, Vm, Node> for VmManager<'_> {
+    async fn create(
+        &self,
+        input: &'_ VmSpec,
+        parents: Vec<&'_ Node>,
+    ) -> Result<Vm, Box<dyn std::error::Error + Send + Sync>> {
+        let network_node = parents
+            .iter()
+            .find(|parent| matches!(parent, Node::Resource(ResourceType::Network(_))));
+
+        let network_name =
+            if let Some(Node::Resource(ResourceType::Network(network))) = network_node {
+                Ok(network.name.clone())
+            } else {
+                Err("VM expects Network as a parent")
+            }?;
+
+        let (name, public_ip) = self
+            .client
+            .create_instance(
+                input.name.clone(),
+                network_name,
+                input.zone.clone(),
+                input.machine_type.clone(),
+                input.image.clone(),
+                input.startup_script.clone(),
+            )
+            .await?;
+
+        Ok(Vm {
+            name,
+            zone: input.zone.clone(),
+            public_ip,
+            machine_type: input.machine_type.clone(),
+            image: input.image.clone(),
+        })
+    }
+
+    async fn destroy(
+        &self,
+        input: &'_ Vm,
+        _parents: Vec<&'_ Node>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.client
+            .delete_instance(input.name.clone(), input.zone.clone())
+            .await
+    }
+}
+
+#[derive(Debug)]
+pub enum ResourceSpecType {
+    Network(NetworkSpec),
+    Vm(VmSpec),
+}
+
+#[derive(Debug, Default)]
+pub enum SpecNode {
+    /// The synthetic root node.
+    #[default]
+    Root,
+    /// A resource spec in the dependency graph.
+    Resource(ResourceSpecType),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ResourceType {
+    Network(Network),
+    Vm(Vm),
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum Node {
+    /// The synthetic root node.
+    #[default]
+    Root,
+    /// A cloud resource in the dependency graph.
+    Resource(ResourceType),
+}
+
+#[cfg(test)]
+mod tests {
+    use mockall::predicate::eq;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_create_network() {
+        // Arrange
+        let mut compute_client_mock = client::Compute::default();
+        compute_client_mock
+            .expect_create_network()
+            .with(eq("network-1".to_string()))
+            .return_once(|_| Ok("network-id-1".to_string()));
+
+        let network_manager = NetworkManager {
+            client: &compute_client_mock,
+        };
+
+        let network_spec = NetworkSpec {
+            name: "network-1".to_string(),
+        };
+
+        // Act
+        let network = network_manager.create(&network_spec, vec![]).await;
+
+        // Assert
+        assert_eq!(
+            network.expect("Failed to create network"),
+            Network {
+                id: "network-id-1".to_string(),
+                name: "network-1".to_string(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_vm() {
+        // Arrange
+        let mut compute_client_mock = client::Compute::default();
+        compute_client_mock
+            .expect_create_instance()
+            .with(
+                eq("vm-1".to_string()),
+                eq("network-1".to_string()),
+                eq("us-central1-a".to_string()),
+                eq("e2-medium".to_string()),
+                eq("ubuntu-2404-lts".to_string()),
+                eq("#!/bin/bash".to_string()),
+            )
+            .return_once(|_, _, _, _, _, _| Ok(("vm-1".to_string(), "1.2.3.4".to_string())));
+
+        let vm_manager = VmManager {
+            client: &compute_client_mock,
+        };
+
+        let vm_spec = VmSpec {
+            name: "vm-1".to_string(),
+            zone: "us-central1-a".to_string(),
+            machine_type: "e2-medium".to_string(),
+            image: "ubuntu-2404-lts".to_string(),
+            startup_script: "#!/bin/bash".to_string(),
+        };
+
+        let network_node = Node::Resource(ResourceType::Network(Network {
+            id: "network-id-1".to_string(),
+            name: "network-1".to_string(),
+        }));
+
+        // Act
+        let vm = vm_manager.create(&vm_spec, vec![&network_node]).await;
+
+        // Assert
+        assert_eq!(
+            vm.expect("Failed to create VM"),
+            Vm {
+                name: "vm-1".to_string(),
+                zone: "us-central1-a".to_string(),
+                public_ip: "1.2.3.4".to_string(),
+                machine_type: "e2-medium".to_string(),
+                image: "ubuntu-2404-lts".to_string(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_vm_missing_network_parent() {
+        // Arrange
+        let compute_client_mock = client::Compute::default();
+
+        let vm_manager = VmManager {
+            client: &compute_client_mock,
+        };
+
+        let vm_spec = VmSpec {
+            name: "vm-1".to_string(),
+            zone: "us-central1-a".to_string(),
+            machine_type: "e2-medium".to_string(),
+            image: "ubuntu-2404-lts".to_string(),
+            startup_script: "#!/bin/bash".to_string(),
+        };
+
+        // Act
+        let vm = vm_manager.create(&vm_spec, vec![]).await;
+
+        // Assert
+        assert_eq!(
+            vm.expect_err("Expected missing parent error").to_string(),
+            "VM expects Network as a parent"
+        );
+    }
+}
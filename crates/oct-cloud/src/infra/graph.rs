@@ -4,26 +4,90 @@ use petgraph::dot::Dot;
 use petgraph::graph::NodeIndex;
 use petgraph::visit::NodeIndexable;
 use petgraph::{Graph, Incoming, Outgoing};
+use tokio::sync::mpsc;
 
 use crate::aws::{client, types};
 use crate::infra::resource::{
     DnsRecordManager, DnsRecordSpec, Ecr, EcrManager, EcrSpec, HostedZoneManager, HostedZoneSpec,
     InboundRule, InstanceProfileManager, InstanceProfileSpec, InstanceRoleManager,
-    InstanceRoleSpec, InternetGatewayManager, InternetGatewaySpec, Manager, Node, ResourceSpecType,
-    ResourceType, RouteTableManager, RouteTableSpec, SecurityGroupManager, SecurityGroupSpec,
-    SpecNode, SubnetManager, SubnetSpec, Vm, VmManager, VmSpec, VpcManager, VpcSpec,
+    InstanceRoleSpec, InternetGatewayManager, InternetGatewaySpec, LoadBalancer,
+    LoadBalancerManager, LoadBalancerSpec, Manager, Node, ResourceSpecType, ResourceType,
+    RouteTableManager, RouteTableSpec, SecurityGroupManager, SecurityGroupSpec, SpecNode, Subnet,
+    SubnetManager, SubnetSpec, Vm, VmManager, VmSpec, Vpc, VpcManager, VpcSpec,
 };
 
+/// CIDR block of the VPC, subnets are carved from it with
+/// [`allocate_subnet_cidrs`]
+const VPC_CIDR_BLOCK: &str = "10.0.0.0/16";
+
+/// Prefix length of the subnets allocated from the VPC CIDR block
+const SUBNET_PREFIX_LENGTH: u32 = 24;
+
+/// Resource deployment progress event emitted by [`GraphManager::deploy`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeployEvent {
+    ResourceCreateStarted { name: String },
+    ResourceCreated { name: String },
+    ResourceFailed { name: String, error: String },
+}
+
+/// Domain routed to the deployed VM by [`GraphManager::get_genesis_graph`]
+/// and [`GraphManager::get_spec_graph`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DomainSpec {
+    pub name: String,
+    /// Time to live in seconds of the DNS records
+    pub ttl: i64,
+    /// Also creates `*.<name>` record routing all subdomains to the VM
+    pub wildcard: bool,
+}
+
+impl DomainSpec {
+    pub const DEFAULT_TTL: i64 = 3600;
+}
+
+/// Leader VM deployed by [`GraphManager::get_genesis_graph`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeaderVmSpec {
+    pub instance_type: types::InstanceType,
+    /// Image of the deployment region, see [`crate::aws::ami::AmiResolver`]
+    pub ami: String,
+}
+
+/// Project infra deployed next to the Leader VM by
+/// [`GraphManager::get_genesis_graph`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProjectInfraSpec {
+    /// Domain routed to the Leader VM, it requires the Leader VM
+    pub domain: Option<DomainSpec>,
+    /// ECR repositories of the service images, e.g. one per service
+    pub ecrs: Vec<EcrSpec>,
+    /// Instance port an application load balancer forwards the traffic to,
+    /// no load balancer when `None`. It requires the Leader VM and a new VPC
+    pub load_balancer_port: Option<u16>,
+}
+
+/// Network created outside of oct, used by [`GraphManager::get_genesis_graph`]
+/// instead of creating a new VPC
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExistingNetworkSpec {
+    pub vpc_id: String,
+    /// Subnet of the Leader VM, it has to route to an internet gateway and
+    /// assign public IPs
+    pub subnet_id: String,
+}
+
 pub struct GraphManager {
     ec2: client::Ec2,
     iam: client::IAM,
     ecr: client::ECR,
     route53: client::Route53,
+    elbv2: client::ELBv2,
 }
 
 impl GraphManager {
-    pub async fn new() -> Self {
-        let region_provider = aws_sdk_ec2::config::Region::new("us-west-2");
+    pub async fn new(region: &str) -> Self {
+        let region_provider = aws_sdk_ec2::config::Region::new(region.to_string());
         let config = aws_config::defaults(aws_config::BehaviorVersion::latest())
             .region(region_provider)
             .load()
@@ -33,12 +97,14 @@ impl GraphManager {
         let iam_client = client::IAM::new(aws_sdk_iam::Client::new(&config));
         let ecr_client = client::ECR::new(aws_sdk_ecr::Client::new(&config));
         let route53_client = client::Route53::new(aws_sdk_route53::Client::new(&config));
+        let elbv2_client = client::ELBv2::new(aws_sdk_elasticloadbalancingv2::Client::new(&config));
 
         Self {
             ec2: ec2_client,
             iam: iam_client,
             ecr: ecr_client,
             route53: route53_client,
+            elbv2: elbv2_client,
         }
     }
 
@@ -48,63 +114,108 @@ impl GraphManager {
         iam_client: client::IAM,
         ecr_client: client::ECR,
         route53_client: client::Route53,
+        elbv2_client: client::ELBv2,
     ) -> Self {
         Self {
             ec2: ec2_client,
             iam: iam_client,
             ecr: ecr_client,
             route53: route53_client,
+            elbv2: elbv2_client,
         }
     }
 
+    /// Generates VM boot script which installs `podman` and starts `oct-ctl`
+    ///
+    /// The script runs with `set -e` and stops at the first failing command.
+    /// When `oct_ctl_sha256` is provided, the downloaded `oct-ctl` binary is
+    /// verified with `sha256sum -c` and the script aborts on mismatch.
+    /// `oct_ctl_token` is passed to `oct-ctl` as `OCT_CTL_TOKEN` to require
+    /// the bearer token on its API
+    fn get_user_data(oct_ctl_sha256: Option<&str>, oct_ctl_token: Option<&str>) -> String {
+        const OCT_CTL_URL: &str =
+            "https://github.com/opencloudtool/opencloudtool/releases/download/tip/oct-ctl";
+
+        let oct_ctl_envs = oct_ctl_token
+            .map(|oct_ctl_token| format!("OCT_CTL_TOKEN={oct_ctl_token} "))
+            .unwrap_or_default();
+
+        let oct_ctl_checksum = oct_ctl_sha256
+            .map(|oct_ctl_sha256| {
+                format!(
+                    r#"
+        echo "{oct_ctl_sha256}  /home/ubuntu/oct-ctl" | sha256sum -c -
+"#
+                )
+            })
+            .unwrap_or_default();
+
+        format!(
+            r#"#!/bin/bash
+        set -e
+
+        sudo apt update
+        sudo apt -y install podman
+        sudo systemctl start podman
+
+        curl \
+            --output /home/ubuntu/oct-ctl \
+            -L \
+            {OCT_CTL_URL}
+{oct_ctl_checksum}
+        sudo chmod +x /home/ubuntu/oct-ctl
+        sudo {oct_ctl_envs}/home/ubuntu/oct-ctl &
+
+        sudo snap install aws-cli --classic || true
+        "#
+        )
+    }
+
     /// Generates spec graph for the Genesis step
     ///
     /// Contains only the minimal required infra components to deploy the Leader
-    /// node
-    pub fn get_genesis_graph(instance_type: types::InstanceType) -> Graph<SpecNode, String> {
+    /// node, without `leader_vm` only the shared networking and instance
+    /// role are created, without the Leader node VM
+    ///
+    /// With `existing_network` the VPC and the subnet are reused as is, the
+    /// internet gateway and the route table are not created
+    ///
+    /// `project_infra` adds the project resources routing to the Leader VM
+    pub fn get_genesis_graph(
+        leader_vm: Option<LeaderVmSpec>,
+        oct_ctl_sha256: Option<&str>,
+        oct_ctl_token: Option<&str>,
+        region: &str,
+        existing_network: Option<&ExistingNetworkSpec>,
+        project_infra: &ProjectInfraSpec,
+    ) -> Graph<SpecNode, String> {
         let mut deps = Graph::<SpecNode, String>::new();
         let root = deps.add_node(SpecNode::Root);
 
         let vpc_1 = deps.add_node(SpecNode::Resource(ResourceSpecType::Vpc(VpcSpec {
-            region: String::from("us-west-2"),
-            cidr_block: String::from("10.0.0.0/16"),
+            region: region.to_string(),
+            cidr_block: VPC_CIDR_BLOCK.to_string(),
             name: String::from("vpc-1"),
+            existing_id: existing_network.map(|network| network.vpc_id.clone()),
         })));
 
-        let igw_1 = deps.add_node(SpecNode::Resource(ResourceSpecType::InternetGateway(
-            InternetGatewaySpec,
-        )));
-
-        let route_table_1 = deps.add_node(SpecNode::Resource(ResourceSpecType::RouteTable(
-            RouteTableSpec,
-        )));
-
         let subnet_1 = deps.add_node(SpecNode::Resource(ResourceSpecType::Subnet(SubnetSpec {
             name: String::from("vpc-1-subnet"),
-            cidr_block: String::from("10.0.1.0/24"),
-            availability_zone: String::from("us-west-2a"),
+            cidr_block: get_subnet_cidr_block(0),
+            availability_zone: format!("{region}a"),
+            existing_id: existing_network.map(|network| network.subnet_id.clone()),
         })));
 
+        // The load balancer forwards to the Leader VM, it is not created in
+        // an existing VPC which may overlap with its second subnet
+        let load_balancer_port = project_infra
+            .load_balancer_port
+            .filter(|_| leader_vm.is_some() && existing_network.is_none());
+
         let security_group_1 = deps.add_node(SpecNode::Resource(ResourceSpecType::SecurityGroup(
             SecurityGroupSpec {
                 name: String::from("vpc-1-security-group"),
-                inbound_rules: vec![
-                    InboundRule {
-                        cidr_block: "0.0.0.0/0".to_string(),
-                        protocol: "tcp".to_string(),
-                        port: 80,
-                    },
-                    InboundRule {
-                        cidr_block: "0.0.0.0/0".to_string(),
-                        protocol: "tcp".to_string(),
-                        port: 31888,
-                    },
-                    InboundRule {
-                        cidr_block: "0.0.0.0/0".to_string(),
-                        protocol: "tcp".to_string(),
-                        port: 22,
-                    },
-                ],
+                inbound_rules: get_inbound_rules(load_balancer_port),
             },
         )));
 
@@ -139,44 +250,92 @@ impl GraphManager {
             }),
         ));
 
-        let user_data = String::from(
-            r#"#!/bin/bash
-        sudo apt update
-        sudo apt -y install podman
-        sudo systemctl start podman
-
-        curl \
-            --output /home/ubuntu/oct-ctl \
-            -L \
-            https://github.com/opencloudtool/opencloudtool/releases/download/tip/oct-ctl \
-            && sudo chmod +x /home/ubuntu/oct-ctl \
-            && sudo /home/ubuntu/oct-ctl &
-
-        sudo snap install aws-cli --classic || true
-        "#,
-        );
-
-        let vm = deps.add_node(SpecNode::Resource(ResourceSpecType::Vm(VmSpec {
-            instance_type,
-            ami: String::from("ami-04dd23e62ed049936"),
-            user_data,
-        })));
-
-        let edges = vec![
+        let mut edges = vec![
             (root, instance_role_1, String::new()),
             (root, vpc_1, String::new()),
             (vpc_1, security_group_1, String::new()),
             (vpc_1, subnet_1, String::new()),
-            (vpc_1, route_table_1, String::new()),
-            (vpc_1, igw_1, String::new()),
-            (igw_1, route_table_1, String::new()),
-            (route_table_1, subnet_1, String::new()),
             (instance_role_1, instance_profile_1, String::new()),
-            (subnet_1, vm, String::new()),
-            (instance_profile_1, vm, String::new()),
-            (security_group_1, vm, String::new()),
         ];
 
+        for ecr in &project_infra.ecrs {
+            let ecr = deps.add_node(SpecNode::Resource(ResourceSpecType::Ecr(ecr.clone())));
+
+            edges.insert(0, (root, ecr, String::new()));
+        }
+
+        // An existing subnet is already routed to the internet
+        let route_table_1 = if existing_network.is_none() {
+            let igw_1 = deps.add_node(SpecNode::Resource(ResourceSpecType::InternetGateway(
+                InternetGatewaySpec,
+            )));
+
+            let route_table_1 = deps.add_node(SpecNode::Resource(ResourceSpecType::RouteTable(
+                RouteTableSpec,
+            )));
+
+            edges.extend([
+                (vpc_1, route_table_1, String::new()),
+                (vpc_1, igw_1, String::new()),
+                (igw_1, route_table_1, String::new()),
+                (route_table_1, subnet_1, String::new()),
+            ]);
+
+            Some(route_table_1)
+        } else {
+            None
+        };
+
+        if let Some(leader_vm) = leader_vm {
+            let user_data = Self::get_user_data(oct_ctl_sha256, oct_ctl_token);
+
+            let vm = deps.add_node(SpecNode::Resource(ResourceSpecType::Vm(VmSpec {
+                instance_type: leader_vm.instance_type,
+                ami: leader_vm.ami,
+                user_data,
+            })));
+
+            edges.extend([
+                (subnet_1, vm, String::new()),
+                (instance_profile_1, vm, String::new()),
+                (security_group_1, vm, String::new()),
+            ]);
+
+            let load_balancer = match (load_balancer_port, route_table_1) {
+                (Some(target_port), Some(route_table_1)) => Some(Self::add_load_balancer_nodes(
+                    &mut deps,
+                    &mut edges,
+                    region,
+                    target_port,
+                    vpc_1,
+                    subnet_1,
+                    route_table_1,
+                    security_group_1,
+                    vm,
+                )),
+                _ => None,
+            };
+
+            if let Some(domain) = project_infra.domain.clone() {
+                // The records route to the load balancer instead of the VM when
+                // it is created, its DNS name requires a CNAME record
+                let (dns_target, record_type) = match load_balancer {
+                    Some(load_balancer) => (load_balancer, types::RecordType::CNAME),
+                    None => (vm, types::RecordType::A),
+                };
+
+                Self::add_domain_nodes(
+                    &mut deps,
+                    &mut edges,
+                    root,
+                    domain,
+                    region,
+                    dns_target,
+                    record_type,
+                );
+            }
+        }
+
         deps.extend_with_edges(&edges);
 
         deps
@@ -190,8 +349,27 @@ impl GraphManager {
         graph: &Graph<SpecNode, String>,
     ) -> Result<(Graph<Node, String>, Option<Vm>), Box<dyn std::error::Error + Send + Sync>> {
         let mut resource_graph = Graph::<Node, String>::new();
-        let mut edges = vec![];
 
+        let vm = self
+            .deploy_genesis_graph_into(graph, &mut resource_graph, None)
+            .await?;
+
+        Ok((resource_graph, vm))
+    }
+
+    /// Deploys the Genesis spec graph, adding created resources to the
+    /// provided resource graph as soon as they are created
+    ///
+    /// The resource graph always reflects the resources created so far, so it
+    /// can be saved as a partial state if the deployment is interrupted or a
+    /// resource fails, the first failure stops the deployment and is returned.
+    /// `events` receives the same [`DeployEvent`]s as in [`Self::deploy`]
+    pub async fn deploy_genesis_graph_into(
+        &self,
+        graph: &Graph<SpecNode, String>,
+        resource_graph: &mut Graph<Node, String>,
+        events: Option<mpsc::Sender<DeployEvent>>,
+    ) -> Result<Option<Vm>, Box<dyn std::error::Error + Send + Sync>> {
         let mut parents: HashMap<NodeIndex, Vec<NodeIndex>> = HashMap::new();
 
         let mut vm: Option<Vm> = None;
@@ -209,6 +387,19 @@ impl GraphManager {
                 .collect();
 
             let node_to_deploy = &graph[*node_index];
+
+            let resource_name = match node_to_deploy {
+                SpecNode::Root => None,
+                SpecNode::Resource(resource_type) => Some(resource_type.name()),
+            };
+            if let Some(name) = &resource_name {
+                send_deploy_event(
+                    events.as_ref(),
+                    DeployEvent::ResourceCreateStarted { name: name.clone() },
+                )
+                .await;
+            }
+
             let deployed_node = match node_to_deploy {
                 SpecNode::Root => Ok(Node::Root),
                 SpecNode::Resource(resource_type) => match resource_type {
@@ -324,6 +515,19 @@ impl GraphManager {
                             Err(e) => Err(Box::new(e)),
                         }
                     }
+                    ResourceSpecType::LoadBalancer(resource) => {
+                        let manager = LoadBalancerManager {
+                            client: &self.elbv2,
+                        };
+                        let output_resource = manager.create(resource, parent_nodes).await;
+
+                        match output_resource {
+                            Ok(output_resource) => {
+                                Ok(Node::Resource(ResourceType::LoadBalancer(output_resource)))
+                            }
+                            Err(e) => Err(Box::new(e)),
+                        }
+                    }
                     ResourceSpecType::Vm(resource) => {
                         let manager = VmManager { client: &self.ec2 };
                         let output_vm = manager.create(resource, parent_nodes).await;
@@ -340,20 +544,44 @@ impl GraphManager {
                 },
             };
 
-            let Ok(deployed_node) = deployed_node else {
-                log::error!("Failed to create a resource {node_to_deploy:?} {deployed_node:?}");
+            let deployed_node = match deployed_node {
+                Ok(deployed_node) => deployed_node,
+                Err(e) => {
+                    log::error!("Failed to create a resource {node_to_deploy:?} {e:?}");
+                    log::info!("Created graph {}", Dot::new(&*resource_graph));
+
+                    let error = format!(
+                        "Failed to create {}: {e}",
+                        resource_name.as_deref().unwrap_or("a resource")
+                    );
+
+                    if let Some(name) = resource_name {
+                        send_deploy_event(
+                            events.as_ref(),
+                            DeployEvent::ResourceFailed {
+                                name,
+                                error: e.to_string(),
+                            },
+                        )
+                        .await;
+                    }
 
-                break;
+                    return Err(error.into());
+                }
             };
 
+            if let Some(name) = resource_name {
+                send_deploy_event(events.as_ref(), DeployEvent::ResourceCreated { name }).await;
+            }
+
             let created_resource_node_index = resource_graph.add_node(deployed_node.clone());
 
             for parent_node_index in parent_node_indexes {
-                edges.push((
+                resource_graph.add_edge(
                     parent_node_index,
                     created_resource_node_index,
                     String::new(),
-                ));
+                );
             }
 
             for neighbor_index in graph.neighbors(*node_index) {
@@ -364,24 +592,27 @@ impl GraphManager {
             }
         }
 
-        resource_graph.extend_with_edges(&edges);
-
-        log::info!("Created graph {}", Dot::new(&resource_graph));
+        log::info!("Created graph {}", Dot::new(&*resource_graph));
 
-        Ok((resource_graph, vm))
+        Ok(vm)
     }
 
     pub fn get_spec_graph(
         instance_type: &types::InstanceType,
-        domain_name: Option<String>,
+        domain: Option<DomainSpec>,
+        oct_ctl_sha256: Option<&str>,
+        ecr_keep_last_images: Option<u32>,
+        ecr_repositories: &[String],
+        load_balancer_port: Option<u16>,
     ) -> Graph<SpecNode, String> {
         let mut deps = Graph::<SpecNode, String>::new();
         let root = deps.add_node(SpecNode::Root);
 
         let vpc_1 = deps.add_node(SpecNode::Resource(ResourceSpecType::Vpc(VpcSpec {
             region: String::from("us-west-2"),
-            cidr_block: String::from("10.0.0.0/16"),
+            cidr_block: VPC_CIDR_BLOCK.to_string(),
             name: String::from("vpc-1"),
+            existing_id: None,
         })));
 
         let igw_1 = deps.add_node(SpecNode::Resource(ResourceSpecType::InternetGateway(
@@ -394,30 +625,15 @@ impl GraphManager {
 
         let subnet_1 = deps.add_node(SpecNode::Resource(ResourceSpecType::Subnet(SubnetSpec {
             name: String::from("vpc-1-subnet"),
-            cidr_block: String::from("10.0.1.0/24"),
+            cidr_block: get_subnet_cidr_block(0),
             availability_zone: String::from("us-west-2a"),
+            existing_id: None,
         })));
 
         let security_group_1 = deps.add_node(SpecNode::Resource(ResourceSpecType::SecurityGroup(
             SecurityGroupSpec {
                 name: String::from("vpc-1-security-group"),
-                inbound_rules: vec![
-                    InboundRule {
-                        cidr_block: "0.0.0.0/0".to_string(),
-                        protocol: "tcp".to_string(),
-                        port: 80,
-                    },
-                    InboundRule {
-                        cidr_block: "0.0.0.0/0".to_string(),
-                        protocol: "tcp".to_string(),
-                        port: 31888,
-                    },
-                    InboundRule {
-                        cidr_block: "0.0.0.0/0".to_string(),
-                        protocol: "tcp".to_string(),
-                        port: 22,
-                    },
-                ],
+                inbound_rules: get_inbound_rules(load_balancer_port),
             },
         )));
 
@@ -450,26 +666,25 @@ impl GraphManager {
             }),
         ));
 
-        let ecr_1 = deps.add_node(SpecNode::Resource(ResourceSpecType::Ecr(EcrSpec {
-            name: String::from("ecr_1"),
-        })));
-
-        let user_data = String::from(
-            r#"#!/bin/bash
-        sudo apt update
-        sudo apt -y install podman
-        sudo systemctl start podman
-
-        curl \
-            --output /home/ubuntu/oct-ctl \
-            -L \
-            https://github.com/opencloudtool/opencloudtool/releases/download/tip/oct-ctl \
-            && sudo chmod +x /home/ubuntu/oct-ctl \
-            && sudo /home/ubuntu/oct-ctl &
+        // One repository per service when their names are provided,
+        // otherwise all the services share `ecr_1`
+        let ecr_names = if ecr_repositories.is_empty() {
+            vec![String::from("ecr_1")]
+        } else {
+            ecr_repositories.to_vec()
+        };
+
+        let ecrs = ecr_names
+            .into_iter()
+            .map(|name| {
+                deps.add_node(SpecNode::Resource(ResourceSpecType::Ecr(EcrSpec {
+                    name,
+                    keep_last_images: ecr_keep_last_images,
+                })))
+            })
+            .collect::<Vec<_>>();
 
-        sudo snap install aws-cli --classic || true
-        "#,
-        );
+        let user_data = Self::get_user_data(oct_ctl_sha256, None);
 
         let vm = deps.add_node(SpecNode::Resource(ResourceSpecType::Vm(VmSpec {
             instance_type: *instance_type,
@@ -478,7 +693,6 @@ impl GraphManager {
         })));
 
         let mut edges = vec![
-            (root, ecr_1, String::new()),
             (root, instance_role_1, String::new()),
             (root, vpc_1, String::new()),
             (vpc_1, security_group_1, String::new()),
@@ -491,29 +705,44 @@ impl GraphManager {
             (subnet_1, vm, String::new()),
             (instance_profile_1, vm, String::new()),
             (security_group_1, vm, String::new()),
-            (ecr_1, vm, String::new()),
         ];
 
-        if let Some(domain_name) = domain_name {
-            let hosted_zone = deps.add_node(SpecNode::Resource(ResourceSpecType::HostedZone(
-                HostedZoneSpec {
-                    region: String::from("us-west-2"),
-                    name: domain_name,
-                },
-            )));
-
-            // Insert at the first place to deploy it after all other root's children
-            edges.insert(0, (root, hosted_zone, String::new()));
+        for ecr in ecrs {
+            edges.insert(0, (root, ecr, String::new()));
+            edges.push((ecr, vm, String::new()));
+        }
 
-            let dns_record = deps.add_node(SpecNode::Resource(ResourceSpecType::DnsRecord(
-                DnsRecordSpec {
-                    record_type: types::RecordType::A,
-                    ttl: Some(3600),
-                },
-            )));
+        let load_balancer = load_balancer_port.map(|target_port| {
+            Self::add_load_balancer_nodes(
+                &mut deps,
+                &mut edges,
+                "us-west-2",
+                target_port,
+                vpc_1,
+                subnet_1,
+                route_table_1,
+                security_group_1,
+                vm,
+            )
+        });
+
+        if let Some(domain) = domain {
+            // The records route to the load balancer instead of the VM when
+            // it is created, its DNS name requires a CNAME record
+            let (dns_target, record_type) = match load_balancer {
+                Some(load_balancer) => (load_balancer, types::RecordType::CNAME),
+                None => (vm, types::RecordType::A),
+            };
 
-            edges.push((vm, dns_record, String::new()));
-            edges.push((hosted_zone, dns_record, String::new()));
+            Self::add_domain_nodes(
+                &mut deps,
+                &mut edges,
+                root,
+                domain,
+                "us-west-2",
+                dns_target,
+                record_type,
+            );
         }
 
         deps.extend_with_edges(&edges);
@@ -521,23 +750,112 @@ impl GraphManager {
         deps
     }
 
+    /// Adds an application load balancer forwarding to the `vm` on the
+    /// `target_port`, returns the load balancer node
+    ///
+    /// Application load balancers require subnets in at least two
+    /// availability zones, the second subnet is used only by the load balancer
+    /// and is routed by the `route_table` of the VM `subnet`
+    fn add_load_balancer_nodes(
+        deps: &mut Graph<SpecNode, String>,
+        edges: &mut Vec<(NodeIndex, NodeIndex, String)>,
+        region: &str,
+        target_port: u16,
+        vpc: NodeIndex,
+        subnet: NodeIndex,
+        route_table: NodeIndex,
+        security_group: NodeIndex,
+        vm: NodeIndex,
+    ) -> NodeIndex {
+        let subnet_2 = deps.add_node(SpecNode::Resource(ResourceSpecType::Subnet(SubnetSpec {
+            name: String::from("vpc-1-subnet-2"),
+            cidr_block: get_subnet_cidr_block(1),
+            availability_zone: format!("{region}b"),
+            existing_id: None,
+        })));
+
+        let load_balancer = deps.add_node(SpecNode::Resource(ResourceSpecType::LoadBalancer(
+            LoadBalancerSpec {
+                name: String::from("load-balancer-1"),
+                target_port,
+            },
+        )));
+
+        edges.extend([
+            (vpc, subnet_2, String::new()),
+            (route_table, subnet_2, String::new()),
+            (vpc, load_balancer, String::new()),
+            (subnet, load_balancer, String::new()),
+            (subnet_2, load_balancer, String::new()),
+            (security_group, load_balancer, String::new()),
+            (vm, load_balancer, String::new()),
+        ]);
+
+        load_balancer
+    }
+
+    /// Adds the hosted zone of the `domain` and its records of the given
+    /// type routing to the `dns_target` node, `*.<domain>` too when the
+    /// domain is a wildcard one
+    fn add_domain_nodes(
+        deps: &mut Graph<SpecNode, String>,
+        edges: &mut Vec<(NodeIndex, NodeIndex, String)>,
+        root: NodeIndex,
+        domain: DomainSpec,
+        region: &str,
+        dns_target: NodeIndex,
+        record_type: types::RecordType,
+    ) {
+        let hosted_zone = deps.add_node(SpecNode::Resource(ResourceSpecType::HostedZone(
+            HostedZoneSpec {
+                region: region.to_string(),
+                name: domain.name,
+            },
+        )));
+
+        // Insert at the first place to deploy it after all other root's children
+        edges.insert(0, (root, hosted_zone, String::new()));
+
+        let dns_record = deps.add_node(SpecNode::Resource(ResourceSpecType::DnsRecord(
+            DnsRecordSpec {
+                record_type,
+                ttl: Some(domain.ttl),
+                wildcard: false,
+            },
+        )));
+
+        edges.push((dns_target, dns_record, String::new()));
+        edges.push((hosted_zone, dns_record, String::new()));
+
+        if domain.wildcard {
+            let wildcard_dns_record = deps.add_node(SpecNode::Resource(
+                ResourceSpecType::DnsRecord(DnsRecordSpec {
+                    record_type,
+                    ttl: Some(domain.ttl),
+                    wildcard: true,
+                }),
+            ));
+
+            edges.push((dns_target, wildcard_dns_record, String::new()));
+            edges.push((hosted_zone, wildcard_dns_record, String::new()));
+        }
+    }
+
     /// Deploy spec graph
     ///
-    /// Temporarily also returns a list of VMs and optional ECR
+    /// Temporarily also returns a list of VMs and the created ECRs
     /// to be used for user services deployment
     pub async fn deploy_spec_graph(
         &self,
         graph: &Graph<SpecNode, String>,
-    ) -> Result<
-        (Graph<Node, String>, Option<Vm>, Option<Ecr>),
-        Box<dyn std::error::Error + Send + Sync>,
-    > {
+    ) -> Result<(Graph<Node, String>, Option<Vm>, Vec<Ecr>), Box<dyn std::error::Error + Send + Sync>>
+    {
         let mut resource_graph = Graph::<Node, String>::new();
         let mut edges = vec![];
 
         let mut parents: HashMap<NodeIndex, Vec<NodeIndex>> = HashMap::new();
 
-        let mut ecr: Option<Ecr> = None;
+        let mut ecrs: Vec<Ecr> = Vec::new();
         let mut vm: Option<Vm> = None;
 
         let result = kahn_traverse(graph)?;
@@ -663,13 +981,26 @@ impl GraphManager {
 
                         match output_resource {
                             Ok(output_resource) => {
-                                ecr = Some(output_resource.clone());
+                                ecrs.push(output_resource.clone());
 
                                 Ok(Node::Resource(ResourceType::Ecr(output_resource)))
                             }
                             Err(e) => Err(Box::new(e)),
                         }
                     }
+                    ResourceSpecType::LoadBalancer(resource) => {
+                        let manager = LoadBalancerManager {
+                            client: &self.elbv2,
+                        };
+                        let output_resource = manager.create(resource, parent_nodes).await;
+
+                        match output_resource {
+                            Ok(output_resource) => {
+                                Ok(Node::Resource(ResourceType::LoadBalancer(output_resource)))
+                            }
+                            Err(e) => Err(Box::new(e)),
+                        }
+                    }
                     ResourceSpecType::Vm(resource) => {
                         let manager = VmManager { client: &self.ec2 };
                         let output_vm = manager.create(resource, parent_nodes).await;
@@ -714,13 +1045,17 @@ impl GraphManager {
 
         log::info!("Created graph {}", Dot::new(&resource_graph));
 
-        Ok((resource_graph, vm, ecr))
+        Ok((resource_graph, vm, ecrs))
     }
 
     /// Deploy arbitrary graph
+    ///
+    /// When `events` is provided, a [`DeployEvent`] is sent for each resource
+    /// creation start, success and failure
     pub async fn deploy(
         &self,
         graph: &Graph<SpecNode, String>,
+        events: Option<mpsc::Sender<DeployEvent>>,
     ) -> Result<Graph<Node, String>, Box<dyn std::error::Error + Send + Sync>> {
         let mut resource_graph = Graph::<Node, String>::new();
         let mut edges = vec![];
@@ -740,6 +1075,19 @@ impl GraphManager {
                 .collect();
 
             let node_to_deploy = &graph[*node_index];
+
+            let resource_name = match node_to_deploy {
+                SpecNode::Root => None,
+                SpecNode::Resource(resource_type) => Some(resource_type.name()),
+            };
+            if let Some(name) = &resource_name {
+                send_deploy_event(
+                    events.as_ref(),
+                    DeployEvent::ResourceCreateStarted { name: name.clone() },
+                )
+                .await;
+            }
+
             let deployed_node = match node_to_deploy {
                 SpecNode::Root => Ok(Node::Root),
                 SpecNode::Resource(resource_type) => match resource_type {
@@ -855,6 +1203,19 @@ impl GraphManager {
                             Err(e) => Err(Box::new(e)),
                         }
                     }
+                    ResourceSpecType::LoadBalancer(resource) => {
+                        let manager = LoadBalancerManager {
+                            client: &self.elbv2,
+                        };
+                        let output_resource = manager.create(resource, parent_nodes).await;
+
+                        match output_resource {
+                            Ok(output_resource) => {
+                                Ok(Node::Resource(ResourceType::LoadBalancer(output_resource)))
+                            }
+                            Err(e) => Err(Box::new(e)),
+                        }
+                    }
                     ResourceSpecType::Vm(resource) => {
                         let manager = VmManager { client: &self.ec2 };
                         let output_vm = manager.create(resource, parent_nodes).await;
@@ -867,12 +1228,30 @@ impl GraphManager {
                 },
             };
 
-            let Ok(created_node) = deployed_node else {
-                log::error!("Failed to create a resource {node_to_deploy:?}");
+            let created_node = match deployed_node {
+                Ok(created_node) => created_node,
+                Err(e) => {
+                    log::error!("Failed to create a resource {node_to_deploy:?}");
+
+                    if let Some(name) = resource_name {
+                        send_deploy_event(
+                            events.as_ref(),
+                            DeployEvent::ResourceFailed {
+                                name,
+                                error: e.to_string(),
+                            },
+                        )
+                        .await;
+                    }
 
-                break;
+                    break;
+                }
             };
 
+            if let Some(name) = resource_name {
+                send_deploy_event(events.as_ref(), DeployEvent::ResourceCreated { name }).await;
+            }
+
             let created_resource_node_index = resource_graph.add_node(created_node.clone());
 
             for parent_node_index in parent_node_indexes {
@@ -898,6 +1277,85 @@ impl GraphManager {
         Ok(resource_graph)
     }
 
+    /// Describes a resource created outside of oct to be imported into the
+    /// state, `resource_type` is `vpc` or `subnet`
+    ///
+    /// The resource is named by its ID and is managed as if oct created it,
+    /// so it is deleted on destroy
+    pub async fn describe_resource(
+        &self,
+        resource_type: &str,
+        id: &str,
+        region: &str,
+    ) -> Result<ResourceType, Box<dyn std::error::Error + Send + Sync>> {
+        match resource_type {
+            "vpc" => {
+                let cidr_block = self
+                    .ec2
+                    .get_vpc_cidr_block(id.to_string())
+                    .await?
+                    .ok_or_else(|| format!("VPC {id} is not found"))?;
+
+                Ok(ResourceType::Vpc(Vpc {
+                    id: id.to_string(),
+                    region: region.to_string(),
+                    cidr_block,
+                    name: id.to_string(),
+                    existing: false,
+                }))
+            }
+            "subnet" => {
+                let (cidr_block, availability_zone) = self
+                    .ec2
+                    .get_subnet_placement(id.to_string())
+                    .await?
+                    .ok_or_else(|| format!("Subnet {id} is not found"))?;
+
+                Ok(ResourceType::Subnet(Subnet {
+                    id: id.to_string(),
+                    name: id.to_string(),
+                    cidr_block,
+                    availability_zone,
+                    existing: false,
+                }))
+            }
+            _ => Err(format!(
+                "Unsupported resource type '{resource_type}', expected vpc or subnet"
+            )
+            .into()),
+        }
+    }
+
+    /// Adds the instances back to the load balancer target group
+    pub async fn register_load_balancer_targets(
+        &self,
+        load_balancer: &LoadBalancer,
+        instance_ids: Vec<String>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let manager = LoadBalancerManager {
+            client: &self.elbv2,
+        };
+
+        manager
+            .register_instances(load_balancer, instance_ids)
+            .await
+    }
+
+    /// Removes the instances from the load balancer target group
+    pub async fn deregister_load_balancer_targets(
+        &self,
+        load_balancer: &LoadBalancer,
+        instance_ids: Vec<String>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let manager = LoadBalancerManager {
+            client: &self.elbv2,
+        };
+
+        manager
+            .deregister_instances(load_balancer, instance_ids)
+            .await
+    }
+
     /// Destroys resource graph
     ///
     /// Modifies the input graph by deleting all the destroyed nodes.
@@ -906,6 +1364,39 @@ impl GraphManager {
     pub async fn destroy(
         &self,
         graph: &mut Graph<Node, String>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let batches = kahn_traverse(graph)
+            .map_err(|e| get_destroy_cycle_error(graph, &e))?
+            .into_iter()
+            .rev()
+            .map(|node_index| vec![node_index])
+            .collect();
+
+        self.destroy_batches(graph, batches).await
+    }
+
+    /// Same as [`Self::destroy`] but destroys independent resources of the
+    /// same dependency level concurrently, children are still destroyed
+    /// before their parents
+    pub async fn destroy_parallel(
+        &self,
+        graph: &mut Graph<Node, String>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let batches = get_dependency_levels(graph)
+            .map_err(|e| get_destroy_cycle_error(graph, &e))?
+            .into_iter()
+            .rev()
+            .collect();
+
+        self.destroy_batches(graph, batches).await
+    }
+
+    /// Destroys batches one after another, resources of a batch are destroyed
+    /// concurrently. Stops after the first batch with a failed resource.
+    async fn destroy_batches(
+        &self,
+        graph: &mut Graph<Node, String>,
+        batches: Vec<Vec<NodeIndex>>,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let mut parents: HashMap<NodeIndex, Vec<NodeIndex>> = HashMap::new();
 
@@ -937,89 +1428,46 @@ impl GraphManager {
             }
         }
 
-        let result = kahn_traverse(graph)?;
-
         let mut destroyed_nodes: Vec<NodeIndex> = Vec::new();
 
-        // Destroying resources in reversed order
-        for node_index in result.iter().rev() {
-            let parent_node_indexes = match parents.get(node_index) {
-                Some(parent_node_indexes) => parent_node_indexes.clone(),
-                None => Vec::new(),
-            };
-            let parent_nodes = parent_node_indexes
-                .iter()
-                .filter_map(|x| graph.node_weight(*x))
-                .collect();
+        for batch in batches {
+            let destroy_futures = batch.iter().map(|node_index| {
+                let parent_nodes = parents
+                    .get(node_index)
+                    .map(|parent_node_indexes| {
+                        parent_node_indexes
+                            .iter()
+                            .filter_map(|x| graph.node_weight(*x))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                self.destroy_node(&graph[*node_index], parent_nodes)
+            });
+            let destroy_results = futures::future::join_all(destroy_futures).await;
 
-            let node_to_destroy = &graph[*node_index];
-            let destroyed_node = match node_to_destroy {
-                Node::Root => Ok(()),
-                Node::Resource(resource_type) => match resource_type {
-                    ResourceType::HostedZone(resource) => {
-                        let manager = HostedZoneManager {
-                            client: &self.route53,
-                        };
-                        manager.destroy(resource, parent_nodes).await
-                    }
-                    ResourceType::DnsRecord(resource) => {
-                        let manager = DnsRecordManager {
-                            client: &self.route53,
-                        };
-                        manager.destroy(resource, parent_nodes).await
-                    }
-                    ResourceType::Vpc(resource) => {
-                        let manager = VpcManager { client: &self.ec2 };
-                        manager.destroy(resource, parent_nodes).await
-                    }
-                    ResourceType::InternetGateway(resource) => {
-                        let manager = InternetGatewayManager { client: &self.ec2 };
-                        manager.destroy(resource, parent_nodes).await
-                    }
-                    ResourceType::RouteTable(resource) => {
-                        let manager = RouteTableManager { client: &self.ec2 };
-                        manager.destroy(resource, parent_nodes).await
-                    }
-                    ResourceType::Subnet(resource) => {
-                        let manager = SubnetManager { client: &self.ec2 };
-                        manager.destroy(resource, parent_nodes).await
-                    }
-                    ResourceType::SecurityGroup(resource) => {
-                        let manager = SecurityGroupManager { client: &self.ec2 };
-                        manager.destroy(resource, parent_nodes).await
-                    }
-                    ResourceType::InstanceRole(resource) => {
-                        let manager = InstanceRoleManager { client: &self.iam };
-                        manager.destroy(resource, parent_nodes).await
-                    }
-                    ResourceType::InstanceProfile(resource) => {
-                        let manager = InstanceProfileManager { client: &self.iam };
-                        manager.destroy(resource, parent_nodes).await
-                    }
-                    ResourceType::Ecr(resource) => {
-                        let manager = EcrManager { client: &self.ecr };
-                        manager.destroy(resource, parent_nodes).await
-                    }
-                    ResourceType::Vm(resource) => {
-                        let manager = VmManager { client: &self.ec2 };
-                        manager.destroy(resource, parent_nodes).await
-                    }
-                    ResourceType::None => Err("Unexpected case ResourceType::None".into()),
-                },
-            };
+            let mut is_batch_failed = false;
 
-            match destroyed_node {
-                Ok(()) => {
-                    log::info!("Destroyed {node_to_destroy:?}");
+            for (node_index, destroyed_node) in batch.iter().zip(destroy_results) {
+                let node_to_destroy = &graph[*node_index];
 
-                    destroyed_nodes.push(*node_index);
-                }
-                Err(e) => {
-                    log::error!("Failed to destroy {node_to_destroy:?}: {e}");
+                match destroyed_node {
+                    Ok(()) => {
+                        log::info!("Destroyed {node_to_destroy:?}");
 
-                    break;
+                        destroyed_nodes.push(*node_index);
+                    }
+                    Err(e) => {
+                        log::error!("Failed to destroy {node_to_destroy:?}: {e}");
+
+                        is_batch_failed = true;
+                    }
                 }
             }
+
+            if is_batch_failed {
+                break;
+            }
         }
 
         graph.retain_nodes(|_, node_idx| !destroyed_nodes.contains(&node_idx));
@@ -1030,12 +1478,233 @@ impl GraphManager {
             Err("Failed to destroy some resources".into())
         }
     }
+
+    /// Destroys a single resource using its manager
+    async fn destroy_node(
+        &self,
+        node: &Node,
+        parent_nodes: Vec<&Node>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        match node {
+            Node::Root => Ok(()),
+            Node::Resource(resource_type) => match resource_type {
+                ResourceType::HostedZone(resource) => {
+                    let manager = HostedZoneManager {
+                        client: &self.route53,
+                    };
+                    manager.destroy(resource, parent_nodes).await
+                }
+                ResourceType::DnsRecord(resource) => {
+                    let manager = DnsRecordManager {
+                        client: &self.route53,
+                    };
+                    manager.destroy(resource, parent_nodes).await
+                }
+                ResourceType::Vpc(resource) => {
+                    let manager = VpcManager { client: &self.ec2 };
+                    manager.destroy(resource, parent_nodes).await
+                }
+                ResourceType::InternetGateway(resource) => {
+                    let manager = InternetGatewayManager { client: &self.ec2 };
+                    manager.destroy(resource, parent_nodes).await
+                }
+                ResourceType::RouteTable(resource) => {
+                    let manager = RouteTableManager { client: &self.ec2 };
+                    manager.destroy(resource, parent_nodes).await
+                }
+                ResourceType::Subnet(resource) => {
+                    let manager = SubnetManager { client: &self.ec2 };
+                    manager.destroy(resource, parent_nodes).await
+                }
+                ResourceType::SecurityGroup(resource) => {
+                    let manager = SecurityGroupManager { client: &self.ec2 };
+                    manager.destroy(resource, parent_nodes).await
+                }
+                ResourceType::InstanceRole(resource) => {
+                    let manager = InstanceRoleManager { client: &self.iam };
+                    manager.destroy(resource, parent_nodes).await
+                }
+                ResourceType::InstanceProfile(resource) => {
+                    let manager = InstanceProfileManager { client: &self.iam };
+                    manager.destroy(resource, parent_nodes).await
+                }
+                ResourceType::Ecr(resource) => {
+                    let manager = EcrManager { client: &self.ecr };
+                    manager.destroy(resource, parent_nodes).await
+                }
+                ResourceType::Vm(resource) => {
+                    let manager = VmManager { client: &self.ec2 };
+                    manager.destroy(resource, parent_nodes).await
+                }
+                ResourceType::LoadBalancer(resource) => {
+                    let manager = LoadBalancerManager {
+                        client: &self.elbv2,
+                    };
+                    manager.destroy(resource, parent_nodes).await
+                }
+                ResourceType::None => Err("Unexpected case ResourceType::None".into()),
+            },
+        }
+    }
+}
+
+/// Sends deployment event if the events channel is provided,
+/// a dropped receiver does not interrupt the deployment
+async fn send_deploy_event(events: Option<&mpsc::Sender<DeployEvent>>, event: DeployEvent) {
+    let Some(events) = events else {
+        return;
+    };
+
+    if events.send(event).await.is_err() {
+        log::warn!("Deployment events receiver is closed");
+    }
+}
+
+/// Splits `vpc_cidr` into `count` non-overlapping /24 subnet CIDR blocks
+///
+/// The blocks are allocated in order skipping the first one, so a single
+/// subnet keeps the `10.0.1.0/24` block of the existing deployments
+pub fn allocate_subnet_cidrs(
+    vpc_cidr: &str,
+    count: usize,
+) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let invalid_cidr = || format!("Invalid VPC CIDR block '{vpc_cidr}'");
+
+    let (address, prefix_length) = vpc_cidr.split_once('/').ok_or_else(invalid_cidr)?;
+    let address: std::net::Ipv4Addr = address.parse().map_err(|_| invalid_cidr())?;
+    let prefix_length: u32 = prefix_length
+        .parse()
+        .ok()
+        .filter(|prefix_length| *prefix_length <= SUBNET_PREFIX_LENGTH)
+        .ok_or_else(invalid_cidr)?;
+
+    let available = (1_u32 << (SUBNET_PREFIX_LENGTH - prefix_length)) - 1;
+    let count = u32::try_from(count)
+        .ok()
+        .filter(|count| *count <= available)
+        .ok_or_else(|| {
+            format!("VPC CIDR block '{vpc_cidr}' fits only {available} subnets, {count} requested")
+        })?;
+
+    let network = u32::from(address) & u32::MAX.checked_shl(32 - prefix_length).unwrap_or(0);
+    let subnet_size = 1_u32 << (32 - SUBNET_PREFIX_LENGTH);
+
+    Ok((1..=count)
+        .map(|index| {
+            let subnet_address = std::net::Ipv4Addr::from(network + subnet_size * index);
+
+            format!("{subnet_address}/{SUBNET_PREFIX_LENGTH}")
+        })
+        .collect())
+}
+
+/// Security group rules of the VMs, the load balancer forwards the traffic
+/// to the VMs on `load_balancer_port` from inside the VPC
+fn get_inbound_rules(load_balancer_port: Option<u16>) -> Vec<InboundRule> {
+    let mut inbound_rules = vec![
+        InboundRule {
+            cidr_block: "0.0.0.0/0".to_string(),
+            protocol: "tcp".to_string(),
+            port: 80,
+        },
+        InboundRule {
+            cidr_block: "0.0.0.0/0".to_string(),
+            protocol: "tcp".to_string(),
+            port: 31888,
+        },
+        InboundRule {
+            cidr_block: "0.0.0.0/0".to_string(),
+            protocol: "tcp".to_string(),
+            port: 22,
+        },
+    ];
+
+    if let Some(port) = load_balancer_port {
+        inbound_rules.push(InboundRule {
+            cidr_block: VPC_CIDR_BLOCK.to_string(),
+            protocol: "tcp".to_string(),
+            port: i32::from(port),
+        });
+    }
+
+    inbound_rules
+}
+
+/// CIDR block of the `index`-th subnet of the spec graphs
+fn get_subnet_cidr_block(index: usize) -> String {
+    allocate_subnet_cidrs(VPC_CIDR_BLOCK, index + 1)
+        .ok()
+        .and_then(|cidr_blocks| cidr_blocks.into_iter().nth(index))
+        .expect("VPC CIDR block fits the spec graph subnets")
+}
+
+/// Groups nodes by the longest path from the graph sources, nodes of the
+/// same level have no edges between each other
+fn get_dependency_levels<T>(graph: &Graph<T, String>) -> Result<Vec<Vec<NodeIndex>>, CycleError> {
+    let mut depths: HashMap<NodeIndex, usize> = HashMap::new();
+    let mut levels: Vec<Vec<NodeIndex>> = Vec::new();
+
+    for node_index in kahn_traverse(graph)? {
+        let depth = graph
+            .neighbors_directed(node_index, Incoming)
+            .filter_map(|parent_index| depths.get(&parent_index))
+            .map(|parent_depth| parent_depth + 1)
+            .max()
+            .unwrap_or(0);
+
+        depths.insert(node_index, depth);
+
+        if levels.len() <= depth {
+            levels.resize_with(depth + 1, Vec::new);
+        }
+        levels[depth].push(node_index);
+    }
+
+    Ok(levels)
+}
+
+/// Logs and returns the destroy error of a resource graph with a cycle, the
+/// resources left out of the destroy order are listed by name
+fn get_destroy_cycle_error(
+    graph: &Graph<Node, String>,
+    cycle_error: &CycleError,
+) -> Box<dyn std::error::Error + Send + Sync> {
+    let names = cycle_error
+        .nodes
+        .iter()
+        .map(|&node_index| match &graph[node_index] {
+            Node::Root => String::from("root"),
+            Node::Resource(resource_type) => resource_type.name(),
+        })
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    let message = format!("{cycle_error}, resources are not destroyed: {names}");
+    log::error!("{message}");
+
+    message.into()
+}
+
+/// Graph has a cycle, [`kahn_traverse`] can't order `nodes`: the nodes of
+/// the cycle and the ones depending on them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleError {
+    pub nodes: Vec<NodeIndex>,
+}
+
+impl std::fmt::Display for CycleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Cycle detected in graph")
+    }
 }
 
+impl std::error::Error for CycleError {}
+
 /// Kahn's Algorithm Implementation
-pub fn kahn_traverse<T>(
-    graph: &Graph<T, String>,
-) -> Result<Vec<NodeIndex>, Box<dyn std::error::Error + Send + Sync>> {
+///
+/// Nodes are ordered so each one comes after all of its parents, a cycle
+/// fails the traversal instead of dropping its nodes from the result
+pub fn kahn_traverse<T>(graph: &Graph<T, String>) -> Result<Vec<NodeIndex>, CycleError> {
     // 1. Calculate the in-degree for each node.
     let mut in_degrees = vec![0; graph.node_bound()];
     for node in graph.node_indices() {
@@ -1067,7 +1736,12 @@ pub fn kahn_traverse<T>(
     }
 
     if result.len() < graph.node_count() {
-        return Err("Cycle detected in graph".into());
+        let nodes = graph
+            .node_indices()
+            .filter(|&i| in_degrees[graph.to_index(i)] > 0)
+            .collect();
+
+        return Err(CycleError { nodes });
     }
 
     Ok(result)
@@ -1075,6 +1749,7 @@ pub fn kahn_traverse<T>(
 
 #[cfg(test)]
 mod tests {
+    use aws_sdk_ec2::types::{SubnetState, VpcState};
     use mockall::predicate::eq;
 
     use super::*;
@@ -1085,10 +1760,10 @@ mod tests {
     fn test_get_spec_graph_with_one_instance_no_domain() {
         // Arrange
         let instance_type = InstanceType::T3Micro;
-        let domain_name = None;
+        let domain = None;
 
         // Act
-        let graph = GraphManager::get_spec_graph(&instance_type, domain_name);
+        let graph = GraphManager::get_spec_graph(&instance_type, domain, None, None, &[], None);
 
         // Assert
         assert_eq!(graph.node_count(), 10);
@@ -1102,14 +1777,584 @@ mod tests {
         assert_eq!(vm_nodes_count, 1);
     }
 
+    #[test]
+    fn test_get_spec_graph_with_load_balancer() {
+        // Arrange
+        let instance_type = InstanceType::T3Micro;
+        let domain = Some(DomainSpec {
+            name: String::from("example.com"),
+            ttl: DomainSpec::DEFAULT_TTL,
+            wildcard: false,
+        });
+
+        // Act
+        let graph =
+            GraphManager::get_spec_graph(&instance_type, domain, None, None, &[], Some(8080));
+
+        // Assert
+        let subnet_cidr_blocks = graph
+            .raw_nodes()
+            .iter()
+            .filter_map(|node| match &node.weight {
+                SpecNode::Resource(ResourceSpecType::Subnet(subnet_spec)) => {
+                    Some(subnet_spec.cidr_block.clone())
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            subnet_cidr_blocks,
+            vec![String::from("10.0.1.0/24"), String::from("10.0.2.0/24")]
+        );
+
+        let load_balancer_index = graph
+            .node_indices()
+            .find(|index| {
+                matches!(
+                    &graph[*index],
+                    SpecNode::Resource(ResourceSpecType::LoadBalancer(_))
+                )
+            })
+            .expect("Expected LoadBalancer node");
+        let load_balancer_parents = graph
+            .neighbors_directed(load_balancer_index, Incoming)
+            .map(|index| graph[index].to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(load_balancer_parents.len(), 5);
+        assert!(load_balancer_parents.contains(&String::from("spec VM")));
+
+        let dns_record_spec = graph
+            .neighbors_directed(load_balancer_index, Outgoing)
+            .find_map(|index| match &graph[index] {
+                SpecNode::Resource(ResourceSpecType::DnsRecord(dns_record_spec)) => {
+                    Some(dns_record_spec)
+                }
+                _ => None,
+            })
+            .expect("Expected DnsRecord child");
+        assert_eq!(dns_record_spec.record_type, types::RecordType::CNAME);
+    }
+
+    #[test]
+    fn test_allocate_subnet_cidrs_single() {
+        // Act
+        let cidr_blocks =
+            allocate_subnet_cidrs("10.0.0.0/16", 1).expect("Failed to allocate subnet CIDRs");
+
+        // Assert
+        assert_eq!(cidr_blocks, vec![String::from("10.0.1.0/24")]);
+    }
+
+    #[test]
+    fn test_allocate_subnet_cidrs_multiple() {
+        // Act
+        let cidr_blocks =
+            allocate_subnet_cidrs("10.0.0.0/16", 3).expect("Failed to allocate subnet CIDRs");
+
+        // Assert
+        assert_eq!(
+            cidr_blocks,
+            vec![
+                String::from("10.0.1.0/24"),
+                String::from("10.0.2.0/24"),
+                String::from("10.0.3.0/24"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_allocate_subnet_cidrs_oversubscribed() {
+        // Act
+        let result = allocate_subnet_cidrs("10.0.0.0/22", 4);
+
+        // Assert
+        assert_eq!(
+            result
+                .expect_err("Expected over-subscription error")
+                .to_string(),
+            "VPC CIDR block '10.0.0.0/22' fits only 3 subnets, 4 requested"
+        );
+    }
+
+    #[test]
+    fn test_get_spec_graph_with_ecr_per_service() {
+        // Arrange
+        let instance_type = InstanceType::T3Micro;
+        let ecr_repositories = [String::from("api"), String::from("web")];
+
+        // Act
+        let graph = GraphManager::get_spec_graph(
+            &instance_type,
+            None,
+            None,
+            Some(5),
+            &ecr_repositories,
+            None,
+        );
+
+        // Assert
+        assert_eq!(graph.node_count(), 10 + 1);
+        assert_eq!(graph.edge_count(), 10 + 4 + 2);
+
+        let ecr_specs = graph
+            .raw_nodes()
+            .iter()
+            .filter_map(|node| match &node.weight {
+                SpecNode::Resource(ResourceSpecType::Ecr(ecr_spec)) => Some(ecr_spec),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ecr_specs,
+            vec![
+                &EcrSpec {
+                    name: String::from("api"),
+                    keep_last_images: Some(5),
+                },
+                &EcrSpec {
+                    name: String::from("web"),
+                    keep_last_images: Some(5),
+                },
+            ]
+        );
+
+        let vm_index = graph
+            .node_indices()
+            .find(|index| matches!(graph[*index], SpecNode::Resource(ResourceSpecType::Vm(_))))
+            .expect("Failed to find VM spec");
+        let vm_ecr_parents_count = graph
+            .neighbors_directed(vm_index, Incoming)
+            .filter(|index| matches!(graph[*index], SpecNode::Resource(ResourceSpecType::Ecr(_))))
+            .count();
+        assert_eq!(vm_ecr_parents_count, 2);
+    }
+
+    #[test]
+    fn test_get_user_data_without_checksum() {
+        // Act
+        let user_data = GraphManager::get_user_data(None, None);
+
+        // Assert
+        assert!(user_data.starts_with("#!/bin/bash\n        set -e\n"));
+        assert!(user_data.contains("\n        sudo chmod +x /home/ubuntu/oct-ctl\n"));
+        assert!(!user_data.contains("sha256sum"));
+    }
+
+    #[test]
+    fn test_get_user_data_with_checksum() {
+        // Act
+        let user_data = GraphManager::get_user_data(Some("abc123"), None);
+
+        // Assert
+        assert!(user_data.starts_with("#!/bin/bash\n        set -e\n"));
+        assert!(user_data.contains(r#"echo "abc123  /home/ubuntu/oct-ctl" | sha256sum -c -"#));
+
+        let checksum_position = user_data
+            .find("sha256sum -c")
+            .expect("Failed to find checksum verification");
+        let chmod_position = user_data
+            .find("sudo chmod +x /home/ubuntu/oct-ctl")
+            .expect("Failed to find chmod");
+        assert!(checksum_position < chmod_position);
+    }
+
+    #[test]
+    fn test_get_user_data_with_token() {
+        // Act
+        let user_data = GraphManager::get_user_data(None, Some("secret-token"));
+
+        // Assert
+        assert!(user_data.contains("sudo OCT_CTL_TOKEN=secret-token /home/ubuntu/oct-ctl &"));
+    }
+
+    fn get_leader_vm_spec() -> LeaderVmSpec {
+        LeaderVmSpec {
+            instance_type: InstanceType::T3Micro,
+            ami: String::from("ami-0123456789abcdef0"),
+        }
+    }
+
+    #[test]
+    fn test_get_genesis_graph_region() {
+        // Act
+        let graph = GraphManager::get_genesis_graph(
+            Some(get_leader_vm_spec()),
+            None,
+            None,
+            "eu-central-1",
+            None,
+            &ProjectInfraSpec::default(),
+        );
+
+        // Assert
+        let vpc_spec = graph
+            .node_weights()
+            .find_map(|node| match node {
+                SpecNode::Resource(ResourceSpecType::Vpc(vpc_spec)) => Some(vpc_spec),
+                _ => None,
+            })
+            .expect("Failed to find VPC spec");
+        let subnet_spec = graph
+            .node_weights()
+            .find_map(|node| match node {
+                SpecNode::Resource(ResourceSpecType::Subnet(subnet_spec)) => Some(subnet_spec),
+                _ => None,
+            })
+            .expect("Failed to find subnet spec");
+
+        let vm_spec = graph
+            .node_weights()
+            .find_map(|node| match node {
+                SpecNode::Resource(ResourceSpecType::Vm(vm_spec)) => Some(vm_spec),
+                _ => None,
+            })
+            .expect("Failed to find VM spec");
+
+        assert_eq!(vpc_spec.region, "eu-central-1");
+        assert_eq!(subnet_spec.availability_zone, "eu-central-1a");
+        assert_eq!(vm_spec.ami, "ami-0123456789abcdef0");
+    }
+
+    #[test]
+    fn test_get_genesis_graph_without_vm() {
+        // Act
+        let graph = GraphManager::get_genesis_graph(
+            None,
+            None,
+            None,
+            "us-west-2",
+            None,
+            &ProjectInfraSpec::default(),
+        );
+
+        // Assert
+        assert!(
+            graph
+                .node_weights()
+                .any(|node| matches!(node, SpecNode::Resource(ResourceSpecType::Vpc(_))))
+        );
+        assert!(
+            !graph
+                .node_weights()
+                .any(|node| matches!(node, SpecNode::Resource(ResourceSpecType::Vm(_))))
+        );
+    }
+
+    #[test]
+    fn test_get_genesis_graph_existing_network() {
+        // Arrange
+        let existing_network = ExistingNetworkSpec {
+            vpc_id: String::from("vpc-existing"),
+            subnet_id: String::from("subnet-existing"),
+        };
+
+        // Act
+        let graph = GraphManager::get_genesis_graph(
+            Some(get_leader_vm_spec()),
+            None,
+            None,
+            "us-west-2",
+            Some(&existing_network),
+            &ProjectInfraSpec::default(),
+        );
+
+        // Assert
+        let creates_network = graph.node_weights().any(|node| match node {
+            SpecNode::Resource(ResourceSpecType::Vpc(vpc_spec)) => vpc_spec.existing_id.is_none(),
+            SpecNode::Resource(ResourceSpecType::Subnet(subnet_spec)) => {
+                subnet_spec.existing_id.is_none()
+            }
+            SpecNode::Resource(
+                ResourceSpecType::InternetGateway(_) | ResourceSpecType::RouteTable(_),
+            ) => true,
+            _ => false,
+        });
+        assert!(!creates_network);
+
+        let subnet_index = graph
+            .node_indices()
+            .find(|index| {
+                matches!(
+                    &graph[*index],
+                    SpecNode::Resource(ResourceSpecType::Subnet(subnet_spec))
+                        if subnet_spec.existing_id.as_deref() == Some("subnet-existing")
+                )
+            })
+            .expect("Failed to find subnet spec");
+        assert!(
+            graph
+                .neighbors(subnet_index)
+                .any(|index| matches!(graph[index], SpecNode::Resource(ResourceSpecType::Vm(_))))
+        );
+    }
+
+    #[test]
+    fn test_get_genesis_graph_with_domain() {
+        // Arrange
+        let project_infra = ProjectInfraSpec {
+            domain: Some(DomainSpec {
+                name: String::from("example.com"),
+                ttl: 300,
+                wildcard: true,
+            }),
+            ..ProjectInfraSpec::default()
+        };
+
+        // Act
+        let graph = GraphManager::get_genesis_graph(
+            Some(get_leader_vm_spec()),
+            None,
+            None,
+            "eu-central-1",
+            None,
+            &project_infra,
+        );
+
+        // Assert
+        let hosted_zone_spec = graph
+            .node_weights()
+            .find_map(|node| match node {
+                SpecNode::Resource(ResourceSpecType::HostedZone(hosted_zone_spec)) => {
+                    Some(hosted_zone_spec)
+                }
+                _ => None,
+            })
+            .expect("Failed to find hosted zone spec");
+        assert_eq!(hosted_zone_spec.name, "example.com");
+        assert_eq!(hosted_zone_spec.region, "eu-central-1");
+
+        let dns_record_indices = graph
+            .node_indices()
+            .filter(|index| {
+                matches!(
+                    &graph[*index],
+                    SpecNode::Resource(ResourceSpecType::DnsRecord(DnsRecordSpec {
+                        record_type: types::RecordType::A,
+                        ttl: Some(300),
+                        ..
+                    }))
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(dns_record_indices.len(), 2);
+
+        // Both records route to the Leader VM
+        for dns_record_index in dns_record_indices {
+            assert!(
+                graph
+                    .neighbors_directed(dns_record_index, Incoming)
+                    .any(|index| matches!(
+                        graph[index],
+                        SpecNode::Resource(ResourceSpecType::Vm(_))
+                    ))
+            );
+        }
+    }
+
+    #[test]
+    fn test_get_genesis_graph_with_ecr_per_service() {
+        // Arrange
+        let project_infra = ProjectInfraSpec {
+            ecrs: vec![
+                EcrSpec {
+                    name: String::from("api"),
+                    keep_last_images: None,
+                },
+                EcrSpec {
+                    name: String::from("web"),
+                    keep_last_images: None,
+                },
+            ],
+            ..ProjectInfraSpec::default()
+        };
+
+        // Act
+        let graph =
+            GraphManager::get_genesis_graph(None, None, None, "us-west-2", None, &project_infra);
+
+        // Assert
+        let mut ecr_names = graph
+            .node_weights()
+            .filter_map(|node| match node {
+                SpecNode::Resource(ResourceSpecType::Ecr(ecr_spec)) => Some(ecr_spec.name.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        ecr_names.sort_unstable();
+        assert_eq!(ecr_names, vec!["api", "web"]);
+    }
+
+    #[test]
+    fn test_get_genesis_graph_with_load_balancer() {
+        // Arrange
+        let project_infra = ProjectInfraSpec {
+            domain: Some(DomainSpec {
+                name: String::from("example.com"),
+                ttl: DomainSpec::DEFAULT_TTL,
+                wildcard: false,
+            }),
+            load_balancer_port: Some(8080),
+            ..ProjectInfraSpec::default()
+        };
+
+        // Act
+        let graph = GraphManager::get_genesis_graph(
+            Some(get_leader_vm_spec()),
+            None,
+            None,
+            "eu-central-1",
+            None,
+            &project_infra,
+        );
+
+        // Assert
+        let subnet_availability_zones = graph
+            .node_weights()
+            .filter_map(|node| match node {
+                SpecNode::Resource(ResourceSpecType::Subnet(subnet_spec)) => {
+                    Some(subnet_spec.availability_zone.as_str())
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            subnet_availability_zones,
+            vec!["eu-central-1a", "eu-central-1b"]
+        );
+
+        let security_group_spec = graph
+            .node_weights()
+            .find_map(|node| match node {
+                SpecNode::Resource(ResourceSpecType::SecurityGroup(security_group_spec)) => {
+                    Some(security_group_spec)
+                }
+                _ => None,
+            })
+            .expect("Failed to find security group spec");
+        assert!(security_group_spec.inbound_rules.contains(&InboundRule {
+            cidr_block: String::from("10.0.0.0/16"),
+            protocol: String::from("tcp"),
+            port: 8080,
+        }));
+
+        let load_balancer_index = graph
+            .node_indices()
+            .find(|index| {
+                matches!(
+                    &graph[*index],
+                    SpecNode::Resource(ResourceSpecType::LoadBalancer(LoadBalancerSpec {
+                        target_port: 8080,
+                        ..
+                    }))
+                )
+            })
+            .expect("Expected LoadBalancer node");
+        assert!(
+            graph
+                .neighbors_directed(load_balancer_index, Incoming)
+                .any(|index| matches!(graph[index], SpecNode::Resource(ResourceSpecType::Vm(_))))
+        );
+
+        let dns_record_spec = graph
+            .neighbors_directed(load_balancer_index, Outgoing)
+            .find_map(|index| match &graph[index] {
+                SpecNode::Resource(ResourceSpecType::DnsRecord(dns_record_spec)) => {
+                    Some(dns_record_spec)
+                }
+                _ => None,
+            })
+            .expect("Expected DnsRecord child");
+        assert_eq!(dns_record_spec.record_type, types::RecordType::CNAME);
+    }
+
+    #[test]
+    fn test_get_genesis_graph_load_balancer_with_existing_network() {
+        // Arrange
+        let existing_network = ExistingNetworkSpec {
+            vpc_id: String::from("vpc-existing"),
+            subnet_id: String::from("subnet-existing"),
+        };
+        let project_infra = ProjectInfraSpec {
+            load_balancer_port: Some(8080),
+            ..ProjectInfraSpec::default()
+        };
+
+        // Act
+        let graph = GraphManager::get_genesis_graph(
+            Some(get_leader_vm_spec()),
+            None,
+            None,
+            "us-west-2",
+            Some(&existing_network),
+            &project_infra,
+        );
+
+        // Assert
+        assert!(
+            !graph
+                .node_weights()
+                .any(|node| matches!(node, SpecNode::Resource(ResourceSpecType::LoadBalancer(_))))
+        );
+    }
+
+    #[test]
+    fn test_get_genesis_graph_domain_without_vm() {
+        // Arrange
+        let project_infra = ProjectInfraSpec {
+            domain: Some(DomainSpec {
+                name: String::from("example.com"),
+                ttl: DomainSpec::DEFAULT_TTL,
+                wildcard: false,
+            }),
+            ..ProjectInfraSpec::default()
+        };
+
+        // Act
+        let graph =
+            GraphManager::get_genesis_graph(None, None, None, "us-west-2", None, &project_infra);
+
+        // Assert
+        assert!(!graph.node_weights().any(|node| matches!(
+            node,
+            SpecNode::Resource(ResourceSpecType::HostedZone(_) | ResourceSpecType::DnsRecord(_))
+        )));
+    }
+
+    #[test]
+    fn test_get_genesis_graph_with_checksum() {
+        // Act
+        let graph = GraphManager::get_genesis_graph(
+            Some(get_leader_vm_spec()),
+            Some("abc123"),
+            None,
+            "us-west-2",
+            None,
+            &ProjectInfraSpec::default(),
+        );
+
+        // Assert
+        let vm_spec = graph
+            .node_weights()
+            .find_map(|node| match node {
+                SpecNode::Resource(ResourceSpecType::Vm(vm_spec)) => Some(vm_spec),
+                _ => None,
+            })
+            .expect("Failed to find VM spec");
+        assert!(vm_spec.user_data.contains("sha256sum -c"));
+    }
+
     #[test]
     fn test_get_spec_graph_with_one_instance_and_domain() {
         // Arrange
         let instance_type = InstanceType::T3Micro;
-        let domain_name = Some(String::from("example.com"));
+        let domain = Some(DomainSpec {
+            name: String::from("example.com"),
+            ttl: DomainSpec::DEFAULT_TTL,
+            wildcard: false,
+        });
 
         // Act
-        let graph = GraphManager::get_spec_graph(&instance_type, domain_name);
+        let graph = GraphManager::get_spec_graph(&instance_type, domain, None, None, &[], None);
 
         // Assert
         assert_eq!(graph.node_count(), 10 + 2);
@@ -1147,13 +2392,58 @@ mod tests {
         assert_eq!(dns_record_nodes_count, 1);
     }
 
+    #[test]
+    fn test_get_spec_graph_with_one_instance_and_wildcard_domain() {
+        // Arrange
+        let instance_type = InstanceType::T3Micro;
+        let domain = Some(DomainSpec {
+            name: String::from("example.com"),
+            ttl: 300,
+            wildcard: true,
+        });
+
+        // Act
+        let graph = GraphManager::get_spec_graph(&instance_type, domain, None, None, &[], None);
+
+        // Assert
+        assert_eq!(graph.node_count(), 10 + 3);
+        assert_eq!(graph.edge_count(), 11 + 8);
+
+        let dns_record_specs = graph
+            .raw_nodes()
+            .iter()
+            .filter_map(|node| match &node.weight {
+                SpecNode::Resource(ResourceSpecType::DnsRecord(dns_record_spec)) => {
+                    Some(dns_record_spec)
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            dns_record_specs,
+            vec![
+                &DnsRecordSpec {
+                    record_type: types::RecordType::A,
+                    ttl: Some(300),
+                    wildcard: false,
+                },
+                &DnsRecordSpec {
+                    record_type: types::RecordType::A,
+                    ttl: Some(300),
+                    wildcard: true,
+                },
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn test_deploy_spec_graph_with_one_instance_no_domain() {
         // Arrange
         let instance_type = InstanceType::T3Micro;
-        let domain_name = None;
+        let domain = None;
 
-        let spec_graph = GraphManager::get_spec_graph(&instance_type, domain_name);
+        let spec_graph =
+            GraphManager::get_spec_graph(&instance_type, domain, None, None, &[], None);
 
         let mut ec2_client_mock = client::Ec2::default();
         let mut iam_client_mock = client::IAM::default();
@@ -1165,6 +2455,9 @@ mod tests {
             .expect_create_vpc()
             .with(eq(String::from("10.0.0.0/16")), eq(String::from("vpc-1")))
             .return_once(|_, _| Ok(String::from("vpc-id-1")));
+        ec2_client_mock
+            .expect_get_vpc_state()
+            .return_once(|_| Ok(Some(VpcState::Available)));
 
         iam_client_mock
             .expect_create_instance_iam_role()
@@ -1188,7 +2481,7 @@ mod tests {
                     "arn:aws:iam::aws:policy/AmazonEC2ContainerRegistryReadOnly",
                 )]),
             )
-            .return_once(|_, _, _| Ok(()));
+            .return_once(|_, _, _| Ok(true));
 
         ecr_client_mock
             .expect_create_repository()
@@ -1220,6 +2513,10 @@ mod tests {
             )
             .return_once(|_, _, _, _| Ok(String::from("subnet-id-1")));
 
+        ec2_client_mock
+            .expect_get_subnet_state()
+            .return_once(|_| Ok(Some(SubnetState::Available)));
+
         ec2_client_mock
             .expect_enable_auto_assign_ip_addresses_for_subnet()
             .with(eq(String::from("subnet-id-1")))
@@ -1273,7 +2570,7 @@ mod tests {
                 eq(String::from("instance_profile_1")),
                 eq(vec![String::from("instance-role-1")]),
             )
-            .return_once(|_, _| Ok(()));
+            .return_once(|_, _| Ok(true));
 
         ec2_client_mock
             .expect_run_instances()
@@ -1302,10 +2599,11 @@ mod tests {
             iam_client_mock,
             ecr_client_mock,
             route53_client_mock,
+            client::ELBv2::default(),
         );
 
         // Act
-        let (resource_graph, vm, ecr) = graph_manager
+        let (resource_graph, vm, ecrs) = graph_manager
             .deploy_spec_graph(&spec_graph)
             .await
             .expect("Failed to deploy");
@@ -1341,12 +2639,73 @@ mod tests {
         );
 
         assert_eq!(
-            ecr.expect("Failed to get ECR"),
-            Ecr {
-                id: String::from("ecr-id-1"),
-                name: String::from("ecr_1"),
-                uri: String::from("ecr-uri-1/foo"),
-            }
+            ecrs,
+            vec![Ecr {
+                id: String::from("ecr-id-1"),
+                name: String::from("ecr_1"),
+                uri: String::from("ecr-uri-1/foo"),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_describe_resource_vpc() {
+        // Arrange
+        let mut ec2_client_mock = client::Ec2::default();
+        ec2_client_mock
+            .expect_get_vpc_cidr_block()
+            .with(eq(String::from("vpc-0123")))
+            .return_once(|_| Ok(Some(String::from("172.31.0.0/16"))));
+
+        let graph_manager = GraphManager::new_with_clients(
+            ec2_client_mock,
+            client::IAM::default(),
+            client::ECR::default(),
+            client::Route53::default(),
+            client::ELBv2::default(),
+        );
+
+        // Act
+        let resource = graph_manager
+            .describe_resource("vpc", "vpc-0123", "us-west-2")
+            .await
+            .expect("Failed to describe VPC");
+
+        // Assert
+        assert_eq!(
+            resource,
+            ResourceType::Vpc(Vpc {
+                id: String::from("vpc-0123"),
+                region: String::from("us-west-2"),
+                cidr_block: String::from("172.31.0.0/16"),
+                name: String::from("vpc-0123"),
+                existing: false,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_describe_resource_unsupported_type() {
+        // Arrange
+        let graph_manager = GraphManager::new_with_clients(
+            client::Ec2::default(),
+            client::IAM::default(),
+            client::ECR::default(),
+            client::Route53::default(),
+            client::ELBv2::default(),
+        );
+
+        // Act
+        let result = graph_manager
+            .describe_resource("vm", "i-0123", "us-west-2")
+            .await;
+
+        // Assert
+        assert_eq!(
+            result
+                .expect_err("Expected unsupported type error")
+                .to_string(),
+            "Unsupported resource type 'vm', expected vpc or subnet"
         );
     }
 
@@ -1365,10 +2724,11 @@ mod tests {
             iam_client_mock,
             ecr_client_mock,
             route53_client_mock,
+            client::ELBv2::default(),
         );
 
         // Act
-        let (resource_graph, vm, ecr) = graph_manager
+        let (resource_graph, vm, ecrs) = graph_manager
             .deploy_spec_graph(&spec_graph)
             .await
             .expect("Failed to deploy");
@@ -1377,7 +2737,7 @@ mod tests {
         assert_eq!(resource_graph.node_count(), 0);
         assert_eq!(resource_graph.edge_count(), 0);
         assert!(vm.is_none());
-        assert!(ecr.is_none());
+        assert!(ecrs.is_empty());
     }
 
     #[tokio::test]
@@ -1389,12 +2749,14 @@ mod tests {
             region: String::from("us-west-2"),
             cidr_block: String::from("10.0.0.0/16"),
             name: String::from("vpc-1"),
+            existing_id: None,
         })));
         let subnet_1 =
             spec_graph.add_node(SpecNode::Resource(ResourceSpecType::Subnet(SubnetSpec {
                 name: String::from("vpc-1-subnet"),
                 cidr_block: String::from("10.0.1.0/24"),
                 availability_zone: String::from("us-west-2a"),
+                existing_id: None,
             })));
         let edges = vec![
             (root, vpc_1, String::new()),
@@ -1411,6 +2773,9 @@ mod tests {
             .expect_create_vpc()
             .with(eq(String::from("10.0.0.0/16")), eq(String::from("vpc-1")))
             .return_once(|_, _| Ok(String::from("vpc-id-1")));
+        ec2_client_mock
+            .expect_get_vpc_state()
+            .return_once(|_| Ok(Some(VpcState::Available)));
 
         // Simulate Subnet creation failure
         ec2_client_mock
@@ -1428,10 +2793,11 @@ mod tests {
             iam_client_mock,
             ecr_client_mock,
             route53_client_mock,
+            client::ELBv2::default(),
         );
 
         // Act
-        let (resource_graph, vm, ecr) = graph_manager
+        let (resource_graph, vm, ecrs) = graph_manager
             .deploy_spec_graph(&spec_graph)
             .await
             .expect("Failed to deploy");
@@ -1441,7 +2807,7 @@ mod tests {
         assert_eq!(resource_graph.node_count(), 2);
         assert_eq!(resource_graph.edge_count(), 1);
         assert!(vm.is_none());
-        assert!(ecr.is_none());
+        assert!(ecrs.is_empty());
 
         let vpc_node_exists = resource_graph
             .node_weights()
@@ -1453,6 +2819,157 @@ mod tests {
         assert!(!subnet_node_exists);
     }
 
+    #[tokio::test]
+    async fn test_deploy_genesis_graph_into_resource_creation_fails() {
+        // Arrange
+        let mut spec_graph = Graph::<SpecNode, String>::new();
+        let root = spec_graph.add_node(SpecNode::Root);
+        let vpc_1 = spec_graph.add_node(SpecNode::Resource(ResourceSpecType::Vpc(VpcSpec {
+            region: String::from("us-west-2"),
+            cidr_block: String::from("10.0.0.0/16"),
+            name: String::from("vpc-1"),
+            existing_id: None,
+        })));
+        let subnet_1 =
+            spec_graph.add_node(SpecNode::Resource(ResourceSpecType::Subnet(SubnetSpec {
+                name: String::from("vpc-1-subnet"),
+                cidr_block: String::from("10.0.1.0/24"),
+                availability_zone: String::from("us-west-2a"),
+                existing_id: None,
+            })));
+        let edges = vec![
+            (root, vpc_1, String::new()),
+            (vpc_1, subnet_1, String::new()),
+        ];
+        spec_graph.extend_with_edges(&edges);
+
+        let mut ec2_client_mock = client::Ec2::default();
+
+        ec2_client_mock
+            .expect_create_vpc()
+            .return_once(|_, _| Ok(String::from("vpc-id-1")));
+        ec2_client_mock
+            .expect_get_vpc_state()
+            .return_once(|_| Ok(Some(VpcState::Available)));
+        ec2_client_mock
+            .expect_create_subnet()
+            .return_once(|_, _, _, _| Err("Subnet creation failed".into()));
+
+        let graph_manager = GraphManager::new_with_clients(
+            ec2_client_mock,
+            client::IAM::default(),
+            client::ECR::default(),
+            client::Route53::default(),
+            client::ELBv2::default(),
+        );
+
+        let mut resource_graph = Graph::<Node, String>::new();
+
+        // Act
+        let result = graph_manager
+            .deploy_genesis_graph_into(&spec_graph, &mut resource_graph, None)
+            .await;
+
+        // Assert
+        assert_eq!(
+            result.expect_err("Expected deployment error").to_string(),
+            "Failed to create Subnet vpc-1-subnet: Subnet creation failed"
+        );
+
+        // The resources created before the failure are kept for the partial state
+        assert_eq!(resource_graph.node_count(), 2);
+        assert!(
+            resource_graph
+                .node_weights()
+                .any(|w| matches!(w, Node::Resource(ResourceType::Vpc(_))))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deploy_emits_events() {
+        // Arrange
+        let mut spec_graph = Graph::<SpecNode, String>::new();
+        let root = spec_graph.add_node(SpecNode::Root);
+        let vpc_1 = spec_graph.add_node(SpecNode::Resource(ResourceSpecType::Vpc(VpcSpec {
+            region: String::from("us-west-2"),
+            cidr_block: String::from("10.0.0.0/16"),
+            name: String::from("vpc-1"),
+            existing_id: None,
+        })));
+        let subnet_1 =
+            spec_graph.add_node(SpecNode::Resource(ResourceSpecType::Subnet(SubnetSpec {
+                name: String::from("vpc-1-subnet"),
+                cidr_block: String::from("10.0.1.0/24"),
+                availability_zone: String::from("us-west-2a"),
+                existing_id: None,
+            })));
+        let edges = vec![
+            (root, vpc_1, String::new()),
+            (vpc_1, subnet_1, String::new()),
+        ];
+        spec_graph.extend_with_edges(&edges);
+
+        let mut ec2_client_mock = client::Ec2::default();
+        let iam_client_mock = client::IAM::default();
+        let ecr_client_mock = client::ECR::default();
+        let route53_client_mock = client::Route53::default();
+
+        ec2_client_mock
+            .expect_create_vpc()
+            .with(eq(String::from("10.0.0.0/16")), eq(String::from("vpc-1")))
+            .return_once(|_, _| Ok(String::from("vpc-id-1")));
+        ec2_client_mock
+            .expect_get_vpc_state()
+            .return_once(|_| Ok(Some(VpcState::Available)));
+
+        ec2_client_mock
+            .expect_create_subnet()
+            .return_once(|_, _, _, _| Err("Subnet creation failed".into()));
+
+        let graph_manager = GraphManager::new_with_clients(
+            ec2_client_mock,
+            iam_client_mock,
+            ecr_client_mock,
+            route53_client_mock,
+            client::ELBv2::default(),
+        );
+
+        let (events_sender, mut events_receiver) = mpsc::channel(16);
+
+        // Act
+        let resource_graph = graph_manager
+            .deploy(&spec_graph, Some(events_sender))
+            .await
+            .expect("Failed to deploy");
+
+        // Assert
+        assert_eq!(resource_graph.node_count(), 2);
+
+        let mut events = vec![];
+        while let Some(event) = events_receiver.recv().await {
+            events.push(event);
+        }
+
+        assert_eq!(
+            events,
+            vec![
+                DeployEvent::ResourceCreateStarted {
+                    name: String::from("Vpc vpc-1"),
+                },
+                DeployEvent::ResourceCreated {
+                    name: String::from("Vpc vpc-1"),
+                },
+                DeployEvent::ResourceCreateStarted {
+                    name: String::from("Subnet vpc-1-subnet"),
+                },
+                DeployEvent::ResourceFailed {
+                    name: String::from("Subnet vpc-1-subnet"),
+                    error: String::from("Subnet creation failed"),
+                },
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn test_destroy_with_one_instance_no_domain() {
         // Arrange
@@ -1467,7 +2984,7 @@ mod tests {
         ec2_client_mock
             .expect_terminate_instance()
             .with(eq(String::from("vm-id-1")))
-            .return_once(|_| Ok(()));
+            .return_once(|_| Ok(true));
 
         // VmManager::is_terminated mock
         ec2_client_mock
@@ -1541,6 +3058,7 @@ mod tests {
             iam_client_mock,
             ecr_client_mock,
             route53_client_mock,
+            client::ELBv2::default(),
         );
 
         // Act
@@ -1568,6 +3086,7 @@ mod tests {
             iam_client_mock,
             ecr_client_mock,
             route53_client_mock,
+            client::ELBv2::default(),
         );
 
         // Act
@@ -1590,12 +3109,14 @@ mod tests {
             region: "us-west-2".to_string(),
             cidr_block: "10.0.0.0/16".to_string(),
             name: "vpc-1".to_string(),
+            existing: false,
         })));
         let subnet = resource_graph.add_node(Node::Resource(ResourceType::Subnet(Subnet {
             id: "subnet-id-1".to_string(),
             name: "vpc-1-subnet".to_string(),
             cidr_block: "10.0.1.0/24".to_string(),
             availability_zone: "us-west-2a".to_string(),
+            existing: false,
         })));
         let route_table =
             resource_graph.add_node(Node::Resource(ResourceType::RouteTable(RouteTable {
@@ -1639,6 +3160,7 @@ mod tests {
             iam_client_mock,
             ecr_client_mock,
             route53_client_mock,
+            client::ELBv2::default(),
         );
 
         // Act
@@ -1661,6 +3183,218 @@ mod tests {
         assert!(!subnet_node_exists);
     }
 
+    #[tokio::test]
+    async fn test_destroy_graph_with_cycle() {
+        // Arrange
+        let mut resource_graph = Graph::<Node, String>::new();
+        let root = resource_graph.add_node(Node::Root);
+        let vpc = resource_graph.add_node(Node::Resource(ResourceType::Vpc(Vpc {
+            id: "vpc-id-1".to_string(),
+            region: "us-west-2".to_string(),
+            cidr_block: "10.0.0.0/16".to_string(),
+            name: "vpc-1".to_string(),
+            existing: false,
+        })));
+        let subnet = resource_graph.add_node(Node::Resource(ResourceType::Subnet(Subnet {
+            id: "subnet-id-1".to_string(),
+            name: "vpc-1-subnet".to_string(),
+            cidr_block: "10.0.1.0/24".to_string(),
+            availability_zone: "us-west-2a".to_string(),
+            existing: false,
+        })));
+        let route_table =
+            resource_graph.add_node(Node::Resource(ResourceType::RouteTable(RouteTable {
+                id: "rt-id-1".to_string(),
+            })));
+
+        resource_graph.extend_with_edges(&[
+            (root, vpc, String::new()),
+            (vpc, subnet, String::new()),
+            (subnet, route_table, String::new()),
+            (route_table, subnet, String::new()),
+        ]);
+
+        // No client expectations, any destroyed resource fails the test
+        let graph_manager = GraphManager::new_with_clients(
+            client::Ec2::default(),
+            client::IAM::default(),
+            client::ECR::default(),
+            client::Route53::default(),
+            client::ELBv2::default(),
+        );
+
+        // Act
+        let destroy_result = graph_manager.destroy(&mut resource_graph).await;
+        let destroy_parallel_result = graph_manager.destroy_parallel(&mut resource_graph).await;
+
+        // Assert
+        let expected_error = "Cycle detected in graph, resources are not destroyed: \
+            subnet.vpc-1-subnet, route_table.rt-id-1";
+        assert_eq!(
+            destroy_result
+                .expect_err("Expected cycle error")
+                .to_string(),
+            expected_error
+        );
+        assert_eq!(
+            destroy_parallel_result
+                .expect_err("Expected cycle error")
+                .to_string(),
+            expected_error
+        );
+
+        assert_eq!(resource_graph.node_count(), 4);
+        assert_eq!(resource_graph.edge_count(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_destroy_parallel_terminates_vms_concurrently() {
+        // Arrange
+        let mut resource_graph = Graph::<Node, String>::new();
+        let root = resource_graph.add_node(Node::Root);
+        let vpc = resource_graph.add_node(Node::Resource(ResourceType::Vpc(Vpc {
+            id: "vpc-id-1".to_string(),
+            region: "us-west-2".to_string(),
+            cidr_block: "10.0.0.0/16".to_string(),
+            name: "vpc-1".to_string(),
+            existing: false,
+        })));
+        let vms = ["vm-id-1", "vm-id-2"].map(|id| {
+            resource_graph.add_node(Node::Resource(ResourceType::Vm(Vm {
+                id: id.to_string(),
+                public_ip: "1.1.1.1".to_string(),
+                instance_type: InstanceType::T3Micro,
+                ami: "ami-830c94e3".to_string(),
+                user_data: String::new(),
+            })))
+        });
+
+        resource_graph.extend_with_edges(&[
+            (root, vpc, String::new()),
+            (vpc, vms[0], String::new()),
+            (vpc, vms[1], String::new()),
+        ]);
+
+        let calls = std::sync::Arc::new(std::sync::Mutex::new(Vec::<String>::new()));
+
+        let mut ec2_client_mock = client::Ec2::default();
+
+        let terminate_calls = calls.clone();
+        ec2_client_mock
+            .expect_terminate_instance()
+            .times(2)
+            .returning(move |id| {
+                terminate_calls
+                    .lock()
+                    .expect("Failed to lock calls")
+                    .push(format!("terminate {id}"));
+
+                Ok(true)
+            });
+
+        // The first VM is still running on the first check, so the second VM
+        // is terminated while the first one is waited for
+        let describe_calls = calls.clone();
+        ec2_client_mock
+            .expect_describe_instances()
+            .times(3)
+            .returning(move |id| {
+                let mut calls = describe_calls.lock().expect("Failed to lock calls");
+
+                let state_name = if id == "vm-id-1" && !calls.contains(&format!("running {id}")) {
+                    calls.push(format!("running {id}"));
+
+                    aws_sdk_ec2::types::InstanceStateName::Running
+                } else {
+                    calls.push(format!("terminated {id}"));
+
+                    aws_sdk_ec2::types::InstanceStateName::Terminated
+                };
+
+                Ok(aws_sdk_ec2::types::Instance::builder()
+                    .state(
+                        aws_sdk_ec2::types::InstanceState::builder()
+                            .name(state_name)
+                            .build(),
+                    )
+                    .build())
+            });
+
+        let delete_vpc_calls = calls.clone();
+        ec2_client_mock
+            .expect_delete_vpc()
+            .with(eq(String::from("vpc-id-1")))
+            .return_once(move |id| {
+                delete_vpc_calls
+                    .lock()
+                    .expect("Failed to lock calls")
+                    .push(format!("delete {id}"));
+
+                Ok(())
+            });
+
+        let graph_manager = GraphManager::new_with_clients(
+            ec2_client_mock,
+            client::IAM::default(),
+            client::ECR::default(),
+            client::Route53::default(),
+            client::ELBv2::default(),
+        );
+
+        // Act
+        let destroy_result = graph_manager.destroy_parallel(&mut resource_graph).await;
+
+        // Assert
+        assert!(destroy_result.is_ok());
+        assert_eq!(resource_graph.node_count(), 0);
+        assert_eq!(
+            *calls.lock().expect("Failed to lock calls"),
+            vec![
+                String::from("terminate vm-id-1"),
+                String::from("running vm-id-1"),
+                String::from("terminate vm-id-2"),
+                String::from("terminated vm-id-2"),
+                String::from("terminated vm-id-1"),
+                String::from("delete vpc-id-1"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_dependency_levels() {
+        // Arrange
+        let mut graph = Graph::<i32, String>::new();
+        let root = graph.add_node(0);
+        let vpc = graph.add_node(1);
+        let subnet = graph.add_node(2);
+        let internet_gateway = graph.add_node(3);
+        let vm = graph.add_node(4);
+
+        graph.extend_with_edges(&[
+            (root, vpc, String::new()),
+            (vpc, subnet, String::new()),
+            (vpc, internet_gateway, String::new()),
+            (subnet, vm, String::new()),
+        ]);
+
+        // Act
+        let mut levels = get_dependency_levels(&graph).expect("Failed to get levels");
+
+        // Assert
+        for level in &mut levels {
+            level.sort();
+        }
+        assert_eq!(
+            levels,
+            vec![
+                vec![root],
+                vec![vpc],
+                vec![subnet, internet_gateway],
+                vec![vm]
+            ]
+        );
+    }
+
     fn get_test_resource_graph() -> Graph<Node, String> {
         let mut graph = Graph::<Node, String>::new();
         let root = graph.add_node(Node::Root);
@@ -1698,6 +3432,7 @@ mod tests {
             region: "us-west-2".to_string(),
             cidr_block: "10.0.0.0/16".to_string(),
             name: "vpc-1".to_string(),
+            existing: false,
         })));
 
         let security_group =
@@ -1722,6 +3457,7 @@ mod tests {
             name: "vpc-1-subnet".to_string(),
             cidr_block: "10.0.1.0/24".to_string(),
             availability_zone: "us-west-2a".to_string(),
+            existing: false,
         })));
 
         let instance_profile = graph.add_node(Node::Resource(ResourceType::InstanceProfile(
@@ -1875,6 +3611,7 @@ mod tests {
 
         // Assert
         assert_eq!(result.to_string(), "Cycle detected in graph");
+        assert_eq!(result.nodes, vec![a, b, c]);
     }
 
     #[test]
@@ -1895,7 +3632,29 @@ mod tests {
         let result = kahn_traverse(&graph).expect_err("Graph should have a cycle");
 
         // Assert
-        assert_eq!(result.to_string(), "Cycle detected in graph");
+        assert_eq!(result, CycleError { nodes: vec![c, d] });
+    }
+
+    #[test]
+    fn test_kahn_traverse_graph_with_cycle_dependent_node() {
+        // Arrange
+        let mut graph = Graph::<&str, String>::new();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+        let d = graph.add_node("d");
+        graph.extend_with_edges(&[
+            (a, b, String::new()),
+            (b, c, String::new()),
+            (c, b, String::new()), // Cycle b -> c -> b
+            (c, d, String::new()),
+        ]);
+
+        // Act
+        let result = kahn_traverse(&graph).expect_err("Graph should have a cycle");
+
+        // Assert
+        assert_eq!(result.nodes, vec![b, c, d]);
     }
 
     #[test]
@@ -1,26 +1,36 @@
-use aws_sdk_ec2::types::InstanceStateName;
+use aws_sdk_ec2::types::{InstanceStateName, SubnetState, VpcState};
 use base64::Engine as _;
 use base64::engine::general_purpose;
 use serde::{Deserialize, Serialize};
 
 use crate::aws::{client, types};
 
+/// Number of state checks while waiting for a new VPC or subnet
+/// to become available
+const AVAILABILITY_MAX_ATTEMPTS: usize = 24;
+
+const AVAILABILITY_SLEEP_DURATION: std::time::Duration = std::time::Duration::from_secs(5);
+
 /// Defines the main methods to manage resources
-pub trait Manager<'a, I, O>
+///
+/// `P` is the graph node type of the cloud provider, AWS [`Node`] is used by
+/// default
+pub trait Manager<'a, I, O, P = Node>
 where
     I: 'a + Send + Sync,
     O: 'a + Send + Sync,
+    P: 'a + Send + Sync,
 {
     fn create(
         &self,
         input: &'a I,
-        parents: Vec<&'a Node>,
+        parents: Vec<&'a P>,
     ) -> impl std::future::Future<Output = Result<O, Box<dyn std::error::Error + Send + Sync>>> + Send;
 
     fn destroy(
         &self,
         input: &'a O,
-        parents: Vec<&'a Node>,
+        parents: Vec<&'a P>,
     ) -> impl std::future::Future<Output = Result<(), Box<dyn std::error::Error + Send + Sync>>> + Send;
 }
 
@@ -69,6 +79,9 @@ impl Manager<'_, HostedZoneSpec, HostedZone> for HostedZoneManager<'_> {
 pub struct DnsRecordSpec {
     pub record_type: types::RecordType,
     pub ttl: Option<i64>,
+    /// Creates `*.<hosted zone>` record routing all subdomains to the VM
+    /// instead of the `<vm id>.<hosted zone>` one
+    pub wildcard: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -100,24 +113,43 @@ impl Manager<'_, DnsRecordSpec, DnsRecord> for DnsRecordManager<'_> {
                 Err("DnsRecord expects HostedZone as a parent")
             }?;
 
-        let vm_node = parents
+        // A load balancer parent replaces the VM, the record routes to
+        // the load balancer DNS name then
+        let load_balancer_node = parents
             .iter()
-            .find(|parent| matches!(parent, Node::Resource(ResourceType::Vm(_))));
+            .find(|parent| matches!(parent, Node::Resource(ResourceType::LoadBalancer(_))));
 
-        let vm = if let Some(Node::Resource(ResourceType::Vm(vm))) = vm_node {
-            Ok(vm.clone())
-        } else {
-            Err("DnsRecord expects Vm as a parent")
-        }?;
+        let (target_name, value) =
+            if let Some(Node::Resource(ResourceType::LoadBalancer(load_balancer))) =
+                load_balancer_node
+            {
+                (load_balancer.name.clone(), load_balancer.dns_name.clone())
+            } else {
+                let vm_node = parents
+                    .iter()
+                    .find(|parent| matches!(parent, Node::Resource(ResourceType::Vm(_))));
+
+                let vm = if let Some(Node::Resource(ResourceType::Vm(vm))) = vm_node {
+                    Ok(vm.clone())
+                } else {
+                    Err("DnsRecord expects Vm as a parent")
+                }?;
+
+                (vm.id, vm.public_ip)
+            };
 
-        let domain_name = format!("{}.{}", vm.id, hosted_zone.name);
+        let domain_name = if input.wildcard {
+            format!("*.{}", hosted_zone.name)
+        } else {
+            format!("{target_name}.{}", hosted_zone.name)
+        };
 
         self.client
             .create_dns_record(
                 hosted_zone.id.clone(),
                 domain_name.clone(),
                 input.record_type,
-                vm.public_ip.clone(),
+                value.clone(),
                 input.ttl,
             )
             .await?;
@@ -125,7 +157,7 @@ impl Manager<'_, DnsRecordSpec, DnsRecord> for DnsRecordManager<'_> {
         Ok(DnsRecord {
             record_type: input.record_type,
             name: domain_name.clone(),
-            value: vm.public_ip.clone(),
+            value,
             ttl: input.ttl,
         })
     }
@@ -163,6 +195,9 @@ pub struct VpcSpec {
     pub region: String,
     pub cidr_block: String,
     pub name: String,
+    /// ID of a VPC created outside of oct, it is used as is instead of
+    /// creating a new one and `cidr_block` is ignored
+    pub existing_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -171,28 +206,81 @@ pub struct Vpc {
     pub region: String,
     pub cidr_block: String,
     pub name: String,
+    /// The VPC is created outside of oct and is kept on destroy
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub existing: bool,
 }
 
 pub struct VpcManager<'a> {
     pub client: &'a client::Ec2,
 }
 
+impl VpcManager<'_> {
+    /// Waits until the VPC is `available`, AWS creates VPCs eventually
+    /// consistent and its children fail with `InvalidVpcID.NotFound` before
+    async fn wait_for_available(
+        &self,
+        id: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        log::info!("Waiting for VPC {id:?} to be available...");
+
+        for _ in 0..AVAILABILITY_MAX_ATTEMPTS {
+            let state = self.client.get_vpc_state(String::from(id)).await?;
+
+            if state == Some(VpcState::Available) {
+                log::info!("VPC {id:?} is available");
+                return Ok(());
+            }
+
+            log::info!(
+                "VPC is not available yet. Retrying in {} sec...",
+                AVAILABILITY_SLEEP_DURATION.as_secs()
+            );
+
+            tokio::time::sleep(AVAILABILITY_SLEEP_DURATION).await;
+        }
+
+        Err(format!("VPC {id} failed to become available").into())
+    }
+}
+
 impl Manager<'_, VpcSpec, Vpc> for VpcManager<'_> {
     async fn create(
         &self,
         input: &'_ VpcSpec,
         _parents: Vec<&Node>,
     ) -> Result<Vpc, Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(vpc_id) = &input.existing_id {
+            let cidr_block = self
+                .client
+                .get_vpc_cidr_block(vpc_id.clone())
+                .await?
+                .ok_or_else(|| format!("Existing VPC {vpc_id} is not found"))?;
+
+            log::info!("Using existing VPC {vpc_id}");
+
+            return Ok(Vpc {
+                id: vpc_id.clone(),
+                region: input.region.clone(),
+                cidr_block,
+                name: input.name.clone(),
+                existing: true,
+            });
+        }
+
         let vpc_id = self
             .client
             .create_vpc(input.cidr_block.clone(), input.name.clone())
             .await?;
 
+        self.wait_for_available(&vpc_id).await?;
+
         Ok(Vpc {
             id: vpc_id,
             region: input.region.clone(),
             cidr_block: input.cidr_block.clone(),
             name: input.name.clone(),
+            existing: false,
         })
     }
 
@@ -201,6 +289,12 @@ impl Manager<'_, VpcSpec, Vpc> for VpcManager<'_> {
         input: &'_ Vpc,
         _parents: Vec<&Node>,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if input.existing {
+            log::info!("Keeping existing VPC {}", input.id);
+
+            return Ok(());
+        }
+
         self.client.delete_vpc(input.id.clone()).await
     }
 }
@@ -255,9 +349,7 @@ impl Manager<'_, InternetGatewaySpec, InternetGateway> for InternetGatewayManage
 
         self.client
             .delete_internet_gateway(input.id.clone(), vpc.id.clone())
-            .await?;
-
-        Ok(())
+            .await
     }
 }
 
@@ -322,6 +414,9 @@ pub struct SubnetSpec {
     pub name: String,
     pub cidr_block: String,
     pub availability_zone: String,
+    /// ID of a subnet created outside of oct, it is used as is with its own
+    /// routing, `cidr_block` and `availability_zone` are ignored
+    pub existing_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -330,18 +425,67 @@ pub struct Subnet {
     pub name: String,
     pub cidr_block: String,
     pub availability_zone: String,
+    /// The subnet is created outside of oct and is kept on destroy
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub existing: bool,
 }
 
 pub struct SubnetManager<'a> {
     pub client: &'a client::Ec2,
 }
 
+impl SubnetManager<'_> {
+    /// Waits until the subnet is `available` before it is configured
+    async fn wait_for_available(
+        &self,
+        id: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        log::info!("Waiting for subnet {id:?} to be available...");
+
+        for _ in 0..AVAILABILITY_MAX_ATTEMPTS {
+            let state = self.client.get_subnet_state(String::from(id)).await?;
+
+            if state == Some(SubnetState::Available) {
+                log::info!("Subnet {id:?} is available");
+                return Ok(());
+            }
+
+            log::info!(
+                "Subnet is not available yet. Retrying in {} sec...",
+                AVAILABILITY_SLEEP_DURATION.as_secs()
+            );
+
+            tokio::time::sleep(AVAILABILITY_SLEEP_DURATION).await;
+        }
+
+        Err(format!("Subnet {id} failed to become available").into())
+    }
+}
+
 impl Manager<'_, SubnetSpec, Subnet> for SubnetManager<'_> {
     async fn create(
         &self,
         input: &'_ SubnetSpec,
         parents: Vec<&Node>,
     ) -> Result<Subnet, Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(subnet_id) = &input.existing_id {
+            let (cidr_block, availability_zone) = self
+                .client
+                .get_subnet_placement(subnet_id.clone())
+                .await?
+                .ok_or_else(|| format!("Existing subnet {subnet_id} is not found"))?;
+
+            log::info!("Using existing subnet {subnet_id}");
+
+            return Ok(Subnet {
+                id: subnet_id.clone(),
+                name: input.name.clone(),
+                cidr_block,
+                availability_zone,
+                existing: true,
+            });
+        }
+
         let vpc_node = parents
             .iter()
             .find(|parent| matches!(parent, Node::Resource(ResourceType::Vpc(_))));
@@ -373,6 +517,8 @@ impl Manager<'_, SubnetSpec, Subnet> for SubnetManager<'_> {
             )
             .await?;
 
+        self.wait_for_available(&subnet_id).await?;
+
         self.client
             .enable_auto_assign_ip_addresses_for_subnet(subnet_id.clone())
             .await?;
@@ -386,6 +532,7 @@ impl Manager<'_, SubnetSpec, Subnet> for SubnetManager<'_> {
             name: input.name.clone(),
             cidr_block: input.cidr_block.clone(),
             availability_zone: input.availability_zone.clone(),
+            existing: false,
         })
     }
 
@@ -394,6 +541,12 @@ impl Manager<'_, SubnetSpec, Subnet> for SubnetManager<'_> {
         input: &'_ Subnet,
         parents: Vec<&Node>,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if input.existing {
+            log::info!("Keeping existing subnet {}", input.id);
+
+            return Ok(());
+        }
+
         let route_table_node = parents
             .iter()
             .find(|parent| matches!(parent, Node::Resource(ResourceType::RouteTable(_))));
@@ -513,7 +666,7 @@ impl Manager<'_, InstanceRoleSpec, InstanceRole> for InstanceRoleManager<'_> {
         input: &'_ InstanceRoleSpec,
         _parents: Vec<&'_ Node>,
     ) -> Result<InstanceRole, Box<dyn std::error::Error + Send + Sync>> {
-        let () = self
+        let is_created = self
             .client
             .create_instance_iam_role(
                 input.name.clone(),
@@ -522,6 +675,23 @@ impl Manager<'_, InstanceRoleSpec, InstanceRole> for InstanceRoleManager<'_> {
             )
             .await?;
 
+        // Role left by a previous run is reused, only the missing policies
+        // are attached
+        if !is_created {
+            let attached_policy_arns = self
+                .client
+                .list_attached_role_policies(input.name.clone())
+                .await?;
+
+            for policy_arn in &input.policy_arns {
+                if !attached_policy_arns.contains(policy_arn) {
+                    self.client
+                        .attach_role_policy(input.name.clone(), policy_arn.clone())
+                        .await?;
+                }
+            }
+        }
+
         Ok(InstanceRole {
             name: input.name.clone(),
             assume_role_policy: input.assume_role_policy.clone(),
@@ -568,12 +738,30 @@ impl Manager<'_, InstanceProfileSpec, InstanceProfile> for InstanceProfileManage
                 }
                 _ => None,
             })
-            .collect();
+            .collect::<Vec<_>>();
 
-        self.client
-            .create_instance_profile(input.name.clone(), instance_role_names)
+        let is_created = self
+            .client
+            .create_instance_profile(input.name.clone(), instance_role_names.clone())
             .await?;
 
+        // Instance profile left by a previous run is reused, only the missing
+        // roles are added
+        if !is_created {
+            let added_role_names = self
+                .client
+                .get_instance_profile_role_names(input.name.clone())
+                .await?;
+
+            for role_name in instance_role_names {
+                if !added_role_names.contains(&role_name) {
+                    self.client
+                        .add_role_to_instance_profile(input.name.clone(), role_name)
+                        .await?;
+                }
+            }
+        }
+
         Ok(InstanceProfile {
             name: input.name.clone(),
         })
@@ -603,6 +791,9 @@ impl Manager<'_, InstanceProfileSpec, InstanceProfile> for InstanceProfileManage
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EcrSpec {
     pub name: String,
+    /// Number of the most recent images to keep, older images are expired
+    /// by the repository lifecycle policy
+    pub keep_last_images: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -627,6 +818,29 @@ pub struct EcrManager<'a> {
     pub client: &'a client::ECR,
 }
 
+impl EcrManager<'_> {
+    /// Builds lifecycle policy expiring all images except the last `keep_last_images`
+    fn get_lifecycle_policy(keep_last_images: u32) -> String {
+        serde_json::json!({
+            "rules": [
+                {
+                    "rulePriority": 1,
+                    "description": format!("Keep last {keep_last_images} images"),
+                    "selection": {
+                        "tagStatus": "any",
+                        "countType": "imageCountMoreThan",
+                        "countNumber": keep_last_images,
+                    },
+                    "action": {
+                        "type": "expire",
+                    },
+                },
+            ],
+        })
+        .to_string()
+    }
+}
+
 impl Manager<'_, EcrSpec, Ecr> for EcrManager<'_> {
     async fn create(
         &self,
@@ -635,6 +849,14 @@ impl Manager<'_, EcrSpec, Ecr> for EcrManager<'_> {
     ) -> Result<Ecr, Box<dyn std::error::Error + Send + Sync>> {
         let (id, uri) = self.client.create_repository(input.name.clone()).await?;
 
+        if let Some(keep_last_images) = input.keep_last_images {
+            let policy = Self::get_lifecycle_policy(keep_last_images);
+
+            self.client
+                .put_lifecycle_policy(input.name.clone(), policy)
+                .await?;
+        }
+
         Ok(Ecr {
             id,
             uri,
@@ -804,12 +1026,188 @@ impl Manager<'_, VmSpec, Vm> for VmManager<'_> {
         input: &'_ Vm,
         _parents: Vec<&Node>,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        self.client.terminate_instance(input.id.clone()).await?;
+        if !self.client.terminate_instance(input.id.clone()).await? {
+            return Ok(());
+        }
 
         self.is_terminated(input.id.clone()).await
     }
 }
 
+/// Port of the load balancer HTTP listener
+const LOAD_BALANCER_LISTENER_PORT: i32 = 80;
+
+/// Application load balancer with a target group of all the VM parents
+#[derive(Debug)]
+pub struct LoadBalancerSpec {
+    pub name: String,
+    /// Port of the VMs receiving the forwarded traffic
+    pub target_port: u16,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LoadBalancer {
+    pub name: String,
+    pub arn: String,
+    pub dns_name: String,
+    pub target_group_arn: String,
+    pub listener_arn: String,
+    pub target_port: u16,
+    pub instance_ids: Vec<String>,
+}
+
+pub struct LoadBalancerManager<'a> {
+    pub client: &'a client::ELBv2,
+}
+
+impl LoadBalancerManager<'_> {
+    /// Waits until the load balancer is deleted, its target group can not
+    /// be deleted while the load balancer uses it
+    async fn wait_for_deleted(
+        &self,
+        arn: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        log::info!("Waiting for load balancer {arn:?} to be deleted...");
+
+        for _ in 0..AVAILABILITY_MAX_ATTEMPTS {
+            if self
+                .client
+                .is_load_balancer_deleted(String::from(arn))
+                .await?
+            {
+                log::info!("Load balancer {arn:?} is deleted");
+                return Ok(());
+            }
+
+            log::info!(
+                "Load balancer is not deleted yet. Retrying in {} sec...",
+                AVAILABILITY_SLEEP_DURATION.as_secs()
+            );
+
+            tokio::time::sleep(AVAILABILITY_SLEEP_DURATION).await;
+        }
+
+        Err(format!("Load balancer {arn:?} failed to be deleted").into())
+    }
+
+    /// Adds the instances back to the load balancer target group
+    pub async fn register_instances(
+        &self,
+        load_balancer: &LoadBalancer,
+        instance_ids: Vec<String>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.client
+            .register_targets(load_balancer.target_group_arn.clone(), instance_ids)
+            .await
+    }
+
+    /// Removes the instances from the load balancer target group, e.g. while
+    /// their services are updated
+    pub async fn deregister_instances(
+        &self,
+        load_balancer: &LoadBalancer,
+        instance_ids: Vec<String>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.client
+            .deregister_targets(load_balancer.target_group_arn.clone(), instance_ids)
+            .await
+    }
+}
+
+impl Manager<'_, LoadBalancerSpec, LoadBalancer> for LoadBalancerManager<'_> {
+    async fn create(
+        &self,
+        input: &'_ LoadBalancerSpec,
+        parents: Vec<&'_ Node>,
+    ) -> Result<LoadBalancer, Box<dyn std::error::Error + Send + Sync>> {
+        let vpc_node = parents
+            .iter()
+            .find(|parent| matches!(parent, Node::Resource(ResourceType::Vpc(_))));
+
+        let vpc = if let Some(Node::Resource(ResourceType::Vpc(vpc))) = vpc_node {
+            Ok(vpc.clone())
+        } else {
+            Err("LoadBalancer expects VPC as a parent")
+        }?;
+
+        let mut subnet_ids = Vec::new();
+        let mut security_group_ids = Vec::new();
+        let mut instance_ids = Vec::new();
+
+        for parent in &parents {
+            match parent {
+                Node::Resource(ResourceType::Subnet(subnet)) => subnet_ids.push(subnet.id.clone()),
+                Node::Resource(ResourceType::SecurityGroup(security_group)) => {
+                    security_group_ids.push(security_group.id.clone());
+                }
+                Node::Resource(ResourceType::Vm(vm)) => instance_ids.push(vm.id.clone()),
+                _ => {}
+            }
+        }
+
+        // AWS requires subnets in at least two availability zones
+        if subnet_ids.len() < 2 {
+            return Err("LoadBalancer expects at least two Subnets as parents".into());
+        }
+
+        let target_group_arn = self
+            .client
+            .create_target_group(
+                format!("{}-targets", input.name),
+                vpc.id.clone(),
+                i32::from(input.target_port),
+            )
+            .await?;
+
+        let (arn, dns_name) = self
+            .client
+            .create_load_balancer(input.name.clone(), subnet_ids, security_group_ids)
+            .await?;
+
+        let listener_arn = self
+            .client
+            .create_listener(
+                arn.clone(),
+                target_group_arn.clone(),
+                LOAD_BALANCER_LISTENER_PORT,
+            )
+            .await?;
+
+        if !instance_ids.is_empty() {
+            self.client
+                .register_targets(target_group_arn.clone(), instance_ids.clone())
+                .await?;
+        }
+
+        Ok(LoadBalancer {
+            name: input.name.clone(),
+            arn,
+            dns_name,
+            target_group_arn,
+            listener_arn,
+            target_port: input.target_port,
+            instance_ids,
+        })
+    }
+
+    async fn destroy(
+        &self,
+        input: &'_ LoadBalancer,
+        _parents: Vec<&'_ Node>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.client
+            .delete_listener(input.listener_arn.clone())
+            .await?;
+        self.client.delete_load_balancer(input.arn.clone()).await?;
+
+        self.wait_for_deleted(&input.arn).await?;
+
+        self.client
+            .delete_target_group(input.target_group_arn.clone())
+            .await
+    }
+}
+
 #[derive(Debug)]
 pub enum ResourceSpecType {
     HostedZone(HostedZoneSpec),
@@ -823,6 +1221,27 @@ pub enum ResourceSpecType {
     InstanceProfile(InstanceProfileSpec),
     Ecr(EcrSpec),
     Vm(VmSpec),
+    LoadBalancer(LoadBalancerSpec),
+}
+
+impl ResourceSpecType {
+    /// Human-readable resource name used in deployment events
+    pub fn name(&self) -> String {
+        match self {
+            Self::HostedZone(spec) => format!("HostedZone {}", spec.name),
+            Self::DnsRecord(spec) => format!("DnsRecord {}", spec.record_type.as_str()),
+            Self::Vpc(spec) => format!("Vpc {}", spec.name),
+            Self::InternetGateway(_) => String::from("InternetGateway"),
+            Self::RouteTable(_) => String::from("RouteTable"),
+            Self::Subnet(spec) => format!("Subnet {}", spec.name),
+            Self::SecurityGroup(spec) => format!("SecurityGroup {}", spec.name),
+            Self::InstanceRole(spec) => format!("InstanceRole {}", spec.name),
+            Self::InstanceProfile(spec) => format!("InstanceProfile {}", spec.name),
+            Self::Ecr(spec) => format!("Ecr {}", spec.name),
+            Self::Vm(spec) => format!("Vm {}", spec.instance_type.as_str()),
+            Self::LoadBalancer(spec) => format!("LoadBalancer {}", spec.name),
+        }
+    }
 }
 
 #[derive(Debug, Default)]
@@ -872,6 +1291,9 @@ impl std::fmt::Display for SpecNode {
                 ResourceSpecType::Vm(_resource) => {
                     write!(f, "spec VM")
                 }
+                ResourceSpecType::LoadBalancer(resource) => {
+                    write!(f, "spec LoadBalancer {}", resource.name)
+                }
             },
         }
     }
@@ -893,6 +1315,7 @@ pub enum ResourceType {
     InstanceProfile(InstanceProfile),
     Ecr(Ecr),
     Vm(Vm),
+    LoadBalancer(LoadBalancer),
 }
 
 impl ResourceType {
@@ -911,6 +1334,7 @@ impl ResourceType {
             }
             ResourceType::Ecr(resource) => format!("ecr.{}", resource.id),
             ResourceType::Vm(resource) => format!("vm.{}", resource.id),
+            ResourceType::LoadBalancer(resource) => format!("load_balancer.{}", resource.name),
             ResourceType::None => String::from("none"),
         }
     }
@@ -963,6 +1387,9 @@ impl std::fmt::Display for Node {
                 ResourceType::Vm(resource) => {
                     write!(f, "cloud VM {}", resource.id)
                 }
+                ResourceType::LoadBalancer(resource) => {
+                    write!(f, "cloud LoadBalancer {}", resource.name)
+                }
                 ResourceType::None => {
                     write!(f, "cloud None")
                 }
@@ -985,6 +1412,10 @@ mod tests {
             .expect_create_vpc()
             .with(eq(String::from("0.0.0.0/0")), eq(String::from("vpc")))
             .return_once(|_, _| Ok(String::from("vpc-id")));
+        ec2_client_mock
+            .expect_get_vpc_state()
+            .with(eq(String::from("vpc-id")))
+            .return_once(|_| Ok(Some(VpcState::Available)));
 
         let vpc_manager = VpcManager {
             client: &ec2_client_mock,
@@ -994,6 +1425,7 @@ mod tests {
             region: String::from("us-west-2"),
             cidr_block: String::from("0.0.0.0/0"),
             name: String::from("vpc"),
+            existing_id: None,
         };
 
         // Act
@@ -1008,6 +1440,7 @@ mod tests {
                 region: String::from("us-west-2"),
                 cidr_block: String::from("0.0.0.0/0"),
                 name: String::from("vpc"),
+                existing: false,
             }
         );
     }
@@ -1029,6 +1462,7 @@ mod tests {
             region: String::new(),
             cidr_block: String::new(),
             name: String::new(),
+            existing_id: None,
         };
 
         // Act
@@ -1038,6 +1472,84 @@ mod tests {
         assert!(vpc.is_err());
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn test_vpc_manager_create_waits_for_available() {
+        // Arrange
+        let mut sequence = mockall::Sequence::new();
+        let mut ec2_client_mock = client::Ec2::default();
+        ec2_client_mock
+            .expect_create_vpc()
+            .return_once(|_, _| Ok(String::from("vpc-id")));
+        ec2_client_mock
+            .expect_get_vpc_state()
+            .with(eq(String::from("vpc-id")))
+            .times(1)
+            .in_sequence(&mut sequence)
+            .return_once(|_| Ok(None));
+        ec2_client_mock
+            .expect_get_vpc_state()
+            .with(eq(String::from("vpc-id")))
+            .times(1)
+            .in_sequence(&mut sequence)
+            .return_once(|_| Ok(Some(VpcState::Pending)));
+        ec2_client_mock
+            .expect_get_vpc_state()
+            .with(eq(String::from("vpc-id")))
+            .times(1)
+            .in_sequence(&mut sequence)
+            .return_once(|_| Ok(Some(VpcState::Available)));
+
+        let vpc_manager = VpcManager {
+            client: &ec2_client_mock,
+        };
+
+        let vpc_spec = VpcSpec {
+            region: String::from("us-west-2"),
+            cidr_block: String::from("0.0.0.0/0"),
+            name: String::from("vpc"),
+            existing_id: None,
+        };
+
+        // Act
+        let vpc = vpc_manager.create(&vpc_spec, vec![]).await;
+
+        // Assert
+        assert_eq!(vpc.expect("Failed to get VPC").id, "vpc-id");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_vpc_manager_create_never_available() {
+        // Arrange
+        let mut ec2_client_mock = client::Ec2::default();
+        ec2_client_mock
+            .expect_create_vpc()
+            .return_once(|_, _| Ok(String::from("vpc-id")));
+        ec2_client_mock
+            .expect_get_vpc_state()
+            .times(AVAILABILITY_MAX_ATTEMPTS)
+            .returning(|_| Ok(Some(VpcState::Pending)));
+
+        let vpc_manager = VpcManager {
+            client: &ec2_client_mock,
+        };
+
+        let vpc_spec = VpcSpec {
+            region: String::from("us-west-2"),
+            cidr_block: String::from("0.0.0.0/0"),
+            name: String::from("vpc"),
+            existing_id: None,
+        };
+
+        // Act
+        let vpc = vpc_manager.create(&vpc_spec, vec![]).await;
+
+        // Assert
+        assert_eq!(
+            vpc.expect_err("Expected availability error").to_string(),
+            "VPC vpc-id failed to become available"
+        );
+    }
+
     #[tokio::test]
     async fn test_vpc_manager_destroy() {
         // Arrange
@@ -1056,6 +1568,7 @@ mod tests {
             region: String::from("us-west-2"),
             cidr_block: String::from("0.0.0.0/0"),
             name: String::from("vpc"),
+            existing: false,
         };
 
         // Act
@@ -1083,6 +1596,7 @@ mod tests {
             region: String::from("us-west-2"),
             cidr_block: String::from("0.0.0.0/0"),
             name: String::from("vpc"),
+            existing: false,
         };
 
         // Act
@@ -1093,30 +1607,119 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_hosted_zone_manager_create() {
+    async fn test_vpc_manager_create_existing() {
         // Arrange
-        let mut route53_client_mock = client::Route53::default();
-        route53_client_mock
-            .expect_create_hosted_zone()
-            .with(eq(String::from("example.com")))
-            .return_once(|_| Ok(String::from("hosted-zone-id")));
+        let mut ec2_client_mock = client::Ec2::default();
+        ec2_client_mock
+            .expect_get_vpc_cidr_block()
+            .with(eq(String::from("vpc-existing")))
+            .return_once(|_| Ok(Some(String::from("172.31.0.0/16"))));
 
-        let hosted_zone_manager = HostedZoneManager {
-            client: &route53_client_mock,
+        let vpc_manager = VpcManager {
+            client: &ec2_client_mock,
         };
 
-        let hosted_zone_spec = HostedZoneSpec {
+        let vpc_spec = VpcSpec {
             region: String::from("us-west-2"),
-            name: String::from("example.com"),
+            cidr_block: String::from("10.0.0.0/16"),
+            name: String::from("vpc"),
+            existing_id: Some(String::from("vpc-existing")),
         };
 
         // Act
-        let hosted_zone = hosted_zone_manager.create(&hosted_zone_spec, vec![]).await;
+        let vpc = vpc_manager.create(&vpc_spec, vec![]).await;
 
         // Assert
-        assert!(hosted_zone.is_ok());
         assert_eq!(
-            hosted_zone.expect("Failed to get HostedZone"),
+            vpc.expect("Failed to get VPC"),
+            Vpc {
+                id: String::from("vpc-existing"),
+                region: String::from("us-west-2"),
+                cidr_block: String::from("172.31.0.0/16"),
+                name: String::from("vpc"),
+                existing: true,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_vpc_manager_create_existing_not_found() {
+        // Arrange
+        let mut ec2_client_mock = client::Ec2::default();
+        ec2_client_mock
+            .expect_get_vpc_cidr_block()
+            .with(eq(String::from("vpc-existing")))
+            .return_once(|_| Ok(None));
+
+        let vpc_manager = VpcManager {
+            client: &ec2_client_mock,
+        };
+
+        let vpc_spec = VpcSpec {
+            region: String::from("us-west-2"),
+            cidr_block: String::from("10.0.0.0/16"),
+            name: String::from("vpc"),
+            existing_id: Some(String::from("vpc-existing")),
+        };
+
+        // Act
+        let vpc = vpc_manager.create(&vpc_spec, vec![]).await;
+
+        // Assert
+        assert_eq!(
+            vpc.expect_err("Expected not found error").to_string(),
+            "Existing VPC vpc-existing is not found"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_vpc_manager_destroy_existing() {
+        // Arrange
+        let ec2_client_mock = client::Ec2::default();
+        let vpc_manager = VpcManager {
+            client: &ec2_client_mock,
+        };
+
+        let vpc = Vpc {
+            id: String::from("vpc-existing"),
+            region: String::from("us-west-2"),
+            cidr_block: String::from("172.31.0.0/16"),
+            name: String::from("vpc"),
+            existing: true,
+        };
+
+        // Act
+        let result = vpc_manager.destroy(&vpc, vec![]).await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_hosted_zone_manager_create() {
+        // Arrange
+        let mut route53_client_mock = client::Route53::default();
+        route53_client_mock
+            .expect_create_hosted_zone()
+            .with(eq(String::from("example.com")))
+            .return_once(|_| Ok(String::from("hosted-zone-id")));
+
+        let hosted_zone_manager = HostedZoneManager {
+            client: &route53_client_mock,
+        };
+
+        let hosted_zone_spec = HostedZoneSpec {
+            region: String::from("us-west-2"),
+            name: String::from("example.com"),
+        };
+
+        // Act
+        let hosted_zone = hosted_zone_manager.create(&hosted_zone_spec, vec![]).await;
+
+        // Assert
+        assert!(hosted_zone.is_ok());
+        assert_eq!(
+            hosted_zone.expect("Failed to get HostedZone"),
             HostedZone {
                 id: String::from("hosted-zone-id"),
                 region: String::from("us-west-2"),
@@ -1215,7 +1818,7 @@ mod tests {
                     "arn:aws:iam::aws:policy/AmazonS3ReadOnlyAccess",
                 )]),
             )
-            .return_once(|_, _, _| Ok(()));
+            .return_once(|_, _, _| Ok(true));
 
         let instance_role_manager = InstanceRoleManager {
             client: &iam_client_mock,
@@ -1248,6 +1851,64 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_instance_role_manager_create_already_exists() {
+        // Arrange
+        let mut iam_client_mock = client::IAM::default();
+        iam_client_mock
+            .expect_create_instance_iam_role()
+            .return_once(|_, _, _| Ok(false));
+        iam_client_mock
+            .expect_list_attached_role_policies()
+            .with(eq(String::from("role-name")))
+            .return_once(|_| {
+                Ok(vec![String::from(
+                    "arn:aws:iam::aws:policy/AmazonS3ReadOnlyAccess",
+                )])
+            });
+        iam_client_mock
+            .expect_attach_role_policy()
+            .with(
+                eq(String::from("role-name")),
+                eq(String::from(
+                    "arn:aws:iam::aws:policy/AmazonEC2ContainerRegistryReadOnly",
+                )),
+            )
+            .times(1)
+            .return_once(|_, _| Ok(()));
+
+        let instance_role_manager = InstanceRoleManager {
+            client: &iam_client_mock,
+        };
+
+        let instance_role_spec = InstanceRoleSpec {
+            name: String::from("role-name"),
+            assume_role_policy: String::from("assume-policy"),
+            policy_arns: vec![
+                String::from("arn:aws:iam::aws:policy/AmazonS3ReadOnlyAccess"),
+                String::from("arn:aws:iam::aws:policy/AmazonEC2ContainerRegistryReadOnly"),
+            ],
+        };
+
+        // Act
+        let instance_role = instance_role_manager
+            .create(&instance_role_spec, vec![])
+            .await;
+
+        // Assert
+        assert_eq!(
+            instance_role.expect("Failed to get InstanceRole"),
+            InstanceRole {
+                name: String::from("role-name"),
+                assume_role_policy: String::from("assume-policy"),
+                policy_arns: vec![
+                    String::from("arn:aws:iam::aws:policy/AmazonS3ReadOnlyAccess"),
+                    String::from("arn:aws:iam::aws:policy/AmazonEC2ContainerRegistryReadOnly"),
+                ],
+            }
+        );
+    }
+
     #[tokio::test]
     async fn test_instance_role_manager_create_error() {
         // Arrange
@@ -1352,6 +2013,7 @@ mod tests {
 
         let ecr_spec = EcrSpec {
             name: String::from("repo-name"),
+            keep_last_images: None,
         };
 
         // Act
@@ -1369,6 +2031,65 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_ecr_manager_create_with_lifecycle_policy() {
+        // Arrange
+        let mut ecr_client_mock = client::ECR::default();
+        ecr_client_mock
+            .expect_create_repository()
+            .with(eq(String::from("repo-name")))
+            .return_once(|_| Ok((String::from("repo-id"), String::from("repo-uri"))));
+        ecr_client_mock
+            .expect_put_lifecycle_policy()
+            .withf(|name, policy| {
+                let policy: serde_json::Value =
+                    serde_json::from_str(policy).expect("Failed to parse policy");
+
+                name == "repo-name"
+                    && policy
+                        == serde_json::json!({
+                            "rules": [
+                                {
+                                    "rulePriority": 1,
+                                    "description": "Keep last 10 images",
+                                    "selection": {
+                                        "tagStatus": "any",
+                                        "countType": "imageCountMoreThan",
+                                        "countNumber": 10,
+                                    },
+                                    "action": {
+                                        "type": "expire",
+                                    },
+                                },
+                            ],
+                        })
+            })
+            .times(1)
+            .return_once(|_, _| Ok(()));
+
+        let ecr_manager = EcrManager {
+            client: &ecr_client_mock,
+        };
+
+        let ecr_spec = EcrSpec {
+            name: String::from("repo-name"),
+            keep_last_images: Some(10),
+        };
+
+        // Act
+        let ecr = ecr_manager.create(&ecr_spec, vec![]).await;
+
+        // Assert
+        assert_eq!(
+            ecr.expect("Failed to create ECR"),
+            Ecr {
+                id: String::from("repo-id"),
+                uri: String::from("repo-uri"),
+                name: String::from("repo-name"),
+            }
+        );
+    }
+
     #[tokio::test]
     async fn test_ecr_manager_create_error() {
         // Arrange
@@ -1384,6 +2105,7 @@ mod tests {
 
         let ecr_spec = EcrSpec {
             name: String::from("repo-name"),
+            keep_last_images: None,
         };
 
         // Act
@@ -1464,6 +2186,7 @@ mod tests {
             region: String::from("us-west-2"),
             cidr_block: String::from("0.0.0.0/0"),
             name: String::from("vpc"),
+            existing: false,
         };
         let parents = [Node::Resource(ResourceType::Vpc(vpc))];
 
@@ -1520,6 +2243,7 @@ mod tests {
             region: String::from("us-west-2"),
             cidr_block: String::from("0.0.0.0/0"),
             name: String::from("vpc"),
+            existing: false,
         };
         let parents = [Node::Resource(ResourceType::Vpc(vpc))];
 
@@ -1553,6 +2277,7 @@ mod tests {
             region: String::from("us-west-2"),
             cidr_block: String::from("0.0.0.0/0"),
             name: String::from("vpc"),
+            existing: false,
         };
         let parents = [Node::Resource(ResourceType::Vpc(vpc))];
 
@@ -1605,6 +2330,7 @@ mod tests {
             region: String::from("us-west-2"),
             cidr_block: String::from("0.0.0.0/0"),
             name: String::from("vpc"),
+            existing: false,
         };
         let parents = [Node::Resource(ResourceType::Vpc(vpc))];
 
@@ -1628,6 +2354,10 @@ mod tests {
                 eq(String::from("subnet-name")),
             )
             .return_once(|_, _, _, _| Ok(String::from("subnet-id")));
+        ec2_client_mock
+            .expect_get_subnet_state()
+            .with(eq(String::from("subnet-id")))
+            .return_once(|_| Ok(Some(SubnetState::Available)));
         ec2_client_mock
             .expect_enable_auto_assign_ip_addresses_for_subnet()
             .with(eq(String::from("subnet-id")))
@@ -1645,12 +2375,14 @@ mod tests {
             name: String::from("subnet-name"),
             cidr_block: String::from("10.0.1.0/24"),
             availability_zone: String::from("us-west-2a"),
+            existing_id: None,
         };
         let vpc = Vpc {
             id: String::from("vpc-id"),
             region: String::from("us-west-2"),
             cidr_block: String::from("10.0.0.0/16"),
             name: String::from("vpc-name"),
+            existing: false,
         };
         let route_table = RouteTable {
             id: String::from("rt-id"),
@@ -1674,10 +2406,72 @@ mod tests {
                 name: String::from("subnet-name"),
                 cidr_block: String::from("10.0.1.0/24"),
                 availability_zone: String::from("us-west-2a"),
+                existing: false,
             }
         );
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn test_subnet_manager_create_waits_for_available() {
+        // Arrange
+        let mut sequence = mockall::Sequence::new();
+        let mut ec2_client_mock = client::Ec2::default();
+        ec2_client_mock
+            .expect_create_subnet()
+            .return_once(|_, _, _, _| Ok(String::from("subnet-id")));
+        ec2_client_mock
+            .expect_get_subnet_state()
+            .with(eq(String::from("subnet-id")))
+            .times(1)
+            .in_sequence(&mut sequence)
+            .return_once(|_| Ok(Some(SubnetState::Pending)));
+        ec2_client_mock
+            .expect_get_subnet_state()
+            .with(eq(String::from("subnet-id")))
+            .times(1)
+            .in_sequence(&mut sequence)
+            .return_once(|_| Ok(Some(SubnetState::Available)));
+        ec2_client_mock
+            .expect_enable_auto_assign_ip_addresses_for_subnet()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .return_once(|_| Ok(()));
+        ec2_client_mock
+            .expect_associate_route_table_with_subnet()
+            .return_once(|_, _| Ok(()));
+
+        let subnet_manager = SubnetManager {
+            client: &ec2_client_mock,
+        };
+
+        let subnet_spec = SubnetSpec {
+            name: String::from("subnet-name"),
+            cidr_block: String::from("10.0.1.0/24"),
+            availability_zone: String::from("us-west-2a"),
+            existing_id: None,
+        };
+        let parents = [
+            Node::Resource(ResourceType::Vpc(Vpc {
+                id: String::from("vpc-id"),
+                region: String::from("us-west-2"),
+                cidr_block: String::from("10.0.0.0/16"),
+                name: String::from("vpc-name"),
+                existing: false,
+            })),
+            Node::Resource(ResourceType::RouteTable(RouteTable {
+                id: String::from("rt-id"),
+            })),
+        ];
+
+        // Act
+        let subnet = subnet_manager
+            .create(&subnet_spec, parents.iter().collect())
+            .await;
+
+        // Assert
+        assert_eq!(subnet.expect("Failed to create subnet").id, "subnet-id");
+    }
+
     #[tokio::test]
     async fn test_subnet_manager_create_no_vpc_parent() {
         // Arrange
@@ -1689,6 +2483,7 @@ mod tests {
             name: String::from("subnet-name"),
             cidr_block: String::from("10.0.1.0/24"),
             availability_zone: String::from("us-west-2a"),
+            existing_id: None,
         };
         let route_table = RouteTable {
             id: String::from("rt-id"),
@@ -1719,12 +2514,14 @@ mod tests {
             name: String::from("subnet-name"),
             cidr_block: String::from("10.0.1.0/24"),
             availability_zone: String::from("us-west-2a"),
+            existing_id: None,
         };
         let vpc = Vpc {
             id: String::from("vpc-id"),
             region: String::from("us-west-2"),
             cidr_block: String::from("10.0.0.0/16"),
             name: String::from("vpc-name"),
+            existing: false,
         };
         let parents = [Node::Resource(ResourceType::Vpc(vpc))];
 
@@ -1757,12 +2554,14 @@ mod tests {
             name: String::from("subnet-name"),
             cidr_block: String::from("10.0.1.0/24"),
             availability_zone: String::from("us-west-2a"),
+            existing_id: None,
         };
         let vpc = Vpc {
             id: String::from("vpc-id"),
             region: String::from("us-west-2"),
             cidr_block: String::from("10.0.0.0/16"),
             name: String::from("vpc-name"),
+            existing: false,
         };
         let route_table = RouteTable {
             id: String::from("rt-id"),
@@ -1803,6 +2602,7 @@ mod tests {
             name: String::from("subnet-name"),
             cidr_block: String::from("10.0.1.0/24"),
             availability_zone: String::from("us-west-2a"),
+            existing: false,
         };
         let route_table = RouteTable {
             id: String::from("rt-id"),
@@ -1818,6 +2618,70 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_subnet_manager_create_existing() {
+        // Arrange
+        let mut ec2_client_mock = client::Ec2::default();
+        ec2_client_mock
+            .expect_get_subnet_placement()
+            .with(eq(String::from("subnet-existing")))
+            .return_once(|_| {
+                Ok(Some((
+                    String::from("172.31.16.0/20"),
+                    String::from("us-west-2c"),
+                )))
+            });
+
+        let subnet_manager = SubnetManager {
+            client: &ec2_client_mock,
+        };
+
+        let subnet_spec = SubnetSpec {
+            name: String::from("vpc-1-subnet"),
+            cidr_block: String::from("10.0.1.0/24"),
+            availability_zone: String::from("us-west-2a"),
+            existing_id: Some(String::from("subnet-existing")),
+        };
+
+        // Act
+        let subnet = subnet_manager.create(&subnet_spec, vec![]).await;
+
+        // Assert
+        assert_eq!(
+            subnet.expect("Failed to get subnet"),
+            Subnet {
+                id: String::from("subnet-existing"),
+                name: String::from("vpc-1-subnet"),
+                cidr_block: String::from("172.31.16.0/20"),
+                availability_zone: String::from("us-west-2c"),
+                existing: true,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_subnet_manager_destroy_existing() {
+        // Arrange
+        let ec2_client_mock = client::Ec2::default();
+        let subnet_manager = SubnetManager {
+            client: &ec2_client_mock,
+        };
+
+        let subnet = Subnet {
+            id: String::from("subnet-existing"),
+            name: String::from("vpc-1-subnet"),
+            cidr_block: String::from("172.31.16.0/20"),
+            availability_zone: String::from("us-west-2c"),
+            existing: true,
+        };
+
+        // Act
+        let result = subnet_manager.destroy(&subnet, vec![]).await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
     #[tokio::test]
     async fn test_subnet_manager_destroy_no_route_table_parent() {
         // Arrange
@@ -1830,6 +2694,7 @@ mod tests {
             name: String::from("subnet-name"),
             cidr_block: String::from("10.0.1.0/24"),
             availability_zone: String::from("us-west-2a"),
+            existing: false,
         };
 
         // Act
@@ -1860,6 +2725,7 @@ mod tests {
             name: String::from("subnet-name"),
             cidr_block: String::from("10.0.1.0/24"),
             availability_zone: String::from("us-west-2a"),
+            existing: false,
         };
         let route_table = RouteTable {
             id: String::from("rt-id"),
@@ -1898,6 +2764,7 @@ mod tests {
             region: String::from("us-west-2"),
             cidr_block: String::from("10.0.0.0/16"),
             name: String::from("vpc-name"),
+            existing: false,
         };
         let igw = InternetGateway {
             id: String::from("igw-id"),
@@ -1961,6 +2828,7 @@ mod tests {
             region: String::from("us-west-2"),
             cidr_block: String::from("10.0.0.0/16"),
             name: String::from("vpc-name"),
+            existing: false,
         };
         let parents = [Node::Resource(ResourceType::Vpc(vpc))];
 
@@ -1995,6 +2863,7 @@ mod tests {
             region: String::from("us-west-2"),
             cidr_block: String::from("10.0.0.0/16"),
             name: String::from("vpc-name"),
+            existing: false,
         };
         let igw = InternetGateway {
             id: String::from("igw-id"),
@@ -2082,6 +2951,7 @@ mod tests {
         let dns_record_spec = DnsRecordSpec {
             record_type: types::RecordType::A,
             ttl: Some(300),
+            wildcard: false,
         };
         let hosted_zone = HostedZone {
             id: String::from("hz-id"),
@@ -2119,26 +2989,137 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_dns_record_manager_create_no_hosted_zone_parent() {
+    async fn test_dns_record_manager_create_load_balancer() {
         // Arrange
-        let route53_client_mock = client::Route53::default();
+        let mut route53_client_mock = client::Route53::default();
+        route53_client_mock
+            .expect_create_dns_record()
+            .with(
+                eq(String::from("hz-id")),
+                eq(String::from("*.example.com")),
+                eq(types::RecordType::CNAME),
+                eq(String::from("lb-1.elb.amazonaws.com")),
+                eq(Some(300)),
+            )
+            .return_once(|_, _, _, _, _| Ok(()));
+
         let dns_record_manager = DnsRecordManager {
             client: &route53_client_mock,
         };
+
         let dns_record_spec = DnsRecordSpec {
-            record_type: types::RecordType::A,
+            record_type: types::RecordType::CNAME,
             ttl: Some(300),
+            wildcard: true,
         };
-        let vm = Vm {
-            id: String::from("vm-id"),
-            public_ip: String::from("1.2.3.4"),
-            instance_type: types::InstanceType::T3Micro,
-            ami: String::from("ami-123"),
-            user_data: String::new(),
+        let hosted_zone = HostedZone {
+            id: String::from("hz-id"),
+            name: String::from("example.com"),
+            region: String::from("us-west-2"),
         };
-        let parents = [Node::Resource(ResourceType::Vm(vm))];
-
-        // Act
+        let parents = [
+            Node::Resource(ResourceType::HostedZone(hosted_zone)),
+            Node::Resource(ResourceType::LoadBalancer(get_load_balancer())),
+        ];
+
+        // Act
+        let dns_record = dns_record_manager
+            .create(&dns_record_spec, parents.iter().collect())
+            .await;
+
+        // Assert
+        assert_eq!(
+            dns_record.expect("Failed to create dns record"),
+            DnsRecord {
+                name: String::from("*.example.com"),
+                value: String::from("lb-1.elb.amazonaws.com"),
+                record_type: types::RecordType::CNAME,
+                ttl: Some(300),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dns_record_manager_create_wildcard() {
+        // Arrange
+        let mut route53_client_mock = client::Route53::default();
+        route53_client_mock
+            .expect_create_dns_record()
+            .with(
+                eq(String::from("hz-id")),
+                eq(String::from("*.example.com")),
+                eq(types::RecordType::A),
+                eq(String::from("1.2.3.4")),
+                eq(Some(300)),
+            )
+            .return_once(|_, _, _, _, _| Ok(()));
+
+        let dns_record_manager = DnsRecordManager {
+            client: &route53_client_mock,
+        };
+
+        let dns_record_spec = DnsRecordSpec {
+            record_type: types::RecordType::A,
+            ttl: Some(300),
+            wildcard: true,
+        };
+        let hosted_zone = HostedZone {
+            id: String::from("hz-id"),
+            name: String::from("example.com"),
+            region: String::from("us-west-2"),
+        };
+        let vm = Vm {
+            id: String::from("vm-id"),
+            public_ip: String::from("1.2.3.4"),
+            instance_type: types::InstanceType::T3Micro,
+            ami: String::from("ami-123"),
+            user_data: String::new(),
+        };
+        let parents = [
+            Node::Resource(ResourceType::HostedZone(hosted_zone)),
+            Node::Resource(ResourceType::Vm(vm)),
+        ];
+
+        // Act
+        let dns_record = dns_record_manager
+            .create(&dns_record_spec, parents.iter().collect())
+            .await;
+
+        // Assert
+        assert!(dns_record.is_ok());
+        assert_eq!(
+            dns_record.expect("Failed to create dns record"),
+            DnsRecord {
+                name: String::from("*.example.com"),
+                value: String::from("1.2.3.4"),
+                record_type: types::RecordType::A,
+                ttl: Some(300),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dns_record_manager_create_no_hosted_zone_parent() {
+        // Arrange
+        let route53_client_mock = client::Route53::default();
+        let dns_record_manager = DnsRecordManager {
+            client: &route53_client_mock,
+        };
+        let dns_record_spec = DnsRecordSpec {
+            record_type: types::RecordType::A,
+            ttl: Some(300),
+            wildcard: false,
+        };
+        let vm = Vm {
+            id: String::from("vm-id"),
+            public_ip: String::from("1.2.3.4"),
+            instance_type: types::InstanceType::T3Micro,
+            ami: String::from("ami-123"),
+            user_data: String::new(),
+        };
+        let parents = [Node::Resource(ResourceType::Vm(vm))];
+
+        // Act
         let result = dns_record_manager
             .create(&dns_record_spec, parents.iter().collect())
             .await;
@@ -2161,6 +3142,7 @@ mod tests {
         let dns_record_spec = DnsRecordSpec {
             record_type: types::RecordType::A,
             ttl: Some(300),
+            wildcard: false,
         };
         let hosted_zone = HostedZone {
             id: String::from("hz-id"),
@@ -2197,6 +3179,7 @@ mod tests {
         let dns_record_spec = DnsRecordSpec {
             record_type: types::RecordType::A,
             ttl: Some(300),
+            wildcard: false,
         };
         let hosted_zone = HostedZone {
             id: String::from("hz-id"),
@@ -2248,6 +3231,7 @@ mod tests {
             value: String::from("1.2.3.4"),
             record_type: types::RecordType::A,
             ttl: Some(300),
+            wildcard: false,
         };
         let hosted_zone = HostedZone {
             id: String::from("hz-id"),
@@ -2277,6 +3261,7 @@ mod tests {
             value: String::from("1.2.3.4"),
             record_type: types::RecordType::A,
             ttl: Some(300),
+            wildcard: false,
         };
 
         // Act
@@ -2307,6 +3292,7 @@ mod tests {
             value: String::from("1.2.3.4"),
             record_type: types::RecordType::A,
             ttl: Some(300),
+            wildcard: false,
         };
         let hosted_zone = HostedZone {
             id: String::from("hz-id"),
@@ -2363,6 +3349,7 @@ mod tests {
             region: String::from("us-west-2"),
             cidr_block: String::from("10.0.0.0/16"),
             name: String::from("vpc-name"),
+            existing: false,
         };
         let parents = [Node::Resource(ResourceType::Vpc(vpc))];
 
@@ -2433,6 +3420,7 @@ mod tests {
             region: String::from("us-west-2"),
             cidr_block: String::from("10.0.0.0/16"),
             name: String::from("vpc-name"),
+            existing: false,
         };
         let parents = [Node::Resource(ResourceType::Vpc(vpc))];
 
@@ -2510,7 +3498,7 @@ mod tests {
                 eq(String::from("profile-name")),
                 eq(vec![String::from("role-name")]),
             )
-            .return_once(|_, _| Ok(()));
+            .return_once(|_, _| Ok(true));
 
         let instance_profile_manager = InstanceProfileManager {
             client: &iam_client_mock,
@@ -2541,6 +3529,91 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_instance_profile_manager_create_already_exists() {
+        // Arrange
+        let mut iam_client_mock = client::IAM::default();
+        iam_client_mock
+            .expect_create_instance_profile()
+            .return_once(|_, _| Ok(false));
+        iam_client_mock
+            .expect_get_instance_profile_role_names()
+            .with(eq(String::from("profile-name")))
+            .return_once(|_| Ok(vec![]));
+        iam_client_mock
+            .expect_add_role_to_instance_profile()
+            .with(
+                eq(String::from("profile-name")),
+                eq(String::from("role-name")),
+            )
+            .times(1)
+            .return_once(|_, _| Ok(()));
+
+        let instance_profile_manager = InstanceProfileManager {
+            client: &iam_client_mock,
+        };
+
+        let instance_profile_spec = InstanceProfileSpec {
+            name: String::from("profile-name"),
+        };
+        let instance_role = InstanceRole {
+            name: String::from("role-name"),
+            assume_role_policy: String::new(),
+            policy_arns: vec![],
+        };
+        let parents = [Node::Resource(ResourceType::InstanceRole(instance_role))];
+
+        // Act
+        let instance_profile = instance_profile_manager
+            .create(&instance_profile_spec, parents.iter().collect())
+            .await;
+
+        // Assert
+        assert_eq!(
+            instance_profile.expect("Failed to create instance profile"),
+            InstanceProfile {
+                name: String::from("profile-name"),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_instance_profile_manager_create_already_exists_with_role() {
+        // Arrange
+        let mut iam_client_mock = client::IAM::default();
+        iam_client_mock
+            .expect_create_instance_profile()
+            .return_once(|_, _| Ok(false));
+        iam_client_mock
+            .expect_get_instance_profile_role_names()
+            .return_once(|_| Ok(vec![String::from("role-name")]));
+        iam_client_mock
+            .expect_add_role_to_instance_profile()
+            .never();
+
+        let instance_profile_manager = InstanceProfileManager {
+            client: &iam_client_mock,
+        };
+
+        let instance_profile_spec = InstanceProfileSpec {
+            name: String::from("profile-name"),
+        };
+        let instance_role = InstanceRole {
+            name: String::from("role-name"),
+            assume_role_policy: String::new(),
+            policy_arns: vec![],
+        };
+        let parents = [Node::Resource(ResourceType::InstanceRole(instance_role))];
+
+        // Act
+        let result = instance_profile_manager
+            .create(&instance_profile_spec, parents.iter().collect())
+            .await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
     #[tokio::test]
     async fn test_instance_profile_manager_create_no_instance_role_parent() {
         // Arrange
@@ -2548,7 +3621,7 @@ mod tests {
         iam_client_mock
             .expect_create_instance_profile()
             .with(eq(String::from("profile-name")), eq(Vec::<String>::new()))
-            .return_once(|_, _| Ok(()));
+            .return_once(|_, _| Ok(true));
         let instance_profile_manager = InstanceProfileManager {
             client: &iam_client_mock,
         };
@@ -2713,6 +3786,7 @@ mod tests {
             name: String::from("subnet-name"),
             cidr_block: String::from("10.0.1.0/24"),
             availability_zone: String::from("us-west-2a"),
+            existing: false,
         };
         let ecr = Ecr {
             id: String::from("ecr-id"),
@@ -2811,6 +3885,7 @@ mod tests {
             name: String::from("subnet-name"),
             cidr_block: String::from("10.0.1.0/24"),
             availability_zone: String::from("us-west-2a"),
+            existing: false,
         };
         let ecr = Ecr {
             id: String::from("ecr-id"),
@@ -2856,6 +3931,7 @@ mod tests {
             name: String::from("subnet-name"),
             cidr_block: String::from("10.0.1.0/24"),
             availability_zone: String::from("us-west-2a"),
+            existing: false,
         };
         let ecr = Ecr {
             id: String::from("ecr-id"),
@@ -2904,6 +3980,7 @@ mod tests {
             name: String::from("subnet-name"),
             cidr_block: String::from("10.0.1.0/24"),
             availability_zone: String::from("us-west-2a"),
+            existing: false,
         };
         let ecr = Ecr {
             id: String::from("ecr-id"),
@@ -2939,7 +4016,7 @@ mod tests {
         ec2_client_mock
             .expect_terminate_instance()
             .with(eq(String::from("vm-id")))
-            .return_once(|_| Ok(()));
+            .return_once(|_| Ok(true));
         ec2_client_mock
             .expect_describe_instances()
             .with(eq(String::from("vm-id")))
@@ -2970,6 +4047,35 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_vm_manager_destroy_already_terminated() {
+        // Arrange
+        let mut ec2_client_mock = client::Ec2::default();
+        ec2_client_mock
+            .expect_terminate_instance()
+            .with(eq(String::from("vm-id")))
+            .return_once(|_| Ok(false));
+        ec2_client_mock.expect_describe_instances().never();
+
+        let vm_manager = VmManager {
+            client: &ec2_client_mock,
+        };
+
+        let vm = Vm {
+            id: String::from("vm-id"),
+            public_ip: String::from("1.2.3.4"),
+            instance_type: types::InstanceType::T3Micro,
+            ami: String::from("ami-123"),
+            user_data: String::from("user-data"),
+        };
+
+        // Act
+        let result = vm_manager.destroy(&vm, vec![]).await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
     #[tokio::test]
     async fn test_vm_manager_destroy_error() {
         // Arrange
@@ -2997,4 +4103,275 @@ mod tests {
         // Assert
         assert!(result.is_err());
     }
+
+    fn get_load_balancer() -> LoadBalancer {
+        LoadBalancer {
+            name: String::from("lb-1"),
+            arn: String::from("lb-arn"),
+            dns_name: String::from("lb-1.elb.amazonaws.com"),
+            target_group_arn: String::from("tg-arn"),
+            listener_arn: String::from("listener-arn"),
+            target_port: 8080,
+            instance_ids: vec![String::from("vm-1"), String::from("vm-2")],
+        }
+    }
+
+    fn get_load_balancer_parents(subnet_count: usize) -> Vec<Node> {
+        let mut parents = vec![Node::Resource(ResourceType::Vpc(Vpc {
+            id: String::from("vpc-id"),
+            region: String::from("us-west-2"),
+            cidr_block: String::from("10.0.0.0/16"),
+            name: String::from("vpc-1"),
+            existing: false,
+        }))];
+
+        for (index, availability_zone) in ["us-west-2a", "us-west-2b"]
+            .into_iter()
+            .take(subnet_count)
+            .enumerate()
+        {
+            parents.push(Node::Resource(ResourceType::Subnet(Subnet {
+                id: format!("subnet-{}", index + 1),
+                name: format!("subnet-{}", index + 1),
+                cidr_block: format!("10.0.{}.0/24", index + 1),
+                availability_zone: String::from(availability_zone),
+                existing: false,
+            })));
+        }
+
+        parents.push(Node::Resource(ResourceType::SecurityGroup(SecurityGroup {
+            id: String::from("sg-id"),
+            name: String::from("sg-name"),
+            inbound_rules: vec![],
+        })));
+
+        for id in ["vm-1", "vm-2"] {
+            parents.push(Node::Resource(ResourceType::Vm(Vm {
+                id: String::from(id),
+                public_ip: String::from("1.2.3.4"),
+                instance_type: types::InstanceType::T3Micro,
+                ami: String::from("ami-123"),
+                user_data: String::new(),
+            })));
+        }
+
+        parents
+    }
+
+    #[tokio::test]
+    async fn test_load_balancer_manager_create() {
+        // Arrange
+        let mut elbv2_client_mock = client::ELBv2::default();
+        elbv2_client_mock
+            .expect_create_target_group()
+            .with(
+                eq(String::from("lb-1-targets")),
+                eq(String::from("vpc-id")),
+                eq(8080),
+            )
+            .return_once(|_, _, _| Ok(String::from("tg-arn")));
+        elbv2_client_mock
+            .expect_create_load_balancer()
+            .with(
+                eq(String::from("lb-1")),
+                eq(vec![String::from("subnet-1"), String::from("subnet-2")]),
+                eq(vec![String::from("sg-id")]),
+            )
+            .return_once(|_, _, _| {
+                Ok((
+                    String::from("lb-arn"),
+                    String::from("lb-1.elb.amazonaws.com"),
+                ))
+            });
+        elbv2_client_mock
+            .expect_create_listener()
+            .with(
+                eq(String::from("lb-arn")),
+                eq(String::from("tg-arn")),
+                eq(80),
+            )
+            .return_once(|_, _, _| Ok(String::from("listener-arn")));
+        elbv2_client_mock
+            .expect_register_targets()
+            .with(
+                eq(String::from("tg-arn")),
+                eq(vec![String::from("vm-1"), String::from("vm-2")]),
+            )
+            .return_once(|_, _| Ok(()));
+
+        let load_balancer_manager = LoadBalancerManager {
+            client: &elbv2_client_mock,
+        };
+
+        let load_balancer_spec = LoadBalancerSpec {
+            name: String::from("lb-1"),
+            target_port: 8080,
+        };
+        let parents = get_load_balancer_parents(2);
+
+        // Act
+        let load_balancer = load_balancer_manager
+            .create(&load_balancer_spec, parents.iter().collect())
+            .await;
+
+        // Assert
+        assert_eq!(
+            load_balancer.expect("Failed to create load balancer"),
+            get_load_balancer()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_load_balancer_manager_create_single_subnet() {
+        // Arrange
+        let elbv2_client_mock = client::ELBv2::default();
+        let load_balancer_manager = LoadBalancerManager {
+            client: &elbv2_client_mock,
+        };
+
+        let load_balancer_spec = LoadBalancerSpec {
+            name: String::from("lb-1"),
+            target_port: 8080,
+        };
+        let parents = get_load_balancer_parents(1);
+
+        // Act
+        let result = load_balancer_manager
+            .create(&load_balancer_spec, parents.iter().collect())
+            .await;
+
+        // Assert
+        assert_eq!(
+            result.expect_err("Expected error").to_string(),
+            "LoadBalancer expects at least two Subnets as parents"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_load_balancer_manager_create_register_error() {
+        // Arrange
+        let mut elbv2_client_mock = client::ELBv2::default();
+        elbv2_client_mock
+            .expect_create_target_group()
+            .return_once(|_, _, _| Ok(String::from("tg-arn")));
+        elbv2_client_mock
+            .expect_create_load_balancer()
+            .return_once(|_, _, _| {
+                Ok((
+                    String::from("lb-arn"),
+                    String::from("lb-1.elb.amazonaws.com"),
+                ))
+            });
+        elbv2_client_mock
+            .expect_create_listener()
+            .return_once(|_, _, _| Ok(String::from("listener-arn")));
+        elbv2_client_mock
+            .expect_register_targets()
+            .return_once(|_, _| Err("InvalidTarget".into()));
+
+        let load_balancer_manager = LoadBalancerManager {
+            client: &elbv2_client_mock,
+        };
+
+        let load_balancer_spec = LoadBalancerSpec {
+            name: String::from("lb-1"),
+            target_port: 8080,
+        };
+        let parents = get_load_balancer_parents(2);
+
+        // Act
+        let result = load_balancer_manager
+            .create(&load_balancer_spec, parents.iter().collect())
+            .await;
+
+        // Assert
+        assert!(result.is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_load_balancer_manager_destroy() {
+        // Arrange
+        let mut sequence = mockall::Sequence::new();
+
+        let mut elbv2_client_mock = client::ELBv2::default();
+        elbv2_client_mock
+            .expect_delete_listener()
+            .with(eq(String::from("listener-arn")))
+            .times(1)
+            .in_sequence(&mut sequence)
+            .return_once(|_| Ok(()));
+        elbv2_client_mock
+            .expect_delete_load_balancer()
+            .with(eq(String::from("lb-arn")))
+            .times(1)
+            .in_sequence(&mut sequence)
+            .return_once(|_| Ok(()));
+        elbv2_client_mock
+            .expect_is_load_balancer_deleted()
+            .with(eq(String::from("lb-arn")))
+            .times(1)
+            .in_sequence(&mut sequence)
+            .return_once(|_| Ok(false));
+        elbv2_client_mock
+            .expect_is_load_balancer_deleted()
+            .with(eq(String::from("lb-arn")))
+            .times(1)
+            .in_sequence(&mut sequence)
+            .return_once(|_| Ok(true));
+        elbv2_client_mock
+            .expect_delete_target_group()
+            .with(eq(String::from("tg-arn")))
+            .times(1)
+            .in_sequence(&mut sequence)
+            .return_once(|_| Ok(()));
+
+        let load_balancer_manager = LoadBalancerManager {
+            client: &elbv2_client_mock,
+        };
+
+        // Act
+        let result = load_balancer_manager
+            .destroy(&get_load_balancer(), vec![])
+            .await;
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_load_balancer_manager_deregister_and_register_instances() {
+        // Arrange
+        let mut sequence = mockall::Sequence::new();
+
+        let mut elbv2_client_mock = client::ELBv2::default();
+        elbv2_client_mock
+            .expect_deregister_targets()
+            .with(eq(String::from("tg-arn")), eq(vec![String::from("vm-1")]))
+            .times(1)
+            .in_sequence(&mut sequence)
+            .return_once(|_, _| Ok(()));
+        elbv2_client_mock
+            .expect_register_targets()
+            .with(eq(String::from("tg-arn")), eq(vec![String::from("vm-1")]))
+            .times(1)
+            .in_sequence(&mut sequence)
+            .return_once(|_, _| Ok(()));
+
+        let load_balancer_manager = LoadBalancerManager {
+            client: &elbv2_client_mock,
+        };
+        let load_balancer = get_load_balancer();
+
+        // Act
+        let deregister_result = load_balancer_manager
+            .deregister_instances(&load_balancer, vec![String::from("vm-1")])
+            .await;
+        let register_result = load_balancer_manager
+            .register_instances(&load_balancer, vec![String::from("vm-1")])
+            .await;
+
+        // Assert
+        assert!(deregister_result.is_ok());
+        assert!(register_result.is_ok());
+    }
 }
@@ -5,13 +5,92 @@ use petgraph::graph::NodeIndex;
 use petgraph::visit::NodeIndexable;
 use serde::{Deserialize, Serialize};
 
-use crate::infra::resource::{Node, ResourceType, Vm};
+use crate::infra::resource::{LoadBalancer, Node, ResourceType, Vm, Vpc};
 
-#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Clone)]
+/// Version of the state file format written by this release, bumped with a
+/// new step in [`MIGRATIONS`] on every incompatible change
+pub const STATE_VERSION: u32 = 1;
+
+/// Infra state, older state files are upgraded to [`STATE_VERSION`] when
+/// they are loaded
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+#[serde(try_from = "serde_json::Value")]
 pub struct State {
+    version: u32,
     resources: Vec<ResourceState>,
 }
 
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            version: STATE_VERSION,
+            resources: Vec::new(),
+        }
+    }
+}
+
+impl TryFrom<serde_json::Value> for State {
+    type Error = String;
+
+    fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
+        /// State file in the current format
+        #[derive(Deserialize)]
+        struct StateFile {
+            version: u32,
+            resources: Vec<ResourceState>,
+        }
+
+        let state_file: StateFile =
+            serde_json::from_value(migrate(value)?).map_err(|e| e.to_string())?;
+
+        Ok(Self {
+            version: state_file.version,
+            resources: state_file.resources,
+        })
+    }
+}
+
+/// State file migrations, the step at index `n` upgrades the JSON from
+/// version `n` to `n + 1`
+const MIGRATIONS: [fn(&mut serde_json::Value); STATE_VERSION as usize] = [
+    // v1 only adds the `version` field, it is set after the migrations
+    |_state| {},
+];
+
+/// Upgrades the state file JSON to [`STATE_VERSION`] one version at a time,
+/// a state without `version` is v0
+fn migrate(mut value: serde_json::Value) -> Result<serde_json::Value, String> {
+    let version = match value.get("version") {
+        None => 0,
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| format!("Invalid state version {version}"))?,
+    };
+
+    if version > STATE_VERSION {
+        return Err(format!(
+            "State version {version} is newer than the supported version {STATE_VERSION}, \
+             upgrade oct"
+        ));
+    }
+
+    for (from_version, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        migration(&mut value);
+
+        log::info!(
+            "Migrated state from version {from_version} to {}",
+            from_version + 1
+        );
+    }
+
+    if let Some(state) = value.as_object_mut() {
+        state.insert(String::from("version"), STATE_VERSION.into());
+    }
+
+    Ok(value)
+}
+
 #[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Clone)]
 struct ResourceState {
     name: String,
@@ -20,6 +99,11 @@ struct ResourceState {
 }
 
 impl State {
+    /// Version of the state file format
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
     pub fn get_vms(&self) -> Vec<Vm> {
         self.resources
             .iter()
@@ -33,6 +117,135 @@ impl State {
             .collect()
     }
 
+    pub fn get_vpcs(&self) -> Vec<Vpc> {
+        self.resources
+            .iter()
+            .filter_map(|resource_state| {
+                if let ResourceType::Vpc(vpc) = &resource_state.resource {
+                    Some(vpc.clone())
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Load balancer fronting the VMs, `None` when it is not deployed
+    pub fn get_load_balancer(&self) -> Option<LoadBalancer> {
+        self.resources
+            .iter()
+            .find_map(|resource_state| match &resource_state.resource {
+                ResourceType::LoadBalancer(load_balancer) => Some(load_balancer.clone()),
+                _ => None,
+            })
+    }
+
+    /// Region the infrastructure is deployed to, recorded on the VPC
+    pub fn get_region(&self) -> Option<&str> {
+        self.resources
+            .iter()
+            .find_map(|resource_state| match &resource_state.resource {
+                ResourceType::Vpc(vpc) if !vpc.region.is_empty() => Some(vpc.region.as_str()),
+                _ => None,
+            })
+    }
+
+    /// Adds a resource created outside of oct to the state, so it is managed
+    /// by the next deployments, the `dependencies` names have to be in the
+    /// state already
+    ///
+    /// Returns the name of the added resource
+    pub fn insert_resource(
+        &mut self,
+        resource: ResourceType,
+        mut dependencies: Vec<String>,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let name = resource.name();
+
+        if self
+            .resources
+            .iter()
+            .any(|resource_state| resource_state.name == name)
+        {
+            return Err(format!("Resource '{name}' already exists in state").into());
+        }
+
+        for dependency in &dependencies {
+            if !self
+                .resources
+                .iter()
+                .any(|resource_state| &resource_state.name == dependency)
+            {
+                return Err(format!("Dependency '{dependency}' is not found in state").into());
+            }
+        }
+
+        dependencies.sort();
+        dependencies.dedup();
+
+        self.resources.push(ResourceState {
+            name: name.clone(),
+            resource,
+            dependencies,
+        });
+
+        Ok(name)
+    }
+
+    /// Keeps the resources of the `previous` state missing in this one, e.g.
+    /// the imported ones, a resource with the same name is replaced by the
+    /// one of this state
+    pub fn merge_previous(&mut self, previous: State) {
+        for resource_state in previous.resources {
+            if !self
+                .resources
+                .iter()
+                .any(|current| current.name == resource_state.name)
+            {
+                self.resources.push(resource_state);
+            }
+        }
+    }
+
+    /// Removes the resource from the state without destroying it,
+    /// resources depending on it are rejected unless `force` is set
+    pub fn remove_resource(
+        &mut self,
+        name: &str,
+        force: bool,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let Some(position) = self
+            .resources
+            .iter()
+            .position(|resource_state| resource_state.name == name)
+        else {
+            return Err(format!("Resource '{name}' is not found in state").into());
+        };
+
+        let dependents: Vec<&str> = self
+            .resources
+            .iter()
+            .filter(|resource_state| resource_state.dependencies.iter().any(|dep| dep == name))
+            .map(|resource_state| resource_state.name.as_str())
+            .collect();
+
+        if !dependents.is_empty() && !force {
+            return Err(format!(
+                "Resource '{name}' is a dependency of {}, use force to remove it anyway",
+                dependents.join(", ")
+            )
+            .into());
+        }
+
+        self.resources.remove(position);
+
+        for resource_state in &mut self.resources {
+            resource_state.dependencies.retain(|dep| dep != name);
+        }
+
+        Ok(())
+    }
+
     pub fn from_graph(graph: &Graph<Node, String>) -> Self {
         let mut resource_states: Vec<ResourceState> = Vec::new();
 
@@ -95,6 +308,7 @@ impl State {
         });
 
         Self {
+            version: STATE_VERSION,
             resources: resource_states,
         }
     }
@@ -164,6 +378,7 @@ mod tests {
             name: String::from("vpc-name"),
             region: String::from("us-west-2"),
             cidr_block: String::from("10.0.0.0/16"),
+            existing: false,
         };
         let resource_node = graph.add_node(Node::Resource(ResourceType::Vpc(vpc.clone())));
         graph.add_edge(root, resource_node, String::new());
@@ -175,6 +390,7 @@ mod tests {
         assert_eq!(
             state,
             State {
+                version: STATE_VERSION,
                 resources: vec![ResourceState {
                     name: String::from("vpc.vpc-name"),
                     resource: ResourceType::Vpc(vpc),
@@ -194,6 +410,7 @@ mod tests {
             name: String::from("vpc-name"),
             region: String::from("us-west-2"),
             cidr_block: String::from("10.0.0.0/16"),
+            existing: false,
         };
         let vpc_node = graph.add_node(Node::Resource(ResourceType::Vpc(vpc.clone())));
         graph.add_edge(root, vpc_node, String::new());
@@ -203,6 +420,7 @@ mod tests {
             name: String::from("subnet-name"),
             cidr_block: String::from("10.0.1.0/24"),
             availability_zone: String::from("us-west-2a"),
+            existing: false,
         };
         let subnet_node = graph.add_node(Node::Resource(ResourceType::Subnet(subnet.clone())));
         graph.add_edge(vpc_node, subnet_node, String::new());
@@ -214,6 +432,7 @@ mod tests {
         assert_eq!(
             state,
             State {
+                version: STATE_VERSION,
                 resources: vec![
                     ResourceState {
                         name: String::from("vpc.vpc-name"),
@@ -251,6 +470,7 @@ mod tests {
             name: String::from("vpc-name"),
             region: String::from("us-west-2"),
             cidr_block: String::from("10.0.0.0/16"),
+            existing: false,
         };
         let resource_state = ResourceState {
             name: String::from("vpc.vpc-name"),
@@ -258,6 +478,7 @@ mod tests {
             dependencies: vec![],
         };
         let state = State {
+            version: STATE_VERSION,
             resources: vec![resource_state],
         };
 
@@ -288,6 +509,7 @@ mod tests {
             name: String::from("vpc-name"),
             region: String::from("us-west-2"),
             cidr_block: String::from("10.0.0.0/16"),
+            existing: false,
         };
         let vpc_resource_state = ResourceState {
             name: String::from("vpc.vpc-name"),
@@ -300,6 +522,7 @@ mod tests {
             name: String::from("subnet-name"),
             cidr_block: String::from("10.0.1.0/24"),
             availability_zone: String::from("us-west-2a"),
+            existing: false,
         };
         let subnet_resource_state = ResourceState {
             name: String::from("subnet.subnet-name"),
@@ -308,6 +531,7 @@ mod tests {
         };
 
         let state = State {
+            version: STATE_VERSION,
             resources: vec![vpc_resource_state, subnet_resource_state],
         };
 
@@ -351,6 +575,7 @@ mod tests {
             dependencies: vec![],
         };
         let state = State {
+            version: STATE_VERSION,
             resources: vec![resource_state],
         };
 
@@ -361,4 +586,354 @@ mod tests {
         assert_eq!(vms.len(), 1);
         assert_eq!(vms[0], vm);
     }
+
+    #[test]
+    fn test_get_vpcs() {
+        // Arrange
+        let vpc = Vpc {
+            id: String::from("vpc-id"),
+            region: String::from("us-west-2"),
+            cidr_block: String::from("10.0.0.0/16"),
+            name: String::from("vpc"),
+            existing: false,
+        };
+        let state = State {
+            version: STATE_VERSION,
+            resources: vec![ResourceState {
+                name: String::from("vpc.vpc-id"),
+                resource: ResourceType::Vpc(vpc.clone()),
+                dependencies: vec![],
+            }],
+        };
+
+        // Act
+        let vpcs = state.get_vpcs();
+
+        // Assert
+        assert_eq!(vpcs, vec![vpc]);
+    }
+
+    #[test]
+    fn test_get_load_balancer() {
+        // Arrange
+        let load_balancer = LoadBalancer {
+            name: String::from("load-balancer-1"),
+            arn: String::from("lb-arn"),
+            dns_name: String::from("lb-1.elb.amazonaws.com"),
+            target_group_arn: String::from("tg-arn"),
+            listener_arn: String::from("listener-arn"),
+            target_port: 8080,
+            instance_ids: vec![String::from("vm-id")],
+        };
+        let state = State {
+            version: STATE_VERSION,
+            resources: vec![ResourceState {
+                name: String::from("load_balancer.load-balancer-1"),
+                resource: ResourceType::LoadBalancer(load_balancer.clone()),
+                dependencies: vec![],
+            }],
+        };
+
+        // Act
+        let loaded_load_balancer = state.get_load_balancer();
+
+        // Assert
+        assert_eq!(loaded_load_balancer, Some(load_balancer));
+        assert_eq!(State::default().get_load_balancer(), None);
+    }
+
+    #[test]
+    fn test_get_region_round_trip() {
+        // Arrange
+        let state = State {
+            version: STATE_VERSION,
+            resources: vec![ResourceState {
+                name: String::from("vpc.vpc-name"),
+                resource: ResourceType::Vpc(Vpc {
+                    id: String::from("vpc-id"),
+                    name: String::from("vpc-name"),
+                    region: String::from("eu-central-1"),
+                    cidr_block: String::from("10.0.0.0/16"),
+                    existing: false,
+                }),
+                dependencies: vec![],
+            }],
+        };
+
+        // Act
+        let loaded_state: State = serde_json::from_str(
+            &serde_json::to_string(&state).expect("Failed to serialize state"),
+        )
+        .expect("Failed to deserialize state");
+
+        // Assert
+        assert_eq!(loaded_state.get_region(), Some("eu-central-1"));
+        assert_eq!(State::default().get_region(), None);
+    }
+
+    #[test]
+    fn test_state_migrates_v0() {
+        // Arrange
+        let v0_state = r#"{
+            "resources": [
+                {
+                    "name": "vpc.vpc-name",
+                    "resource": {
+                        "Vpc": {
+                            "id": "vpc-id",
+                            "region": "us-west-2",
+                            "cidr_block": "10.0.0.0/16",
+                            "name": "vpc-name"
+                        }
+                    },
+                    "dependencies": []
+                }
+            ]
+        }"#;
+
+        // Act
+        let state: State = serde_json::from_str(v0_state).expect("Failed to load v0 state");
+
+        // Assert
+        assert_eq!(state.version(), STATE_VERSION);
+        assert_eq!(
+            state.get_vpcs(),
+            vec![Vpc {
+                id: String::from("vpc-id"),
+                region: String::from("us-west-2"),
+                cidr_block: String::from("10.0.0.0/16"),
+                name: String::from("vpc-name"),
+                existing: false,
+            }]
+        );
+
+        let saved_state: serde_json::Value =
+            serde_json::to_value(&state).expect("Failed to serialize state");
+        assert_eq!(saved_state["version"], STATE_VERSION);
+    }
+
+    #[test]
+    fn test_state_newer_version() {
+        // Act
+        let result = serde_json::from_str::<State>(r#"{"version": 99, "resources": []}"#);
+
+        // Assert
+        let error = result.expect_err("Expected version error").to_string();
+        assert!(error.starts_with(&format!(
+            "State version 99 is newer than the supported version {STATE_VERSION}, upgrade oct"
+        )));
+    }
+
+    fn get_vpc_with_subnet_state() -> State {
+        State {
+            version: STATE_VERSION,
+            resources: vec![
+                ResourceState {
+                    name: String::from("vpc.vpc-id"),
+                    resource: ResourceType::None,
+                    dependencies: vec![],
+                },
+                ResourceState {
+                    name: String::from("subnet.subnet-id"),
+                    resource: ResourceType::None,
+                    dependencies: vec![String::from("vpc.vpc-id")],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_insert_resource() {
+        // Arrange
+        let mut state = get_vpc_with_subnet_state();
+        let subnet = Subnet {
+            id: String::from("subnet-0123"),
+            name: String::from("subnet-0123"),
+            cidr_block: String::from("10.0.2.0/24"),
+            availability_zone: String::from("us-west-2b"),
+            existing: false,
+        };
+
+        // Act
+        let name = state
+            .insert_resource(
+                ResourceType::Subnet(subnet.clone()),
+                vec![String::from("vpc.vpc-id")],
+            )
+            .expect("Failed to insert resource");
+
+        // Assert
+        assert_eq!(name, "subnet.subnet-0123");
+        assert_eq!(
+            state.resources.last(),
+            Some(&ResourceState {
+                name: String::from("subnet.subnet-0123"),
+                resource: ResourceType::Subnet(subnet),
+                dependencies: vec![String::from("vpc.vpc-id")],
+            })
+        );
+
+        let graph = state.to_graph();
+        assert_eq!(graph.node_count(), 4);
+    }
+
+    #[test]
+    fn test_merge_previous() {
+        // Arrange
+        let mut state = State {
+            version: STATE_VERSION,
+            resources: vec![ResourceState {
+                name: String::from("vpc.vpc-id"),
+                resource: ResourceType::Vpc(Vpc {
+                    id: String::from("vpc-id"),
+                    region: String::from("us-west-2"),
+                    cidr_block: String::from("10.0.0.0/16"),
+                    name: String::from("vpc-id"),
+                    existing: false,
+                }),
+                dependencies: vec![],
+            }],
+        };
+
+        // Act
+        state.merge_previous(get_vpc_with_subnet_state());
+
+        // Assert
+        let names = state
+            .resources
+            .iter()
+            .map(|resource_state| resource_state.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["vpc.vpc-id", "subnet.subnet-id"]);
+        assert!(matches!(state.resources[0].resource, ResourceType::Vpc(_)));
+        assert_eq!(
+            state.resources[1].dependencies,
+            vec![String::from("vpc.vpc-id")]
+        );
+    }
+
+    #[test]
+    fn test_insert_resource_already_exists() {
+        // Arrange
+        let mut state = State::default();
+        let vpc = ResourceType::Vpc(Vpc {
+            id: String::from("vpc-0123"),
+            region: String::from("us-west-2"),
+            cidr_block: String::from("10.0.0.0/16"),
+            name: String::from("vpc-0123"),
+            existing: false,
+        });
+        state
+            .insert_resource(vpc.clone(), vec![])
+            .expect("Failed to insert resource");
+
+        // Act
+        let result = state.insert_resource(vpc, vec![]);
+
+        // Assert
+        assert_eq!(
+            result
+                .expect_err("Expected already exists error")
+                .to_string(),
+            "Resource 'vpc.vpc-0123' already exists in state"
+        );
+        assert_eq!(state.resources.len(), 1);
+    }
+
+    #[test]
+    fn test_insert_resource_missing_dependency() {
+        // Arrange
+        let mut state = State::default();
+        let subnet = ResourceType::Subnet(Subnet {
+            id: String::from("subnet-0123"),
+            name: String::from("subnet-0123"),
+            cidr_block: String::from("10.0.2.0/24"),
+            availability_zone: String::from("us-west-2b"),
+            existing: false,
+        });
+
+        // Act
+        let result = state.insert_resource(subnet, vec![String::from("vpc.vpc-0123")]);
+
+        // Assert
+        assert_eq!(
+            result
+                .expect_err("Expected missing dependency error")
+                .to_string(),
+            "Dependency 'vpc.vpc-0123' is not found in state"
+        );
+        assert!(state.resources.is_empty());
+    }
+
+    #[test]
+    fn test_remove_resource_leaf() {
+        // Arrange
+        let mut state = get_vpc_with_subnet_state();
+
+        // Act
+        let result = state.remove_resource("subnet.subnet-id", false);
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(
+            state.resources,
+            vec![ResourceState {
+                name: String::from("vpc.vpc-id"),
+                resource: ResourceType::None,
+                dependencies: vec![],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_remove_resource_with_dependents() {
+        // Arrange
+        let mut state = get_vpc_with_subnet_state();
+
+        // Act
+        let result = state.remove_resource("vpc.vpc-id", false);
+
+        // Assert
+        assert_eq!(
+            result.expect_err("Expected dependents error").to_string(),
+            "Resource 'vpc.vpc-id' is a dependency of subnet.subnet-id, use force to remove it \
+             anyway"
+        );
+        assert_eq!(state, get_vpc_with_subnet_state());
+    }
+
+    #[test]
+    fn test_remove_resource_with_dependents_force() {
+        // Arrange
+        let mut state = get_vpc_with_subnet_state();
+
+        // Act
+        let result = state.remove_resource("vpc.vpc-id", true);
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(
+            state.resources,
+            vec![ResourceState {
+                name: String::from("subnet.subnet-id"),
+                resource: ResourceType::None,
+                dependencies: vec![],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_remove_resource_not_found() {
+        // Arrange
+        let mut state = get_vpc_with_subnet_state();
+
+        // Act
+        let result = state.remove_resource("vm.vm-id", false);
+
+        // Assert
+        assert_eq!(
+            result.expect_err("Expected not found error").to_string(),
+            "Resource 'vm.vm-id' is not found in state"
+        );
+    }
 }
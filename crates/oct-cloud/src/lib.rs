@@ -2,3 +2,5 @@ pub mod infra;
 pub mod resource;
 
 pub mod aws;
+#[cfg(feature = "gcp")]
+pub mod gcp;
@@ -1,3 +1,10 @@
+use std::future::Future;
+use std::time::Duration;
+
+use aws_sdk_ec2::error::ProvideErrorMetadata;
+
+use crate::aws::client::AwsError;
+
 /// Defines the basic operations for managing cloud resources.
 /// It includes methods for creating and destroying resources asynchronously.
 /// Implementations of this trait should provide the specific logic for
@@ -10,3 +17,448 @@ pub trait Resource {
         &mut self,
     ) -> impl std::future::Future<Output = Result<(), Box<dyn std::error::Error + Send + Sync>>> + Send;
 }
+
+/// Maximum number of attempts of a retried AWS call
+pub(crate) const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+
+/// Delay before the first retry of an AWS call, doubled after each failed attempt
+pub(crate) const DEFAULT_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Exponential backoff between the attempts of a retried call, shared by
+/// [`Retry`] and the throttling retries of the AWS clients
+pub(crate) struct Backoff {
+    max_attempts: u32,
+    delay: Duration,
+    attempt: u32,
+}
+
+impl Backoff {
+    pub(crate) fn new(max_attempts: u32, base_delay: Duration) -> Self {
+        Self {
+            max_attempts,
+            delay: base_delay,
+            attempt: 1,
+        }
+    }
+
+    /// Returns the delay before the next attempt after a failed one,
+    /// `None` when all the attempts are used
+    pub(crate) fn next_delay(
+        &mut self,
+        action: &str,
+        error: &(impl std::fmt::Display + ?Sized),
+    ) -> Option<Duration> {
+        if self.attempt >= self.max_attempts {
+            return None;
+        }
+
+        let delay = self.delay;
+
+        log::warn!(
+            "Failed to {action} (attempt {}/{}): {error}. Retrying in {}ms...",
+            self.attempt,
+            self.max_attempts,
+            delay.as_millis()
+        );
+
+        self.delay *= 2;
+        self.attempt += 1;
+
+        Some(delay)
+    }
+}
+
+/// Defines which failed calls are retried and how long to wait between them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// AWS error codes of the transient failures, e.g. `OperationAborted`,
+    /// or of a resource that is not visible yet, e.g. `InvalidVpcID.NotFound`
+    pub error_codes: Vec<String>,
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled after each failed attempt
+    pub base_delay: Duration,
+}
+
+/// Retries the codes of new resources that are not visible yet, throttling
+/// and server errors are already retried by the AWS SDK and the clients
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            error_codes: [
+                // New resources are eventually consistent, their IDs are
+                // not found for a while after creation
+                "InvalidVpcID.NotFound",
+                "InvalidSubnetID.NotFound",
+                "InvalidRouteTableID.NotFound",
+                "InvalidGroup.NotFound",
+                "InvalidInstanceID.NotFound",
+                "NoSuchBucket",
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            base_delay: DEFAULT_BASE_DELAY,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Checks whether the error or one of its sources is an [`AwsError`]
+    /// with a retryable code
+    fn is_retryable(&self, error: &(dyn std::error::Error + 'static)) -> bool {
+        let mut current = Some(error);
+
+        while let Some(error) = current {
+            let code = error
+                .downcast_ref::<AwsError>()
+                .and_then(ProvideErrorMetadata::code);
+            if code.is_some_and(|code| self.error_codes.iter().any(|c| c == code)) {
+                return true;
+            }
+
+            current = error.source();
+        }
+
+        false
+    }
+
+    fn backoff(&self) -> Backoff {
+        Backoff::new(self.max_attempts, self.base_delay)
+    }
+
+    /// Returns the delay before the next attempt, `None` when the failed
+    /// attempt is not retried
+    fn retry_delay(
+        &self,
+        backoff: &mut Backoff,
+        action: &str,
+        error: &(dyn std::error::Error + 'static),
+    ) -> Option<Duration> {
+        if !self.is_retryable(error) {
+            return None;
+        }
+
+        backoff.next_delay(action, error)
+    }
+}
+
+/// Wraps a [`Resource`] and retries its `create` and `destroy` calls, and the
+/// operations passed to [`Retry::run`], with exponential backoff while they
+/// fail with one of the policy error codes
+///
+/// Only [`Resource`] implementations are covered, currently the S3 bucket of
+/// the state backend. The graph managers (`infra::resource::Manager`) are
+/// not wrapped, their AWS calls rely on the throttling retries of the clients
+pub struct Retry<R> {
+    inner: R,
+    policy: RetryPolicy,
+}
+
+impl<R> Retry<R> {
+    pub fn new(inner: R, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    /// Runs an operation of the wrapped resource with the retry policy,
+    /// e.g. an object upload to a bucket
+    pub async fn run<'a, T, F, Fut>(
+        &'a self,
+        operation: F,
+    ) -> Result<T, Box<dyn std::error::Error + Send + Sync>>
+    where
+        F: Fn(&'a R) -> Fut,
+        Fut: Future<Output = Result<T, Box<dyn std::error::Error + Send + Sync>>>,
+    {
+        let mut backoff = self.policy.backoff();
+
+        loop {
+            let error = match operation(&self.inner).await {
+                Err(e) => e,
+                result => return result,
+            };
+
+            let Some(delay) = self
+                .policy
+                .retry_delay(&mut backoff, "run operation", &*error)
+            else {
+                return Err(error);
+            };
+
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+impl<R: Resource + Send> Resource for Retry<R> {
+    async fn create(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut backoff = self.policy.backoff();
+
+        loop {
+            let error = match self.inner.create().await {
+                Err(e) => e,
+                result => return result,
+            };
+
+            let Some(delay) = self
+                .policy
+                .retry_delay(&mut backoff, "create resource", &*error)
+            else {
+                return Err(error);
+            };
+
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    async fn destroy(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut backoff = self.policy.backoff();
+
+        loop {
+            let error = match self.inner.destroy().await {
+                Err(e) => e,
+                result => return result,
+            };
+
+            let Some(delay) = self
+                .policy
+                .retry_delay(&mut backoff, "destroy resource", &*error)
+            else {
+                return Err(error);
+            };
+
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use aws_sdk_ec2::error::ErrorMetadata;
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct TestError(ErrorMetadata);
+
+    impl ProvideErrorMetadata for TestError {
+        fn meta(&self) -> &ErrorMetadata {
+            &self.0
+        }
+    }
+
+    impl std::fmt::Display for TestError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "request failed")
+        }
+    }
+
+    impl std::error::Error for TestError {}
+
+    fn get_aws_error(code: &str) -> Box<dyn std::error::Error + Send + Sync> {
+        Box::new(AwsError::new(TestError(
+            ErrorMetadata::builder().code(code).build(),
+        )))
+    }
+
+    /// Fails the first `failures` calls of both `create` and `destroy`
+    /// with an AWS error with the `error_code`
+    struct MockResource {
+        failures: u32,
+        error_code: &'static str,
+        create_calls: u32,
+        destroy_calls: u32,
+    }
+
+    impl MockResource {
+        fn new(failures: u32, error_code: &'static str) -> Self {
+            Self {
+                failures,
+                error_code,
+                create_calls: 0,
+                destroy_calls: 0,
+            }
+        }
+    }
+
+    impl Resource for MockResource {
+        async fn create(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            self.create_calls += 1;
+
+            if self.create_calls <= self.failures {
+                return Err(get_aws_error(self.error_code));
+            }
+
+            Ok(())
+        }
+
+        async fn destroy(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            self.destroy_calls += 1;
+
+            if self.destroy_calls <= self.failures {
+                return Err(get_aws_error(self.error_code));
+            }
+
+            Ok(())
+        }
+    }
+
+    fn get_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            base_delay: Duration::from_millis(1),
+            ..RetryPolicy::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_create_succeeds_after_transient_failures() {
+        // Arrange
+        let mut resource = Retry::new(MockResource::new(2, "NoSuchBucket"), get_policy(3));
+
+        // Act
+        let result = resource.create().await;
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(resource.inner().create_calls, 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_destroy_succeeds_after_transient_failures() {
+        // Arrange
+        let mut resource = Retry::new(MockResource::new(2, "NoSuchBucket"), get_policy(3));
+
+        // Act
+        let result = resource.destroy().await;
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(resource.inner().destroy_calls, 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_gives_up_after_max_attempts() {
+        // Arrange
+        let mut resource = Retry::new(
+            MockResource::new(2, "InvalidSubnetID.NotFound"),
+            get_policy(2),
+        );
+
+        // Act
+        let result = resource.create().await;
+
+        // Assert
+        assert_eq!(
+            result.expect_err("Expected not found error").to_string(),
+            "InvalidSubnetID.NotFound: request failed"
+        );
+        assert_eq!(resource.inner().create_calls, 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_does_not_retry_other_errors() {
+        // Arrange
+        let mut resource = Retry::new(MockResource::new(2, "BucketNotEmpty"), get_policy(3));
+
+        // Act
+        let result = resource.destroy().await;
+
+        // Assert
+        assert!(result.is_err());
+        assert_eq!(resource.inner().destroy_calls, 1);
+    }
+
+    #[tokio::test]
+    async fn test_retry_leaves_throttling_to_clients() {
+        // Arrange
+        let mut resource = Retry::new(MockResource::new(2, "RequestLimitExceeded"), get_policy(3));
+
+        // Act
+        let result = resource.create().await;
+
+        // Assert
+        assert!(result.is_err());
+        assert_eq!(resource.inner().create_calls, 1);
+    }
+
+    #[tokio::test]
+    async fn test_retry_custom_error_codes() {
+        // Arrange
+        let mut resource = Retry::new(
+            MockResource::new(1, "OperationAborted"),
+            RetryPolicy {
+                error_codes: vec![String::from("OperationAborted")],
+                ..get_policy(3)
+            },
+        );
+
+        // Act
+        let result = resource.create().await;
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(resource.inner().create_calls, 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_create_not_visible_resource() {
+        // Arrange
+        let mut resource = Retry::new(MockResource::new(1, "InvalidVpcID.NotFound"), get_policy(3));
+
+        // Act
+        let result = resource.create().await;
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(resource.inner().create_calls, 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_run_succeeds_after_transient_failures() {
+        // Arrange
+        let resource = Retry::new(MockResource::new(0, "NoSuchBucket"), get_policy(3));
+        let calls = AtomicU32::new(0);
+
+        // Act
+        let result = resource
+            .run(|_| async {
+                if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                    return Err(get_aws_error("NoSuchBucket"));
+                }
+
+                Ok("uploaded")
+            })
+            .await;
+
+        // Assert
+        assert_eq!(result.expect("Failed to run operation"), "uploaded");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_run_does_not_match_error_messages() {
+        // Arrange
+        let resource = Retry::new(MockResource::new(0, "SlowDown"), get_policy(3));
+        let calls = AtomicU32::new(0);
+
+        // Act
+        let result: Result<(), _> = resource
+            .run(|_| async {
+                calls.fetch_add(1, Ordering::SeqCst);
+
+                Err("unhandled error (SlowDown)".into())
+            })
+            .await;
+
+        // Assert
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
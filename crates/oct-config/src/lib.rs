@@ -1,11 +1,16 @@
 use std::collections::HashMap;
 use std::fs;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
 
+use indexmap::IndexMap;
 use petgraph::Graph;
 use petgraph::graph::NodeIndex;
+use petgraph::visit::{Dfs, Reversed};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, JsonSchema)]
 pub struct Config {
     pub project: Project,
 }
@@ -31,21 +36,262 @@ impl std::fmt::Display for Node {
 impl Config {
     const DEFAULT_CONFIG_PATH: &'static str = "oct.toml";
 
+    /// Path value meaning that the config is read from stdin
+    const STDIN_PATH: &'static str = "-";
+
+    /// Loads config from the file, `-` path reads the config from stdin
     pub fn new(path: Option<&str>) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
-        let config =
-            fs::read_to_string(path.unwrap_or(Self::DEFAULT_CONFIG_PATH)).map_err(|e| {
-                format!(
-                    "Failed to read config file {}: {}",
-                    Self::DEFAULT_CONFIG_PATH,
-                    e
-                )
-            })?;
+        let path = path.unwrap_or(Self::DEFAULT_CONFIG_PATH);
+
+        if path == Self::STDIN_PATH {
+            return Self::from_reader(std::io::stdin().lock());
+        }
+
+        let config = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read config file {path}: {e}"))?;
+
+        Self::parse(config)
+    }
+
+    /// Loads the config files and deep-merges them, later files override the
+    /// earlier ones, e.g. a base config followed by an environment overlay
+    ///
+    /// Tables are merged key by key, `project.services` entries are matched
+    /// by `name` so a service `envs` and `labels` are merged too, scalars and
+    /// other arrays are replaced. Only the merged config has to be complete
+    pub fn from_paths(paths: &[String]) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let [first_path, overlay_paths @ ..] = paths else {
+            return Self::new(None);
+        };
+
+        if overlay_paths.is_empty() {
+            return Self::new(Some(first_path));
+        }
+
+        let mut merged = toml::Table::new();
+
+        for path in paths {
+            let config = if path == Self::STDIN_PATH {
+                let mut config = String::new();
+                std::io::stdin()
+                    .lock()
+                    .read_to_string(&mut config)
+                    .map_err(|e| format!("Failed to read config: {e}"))?;
+
+                config
+            } else {
+                fs::read_to_string(path)
+                    .map_err(|e| format!("Failed to read config file {path}: {e}"))?
+            };
+
+            let table: toml::Table = toml::from_str(&Self::render_system_envs(config))
+                .map_err(|e| format!("Failed to parse config file {path}: {e}"))?;
+
+            merge_toml(&mut merged, table);
+        }
+
+        Self::from_table(merged)
+    }
+
+    /// Loads config from any reader, e.g. stdin or an in-memory buffer
+    pub fn from_reader(
+        mut reader: impl Read,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let mut config = String::new();
+        reader
+            .read_to_string(&mut config)
+            .map_err(|e| format!("Failed to read config: {e}"))?;
+
+        Self::parse(config)
+    }
+
+    /// Parses and validates a config from an untrusted source, e.g. a remote
+    /// repository, the environment variables are not rendered so the host
+    /// envs can't leak into it
+    pub fn from_untrusted_str(
+        config: &str,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let table: toml::Table = toml::from_str(config)?;
+
+        Self::from_table(table)
+    }
 
+    /// Returns JSON Schema of the config file for editor autocompletion and
+    /// validation
+    pub fn json_schema() -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let schema = schemars::schema_for!(Config);
+
+        Ok(serde_json::to_string_pretty(&schema)?)
+    }
+
+    /// Renders environment variables in the raw config and parses it
+    fn parse(config: String) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
         let config_with_injected_envs = Self::render_system_envs(config);
 
-        let toml_data: Config = toml::from_str(&config_with_injected_envs)?;
+        let table: toml::Table = toml::from_str(&config_with_injected_envs)?;
+
+        Self::from_table(table)
+    }
+
+    /// Deserializes and validates the user-authored config
+    fn from_table(table: toml::Table) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let value = toml::Value::Table(table);
+
+        let mut config: Config = value.clone().try_into()?;
+        config.validate_unknown_fields(&value)?;
+        config.validate_state_backends()?;
+        config.validate_existing_network()?;
+        config.validate_oct_ctl_sha256()?;
+        config.validate_load_balancer()?;
+        config.validate_service_replicas()?;
+        config.resolve_service_images()?;
+
+        Ok(config)
+    }
+
+    /// Rejects keys of the user-authored config that don't map to any config
+    /// field so typos fail loudly. The config types themselves accept unknown
+    /// fields, they are also the wire and state format shared between versions
+    fn validate_unknown_fields(
+        &self,
+        value: &toml::Value,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let known = toml::Value::try_from(self)?;
+
+        let mut unknown_fields = Vec::new();
+        collect_unknown_fields("", value, &known, &mut unknown_fields);
+
+        if unknown_fields.is_empty() {
+            return Ok(());
+        }
+
+        Err(format!("Unknown config fields: {}", unknown_fields.join(", ")).into())
+    }
+
+    /// Checks that the infra and user states are stored in different
+    /// locations, otherwise each state save overwrites the other one
+    fn validate_state_backends(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if self
+            .project
+            .state_backend
+            .is_same_location(&self.project.user_state_backend)
+        {
+            return Err(
+                "state_backend and user_state_backend must point to different locations".into(),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Checks that `vpc_id` and `subnet_ids` are set together
+    fn validate_existing_network(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        match (&self.project.vpc_id, self.project.subnet_ids.is_empty()) {
+            (Some(_), true) => Err("vpc_id requires at least one subnet in subnet_ids".into()),
+            (None, false) => Err("subnet_ids require vpc_id".into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that the `oct-ctl` checksum is a SHA-256 hex digest, otherwise
+    /// it would be interpolated into the VM boot script as is
+    fn validate_oct_ctl_sha256(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let Some(oct_ctl_sha256) = &self.project.oct_ctl_sha256 else {
+            return Ok(());
+        };
+
+        if oct_ctl_sha256.len() != 64 || !oct_ctl_sha256.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err("oct_ctl_sha256 must be 64 hex characters".into());
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the load balancer has a port to forward to and a new VPC,
+    /// its second subnet may overlap with an existing VPC
+    fn validate_load_balancer(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if !self.project.load_balancer {
+            return Ok(());
+        }
+
+        if self.project.vpc_id.is_some() {
+            return Err("load_balancer can't be used with an existing vpc_id".into());
+        }
+
+        if self.project.load_balancer_port().is_none() {
+            return Err("load_balancer requires a service with external_port".into());
+        }
+
+        Ok(())
+    }
+
+    /// Checks that each service runs at least one replica and that multiple
+    /// replicas don't publish the same host ports
+    fn validate_service_replicas(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        for service in &self.project.services {
+            if service.replicas == 0 {
+                return Err(
+                    format!("Service '{}' requires at least 1 replica", service.name).into(),
+                );
+            }
+
+            if service.replicas > 1
+                && (service.external_port.is_some() || !service.ports.is_empty())
+            {
+                return Err(format!(
+                    "Service '{}' with multiple replicas can't publish host ports",
+                    service.name
+                )
+                .into());
+            }
+
+            if service.total_resources().is_none() {
+                return Err(format!(
+                    "Service '{}' resources overflow with {} replicas",
+                    service.name, service.replicas
+                )
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Applies the `dockerfile_path` over `image` precedence, see [`Service`]
+    fn resolve_service_images(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        for service in &mut self.project.services {
+            if !service.image.is_empty() {
+                continue;
+            }
+
+            if service.dockerfile_path.is_none() {
+                return Err(format!(
+                    "Service '{}' requires either image or dockerfile_path",
+                    service.name
+                )
+                .into());
+            }
+
+            service.image = format!("{}:latest", service.name);
+        }
+
+        Ok(())
+    }
+
+    /// User services by name in the config order, fails on a duplicate
+    /// service name
+    pub fn services_by_name(
+        &self,
+    ) -> Result<IndexMap<String, &Service>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut services = IndexMap::new();
 
-        Ok(toml_data)
+        for service in &self.project.services {
+            if services.insert(service.name.clone(), service).is_some() {
+                return Err(format!("Duplicate service name: '{}'", service.name).into());
+            }
+        }
+
+        Ok(services)
     }
 
     /// Converts user services to a graph
@@ -56,26 +302,28 @@ impl Config {
         let mut edges = Vec::new();
         let root = graph.add_node(Node::Root);
 
-        let mut services_map: HashMap<String, NodeIndex> = HashMap::new();
-        for service in &self.project.services {
-            if services_map.contains_key(&service.name) {
-                return Err(format!("Duplicate service name: '{}'", service.name).into());
-            }
-            let node = graph.add_node(Node::Resource(service.clone()));
+        let services = self.services_by_name()?;
 
-            services_map.insert(service.name.clone(), node);
-        }
+        let services_map: HashMap<&str, NodeIndex> = services
+            .iter()
+            .map(|(name, service)| {
+                (
+                    name.as_str(),
+                    graph.add_node(Node::Resource((*service).clone())),
+                )
+            })
+            .collect();
 
-        for service in &self.project.services {
+        for service in services.values() {
             let resource = services_map
-                .get(&service.name)
+                .get(service.name.as_str())
                 .expect("Missed resource value in resource_map");
 
             if service.depends_on.is_empty() {
                 edges.push((root, *resource, String::new()));
             } else {
                 for dependency_name in &service.depends_on {
-                    let dependency_resource = services_map.get(dependency_name);
+                    let dependency_resource = services_map.get(dependency_name.as_str());
 
                     match dependency_resource {
                         Some(dependency_resource) => {
@@ -99,6 +347,34 @@ impl Config {
         Ok(graph)
     }
 
+    /// Names of the `target` service and all the services it transitively
+    /// depends on, sorted by name
+    pub fn get_target_services(
+        &self,
+        target: &str,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let graph = self.to_graph()?;
+
+        let target_index = graph
+            .node_indices()
+            .find(
+                |index| matches!(&graph[*index], Node::Resource(service) if service.name == target),
+            )
+            .ok_or_else(|| format!("Service '{target}' not found"))?;
+
+        let mut target_services = Vec::new();
+        let mut dfs = Dfs::new(Reversed(&graph), target_index);
+        while let Some(index) = dfs.next(Reversed(&graph)) {
+            if let Node::Resource(service) = &graph[index] {
+                target_services.push(service.name.clone());
+            }
+        }
+
+        target_services.sort();
+
+        Ok(target_services)
+    }
+
     /// Renders environment variables using [tera](https://docs.rs/tera/latest/tera/)
     /// All system environment variables are available under the `env` context
     /// variable
@@ -123,7 +399,88 @@ impl Config {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+/// Collects paths of the `value` keys missing in the `known` re-serialized
+/// config, e.g. `project.services[0].dockerfile_paht`
+fn collect_unknown_fields(
+    path: &str,
+    value: &toml::Value,
+    known: &toml::Value,
+    unknown_fields: &mut Vec<String>,
+) {
+    match (value, known) {
+        (toml::Value::Table(table), toml::Value::Table(known_table)) => {
+            for (key, value) in table {
+                let field_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+
+                match known_table.get(key) {
+                    Some(known) => {
+                        collect_unknown_fields(&field_path, value, known, unknown_fields)
+                    }
+                    None => unknown_fields.push(format!("`{field_path}`")),
+                }
+            }
+        }
+        (toml::Value::Array(values), toml::Value::Array(known_values)) => {
+            for (index, (value, known)) in values.iter().zip(known_values).enumerate() {
+                collect_unknown_fields(&format!("{path}[{index}]"), value, known, unknown_fields);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Deep-merges `overlay` into `base`, see [`Config::from_paths`]
+fn merge_toml(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, overlay_value) in overlay {
+        let value = match (base.remove(&key), overlay_value) {
+            (Some(toml::Value::Table(mut base_table)), toml::Value::Table(overlay_table)) => {
+                merge_toml(&mut base_table, overlay_table);
+
+                toml::Value::Table(base_table)
+            }
+            (Some(toml::Value::Array(services)), toml::Value::Array(overlay_services))
+                if key == "services" =>
+            {
+                toml::Value::Array(merge_services(services, overlay_services))
+            }
+            (_, overlay_value) => overlay_value,
+        };
+
+        base.insert(key, value);
+    }
+}
+
+/// Merges the overlay services into the services with the same `name`,
+/// services missing in `services` are appended
+fn merge_services(
+    mut services: Vec<toml::Value>,
+    overlay_services: Vec<toml::Value>,
+) -> Vec<toml::Value> {
+    for overlay_service in overlay_services {
+        let position = services.iter().position(|service| {
+            service.get("name").is_some() && service.get("name") == overlay_service.get("name")
+        });
+
+        match position {
+            Some(position) => {
+                if let (toml::Value::Table(service), toml::Value::Table(overlay_service)) =
+                    (&mut services[position], overlay_service)
+                {
+                    merge_toml(service, overlay_service);
+                }
+            }
+            None => services.push(overlay_service),
+        }
+    }
+
+    services
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, JsonSchema)]
 pub enum StateBackend {
     #[serde(rename = "local")]
     Local {
@@ -142,7 +499,40 @@ pub enum StateBackend {
     },
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+impl StateBackend {
+    /// Returns `true` if both backends store the state in the same file,
+    /// `./state.json` and `state.json` are the same local file, S3 bucket
+    /// names are global so the region is ignored
+    pub fn is_same_location(&self, other: &StateBackend) -> bool {
+        match (self, other) {
+            (StateBackend::Local { path }, StateBackend::Local { path: other_path }) => {
+                normalize_local_path(path) == normalize_local_path(other_path)
+            }
+            (
+                StateBackend::S3 { bucket, key, .. },
+                StateBackend::S3 {
+                    bucket: other_bucket,
+                    key: other_key,
+                    ..
+                },
+            ) => {
+                bucket == other_bucket
+                    && key.trim_start_matches('/') == other_key.trim_start_matches('/')
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Drops `.` components of the path
+fn normalize_local_path(path: &str) -> PathBuf {
+    Path::new(path)
+        .components()
+        .filter(|component| *component != Component::CurDir)
+        .collect()
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, JsonSchema)]
 pub struct Project {
     pub name: String,
 
@@ -152,150 +542,1561 @@ pub struct Project {
     pub services: Vec<Service>,
 
     pub domain: Option<String>,
+
+    /// Time to live in seconds of the `domain` DNS records, 3600 by default
+    #[serde(default)]
+    pub dns_ttl: Option<i64>,
+
+    /// Also create `*.<domain>` record routing all subdomains to the project
+    #[serde(default)]
+    pub dns_wildcard: bool,
+
+    /// Expected SHA-256 checksum of the `oct-ctl` binary downloaded on
+    /// instances, the boot script aborts on mismatch
+    #[serde(default)]
+    pub oct_ctl_sha256: Option<String>,
+
+    /// Cloud provider to deploy the project to
+    #[serde(default)]
+    pub provider: CloudProvider,
+
+    /// Cloud region to deploy the project to
+    #[serde(default)]
+    pub region: Option<String>,
+
+    /// URL receiving a JSON POST request when a deployment finishes
+    #[serde(default)]
+    pub notify_webhook: Option<String>,
+
+    /// Destination of the deployment timing metrics
+    #[serde(default)]
+    pub metrics: Option<MetricsBackend>,
+
+    /// Create a separate ECR repository for each service with a Dockerfile
+    /// instead of one shared repository
+    #[serde(default)]
+    pub ecr_per_service: bool,
+
+    /// Number of the most recent images kept in the ECR repositories created
+    /// by oct, older images are expired by a lifecycle policy, all the images
+    /// are kept when omitted
+    #[serde(default)]
+    pub ecr_keep_last_images: Option<u32>,
+
+    /// Route the traffic through an application load balancer forwarding to
+    /// the `external_port` of the first service exposing one on all instances
+    #[serde(default)]
+    pub load_balancer: bool,
+
+    /// Existing VPC to deploy to instead of creating a new one, it is never
+    /// deleted by oct
+    #[serde(default)]
+    pub vpc_id: Option<String>,
+
+    /// Public subnets of the existing `vpc_id`, the Leader VM is placed in the
+    /// first one
+    #[serde(default)]
+    pub subnet_ids: Vec<String>,
+}
+
+/// Project without services and with local states next to the config,
+/// the optional fields have their config load defaults
+impl Default for Project {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            state_backend: StateBackend::Local {
+                path: String::from("./state.json"),
+            },
+            user_state_backend: StateBackend::Local {
+                path: String::from("./user_state.json"),
+            },
+            services: Vec::new(),
+            domain: None,
+            dns_ttl: None,
+            dns_wildcard: false,
+            oct_ctl_sha256: None,
+            provider: CloudProvider::default(),
+            region: None,
+            notify_webhook: None,
+            metrics: None,
+            ecr_per_service: false,
+            ecr_keep_last_images: None,
+            load_balancer: false,
+            vpc_id: None,
+            subnet_ids: Vec::new(),
+        }
+    }
+}
+
+impl Project {
+    /// Names of the per-service ECR repositories, empty when the services
+    /// share one repository
+    pub fn ecr_repositories(&self) -> Vec<String> {
+        if !self.ecr_per_service {
+            return Vec::new();
+        }
+
+        self.services
+            .iter()
+            .filter(|service| service.dockerfile_path.is_some())
+            .map(|service| service.name.clone())
+            .collect()
+    }
+
+    /// Instance port the load balancer forwards the traffic to, `None` when
+    /// the load balancer is disabled or no service has an `external_port`
+    pub fn load_balancer_port(&self) -> Option<u16> {
+        if !self.load_balancer {
+            return None;
+        }
+
+        self.services
+            .iter()
+            .find_map(|service| service.external_port)
+            .and_then(|external_port| u16::try_from(external_port).ok())
+    }
+}
+
+/// Destination of the deployment timing metrics
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, JsonSchema)]
+pub enum MetricsBackend {
+    #[serde(rename = "prometheus_textfile")]
+    PrometheusTextfile {
+        /// Path to the `.prom` file read by the node exporter textfile collector
+        path: String,
+    },
+
+    #[serde(rename = "statsd")]
+    Statsd {
+        /// StatsD server address, e.g. `127.0.0.1:8125`
+        address: String,
+    },
+}
+
+/// Cloud provider the project is deployed to
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Clone, Copy, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CloudProvider {
+    #[default]
+    Aws,
+    Gcp,
 }
 
 /// Configuration for a service
 /// This configuration is managed by the user and used to deploy the service
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+///
+/// `dockerfile_path` takes precedence over `image`: when it is set the image
+/// is built from the Dockerfile and `image` is only its tag, defaulting to
+/// `<name>:latest`. Without `dockerfile_path` the `image` is used as is,
+/// a service with neither of them is rejected when the config is loaded
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema)]
 pub struct Service {
     /// Service name
     pub name: String,
-    /// Image to use for the container
+    /// Image to use for the container, the tag of the built image when
+    /// `dockerfile_path` is set
+    #[serde(default)]
     pub image: String,
-    /// Path to the Dockerfile
+    /// Path to the Dockerfile the image is built from
     pub dockerfile_path: Option<String>,
+    /// Entrypoint overriding the image `ENTRYPOINT`, `command` is passed
+    /// to it as arguments
+    pub entrypoint: Option<String>,
     /// Command to run in the container
-    pub command: Option<String>,
+    pub command: Option<ContainerCommand>,
     /// Internal port exposed from the container
     pub internal_port: Option<u32>,
     /// External port exposed to the public internet
     pub external_port: Option<u32>,
+    /// Additional published ports in `external:internal[/protocol]` format,
+    /// both TCP and UDP are published when the protocol is omitted
+    #[serde(default)]
+    #[schemars(with = "Vec<String>")]
+    pub ports: Vec<PortMapping>,
+    /// Container network, the shared `oct` bridge network by default
+    #[serde(default)]
+    pub network_mode: Option<NetworkMode>,
+    /// When the image is pulled before the container runs, the container
+    /// manager default (pull a missing image) when omitted
+    #[serde(default)]
+    pub pull_policy: Option<PullPolicy>,
     /// CPU millicores
     pub cpus: u32,
     /// Memory in MB
     pub memory: u64,
+    /// Number of containers running the service, each reserves `cpus` and
+    /// `memory`, see [`Service::container_names`]
+    #[serde(default = "Service::default_replicas")]
+    pub replicas: u32,
     /// List of services that this service depends on
     #[serde(default)]
     pub depends_on: Vec<String>,
+    /// Command checking that the service is ready, dependent services are
+    /// started only after it succeeds
+    #[serde(default)]
+    pub health_check: Option<HealthCheck>,
+    /// Human-readable description, not used by the runtime
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Free-form labels for tooling, not used by the runtime
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
     /// Raw environment variables to set in the container
     /// All values are rendered using in `render_envs` method
     #[serde(default)]
     pub envs: HashMap<String, String>,
 }
 
-#[cfg(test)]
-mod tests {
-    use std::io::Write;
+/// Service with the config load defaults of the optional fields, e.g. a
+/// single replica
+impl Default for Service {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            image: String::new(),
+            dockerfile_path: None,
+            entrypoint: None,
+            command: None,
+            internal_port: None,
+            external_port: None,
+            ports: Vec::new(),
+            network_mode: None,
+            pull_policy: None,
+            cpus: 0,
+            memory: 0,
+            replicas: Self::default_replicas(),
+            depends_on: Vec::new(),
+            health_check: None,
+            description: None,
+            labels: HashMap::new(),
+            envs: HashMap::new(),
+        }
+    }
+}
 
-    use super::*;
+impl Service {
+    fn default_replicas() -> u32 {
+        1
+    }
 
-    #[test]
-    fn test_config_new_success_path_privided() {
-        // Arrange
-        let config_file_content = r#"
-[project]
-name = "example"
-domain = "opencloudtool.com"
+    /// Names of the service containers, the service name for a single
+    /// replica and `<name>-<index>` for multiple replicas
+    pub fn container_names(&self) -> Vec<String> {
+        if self.replicas <= 1 {
+            return vec![self.name.clone()];
+        }
 
-[project.state_backend.local]
-path = "./state.json"
+        (0..self.replicas)
+            .map(|index| format!("{}-{index}", self.name))
+            .collect()
+    }
 
-[project.user_state_backend.local]
-path = "./user_state.json"
+    /// CPU millicores and memory in MB required by all the replicas, `None`
+    /// when they overflow, such a service is rejected when the config is
+    /// loaded
+    pub fn total_resources(&self) -> Option<(u32, u64)> {
+        Some((
+            self.cpus.checked_mul(self.replicas)?,
+            self.memory.checked_mul(u64::from(self.replicas))?,
+        ))
+    }
+}
 
-[[project.services]]
-name = "app_1"
-image = ""
-dockerfile_path = "Dockerfile"
-command = "echo Hello World!"
-internal_port = 80
-external_port = 80
-cpus = 250
-memory = 64
+/// Service readiness check executed inside the running container
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema)]
+pub struct HealthCheck {
+    /// Command exiting with 0 when the service is ready
+    pub command: ContainerCommand,
+    /// Seconds between the checks
+    #[serde(default = "HealthCheck::default_interval")]
+    pub interval: u64,
+    /// Number of checks before the service is considered unhealthy
+    #[serde(default = "HealthCheck::default_retries")]
+    pub retries: u32,
+}
 
-[project.services.envs]
-KEY1 = "VALUE1"
-KEY2 = """
-Multiline
-string"""
-KEY_WITH_INJECTED_ENV = "{{ env.CARGO_PKG_NAME }}"
-KEY_WITH_OTHER_TEMPLATE_VARIABLE = "{{ other_vars.some_var }}"
+impl HealthCheck {
+    fn default_interval() -> u64 {
+        5
+    }
 
-[[project.services]]
-name = "app_2"
-image = "nginx:latest"
-cpus = 250
-memory = 64
-depends_on = ["app_1"]
-"#;
+    fn default_retries() -> u32 {
+        12
+    }
+}
 
-        let mut config_file = tempfile::NamedTempFile::new().expect("Failed to create a temp file");
-        config_file
+/// Command to run in the container
+///
+/// Accepts either a single string or a list of arguments:
+/// `command = "echo hello"` or `command = ["sh", "-c", "echo hello world"]`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, JsonSchema)]
+#[serde(untagged)]
+pub enum ContainerCommand {
+    /// Command string, split into arguments on whitespace
+    String(String),
+    /// Arguments passed to the container as is, without shell interpretation
+    Args(Vec<String>),
+}
+
+impl ContainerCommand {
+    /// Returns the command arguments passed to the container
+    pub fn to_args(&self) -> Vec<String> {
+        match self {
+            ContainerCommand::String(command) => {
+                command.split_whitespace().map(str::to_string).collect()
+            }
+            ContainerCommand::Args(args) => args.clone(),
+        }
+    }
+}
+
+/// Transport protocol of a published port
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortProtocol {
+    Tcp,
+    Udp,
+}
+
+impl PortProtocol {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PortProtocol::Tcp => "tcp",
+            PortProtocol::Udp => "udp",
+        }
+    }
+}
+
+/// Port published from the container, `external:internal[/protocol]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct PortMapping {
+    pub external: u32,
+    pub internal: u32,
+    /// Both TCP and UDP are published when `None`
+    pub protocol: Option<PortProtocol>,
+}
+
+impl TryFrom<String> for PortMapping {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let invalid_port =
+            || format!("Invalid port '{value}', expected external:internal[/tcp|udp]");
+
+        let (ports, protocol) = match value.split_once('/') {
+            Some((ports, "tcp")) => (ports, Some(PortProtocol::Tcp)),
+            Some((ports, "udp")) => (ports, Some(PortProtocol::Udp)),
+            Some(_) => return Err(invalid_port()),
+            None => (value.as_str(), None),
+        };
+
+        let (external, internal) = ports.split_once(':').ok_or_else(invalid_port)?;
+
+        Ok(Self {
+            external: external.parse().map_err(|_| invalid_port())?,
+            internal: internal.parse().map_err(|_| invalid_port())?,
+            protocol,
+        })
+    }
+}
+
+impl From<PortMapping> for String {
+    fn from(port: PortMapping) -> Self {
+        match port.protocol {
+            Some(protocol) => format!("{}:{}/{}", port.external, port.internal, protocol.as_str()),
+            None => format!("{}:{}", port.external, port.internal),
+        }
+    }
+}
+
+/// Network the container is attached to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum NetworkMode {
+    /// Shared `oct` bridge network, ports are published to the host
+    Bridge,
+    /// Host network stack, the published ports are ignored
+    Host,
+}
+
+/// Image pull behavior before running the container
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum PullPolicy {
+    /// Pull the image on every run
+    Always,
+    /// Pull the image only when it is missing locally
+    IfNotPresent,
+    /// Never pull, the image is loaded locally, e.g. on air-gapped hosts
+    Never,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    #[test]
+    fn test_config_new_success_path_privided() {
+        // Arrange
+        let config_file_content = r#"
+[project]
+name = "example"
+domain = "opencloudtool.com"
+
+[project.state_backend.local]
+path = "./state.json"
+
+[project.user_state_backend.local]
+path = "./user_state.json"
+
+[[project.services]]
+name = "app_1"
+image = ""
+dockerfile_path = "Dockerfile"
+command = "echo Hello World!"
+internal_port = 80
+external_port = 80
+cpus = 250
+memory = 64
+
+[project.services.envs]
+KEY1 = "VALUE1"
+KEY2 = """
+Multiline
+string"""
+KEY_WITH_INJECTED_ENV = "{{ env.CARGO_PKG_NAME }}"
+KEY_WITH_OTHER_TEMPLATE_VARIABLE = "{{ other_vars.some_var }}"
+
+[[project.services]]
+name = "app_2"
+image = "nginx:latest"
+cpus = 250
+memory = 64
+depends_on = ["app_1"]
+"#;
+
+        let mut config_file = tempfile::NamedTempFile::new().expect("Failed to create a temp file");
+        config_file
             .write_all(config_file_content.as_bytes())
             .expect("Failed to write to file");
 
         // Act
-        let config =
-            Config::new(config_file.path().to_str()).expect("Failed to create a new config");
+        let config =
+            Config::new(config_file.path().to_str()).expect("Failed to create a new config");
+
+        // Assert
+        assert_eq!(
+            config,
+            Config {
+                project: Project {
+                    name: String::from("example"),
+                    state_backend: StateBackend::Local {
+                        path: String::from("./state.json")
+                    },
+                    user_state_backend: StateBackend::Local {
+                        path: String::from("./user_state.json")
+                    },
+                    services: vec![
+                        Service {
+                            name: String::from("app_1"),
+                            image: String::from("app_1:latest"),
+                            dockerfile_path: Some(String::from("Dockerfile")),
+                            command: Some(ContainerCommand::String(String::from(
+                                "echo Hello World!"
+                            ))),
+                            internal_port: Some(80),
+                            external_port: Some(80),
+                            cpus: 250,
+                            memory: 64,
+                            envs: HashMap::from([
+                                (String::from("KEY1"), String::from("VALUE1")),
+                                (String::from("KEY2"), String::from("Multiline\nstring")),
+                                (
+                                    String::from("KEY_WITH_INJECTED_ENV"),
+                                    // "oct-orchestrator" was the previous value because it was in
+                                    // that crate. Now it's in
+                                    // oct-config, so CARGO_PKG_NAME will be oct-config.
+                                    // Wait, the test uses {{ env.CARGO_PKG_NAME }}.
+                                    // When running tests for oct-config, CARGO_PKG_NAME is
+                                    // oct-config.
+                                    String::from("oct-config")
+                                ),
+                                (
+                                    String::from("KEY_WITH_OTHER_TEMPLATE_VARIABLE"),
+                                    String::from("{{ other_vars.some_var }}")
+                                ),
+                            ]),
+                            ..Default::default()
+                        },
+                        Service {
+                            name: String::from("app_2"),
+                            image: String::from("nginx:latest"),
+                            cpus: 250,
+                            memory: 64,
+                            depends_on: vec![String::from("app_1")],
+                            ..Default::default()
+                        }
+                    ],
+                    domain: Some(String::from("opencloudtool.com")),
+                    ..Default::default()
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn test_config_from_reader_success() {
+        // Arrange
+        let config_content = r#"
+[project]
+name = "example"
+
+[project.state_backend.local]
+path = "./state.json"
+
+[project.user_state_backend.local]
+path = "./user_state.json"
+
+[[project.services]]
+name = "app_1"
+image = "nginx:latest"
+cpus = 250
+memory = 64
+
+[project.services.envs]
+KEY_WITH_INJECTED_ENV = "{{ env.CARGO_PKG_NAME }}"
+"#;
+
+        // Act
+        let config = Config::from_reader(std::io::Cursor::new(config_content))
+            .expect("Failed to create a new config");
+
+        // Assert
+        assert_eq!(config.project.name, "example");
+        assert_eq!(config.project.services.len(), 1);
+        assert_eq!(
+            config.project.services[0].envs.get("KEY_WITH_INJECTED_ENV"),
+            Some(&String::from("oct-config"))
+        );
+    }
+
+    #[test]
+    fn test_config_from_untrusted_str_keeps_env_templates() {
+        // Arrange
+        let config_content = r#"
+[project]
+name = "example"
+
+[project.state_backend.local]
+path = "./state.json"
+
+[project.user_state_backend.local]
+path = "./user_state.json"
+
+[[project.services]]
+name = "app_1"
+image = "nginx:latest"
+cpus = 250
+memory = 64
+
+[project.services.envs]
+KEY_WITH_INJECTED_ENV = "{{ env.CARGO_PKG_NAME }}"
+"#;
+
+        // Act
+        let config =
+            Config::from_untrusted_str(config_content).expect("Failed to create a new config");
+
+        // Assert
+        assert_eq!(
+            config.project.services[0].envs.get("KEY_WITH_INJECTED_ENV"),
+            Some(&String::from("{{ env.CARGO_PKG_NAME }}"))
+        );
+    }
+
+    #[test]
+    fn test_config_from_reader_invalid_toml() {
+        // Arrange
+        let config_content = "[project";
+
+        // Act
+        let config = Config::from_reader(std::io::Cursor::new(config_content));
+
+        // Assert
+        assert!(config.is_err());
+    }
+
+    #[test]
+    fn test_config_from_reader_provider() {
+        // Arrange
+        let config_content = r#"
+[project]
+name = "example"
+provider = "gcp"
+services = []
+
+[project.state_backend.local]
+path = "./state.json"
+
+[project.user_state_backend.local]
+path = "./user_state.json"
+"#;
+
+        // Act
+        let config = Config::from_reader(std::io::Cursor::new(config_content))
+            .expect("Failed to create a new config");
+
+        // Assert
+        assert_eq!(config.project.provider, CloudProvider::Gcp);
+    }
+
+    #[test]
+    fn test_config_from_reader_default_provider() {
+        // Arrange
+        let config_content = r#"
+[project]
+name = "example"
+services = []
+
+[project.state_backend.local]
+path = "./state.json"
+
+[project.user_state_backend.local]
+path = "./user_state.json"
+"#;
+
+        // Act
+        let config = Config::from_reader(std::io::Cursor::new(config_content))
+            .expect("Failed to create a new config");
+
+        // Assert
+        assert_eq!(config.project.provider, CloudProvider::Aws);
+    }
+
+    #[test]
+    fn test_config_from_reader_metrics() {
+        // Arrange
+        let config_content = r#"
+[project]
+name = "example"
+services = []
+
+[project.state_backend.local]
+path = "./state.json"
+
+[project.user_state_backend.local]
+path = "./user_state.json"
+
+[project.metrics.statsd]
+address = "127.0.0.1:8125"
+"#;
+
+        // Act
+        let config = Config::from_reader(std::io::Cursor::new(config_content))
+            .expect("Failed to create a new config");
+
+        // Assert
+        assert_eq!(
+            config.project.metrics,
+            Some(MetricsBackend::Statsd {
+                address: String::from("127.0.0.1:8125"),
+            })
+        );
+    }
+
+    #[test]
+    fn test_config_from_reader_dns_options() {
+        // Arrange
+        let config_content = r#"
+[project]
+name = "example"
+domain = "example.com"
+dns_ttl = 300
+dns_wildcard = true
+services = []
+
+[project.state_backend.local]
+path = "./state.json"
+
+[project.user_state_backend.local]
+path = "./user_state.json"
+"#;
+
+        // Act
+        let config = Config::from_reader(std::io::Cursor::new(config_content))
+            .expect("Failed to create a new config");
+
+        // Assert
+        assert_eq!(config.project.dns_ttl, Some(300));
+        assert!(config.project.dns_wildcard);
+    }
+
+    #[test]
+    fn test_project_ecr_repositories() {
+        // Arrange
+        let config_content = r#"
+[project]
+name = "example"
+ecr_per_service = true
+ecr_keep_last_images = 10
+
+[project.state_backend.local]
+path = "./state.json"
+
+[project.user_state_backend.local]
+path = "./user_state.json"
+
+[[project.services]]
+name = "api"
+image = "api:latest"
+dockerfile_path = "api/Dockerfile"
+cpus = 250
+memory = 64
+
+[[project.services]]
+name = "db"
+image = "postgres:latest"
+cpus = 250
+memory = 64
+
+[[project.services]]
+name = "web"
+image = "web:latest"
+dockerfile_path = "web/Dockerfile"
+cpus = 250
+memory = 64
+"#;
+
+        let mut config = Config::from_reader(std::io::Cursor::new(config_content))
+            .expect("Failed to create a new config");
+
+        // Act
+        let ecr_repositories = config.project.ecr_repositories();
+
+        config.project.ecr_per_service = false;
+        let shared_ecr_repositories = config.project.ecr_repositories();
+
+        // Assert
+        assert_eq!(
+            ecr_repositories,
+            vec![String::from("api"), String::from("web")]
+        );
+        assert!(shared_ecr_repositories.is_empty());
+        assert_eq!(config.project.ecr_keep_last_images, Some(10));
+    }
+
+    #[test]
+    fn test_project_load_balancer_port() {
+        // Arrange
+        let config_content = r#"
+[project]
+name = "example"
+load_balancer = true
+
+[project.state_backend.local]
+path = "./state.json"
+
+[project.user_state_backend.local]
+path = "./user_state.json"
+
+[[project.services]]
+name = "db"
+image = "postgres:latest"
+cpus = 250
+memory = 64
+
+[[project.services]]
+name = "web"
+image = "web:latest"
+external_port = 8080
+internal_port = 80
+cpus = 250
+memory = 64
+"#;
+
+        let mut config = Config::from_reader(std::io::Cursor::new(config_content))
+            .expect("Failed to create a new config");
+
+        // Act
+        let load_balancer_port = config.project.load_balancer_port();
+
+        config.project.load_balancer = false;
+        let disabled_load_balancer_port = config.project.load_balancer_port();
+
+        // Assert
+        assert_eq!(load_balancer_port, Some(8080));
+        assert_eq!(disabled_load_balancer_port, None);
+    }
+
+    #[test]
+    fn test_config_get_target_services() {
+        // Arrange
+        let config_content = r#"
+[project]
+name = "example"
+
+[project.state_backend.local]
+path = "./state.json"
+
+[project.user_state_backend.local]
+path = "./user_state.json"
+
+[[project.services]]
+name = "db"
+image = "postgres:latest"
+cpus = 250
+memory = 64
+
+[[project.services]]
+name = "cache"
+image = "redis:latest"
+cpus = 250
+memory = 64
+
+[[project.services]]
+name = "api"
+image = "api:latest"
+depends_on = ["db", "cache"]
+cpus = 250
+memory = 64
+
+[[project.services]]
+name = "web"
+image = "web:latest"
+depends_on = ["api"]
+cpus = 250
+memory = 64
+
+[[project.services]]
+name = "worker"
+image = "worker:latest"
+depends_on = ["db"]
+cpus = 250
+memory = 64
+"#;
+
+        let config = Config::from_reader(std::io::Cursor::new(config_content))
+            .expect("Failed to create a new config");
+
+        // Act
+        let target_services = config.get_target_services("web");
+        let unknown_target_services = config.get_target_services("admin");
+
+        // Assert
+        assert_eq!(
+            target_services.expect("Failed to get target services"),
+            vec!["api", "cache", "db", "web"]
+        );
+        assert_eq!(
+            unknown_target_services
+                .expect_err("Expected unknown service error")
+                .to_string(),
+            "Service 'admin' not found"
+        );
+    }
+
+    #[test]
+    fn test_config_services_by_name_preserves_order() {
+        // Arrange
+        let config_content = r#"
+[project]
+name = "example"
+
+[project.state_backend.local]
+path = "./state.json"
+
+[project.user_state_backend.local]
+path = "./user_state.json"
+
+[[project.services]]
+name = "web"
+image = "web:latest"
+cpus = 250
+memory = 64
+
+[[project.services]]
+name = "api"
+image = "api:latest"
+cpus = 250
+memory = 64
+
+[[project.services]]
+name = "db"
+image = "postgres:latest"
+cpus = 250
+memory = 64
+"#;
+
+        let config = Config::from_reader(std::io::Cursor::new(config_content))
+            .expect("Failed to create a new config");
+
+        // Act
+        let services = config
+            .services_by_name()
+            .expect("Failed to get services by name");
+
+        // Assert
+        assert_eq!(
+            services.keys().collect::<Vec<_>>(),
+            vec!["web", "api", "db"]
+        );
+        assert_eq!(services["api"].image, "api:latest");
+    }
+
+    #[test]
+    fn test_config_services_by_name_duplicate_service_names() {
+        // Arrange
+        let config_content = r#"
+[project]
+name = "example"
+
+[project.state_backend.local]
+path = "./state.json"
+
+[project.user_state_backend.local]
+path = "./user_state.json"
+
+[[project.services]]
+name = "app_1"
+image = "nginx:latest"
+cpus = 250
+memory = 64
+
+[[project.services]]
+name = "app_1"
+image = "nginx:1.27"
+cpus = 250
+memory = 64
+"#;
+
+        let config = Config::from_reader(std::io::Cursor::new(config_content))
+            .expect("Failed to create a new config");
+
+        // Act
+        let services = config.services_by_name();
+        let graph = config.to_graph();
+
+        // Assert
+        assert_eq!(
+            services.expect_err("Expected duplicate error").to_string(),
+            "Duplicate service name: 'app_1'"
+        );
+        assert_eq!(
+            graph.expect_err("Expected duplicate error").to_string(),
+            "Duplicate service name: 'app_1'"
+        );
+    }
+
+    #[test]
+    fn test_config_same_local_state_paths() {
+        // Arrange
+        let config_content = r#"
+[project]
+name = "example"
+services = []
+
+[project.state_backend.local]
+path = "./state.json"
+
+[project.user_state_backend.local]
+path = "state.json"
+"#;
+
+        // Act
+        let config = Config::from_reader(std::io::Cursor::new(config_content));
+
+        // Assert
+        assert_eq!(
+            config
+                .expect_err("Expected same location error")
+                .to_string(),
+            "state_backend and user_state_backend must point to different locations"
+        );
+    }
+
+    #[test]
+    fn test_config_existing_network() {
+        // Arrange
+        let config_content = r#"
+[project]
+name = "example"
+services = []
+vpc_id = "vpc-123"
+subnet_ids = ["subnet-1", "subnet-2"]
+
+[project.state_backend.local]
+path = "./state.json"
+
+[project.user_state_backend.local]
+path = "./user_state.json"
+"#;
+
+        // Act
+        let config = Config::from_reader(std::io::Cursor::new(config_content))
+            .expect("Failed to parse config");
+
+        // Assert
+        assert_eq!(config.project.vpc_id.as_deref(), Some("vpc-123"));
+        assert_eq!(config.project.subnet_ids, vec!["subnet-1", "subnet-2"]);
+    }
+
+    #[test]
+    fn test_config_vpc_id_without_subnet_ids() {
+        // Arrange
+        let config_content = r#"
+[project]
+name = "example"
+services = []
+vpc_id = "vpc-123"
+
+[project.state_backend.local]
+path = "./state.json"
+
+[project.user_state_backend.local]
+path = "./user_state.json"
+"#;
+
+        // Act
+        let config = Config::from_reader(std::io::Cursor::new(config_content));
+
+        // Assert
+        assert_eq!(
+            config
+                .expect_err("Expected missing subnets error")
+                .to_string(),
+            "vpc_id requires at least one subnet in subnet_ids"
+        );
+    }
+
+    #[test]
+    fn test_config_oct_ctl_sha256() {
+        // Arrange
+        let get_config_content = |oct_ctl_sha256: &str| {
+            format!(
+                r#"
+[project]
+name = "example"
+oct_ctl_sha256 = "{oct_ctl_sha256}"
+services = []
+
+[project.state_backend.local]
+path = "./state.json"
+
+[project.user_state_backend.local]
+path = "./user_state.json"
+"#
+            )
+        };
+        let valid_sha256 = "0123456789abcdefABCDEF0123456789abcdef0123456789abcdef0123456789";
+
+        // Act
+        let valid_config =
+            Config::from_reader(std::io::Cursor::new(get_config_content(valid_sha256)));
+        let short_config = Config::from_reader(std::io::Cursor::new(get_config_content("abc123")));
+        let non_hex_config = Config::from_reader(std::io::Cursor::new(get_config_content(
+            &valid_sha256.replace('0', "g"),
+        )));
+
+        // Assert
+        assert_eq!(
+            valid_config
+                .expect("Failed to load config")
+                .project
+                .oct_ctl_sha256
+                .as_deref(),
+            Some(valid_sha256)
+        );
+        for config in [short_config, non_hex_config] {
+            assert_eq!(
+                config.expect_err("Expected checksum error").to_string(),
+                "oct_ctl_sha256 must be 64 hex characters"
+            );
+        }
+    }
+
+    #[test]
+    fn test_config_load_balancer_with_existing_vpc() {
+        // Arrange
+        let config_content = r#"
+[project]
+name = "example"
+load_balancer = true
+vpc_id = "vpc-123"
+subnet_ids = ["subnet-123"]
+
+[project.state_backend.local]
+path = "./state.json"
+
+[project.user_state_backend.local]
+path = "./user_state.json"
+
+[[project.services]]
+name = "web"
+image = "nginx:latest"
+external_port = 80
+internal_port = 80
+cpus = 250
+memory = 64
+"#;
+
+        // Act
+        let config = Config::from_reader(std::io::Cursor::new(config_content));
+
+        // Assert
+        assert_eq!(
+            config
+                .expect_err("Expected load balancer error")
+                .to_string(),
+            "load_balancer can't be used with an existing vpc_id"
+        );
+    }
+
+    #[test]
+    fn test_config_load_balancer_without_external_port() {
+        // Arrange
+        let config_content = r#"
+[project]
+name = "example"
+load_balancer = true
+
+[project.state_backend.local]
+path = "./state.json"
+
+[project.user_state_backend.local]
+path = "./user_state.json"
+
+[[project.services]]
+name = "db"
+image = "postgres:latest"
+cpus = 250
+memory = 64
+"#;
+
+        // Act
+        let config = Config::from_reader(std::io::Cursor::new(config_content));
+
+        // Assert
+        assert_eq!(
+            config
+                .expect_err("Expected load balancer error")
+                .to_string(),
+            "load_balancer requires a service with external_port"
+        );
+    }
+
+    #[test]
+    fn test_config_service_without_image_and_dockerfile() {
+        // Arrange
+        let config_content = r#"
+[project]
+name = "example"
+
+[project.state_backend.local]
+path = "./state.json"
+
+[project.user_state_backend.local]
+path = "./user_state.json"
+
+[[project.services]]
+name = "app_1"
+cpus = 250
+memory = 64
+"#;
+
+        // Act
+        let config = Config::from_reader(std::io::Cursor::new(config_content));
+
+        // Assert
+        assert_eq!(
+            config
+                .expect_err("Expected missing image error")
+                .to_string(),
+            "Service 'app_1' requires either image or dockerfile_path"
+        );
+    }
+
+    #[test]
+    fn test_config_service_replicas() {
+        // Arrange
+        let config_content = r#"
+[project]
+name = "example"
+
+[project.state_backend.local]
+path = "./state.json"
+
+[project.user_state_backend.local]
+path = "./user_state.json"
+
+[[project.services]]
+name = "app_1"
+image = "nginx:latest"
+cpus = 250
+memory = 64
+replicas = 3
+
+[[project.services]]
+name = "app_2"
+image = "nginx:latest"
+cpus = 250
+memory = 64
+"#;
+
+        // Act
+        let config = Config::from_reader(std::io::Cursor::new(config_content))
+            .expect("Failed to parse config");
+
+        // Assert
+        let services = &config.project.services;
+        assert_eq!(
+            services[0].container_names(),
+            vec!["app_1-0", "app_1-1", "app_1-2"]
+        );
+        assert_eq!(services[0].total_resources(), Some((750, 192)));
+        assert_eq!(services[1].replicas, 1);
+        assert_eq!(services[1].container_names(), vec!["app_2"]);
+        assert_eq!(services[1].total_resources(), Some((250, 64)));
+    }
+
+    #[test]
+    fn test_config_service_invalid_replicas() {
+        // Arrange
+        let get_config_content = |service: &str| {
+            format!(
+                r#"
+[project]
+name = "example"
+
+[project.state_backend.local]
+path = "./state.json"
+
+[project.user_state_backend.local]
+path = "./user_state.json"
+
+[[project.services]]
+name = "app_1"
+image = "nginx:latest"
+cpus = 250
+memory = 64
+{service}
+"#
+            )
+        };
+
+        // Act
+        let zero_replicas_config =
+            Config::from_reader(std::io::Cursor::new(get_config_content("replicas = 0")));
+        let published_port_config = Config::from_reader(std::io::Cursor::new(get_config_content(
+            "replicas = 2\nexternal_port = 80",
+        )));
+        let overflow_config = Config::from_reader(std::io::Cursor::new(get_config_content(
+            "replicas = 20000000",
+        )));
+
+        // Assert
+        assert_eq!(
+            zero_replicas_config
+                .expect_err("Expected zero replicas error")
+                .to_string(),
+            "Service 'app_1' requires at least 1 replica"
+        );
+        assert_eq!(
+            published_port_config
+                .expect_err("Expected published port error")
+                .to_string(),
+            "Service 'app_1' with multiple replicas can't publish host ports"
+        );
+        assert_eq!(
+            overflow_config
+                .expect_err("Expected resources overflow error")
+                .to_string(),
+            "Service 'app_1' resources overflow with 20000000 replicas"
+        );
+    }
+
+    #[test]
+    fn test_config_service_image_precedence() {
+        // Arrange
+        let config_content = r#"
+[project]
+name = "example"
+
+[project.state_backend.local]
+path = "./state.json"
+
+[project.user_state_backend.local]
+path = "./user_state.json"
+
+[[project.services]]
+name = "built"
+dockerfile_path = "built/Dockerfile"
+cpus = 250
+memory = 64
+
+[[project.services]]
+name = "built_tagged"
+image = "registry.example.com/app:v2"
+dockerfile_path = "app/Dockerfile"
+cpus = 250
+memory = 64
+
+[[project.services]]
+name = "pulled"
+image = "nginx:latest"
+cpus = 250
+memory = 64
+"#;
+
+        // Act
+        let config = Config::from_reader(std::io::Cursor::new(config_content))
+            .expect("Failed to load config");
+
+        // Assert
+        let images = config
+            .project
+            .services
+            .iter()
+            .map(|service| service.image.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            images,
+            vec![
+                "built:latest",
+                "registry.example.com/app:v2",
+                "nginx:latest"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_config_same_s3_state_keys() {
+        // Arrange
+        let config_content = r#"
+[project]
+name = "example"
+services = []
+
+[project.state_backend.s3]
+region = "us-west-2"
+bucket = "oct-state"
+key = "state.json"
+
+[project.user_state_backend.s3]
+region = "eu-central-1"
+bucket = "oct-state"
+key = "state.json"
+"#;
+
+        // Act
+        let config = Config::from_reader(std::io::Cursor::new(config_content));
+
+        // Assert
+        assert_eq!(
+            config
+                .expect_err("Expected same location error")
+                .to_string(),
+            "state_backend and user_state_backend must point to different locations"
+        );
+    }
+
+    #[test]
+    fn test_state_backend_is_same_location() {
+        // Arrange
+        let s3_backend = StateBackend::S3 {
+            region: String::from("us-west-2"),
+            bucket: String::from("oct-state"),
+            key: String::from("state.json"),
+        };
+        let s3_other_key_backend = StateBackend::S3 {
+            region: String::from("us-west-2"),
+            bucket: String::from("oct-state"),
+            key: String::from("user_state.json"),
+        };
+        let local_backend = StateBackend::Local {
+            path: String::from("state.json"),
+        };
+
+        // Act & Assert
+        assert!(s3_backend.is_same_location(&s3_backend.clone()));
+        assert!(!s3_backend.is_same_location(&s3_other_key_backend));
+        assert!(!s3_backend.is_same_location(&local_backend));
+        assert!(local_backend.is_same_location(&StateBackend::Local {
+            path: String::from("./state.json"),
+        }));
+    }
+
+    fn write_config_file(content: &str) -> tempfile::NamedTempFile {
+        let mut config_file = tempfile::NamedTempFile::new().expect("Failed to create a temp file");
+        config_file
+            .write_all(content.as_bytes())
+            .expect("Failed to write to file");
+
+        config_file
+    }
+
+    fn get_config_file_path(config_file: &tempfile::NamedTempFile) -> String {
+        config_file.path().to_string_lossy().to_string()
+    }
+
+    const BASE_CONFIG: &str = r#"
+[project]
+name = "example"
+domain = "example.com"
+
+[project.state_backend.local]
+path = "./state.json"
+
+[project.user_state_backend.local]
+path = "./user_state.json"
+
+[[project.services]]
+name = "app_1"
+image = "app:v1"
+cpus = 250
+memory = 64
+depends_on = []
+
+[project.services.envs]
+LOG_LEVEL = "debug"
+DATABASE_URL = "postgres://localhost"
+"#;
+
+    #[test]
+    fn test_config_from_paths_override_precedence() {
+        // Arrange
+        let base_file = write_config_file(BASE_CONFIG);
+        let prod_file = write_config_file(
+            r#"
+[project]
+domain = "prod.example.com"
+
+[[project.services]]
+name = "app_1"
+image = "app:v2"
+
+[project.services.envs]
+LOG_LEVEL = "warn"
+"#,
+        );
+        let hotfix_file = write_config_file(
+            r#"
+[[project.services]]
+name = "app_1"
+image = "app:v3"
+"#,
+        );
+
+        // Act
+        let config = Config::from_paths(&[
+            get_config_file_path(&base_file),
+            get_config_file_path(&prod_file),
+            get_config_file_path(&hotfix_file),
+        ])
+        .expect("Failed to merge configs");
+
+        // Assert
+        assert_eq!(config.project.name, "example");
+        assert_eq!(
+            config.project.domain,
+            Some(String::from("prod.example.com"))
+        );
+        assert_eq!(config.project.services.len(), 1);
+
+        let service = &config.project.services[0];
+        assert_eq!(service.image, "app:v3");
+        assert_eq!(service.cpus, 250);
+        assert_eq!(
+            service.envs,
+            HashMap::from([
+                (String::from("LOG_LEVEL"), String::from("warn")),
+                (
+                    String::from("DATABASE_URL"),
+                    String::from("postgres://localhost")
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_config_from_paths_adds_service() {
+        // Arrange
+        let base_file = write_config_file(BASE_CONFIG);
+        let overlay_file = write_config_file(
+            r#"
+[[project.services]]
+name = "worker"
+image = "app:v1"
+command = "worker"
+cpus = 500
+memory = 128
+depends_on = ["app_1"]
+"#,
+        );
+
+        // Act
+        let config = Config::from_paths(&[
+            get_config_file_path(&base_file),
+            get_config_file_path(&overlay_file),
+        ])
+        .expect("Failed to merge configs");
+
+        // Assert
+        let service_names = config
+            .project
+            .services
+            .iter()
+            .map(|service| service.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(service_names, vec!["app_1", "worker"]);
+        assert_eq!(config.project.services[1].depends_on, vec!["app_1"]);
+    }
+
+    #[test]
+    fn test_config_from_paths_incomplete_merged_config() {
+        // Arrange
+        let base_file = write_config_file(
+            r#"
+[project]
+name = "example"
+services = []
+"#,
+        );
+        let overlay_file = write_config_file(
+            r#"
+[project]
+domain = "example.com"
+"#,
+        );
+
+        // Act
+        let config = Config::from_paths(&[
+            get_config_file_path(&base_file),
+            get_config_file_path(&overlay_file),
+        ]);
+
+        // Assert
+        assert!(
+            config
+                .expect_err("Expected missing field error")
+                .to_string()
+                .contains("missing field `state_backend`")
+        );
+    }
+
+    #[test]
+    fn test_config_new_missing_file() {
+        // Act
+        let config = Config::new(Some("missing.toml"));
 
         // Assert
-        assert_eq!(
-            config,
-            Config {
-                project: Project {
-                    name: String::from("example"),
-                    state_backend: StateBackend::Local {
-                        path: String::from("./state.json")
-                    },
-                    user_state_backend: StateBackend::Local {
-                        path: String::from("./user_state.json")
-                    },
-                    services: vec![
-                        Service {
-                            name: String::from("app_1"),
-                            image: String::new(),
-                            dockerfile_path: Some(String::from("Dockerfile")),
-                            command: Some(String::from("echo Hello World!")),
-                            internal_port: Some(80),
-                            external_port: Some(80),
-                            cpus: 250,
-                            memory: 64,
-                            depends_on: vec![],
-                            envs: HashMap::from([
-                                (String::from("KEY1"), String::from("VALUE1")),
-                                (String::from("KEY2"), String::from("Multiline\nstring")),
-                                (
-                                    String::from("KEY_WITH_INJECTED_ENV"),
-                                    // "oct-orchestrator" was the previous value because it was in
-                                    // that crate. Now it's in
-                                    // oct-config, so CARGO_PKG_NAME will be oct-config.
-                                    // Wait, the test uses {{ env.CARGO_PKG_NAME }}.
-                                    // When running tests for oct-config, CARGO_PKG_NAME is
-                                    // oct-config.
-                                    String::from("oct-config")
-                                ),
-                                (
-                                    String::from("KEY_WITH_OTHER_TEMPLATE_VARIABLE"),
-                                    String::from("{{ other_vars.some_var }}")
-                                ),
-                            ]),
-                        },
-                        Service {
-                            name: String::from("app_2"),
-                            image: String::from("nginx:latest"),
-                            dockerfile_path: None,
-                            command: None,
-                            internal_port: None,
-                            external_port: None,
-                            cpus: 250,
-                            memory: 64,
-                            depends_on: vec![String::from("app_1")],
-                            envs: HashMap::new(),
-                        }
-                    ],
-                    domain: Some(String::from("opencloudtool.com")),
-                }
-            }
+        assert!(
+            config
+                .expect_err("Expected error")
+                .to_string()
+                .starts_with("Failed to read config file missing.toml")
         );
     }
 
@@ -311,8 +2112,7 @@ depends_on = ["app_1"]
                 user_state_backend: StateBackend::Local {
                     path: String::from("user_state.json"),
                 },
-                services: Vec::new(),
-                domain: None,
+                ..Default::default()
             },
         };
 
@@ -330,14 +2130,9 @@ depends_on = ["app_1"]
         let service = Service {
             name: String::from("app_1"),
             image: String::from("nginx:latest"),
-            dockerfile_path: None,
-            command: None,
-            internal_port: None,
-            external_port: None,
             cpus: 250,
             memory: 64,
-            depends_on: vec![],
-            envs: HashMap::new(),
+            ..Default::default()
         };
         let config = Config {
             project: Project {
@@ -349,7 +2144,7 @@ depends_on = ["app_1"]
                     path: String::from("user_state.json"),
                 },
                 services: vec![service],
-                domain: None,
+                ..Default::default()
             },
         };
 
@@ -378,26 +2173,17 @@ depends_on = ["app_1"]
         let service1 = Service {
             name: String::from("app_1"),
             image: String::from("nginx:latest"),
-            dockerfile_path: None,
-            command: None,
-            internal_port: None,
-            external_port: None,
             cpus: 250,
             memory: 64,
-            depends_on: vec![],
-            envs: HashMap::new(),
+            ..Default::default()
         };
         let service2 = Service {
             name: String::from("app_2"),
             image: String::from("nginx:latest"),
-            dockerfile_path: None,
-            command: None,
-            internal_port: None,
-            external_port: None,
             cpus: 250,
             memory: 64,
             depends_on: vec![String::from("app_1")],
-            envs: HashMap::new(),
+            ..Default::default()
         };
         let config = Config {
             project: Project {
@@ -409,7 +2195,7 @@ depends_on = ["app_1"]
                     path: String::from("user_state.json"),
                 },
                 services: vec![service1.clone(), service2.clone()],
-                domain: None,
+                ..Default::default()
             },
         };
 
@@ -443,14 +2229,10 @@ depends_on = ["app_1"]
         let service = Service {
             name: String::from("app_1"),
             image: String::from("nginx:latest"),
-            dockerfile_path: None,
-            command: None,
-            internal_port: None,
-            external_port: None,
             cpus: 250,
             memory: 64,
             depends_on: vec![String::from("INCORRECT_SERVICE_NAME")],
-            envs: HashMap::new(),
+            ..Default::default()
         };
         let config = Config {
             project: Project {
@@ -462,7 +2244,7 @@ depends_on = ["app_1"]
                     path: String::from("user_state.json"),
                 },
                 services: vec![service],
-                domain: None,
+                ..Default::default()
             },
         };
 
@@ -484,26 +2266,16 @@ depends_on = ["app_1"]
         let service1 = Service {
             name: String::from("app_1"),
             image: String::from("nginx:latest"),
-            dockerfile_path: None,
-            command: None,
-            internal_port: None,
-            external_port: None,
             cpus: 250,
             memory: 64,
-            depends_on: vec![],
-            envs: HashMap::new(),
+            ..Default::default()
         };
         let service2 = Service {
             name: String::from("app_1"),
             image: String::from("nginx:latest"),
-            dockerfile_path: None,
-            command: None,
-            internal_port: None,
-            external_port: None,
             cpus: 250,
             memory: 64,
-            depends_on: vec![],
-            envs: HashMap::new(),
+            ..Default::default()
         };
         let config = Config {
             project: Project {
@@ -515,7 +2287,7 @@ depends_on = ["app_1"]
                     path: String::from("user_state.json"),
                 },
                 services: vec![service1, service2],
-                domain: None,
+                ..Default::default()
             },
         };
 
@@ -529,4 +2301,348 @@ depends_on = ["app_1"]
             "Duplicate service name: 'app_1'"
         );
     }
+
+    #[test]
+    fn test_health_check_deserialize_defaults() {
+        // Arrange
+        let service_content = r#"
+name = "db"
+image = "postgres:17"
+cpus = 250
+memory = 64
+
+[health_check]
+command = "pg_isready -U postgres"
+"#;
+
+        // Act
+        let service: Service = toml::from_str(service_content).expect("Failed to parse service");
+
+        // Assert
+        assert_eq!(
+            service.health_check,
+            Some(HealthCheck {
+                command: ContainerCommand::String(String::from("pg_isready -U postgres")),
+                interval: 5,
+                retries: 12,
+            })
+        );
+    }
+
+    #[test]
+    fn test_container_command_deserialize_string() {
+        // Arrange
+        let service_content = r#"
+name = "app_1"
+image = "nginx:latest"
+command = "echo Hello World!"
+cpus = 250
+memory = 64
+"#;
+
+        // Act
+        let service: Service = toml::from_str(service_content).expect("Failed to parse service");
+
+        // Assert
+        assert_eq!(
+            service.command,
+            Some(ContainerCommand::String(String::from("echo Hello World!")))
+        );
+    }
+
+    #[test]
+    fn test_container_command_deserialize_args() {
+        // Arrange
+        let service_content = r#"
+name = "app_1"
+image = "nginx:latest"
+command = ["sh", "-c", "echo 'Hello World!' > /tmp/out"]
+cpus = 250
+memory = 64
+"#;
+
+        // Act
+        let service: Service = toml::from_str(service_content).expect("Failed to parse service");
+
+        // Assert
+        assert_eq!(
+            service.command,
+            Some(ContainerCommand::Args(vec![
+                String::from("sh"),
+                String::from("-c"),
+                String::from("echo 'Hello World!' > /tmp/out"),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_config_unknown_field() {
+        // Arrange
+        let config_content = r#"
+[project]
+name = "example"
+
+[project.state_backend.local]
+path = "./state.json"
+
+[project.user_state_backend.local]
+path = "./user_state.json"
+
+[[project.services]]
+name = "app_1"
+image = "nginx:latest"
+dockerfile_paht = "Dockerfile"
+cpus = 250
+memory = 64
+"#;
+
+        // Act
+        let config = Config::from_reader(config_content.as_bytes());
+
+        // Assert
+        assert_eq!(
+            config
+                .expect_err("Expected unknown field error")
+                .to_string(),
+            "Unknown config fields: `project.services[0].dockerfile_paht`"
+        );
+    }
+
+    #[test]
+    fn test_config_unknown_field_in_overlay() {
+        // Arrange
+        let base_config_content = r#"
+[project]
+name = "example"
+
+[project.state_backend.local]
+path = "./state.json"
+
+[project.user_state_backend.local]
+path = "./user_state.json"
+
+[[project.services]]
+name = "app_1"
+image = "nginx:latest"
+cpus = 250
+memory = 64
+"#;
+        let overlay_config_content = r#"
+[project]
+regoin = "eu-central-1"
+
+[project.state_backend.local]
+paht = "./prod_state.json"
+"#;
+
+        let base_file = write_config_file(base_config_content);
+        let overlay_file = write_config_file(overlay_config_content);
+
+        // Act
+        let config = Config::from_paths(&[
+            get_config_file_path(&base_file),
+            get_config_file_path(&overlay_file),
+        ]);
+
+        // Assert
+        assert_eq!(
+            config
+                .expect_err("Expected unknown field error")
+                .to_string(),
+            "Unknown config fields: `project.regoin`, `project.state_backend.local.paht`"
+        );
+    }
+
+    #[test]
+    fn test_service_from_json_ignores_unknown_fields() {
+        // Arrange
+        let service_content = r#"{
+            "name": "app_1",
+            "image": "nginx:latest",
+            "cpus": 250,
+            "memory": 64,
+            "added_in_newer_version": true
+        }"#;
+
+        // Act
+        let service: Service =
+            serde_json::from_str(service_content).expect("Failed to deserialize service");
+
+        // Assert
+        assert_eq!(service.name, "app_1");
+        assert_eq!(service.image, "nginx:latest");
+    }
+
+    #[test]
+    fn test_service_metadata_roundtrip() {
+        // Arrange
+        let service_content = r#"
+name = "app_1"
+image = "nginx:latest"
+cpus = 250
+memory = 64
+description = "Public web frontend"
+
+[labels]
+team = "web"
+tier = "frontend"
+"#;
+
+        // Act
+        let service: Service = toml::from_str(service_content).expect("Failed to parse service");
+        let serialized = toml::to_string(&service).expect("Failed to serialize service");
+        let deserialized: Service =
+            toml::from_str(&serialized).expect("Failed to deserialize service");
+
+        // Assert
+        assert_eq!(
+            service.description,
+            Some(String::from("Public web frontend"))
+        );
+        assert_eq!(
+            service.labels,
+            HashMap::from([
+                (String::from("team"), String::from("web")),
+                (String::from("tier"), String::from("frontend")),
+            ])
+        );
+        assert_eq!(deserialized, service);
+    }
+
+    #[test]
+    fn test_container_command_serialize_roundtrip() {
+        // Arrange
+        let commands = [
+            ContainerCommand::String(String::from("echo Hello World!")),
+            ContainerCommand::Args(vec![String::from("sh"), String::from("-c"), String::new()]),
+        ];
+
+        for command in commands {
+            let service = Service {
+                name: String::from("app_1"),
+                image: String::from("nginx:latest"),
+                command: Some(command),
+                cpus: 250,
+                memory: 64,
+                ..Default::default()
+            };
+
+            // Act
+            let serialized = toml::to_string(&service).expect("Failed to serialize service");
+            let deserialized: Service =
+                toml::from_str(&serialized).expect("Failed to deserialize service");
+
+            // Assert
+            assert_eq!(deserialized, service);
+        }
+    }
+
+    #[test]
+    fn test_service_ports_and_network_mode() {
+        // Arrange
+        let service_content = r#"
+name = "dns"
+image = "coredns/coredns:latest"
+ports = ["53:53/udp", "8080:80/tcp", "9153:9153"]
+network_mode = "host"
+cpus = 250
+memory = 64
+"#;
+
+        // Act
+        let service: Service = toml::from_str(service_content).expect("Failed to parse service");
+
+        // Assert
+        assert_eq!(
+            service.ports,
+            vec![
+                PortMapping {
+                    external: 53,
+                    internal: 53,
+                    protocol: Some(PortProtocol::Udp),
+                },
+                PortMapping {
+                    external: 8080,
+                    internal: 80,
+                    protocol: Some(PortProtocol::Tcp),
+                },
+                PortMapping {
+                    external: 9153,
+                    internal: 9153,
+                    protocol: None,
+                },
+            ]
+        );
+        assert_eq!(service.network_mode, Some(NetworkMode::Host));
+        assert_eq!(
+            toml::from_str::<Service>(
+                &toml::to_string(&service).expect("Failed to serialize service")
+            )
+            .expect("Failed to deserialize service"),
+            service
+        );
+    }
+
+    #[test]
+    fn test_port_mapping_invalid() {
+        // Act & Assert
+        for port in ["80", "80:http", "80:80/sctp", ":80"] {
+            assert_eq!(
+                PortMapping::try_from(port.to_string()),
+                Err(format!(
+                    "Invalid port '{port}', expected external:internal[/tcp|udp]"
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn test_container_command_to_args() {
+        // Arrange
+        let string_command = ContainerCommand::String(String::from("echo  Hello World!"));
+        let args_command =
+            ContainerCommand::Args(vec![String::from("echo"), String::from("Hello World!")]);
+
+        // Act & Assert
+        assert_eq!(
+            string_command.to_args(),
+            vec![
+                String::from("echo"),
+                String::from("Hello"),
+                String::from("World!")
+            ]
+        );
+        assert_eq!(
+            args_command.to_args(),
+            vec![String::from("echo"), String::from("Hello World!")]
+        );
+    }
+
+    #[test]
+    fn test_config_json_schema() {
+        // Act
+        let schema = Config::json_schema().expect("Failed to generate schema");
+
+        // Assert
+        let schema: serde_json::Value =
+            serde_json::from_str(&schema).expect("Failed to parse schema");
+
+        let project = &schema["$defs"]["Project"];
+        assert_eq!(project["properties"]["services"]["type"], "array");
+        assert!(
+            project["required"]
+                .as_array()
+                .expect("Project required fields are missing")
+                .contains(&serde_json::json!("services"))
+        );
+
+        let service_required = schema["$defs"]["Service"]["required"]
+            .as_array()
+            .expect("Service required fields are missing");
+        for field in ["name", "cpus", "memory"] {
+            assert!(service_required.contains(&serde_json::json!(field)));
+        }
+        assert!(!service_required.contains(&serde_json::json!("image")));
+        assert!(!service_required.contains(&serde_json::json!("envs")));
+    }
 }
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use oct_config::Config;
 /// TODO(#147): Generate this from `oct-ctl`'s `OpenAPI` spec
 use serde::{Deserialize, Serialize};
@@ -6,67 +8,342 @@ use serde::{Deserialize, Serialize};
 pub struct Client {
     public_ip: String,
     port: u16,
+    scheme: Scheme,
+    /// Sent as `Authorization: Bearer <token>` on every request
+    token: Option<String>,
+}
+
+/// Protocol used to reach `oct-ctl`
+#[derive(Debug, Clone)]
+enum Scheme {
+    Http,
+    /// `root_certificate` is the only trusted certificate when set, e.g.
+    /// the self-signed `oct-ctl` certificate, otherwise the system roots
+    /// are trusted
+    Https {
+        root_certificate: Option<reqwest::Certificate>,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 struct ApplyRequest {
     config: Config,
+
+    #[serde(default)]
+    force_recreate: bool,
+
+    #[serde(default)]
+    partial: bool,
+
+    /// Key - service name, Value - resolved values of the service secret envs
+    #[serde(default)]
+    secret_envs: HashMap<String, HashMap<String, String>>,
+}
+
+/// Container of a running service
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RunContainerResponse {
+    /// Container id assigned by the container manager
+    pub id: String,
+}
+
+/// Result of [`Client::apply`]
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ApplyResponse {
+    /// Key - service name, Value - service container
+    #[serde(default)]
+    pub containers: HashMap<String, RunContainerResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RemoveServiceRequest {
+    name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ExecRequest {
+    name: String,
+    args: Vec<String>,
+}
+
+/// Result of [`Client::remove_service`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoveOutcome {
+    /// The service was running and is stopped now
+    Removed,
+    /// `oct-ctl` doesn't know the service, e.g. it is already removed
+    NotFound,
+}
+
+/// Output of a command run by [`Client::exec`]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ExecOutput {
+    pub stdout: String,
+    pub stderr: String,
+    /// `-1` when the command was terminated by a signal
+    pub exit_code: i32,
+}
+
+/// Readiness of the host dependencies reported by `oct-ctl`
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+struct HealthStatus {
+    /// Container manager is installed and responds
+    podman: bool,
 }
 
 impl Client {
     const DEFAULT_PORT: u16 = 31888;
 
+    /// Lines returned by [`Client::get_container_logs`] without `follow`
+    /// and `tail`, so a long running container doesn't send its whole
+    /// history
+    pub const DEFAULT_LOGS_TAIL: u32 = 1000;
+
     pub fn new(public_ip: String) -> Self {
         Self {
             public_ip,
             port: Self::DEFAULT_PORT,
+            scheme: Scheme::Http,
+            token: None,
         }
     }
 
+    /// Client talking to `oct-ctl` served over HTTPS, `root_ca_pem` pins
+    /// the trusted CA or self-signed certificate
+    pub fn new_https(
+        public_ip: String,
+        root_ca_pem: Option<&[u8]>,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let root_certificate = root_ca_pem
+            .map(reqwest::Certificate::from_pem)
+            .transpose()
+            .map_err(|e| format!("Failed to parse root certificate: {e}"))?;
+
+        Ok(Self {
+            public_ip,
+            port: Self::DEFAULT_PORT,
+            scheme: Scheme::Https { root_certificate },
+            token: None,
+        })
+    }
+
+    /// Authenticates the requests with the `oct-ctl` bearer token
+    #[must_use]
+    pub fn with_token(mut self, token: String) -> Self {
+        self.token = Some(token);
+
+        self
+    }
+
     pub fn public_ip(&self) -> &str {
         &self.public_ip
     }
 
+    fn url(&self, path: &str) -> String {
+        let scheme = match self.scheme {
+            Scheme::Http => "http",
+            Scheme::Https { .. } => "https",
+        };
+
+        format!("{scheme}://{}:{}{path}", self.public_ip, self.port)
+    }
+
+    /// Adds the bearer token to the request when the client has one
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    fn http_client(&self) -> Result<reqwest::Client, Box<dyn std::error::Error + Send + Sync>> {
+        let builder = match &self.scheme {
+            Scheme::Https {
+                root_certificate: Some(root_certificate),
+            } => reqwest::Client::builder().tls_certs_only([root_certificate.clone()]),
+            Scheme::Http | Scheme::Https { .. } => reqwest::Client::builder(),
+        };
+
+        Ok(builder.build()?)
+    }
+
+    /// Applies user services config, `force_recreate` restarts all services
+    /// even if their configuration is unchanged, with `partial` the running
+    /// services missing in the config are kept instead of removed
+    ///
+    /// `config` keeps the secret references in service envs, their resolved
+    /// values are sent separately in `secret_envs` (key - service name) and
+    /// are not stored by `oct-ctl`
+    ///
+    /// Returns containers of the running services, a response without body
+    /// has no containers
     pub async fn apply(
         &self,
         config: Config,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        secret_envs: HashMap<String, HashMap<String, String>>,
+        force_recreate: bool,
+        partial: bool,
+    ) -> Result<ApplyResponse, Box<dyn std::error::Error + Send + Sync>> {
         let () = self.check_host_health().await?;
 
-        let client = reqwest::Client::new();
+        let client = self.http_client()?;
 
-        let request = ApplyRequest { config };
+        let request = ApplyRequest {
+            config,
+            force_recreate,
+            partial,
+            secret_envs,
+        };
 
-        let response = client
-            .post(format!("http://{}:{}/apply", self.public_ip, self.port))
+        let response = self
+            .authorize(client.post(self.url("/apply")))
             .header("Accept", "application/json")
             .json(&request)
             .send()
             .await?;
 
+        let body = response.error_for_status()?.bytes().await?;
+
+        if body.is_empty() {
+            return Ok(ApplyResponse::default());
+        }
+
+        serde_json::from_slice(&body)
+            .map_err(|e| format!("Failed to parse apply response: {e}").into())
+    }
+
+    /// Stops the containers of all the user services on the host and removes
+    /// them from its user state, called before the infrastructure is destroyed
+    pub async fn destroy(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let () = self.check_host_health().await?;
+
+        let client = self.http_client()?;
+
+        let response = self
+            .authorize(client.post(self.url("/destroy")))
+            .header("Accept", "application/json")
+            .send()
+            .await?;
+
         match response.error_for_status() {
             Ok(_) => Ok(()),
             Err(e) => Err(Box::new(e)),
         }
     }
 
-    pub async fn destroy(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    /// Stops a single service and removes it from the user state,
+    /// the infrastructure is kept as is
+    ///
+    /// A service unknown to `oct-ctl` is returned as [`RemoveOutcome::NotFound`],
+    /// so the caller decides whether it is an error
+    pub async fn remove_service(
+        &self,
+        name: &str,
+    ) -> Result<RemoveOutcome, Box<dyn std::error::Error + Send + Sync>> {
         let () = self.check_host_health().await?;
 
-        let client = reqwest::Client::new();
+        let client = self.http_client()?;
 
-        let response = client
-            .post(format!("http://{}:{}/destroy", self.public_ip, self.port))
+        let request = RemoveServiceRequest {
+            name: name.to_string(),
+        };
+
+        let response = self
+            .authorize(client.post(self.url("/remove-service")))
             .header("Accept", "application/json")
+            .json(&request)
             .send()
             .await?;
 
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(RemoveOutcome::NotFound);
+        }
+
         match response.error_for_status() {
-            Ok(_) => Ok(()),
+            Ok(_) => Ok(RemoveOutcome::Removed),
             Err(e) => Err(Box::new(e)),
         }
     }
 
+    /// Runs a one-off command in the container, `<service>-<index>` for
+    /// a service with multiple replicas, a non-zero exit code is returned in
+    /// [`ExecOutput`] instead of an error
+    pub async fn exec(
+        &self,
+        container_name: &str,
+        args: &[String],
+    ) -> Result<ExecOutput, Box<dyn std::error::Error + Send + Sync>> {
+        let () = self.check_host_health().await?;
+
+        let client = self.http_client()?;
+
+        let request = ExecRequest {
+            name: container_name.to_string(),
+            args: args.to_vec(),
+        };
+
+        let response = self
+            .authorize(client.post(self.url("/exec")))
+            .header("Accept", "application/json")
+            .json(&request)
+            .send()
+            .await?;
+
+        let exec_output = response.error_for_status()?.json().await?;
+
+        Ok(exec_output)
+    }
+
+    /// Writes the container logs to `output` as they arrive, `since` limits
+    /// them to the recent ones, `tail` to the last lines and `follow` keeps
+    /// streaming the new output until the connection is closed
+    ///
+    /// Without `follow` the logs are limited to the last
+    /// [`Client::DEFAULT_LOGS_TAIL`] lines unless `tail` is set
+    pub async fn get_container_logs(
+        &self,
+        container_name: &str,
+        since: Option<std::time::Duration>,
+        tail: Option<u32>,
+        follow: bool,
+        output: &mut (impl std::io::Write + Send),
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let () = self.check_host_health().await?;
+
+        let client = self.http_client()?;
+
+        let mut url = reqwest::Url::parse(&self.url("/logs"))?;
+        url.query_pairs_mut().append_pair("name", container_name);
+        if let Some(since) = since {
+            url.query_pairs_mut()
+                .append_pair("since", &format!("{}s", since.as_secs()));
+        }
+        let tail = match tail {
+            Some(tail) => Some(tail),
+            None if !follow => Some(Self::DEFAULT_LOGS_TAIL),
+            None => None,
+        };
+        if let Some(tail) = tail {
+            url.query_pairs_mut().append_pair("tail", &tail.to_string());
+        }
+        if follow {
+            url.query_pairs_mut().append_pair("follow", "true");
+        }
+
+        let mut response = self
+            .authorize(client.get(url))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        while let Some(chunk) = response.chunk().await? {
+            output.write_all(&chunk)?;
+            output.flush()?;
+        }
+
+        Ok(())
+    }
+
     async fn check_host_health(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let max_tries = 24;
         let sleep_duration_s = 5;
@@ -108,28 +385,34 @@ impl Client {
         }
     }
 
+    /// Checks that `oct-ctl` responds and the host dependencies are installed
     async fn health_check(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let client = reqwest::Client::new();
+        let client = self.http_client()?;
 
-        let response = client
-            .get(format!(
-                "http://{}:{}/health-check",
-                self.public_ip, self.port
-            ))
+        let response = self
+            .authorize(client.get(self.url("/health-check")))
             .timeout(std::time::Duration::from_secs(5))
             .send()
             .await?;
 
-        match response.error_for_status() {
-            Ok(_) => Ok(()),
-            Err(e) => Err(Box::new(e)),
+        let health_status: HealthStatus = response.error_for_status()?.json().await?;
+
+        if health_status.podman {
+            Ok(())
+        } else {
+            Err(format!("Host dependencies are not ready: {health_status:?}").into())
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use oct_config::{Project, StateBackend};
+    use std::collections::HashMap;
+
+    use oct_config::{
+        ContainerCommand, NetworkMode, PortMapping, PortProtocol, Project, PullPolicy, Service,
+        StateBackend,
+    };
 
     use super::*;
 
@@ -149,6 +432,7 @@ mod tests {
         let health_check_mock = server
             .mock("GET", "/health-check")
             .with_status(200)
+            .with_body(r#"{"podman": true}"#)
             .create();
 
         let apply_mock = server
@@ -161,6 +445,8 @@ mod tests {
         let client = Client {
             public_ip: ip,
             port,
+            scheme: Scheme::Http,
+            token: None,
         };
 
         let config = Config {
@@ -172,49 +458,935 @@ mod tests {
                 user_state_backend: StateBackend::Local {
                     path: "user_state.json".to_string(),
                 },
-                services: Vec::new(),
-                domain: None,
+                ..Default::default()
             },
         };
 
         // Act
-        let response = client.apply(config).await;
+        let response = client.apply(config, HashMap::new(), false, false).await;
 
         // Assert
-        assert!(response.is_ok());
+        assert_eq!(response.expect("Failed to apply"), ApplyResponse::default());
 
         health_check_mock.assert();
         apply_mock.assert();
     }
 
     #[tokio::test]
-    async fn test_destroy_success() {
+    async fn test_apply_failed_services() {
         // Arrange
         let (ip, port, mut server) = setup_server().await;
 
         let health_check_mock = server
             .mock("GET", "/health-check")
             .with_status(200)
+            .with_body(r#"{"podman": true}"#)
             .create();
 
-        let destroy_mock = server
-            .mock("POST", "/destroy")
+        let apply_mock = server
+            .mock("POST", "/apply")
+            .with_status(500)
+            .with_body("Failed to apply services: Failed to run services: app_1")
+            .create();
+
+        let client = Client {
+            public_ip: ip,
+            port,
+            scheme: Scheme::Http,
+            token: None,
+        };
+
+        let config = Config {
+            project: Project {
+                name: "test".to_string(),
+                state_backend: StateBackend::Local {
+                    path: "state.json".to_string(),
+                },
+                user_state_backend: StateBackend::Local {
+                    path: "user_state.json".to_string(),
+                },
+                ..Default::default()
+            },
+        };
+
+        // Act
+        let response = client.apply(config, HashMap::new(), false, false).await;
+
+        // Assert
+        assert!(response.is_err());
+
+        health_check_mock.assert();
+        apply_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_apply_returns_container_ids() {
+        // Arrange
+        let (ip, port, mut server) = setup_server().await;
+
+        let health_check_mock = server
+            .mock("GET", "/health-check")
             .with_status(200)
-            .match_header("Accept", "application/json")
+            .with_body(r#"{"podman": true}"#)
+            .create();
+
+        let apply_mock = server
+            .mock("POST", "/apply")
+            .with_status(201)
+            .with_body(r#"{"containers": {"app_1": {"id": "3f2a9c1b7d"}}}"#)
             .create();
 
         let client = Client {
             public_ip: ip,
             port,
+            scheme: Scheme::Http,
+            token: None,
+        };
+
+        let config = Config {
+            project: Project {
+                name: "test".to_string(),
+                state_backend: StateBackend::Local {
+                    path: "state.json".to_string(),
+                },
+                user_state_backend: StateBackend::Local {
+                    path: "user_state.json".to_string(),
+                },
+                ..Default::default()
+            },
         };
 
         // Act
-        let response = client.destroy().await;
+        let response = client.apply(config, HashMap::new(), false, false).await;
+
+        // Assert
+        assert_eq!(
+            response.expect("Failed to apply"),
+            ApplyResponse {
+                containers: HashMap::from([(
+                    String::from("app_1"),
+                    RunContainerResponse {
+                        id: String::from("3f2a9c1b7d"),
+                    },
+                )]),
+            }
+        );
+
+        health_check_mock.assert();
+        apply_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_apply_force_recreate() {
+        // Arrange
+        let (ip, port, mut server) = setup_server().await;
+
+        let health_check_mock = server
+            .mock("GET", "/health-check")
+            .with_status(200)
+            .with_body(r#"{"podman": true}"#)
+            .create();
+
+        let apply_mock = server
+            .mock("POST", "/apply")
+            .with_status(201)
+            .match_body(mockito::Matcher::PartialJsonString(
+                r#"{"force_recreate":true}"#.to_string(),
+            ))
+            .create();
+
+        let client = Client {
+            public_ip: ip,
+            port,
+            scheme: Scheme::Http,
+            token: None,
+        };
+
+        let config = Config {
+            project: Project {
+                name: "test".to_string(),
+                state_backend: StateBackend::Local {
+                    path: "state.json".to_string(),
+                },
+                user_state_backend: StateBackend::Local {
+                    path: "user_state.json".to_string(),
+                },
+                ..Default::default()
+            },
+        };
+
+        // Act
+        let response = client.apply(config, HashMap::new(), true, false).await;
 
         // Assert
         assert!(response.is_ok());
 
         health_check_mock.assert();
-        destroy_mock.assert();
+        apply_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_apply_partial() {
+        // Arrange
+        let (ip, port, mut server) = setup_server().await;
+
+        let health_check_mock = server
+            .mock("GET", "/health-check")
+            .with_status(200)
+            .with_body(r#"{"podman": true}"#)
+            .create();
+
+        let apply_mock = server
+            .mock("POST", "/apply")
+            .with_status(201)
+            .match_body(mockito::Matcher::PartialJsonString(
+                r#"{"force_recreate":false,"partial":true}"#.to_string(),
+            ))
+            .create();
+
+        let client = Client {
+            public_ip: ip,
+            port,
+            scheme: Scheme::Http,
+            token: None,
+        };
+
+        let config = Config {
+            project: Project {
+                name: "test".to_string(),
+                state_backend: StateBackend::Local {
+                    path: "state.json".to_string(),
+                },
+                user_state_backend: StateBackend::Local {
+                    path: "user_state.json".to_string(),
+                },
+                ..Default::default()
+            },
+        };
+
+        // Act
+        let response = client.apply(config, HashMap::new(), false, true).await;
+
+        // Assert
+        assert!(response.is_ok());
+
+        health_check_mock.assert();
+        apply_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_apply_secret_envs() {
+        // Arrange
+        let (ip, port, mut server) = setup_server().await;
+
+        let health_check_mock = server
+            .mock("GET", "/health-check")
+            .with_status(200)
+            .with_body(r#"{"podman": true}"#)
+            .create();
+
+        let apply_mock = server
+            .mock("POST", "/apply")
+            .with_status(201)
+            .match_body(mockito::Matcher::PartialJsonString(
+                r#"{"secret_envs":{"app_1":{"DB_PASSWORD":"s3cr3t"}}}"#.to_string(),
+            ))
+            .create();
+
+        let client = Client {
+            public_ip: ip,
+            port,
+            scheme: Scheme::Http,
+            token: None,
+        };
+
+        let config = Config {
+            project: Project {
+                name: "test".to_string(),
+                state_backend: StateBackend::Local {
+                    path: "state.json".to_string(),
+                },
+                user_state_backend: StateBackend::Local {
+                    path: "user_state.json".to_string(),
+                },
+                ..Default::default()
+            },
+        };
+        let secret_envs = HashMap::from([(
+            String::from("app_1"),
+            HashMap::from([(String::from("DB_PASSWORD"), String::from("s3cr3t"))]),
+        )]);
+
+        // Act
+        let response = client.apply(config, secret_envs, false, false).await;
+
+        // Assert
+        assert!(response.is_ok());
+
+        health_check_mock.assert();
+        apply_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_destroy_success() {
+        // Arrange
+        let (ip, port, mut server) = setup_server().await;
+
+        let health_check_mock = server
+            .mock("GET", "/health-check")
+            .with_status(200)
+            .with_body(r#"{"podman": true}"#)
+            .create();
+
+        let destroy_mock = server
+            .mock("POST", "/destroy")
+            .with_status(200)
+            .match_header("Accept", "application/json")
+            .create();
+
+        let client = Client {
+            public_ip: ip,
+            port,
+            scheme: Scheme::Http,
+            token: None,
+        };
+
+        // Act
+        let response = client.destroy().await;
+
+        // Assert
+        assert!(response.is_ok());
+
+        health_check_mock.assert();
+        destroy_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_destroy_with_token() {
+        // Arrange
+        let (ip, port, mut server) = setup_server().await;
+
+        let health_check_mock = server
+            .mock("GET", "/health-check")
+            .with_status(200)
+            .with_body(r#"{"podman": true}"#)
+            .create();
+
+        let destroy_mock = server
+            .mock("POST", "/destroy")
+            .with_status(200)
+            .match_header("Authorization", "Bearer secret-token")
+            .create();
+
+        let client = Client {
+            public_ip: ip,
+            port,
+            scheme: Scheme::Http,
+            token: None,
+        }
+        .with_token(String::from("secret-token"));
+
+        // Act
+        let response = client.destroy().await;
+
+        // Assert
+        assert!(response.is_ok());
+
+        health_check_mock.assert();
+        destroy_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_destroy_without_token_rejected() {
+        // Arrange
+        let (ip, port, mut server) = setup_server().await;
+
+        let health_check_mock = server
+            .mock("GET", "/health-check")
+            .with_status(200)
+            .with_body(r#"{"podman": true}"#)
+            .create();
+
+        let destroy_mock = server
+            .mock("POST", "/destroy")
+            .with_status(401)
+            .match_header("Authorization", mockito::Matcher::Missing)
+            .create();
+
+        let client = Client {
+            public_ip: ip,
+            port,
+            scheme: Scheme::Http,
+            token: None,
+        };
+
+        // Act
+        let response = client.destroy().await;
+
+        // Assert
+        assert!(
+            response
+                .expect_err("Expected unauthorized error")
+                .to_string()
+                .contains("401 Unauthorized")
+        );
+
+        health_check_mock.assert();
+        destroy_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_remove_service_success() {
+        // Arrange
+        let (ip, port, mut server) = setup_server().await;
+
+        let health_check_mock = server
+            .mock("GET", "/health-check")
+            .with_status(200)
+            .with_body(r#"{"podman": true}"#)
+            .create();
+
+        let remove_service_mock = server
+            .mock("POST", "/remove-service")
+            .with_status(200)
+            .match_header("Content-Type", "application/json")
+            .match_body(r#"{"name":"app_1"}"#)
+            .create();
+
+        let client = Client {
+            public_ip: ip,
+            port,
+            scheme: Scheme::Http,
+            token: None,
+        };
+
+        // Act
+        let response = client.remove_service("app_1").await;
+
+        // Assert
+        assert_eq!(
+            response.expect("Failed to remove service"),
+            RemoveOutcome::Removed
+        );
+
+        health_check_mock.assert();
+        remove_service_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_remove_service_not_found() {
+        // Arrange
+        let (ip, port, mut server) = setup_server().await;
+
+        let health_check_mock = server
+            .mock("GET", "/health-check")
+            .with_status(200)
+            .with_body(r#"{"podman": true}"#)
+            .create();
+
+        let remove_service_mock = server
+            .mock("POST", "/remove-service")
+            .with_status(404)
+            .create();
+
+        let client = Client {
+            public_ip: ip,
+            port,
+            scheme: Scheme::Http,
+            token: None,
+        };
+
+        // Act
+        let response = client.remove_service("app_1").await;
+
+        // Assert
+        assert_eq!(
+            response.expect("Failed to remove service"),
+            RemoveOutcome::NotFound
+        );
+
+        health_check_mock.assert();
+        remove_service_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_remove_service_server_error() {
+        // Arrange
+        let (ip, port, mut server) = setup_server().await;
+
+        let health_check_mock = server
+            .mock("GET", "/health-check")
+            .with_status(200)
+            .with_body(r#"{"podman": true}"#)
+            .create();
+
+        let remove_service_mock = server
+            .mock("POST", "/remove-service")
+            .with_status(500)
+            .create();
+
+        let client = Client {
+            public_ip: ip,
+            port,
+            scheme: Scheme::Http,
+            token: None,
+        };
+
+        // Act
+        let response = client.remove_service("app_1").await;
+
+        // Assert
+        assert!(response.is_err());
+
+        health_check_mock.assert();
+        remove_service_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_exec_zero_exit() {
+        // Arrange
+        let (ip, port, mut server) = setup_server().await;
+
+        let health_check_mock = server
+            .mock("GET", "/health-check")
+            .with_status(200)
+            .with_body(r#"{"podman": true}"#)
+            .create();
+
+        let exec_mock = server
+            .mock("POST", "/exec")
+            .with_status(200)
+            .match_header("Content-Type", "application/json")
+            .match_body(r#"{"name":"app_1","args":["ls","/"]}"#)
+            .with_body(r#"{"stdout": "bin\n", "stderr": "", "exit_code": 0}"#)
+            .create();
+
+        let client = Client {
+            public_ip: ip,
+            port,
+            scheme: Scheme::Http,
+            token: None,
+        };
+
+        // Act
+        let response = client
+            .exec("app_1", &[String::from("ls"), String::from("/")])
+            .await;
+
+        // Assert
+        assert_eq!(
+            response.expect("Failed to exec"),
+            ExecOutput {
+                stdout: String::from("bin\n"),
+                stderr: String::new(),
+                exit_code: 0,
+            }
+        );
+
+        health_check_mock.assert();
+        exec_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_get_container_logs_since_follow() {
+        // Arrange
+        let (ip, port, mut server) = setup_server().await;
+
+        let health_check_mock = server
+            .mock("GET", "/health-check")
+            .with_status(200)
+            .with_body(r#"{"podman": true}"#)
+            .create();
+
+        let logs_mock = server
+            .mock("GET", "/logs")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("name".into(), "app_1".into()),
+                mockito::Matcher::UrlEncoded("since".into(), "600s".into()),
+                mockito::Matcher::UrlEncoded("follow".into(), "true".into()),
+            ]))
+            .with_status(200)
+            .with_body("Listening on :80\n")
+            .create();
+
+        let client = Client {
+            public_ip: ip,
+            port,
+            scheme: Scheme::Http,
+            token: None,
+        };
+        let mut output = Vec::new();
+
+        // Act
+        let response = client
+            .get_container_logs(
+                "app_1",
+                Some(std::time::Duration::from_secs(600)),
+                None,
+                true,
+                &mut output,
+            )
+            .await;
+
+        // Assert
+        assert!(response.is_ok());
+        assert_eq!(output, b"Listening on :80\n");
+
+        health_check_mock.assert();
+        logs_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_get_container_logs_without_follow() {
+        // Arrange
+        let (ip, port, mut server) = setup_server().await;
+
+        let _health_check_mock = server
+            .mock("GET", "/health-check")
+            .with_status(200)
+            .with_body(r#"{"podman": true}"#)
+            .create();
+
+        let logs_mock = server
+            .mock("GET", "/logs")
+            .match_query(mockito::Matcher::Exact(String::from(
+                "name=app_1&tail=1000",
+            )))
+            .with_status(200)
+            .with_body("line 1\nline 2\n")
+            .create();
+
+        let client = Client {
+            public_ip: ip,
+            port,
+            scheme: Scheme::Http,
+            token: None,
+        };
+        let mut output = Vec::new();
+
+        // Act
+        let response = client
+            .get_container_logs("app_1", None, None, false, &mut output)
+            .await;
+
+        // Assert
+        assert!(response.is_ok());
+        assert_eq!(output, b"line 1\nline 2\n");
+
+        logs_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_get_container_logs_with_tail() {
+        // Arrange
+        let (ip, port, mut server) = setup_server().await;
+
+        let _health_check_mock = server
+            .mock("GET", "/health-check")
+            .with_status(200)
+            .with_body(r#"{"podman": true}"#)
+            .create();
+
+        let logs_mock = server
+            .mock("GET", "/logs")
+            .match_query(mockito::Matcher::Exact(String::from("name=app_1&tail=20")))
+            .with_status(200)
+            .with_body("line 20\n")
+            .create();
+
+        let client = Client {
+            public_ip: ip,
+            port,
+            scheme: Scheme::Http,
+            token: None,
+        };
+        let mut output = Vec::new();
+
+        // Act
+        let response = client
+            .get_container_logs("app_1", None, Some(20), false, &mut output)
+            .await;
+
+        // Assert
+        assert!(response.is_ok());
+        assert_eq!(output, b"line 20\n");
+
+        logs_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_exec_non_zero_exit() {
+        // Arrange
+        let (ip, port, mut server) = setup_server().await;
+
+        let health_check_mock = server
+            .mock("GET", "/health-check")
+            .with_status(200)
+            .with_body(r#"{"podman": true}"#)
+            .create();
+
+        let exec_mock = server
+            .mock("POST", "/exec")
+            .with_status(200)
+            .with_body(r#"{"stdout": "", "stderr": "migration failed\n", "exit_code": 3}"#)
+            .create();
+
+        let client = Client {
+            public_ip: ip,
+            port,
+            scheme: Scheme::Http,
+            token: None,
+        };
+
+        // Act
+        let response = client.exec("app_1", &[String::from("migrate")]).await;
+
+        // Assert
+        let exec_output = response.expect("Failed to exec");
+        assert_eq!(exec_output.exit_code, 3);
+        assert_eq!(exec_output.stderr, "migration failed\n");
+
+        health_check_mock.assert();
+        exec_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_health_check_ready() {
+        // Arrange
+        let (ip, port, mut server) = setup_server().await;
+
+        let health_check_mock = server
+            .mock("GET", "/health-check")
+            .with_status(200)
+            .with_body(r#"{"podman": true}"#)
+            .create();
+
+        let client = Client {
+            public_ip: ip,
+            port,
+            scheme: Scheme::Http,
+            token: None,
+        };
+
+        // Act
+        let response = client.health_check().await;
+
+        // Assert
+        assert!(response.is_ok());
+
+        health_check_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_health_check_dependencies_not_ready() {
+        // Arrange
+        let (ip, port, mut server) = setup_server().await;
+
+        let health_check_mock = server
+            .mock("GET", "/health-check")
+            .with_status(200)
+            .with_body(r#"{"podman": false}"#)
+            .create();
+
+        let client = Client {
+            public_ip: ip,
+            port,
+            scheme: Scheme::Http,
+            token: None,
+        };
+
+        // Act
+        let response = client.health_check().await;
+
+        // Assert
+        assert_eq!(
+            response.expect_err("Expected not ready error").to_string(),
+            "Host dependencies are not ready: HealthStatus { podman: false }"
+        );
+
+        health_check_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_health_check_error_status_without_json_body() {
+        // Arrange
+        let (ip, port, mut server) = setup_server().await;
+
+        let health_check_mock = server
+            .mock("GET", "/health-check")
+            .with_status(401)
+            .with_body("Unauthorized")
+            .create();
+
+        let client = Client {
+            public_ip: ip,
+            port,
+            scheme: Scheme::Http,
+            token: None,
+        };
+
+        // Act
+        let response = client.health_check().await;
+
+        // Assert
+        assert!(
+            response
+                .expect_err("Expected status error")
+                .to_string()
+                .contains("401 Unauthorized")
+        );
+
+        health_check_mock.assert();
+    }
+
+    #[test]
+    fn test_url_http() {
+        // Arrange
+        let client = Client::new(String::from("10.0.0.1"));
+
+        // Act
+        let url = client.url("/apply");
+
+        // Assert
+        assert_eq!(url, "http://10.0.0.1:31888/apply");
+    }
+
+    #[test]
+    fn test_url_https() {
+        // Arrange
+        let client =
+            Client::new_https(String::from("10.0.0.1"), None).expect("Failed to create client");
+
+        // Act
+        let url = client.url("/apply");
+
+        // Assert
+        assert_eq!(url, "https://10.0.0.1:31888/apply");
+        assert!(client.http_client().is_ok());
+    }
+
+    #[test]
+    fn test_apply_request_serializes_service_run_options() {
+        // Arrange
+        let get_service = |name: &str, command: ContainerCommand| Service {
+            name: name.to_string(),
+            image: "ubuntu:latest".to_string(),
+            entrypoint: Some("/entrypoint.sh".to_string()),
+            command: Some(command),
+            ports: vec![
+                PortMapping {
+                    external: 53,
+                    internal: 53,
+                    protocol: Some(PortProtocol::Udp),
+                },
+                PortMapping {
+                    external: 8080,
+                    internal: 80,
+                    protocol: None,
+                },
+            ],
+            network_mode: Some(NetworkMode::Bridge),
+            pull_policy: Some(PullPolicy::IfNotPresent),
+            cpus: 250,
+            memory: 64,
+            ..Default::default()
+        };
+
+        let request = ApplyRequest {
+            config: Config {
+                project: Project {
+                    name: "test".to_string(),
+                    state_backend: StateBackend::Local {
+                        path: "state.json".to_string(),
+                    },
+                    user_state_backend: StateBackend::Local {
+                        path: "user_state.json".to_string(),
+                    },
+                    services: vec![
+                        get_service("app_1", ContainerCommand::String("echo hello".to_string())),
+                        get_service(
+                            "app_2",
+                            ContainerCommand::Args(vec![
+                                "sh".to_string(),
+                                "-c".to_string(),
+                                "echo 'hello world'".to_string(),
+                            ]),
+                        ),
+                    ],
+                    ..Default::default()
+                },
+            },
+            force_recreate: false,
+            partial: false,
+            secret_envs: HashMap::new(),
+        };
+
+        // Act
+        let serialized = serde_json::to_value(&request).expect("Failed to serialize request");
+        let deserialized: ApplyRequest =
+            serde_json::from_value(serialized.clone()).expect("Failed to deserialize request");
+
+        // Assert
+        assert_eq!(
+            serialized["config"]["project"]["services"][0]["command"],
+            serde_json::json!("echo hello")
+        );
+        assert_eq!(
+            serialized["config"]["project"]["services"][1]["command"],
+            serde_json::json!(["sh", "-c", "echo 'hello world'"])
+        );
+        assert_eq!(
+            serialized["config"]["project"]["services"][0]["entrypoint"],
+            serde_json::json!("/entrypoint.sh")
+        );
+        assert_eq!(
+            serialized["config"]["project"]["services"][0]["ports"],
+            serde_json::json!(["53:53/udp", "8080:80"])
+        );
+        assert_eq!(
+            serialized["config"]["project"]["services"][0]["network_mode"],
+            serde_json::json!("bridge")
+        );
+        assert_eq!(
+            serialized["config"]["project"]["services"][0]["pull_policy"],
+            serde_json::json!("if-not-present")
+        );
+        assert_eq!(deserialized.config, request.config);
+    }
+
+    #[test]
+    fn test_pull_policy_serialization() {
+        // Arrange
+        let pull_policies = [
+            (PullPolicy::Always, "always"),
+            (PullPolicy::IfNotPresent, "if-not-present"),
+            (PullPolicy::Never, "never"),
+        ];
+
+        for (pull_policy, expected) in pull_policies {
+            // Act
+            let serialized =
+                serde_json::to_value(pull_policy).expect("Failed to serialize pull policy");
+            let deserialized: PullPolicy =
+                serde_json::from_value(serialized.clone()).expect("Failed to deserialize");
+
+            // Assert
+            assert_eq!(serialized, serde_json::json!(expected));
+            assert_eq!(deserialized, pull_policy);
+        }
+    }
+
+    #[test]
+    fn test_pull_policy_unknown_value() {
+        // Act
+        let result = serde_json::from_value::<PullPolicy>(serde_json::json!("sometimes"));
+
+        // Assert
+        assert!(
+            result
+                .expect_err("Expected unknown pull policy error")
+                .to_string()
+                .starts_with("unknown variant `sometimes`")
+        );
     }
 }
@@ -1,5 +1,11 @@
 use std::collections::HashMap;
-use std::process::Command;
+use std::pin::Pin;
+use std::process::{Command, Output, Stdio};
+
+use axum::body::Bytes;
+use oct_config::{ContainerCommand, NetworkMode, PortMapping, PortProtocol, PullPolicy};
+use tokio_stream::{Stream, StreamExt};
+use tokio_util::io::ReaderStream;
 
 #[cfg(not(test))]
 use crate::executor::CommandExecutor;
@@ -21,6 +27,27 @@ impl ContainerManager {
     }
 }
 
+/// Container output chunks, stdout and stderr are interleaved
+pub(crate) type LogStream = Pin<Box<dyn Stream<Item = std::io::Result<Bytes>> + Send>>;
+
+/// Options of a container started with `run`
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RunContainerOptions {
+    pub(crate) image: String,
+    pub(crate) entrypoint: Option<String>,
+    pub(crate) command: Option<ContainerCommand>,
+    pub(crate) external_port: Option<u32>,
+    pub(crate) internal_port: Option<u32>,
+    pub(crate) ports: Vec<PortMapping>,
+    pub(crate) network_mode: Option<NetworkMode>,
+    pub(crate) pull_policy: Option<PullPolicy>,
+    /// CPU millicores
+    pub(crate) cpus: u32,
+    /// Memory in MB
+    pub(crate) memory: u64,
+    pub(crate) envs: HashMap<String, String>,
+}
+
 /// Container engine implementation
 #[derive(Clone, Default)]
 pub(crate) struct ContainerEngine {
@@ -32,18 +59,12 @@ pub(crate) struct ContainerEngine {
 impl ContainerEngine {
     const NETWORK_NAME: &str = "oct";
 
-    /// Runs container using `podman`
+    /// Runs container using `podman`, returns the container id
     pub(crate) fn run(
         &self,
         name: String,
-        image: String,
-        command: Option<String>,
-        external_port: Option<u32>,
-        internal_port: Option<u32>,
-        cpus: u32,
-        memory: u64,
-        envs: &HashMap<String, String>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+        options: &RunContainerOptions,
+    ) -> Result<String, Box<dyn std::error::Error>> {
         // We accept errors here, as network might already exist
         let network_create_output =
             self.executor
@@ -55,16 +76,7 @@ impl ContainerEngine {
 
         log::info!("Network create command output: {network_create_output:?}");
 
-        let run_container_args = Self::build_run_container_args(
-            name,
-            image,
-            command,
-            external_port,
-            internal_port,
-            cpus,
-            memory,
-            envs,
-        );
+        let run_container_args = Self::build_run_container_args(name, options);
 
         let run_container_cmd = self
             .executor
@@ -73,17 +85,16 @@ impl ContainerEngine {
         log::info!("Run container command output: {run_container_cmd:?}");
 
         if run_container_cmd.status.success() {
-            Ok(())
+            // `run -d` prints the id of the started container
+            Ok(String::from_utf8_lossy(&run_container_cmd.stdout)
+                .trim()
+                .to_string())
         } else {
             Err("Failed to run container".into())
         }
     }
 
     /// Removes container
-    ///
-    /// Temporarily marked as unused until container destruction logic is
-    /// implemented
-    #[allow(dead_code)]
     pub(crate) fn remove(&self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
         let output = self
             .executor
@@ -96,19 +107,120 @@ impl ContainerEngine {
         }
     }
 
-    fn build_run_container_args(
-        name: String,
-        image: String,
-        command: Option<String>,
-        external_port: Option<u32>,
-        internal_port: Option<u32>,
-        cpus: u32,
-        memory: u64,
-        envs: &HashMap<String, String>,
+    /// Runs a one-off command inside the container, a non-zero exit code
+    /// is returned in the output instead of an error
+    pub(crate) fn exec(
+        &self,
+        name: &str,
+        args: &[String],
+    ) -> Result<Output, Box<dyn std::error::Error>> {
+        let output = self.executor.execute(
+            Command::new(self.manager.as_str())
+                .args(["exec", name])
+                .args(args),
+        )?;
+
+        Ok(output)
+    }
+
+    /// Streams the container logs with `podman logs`, `since` is passed as
+    /// is, e.g. `10m`, `tail` keeps only the last lines and `follow` keeps
+    /// the stream open for new output
+    ///
+    /// `podman` is killed when the stream is dropped, e.g. when the client
+    /// disconnects
+    pub(crate) fn logs(
+        &self,
+        name: &str,
+        since: Option<String>,
+        tail: Option<u32>,
+        follow: bool,
+    ) -> Result<LogStream, Box<dyn std::error::Error>> {
+        let mut child = tokio::process::Command::new(self.manager.as_str())
+            .args(Self::build_logs_args(name, since.as_deref(), tail, follow))
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        let stdout = child.stdout.take().ok_or("Failed to capture logs stdout")?;
+        let stderr = child.stderr.take().ok_or("Failed to capture logs stderr")?;
+
+        let stream = ReaderStream::new(stdout)
+            .merge(ReaderStream::new(stderr))
+            .map(move |chunk| {
+                // The stream owns the process to kill it on drop
+                let _ = &child;
+
+                chunk
+            });
+
+        Ok(Box::pin(stream))
+    }
+
+    /// Checks that the container manager is installed and responds
+    pub(crate) fn is_available(&self) -> bool {
+        self.executor
+            .execute(Command::new(self.manager.as_str()).arg("--version"))
+            .is_ok_and(|output| output.status.success())
+    }
+
+    /// Runs health check command inside the container,
+    /// returns whether the command succeeded
+    pub(crate) fn is_healthy(
+        &self,
+        name: &str,
+        command: &ContainerCommand,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let output = self.executor.execute(
+            Command::new(self.manager.as_str())
+                .args(["exec", name])
+                .args(command.to_args()),
+        )?;
+
+        Ok(output.status.success())
+    }
+
+    fn build_logs_args(
+        name: &str,
+        since: Option<&str>,
+        tail: Option<u32>,
+        follow: bool,
     ) -> Vec<String> {
-        let cpus = f64::from(cpus) / 1000.0; // Convert millicores to cores
+        let mut logs_args = vec!["logs".to_string()];
+
+        if let Some(since) = since {
+            logs_args.push("--since".to_string());
+            logs_args.push(since.to_string());
+        }
+
+        if let Some(tail) = tail {
+            logs_args.push("--tail".to_string());
+            logs_args.push(tail.to_string());
+        }
+
+        if follow {
+            logs_args.push("--follow".to_string());
+        }
+
+        logs_args.push(name.to_string());
+
+        logs_args
+    }
+
+    /// `--pull` value of `podman run`, also accepted by `docker run`
+    fn get_pull_arg(pull_policy: PullPolicy) -> &'static str {
+        match pull_policy {
+            PullPolicy::Always => "always",
+            PullPolicy::IfNotPresent => "missing",
+            PullPolicy::Never => "never",
+        }
+    }
+
+    fn build_run_container_args(name: String, options: &RunContainerOptions) -> Vec<String> {
+        let cpus = f64::from(options.cpus) / 1000.0; // Convert millicores to cores
         let cpus_str = format!("{cpus:.2}");
-        let memory_str = format!("{memory}m");
+        let memory_str = format!("{}m", options.memory);
 
         let mut run_container_args = vec![
             "run".to_string(),
@@ -122,29 +234,62 @@ impl ContainerEngine {
             "--memory".to_string(),
             memory_str,
             "--network".to_string(),
-            Self::NETWORK_NAME.to_string(),
         ];
 
-        if let (Some(external_port), Some(internal_port)) = (external_port, internal_port) {
-            let port_mapping_tcp = format!("{external_port}:{internal_port}/tcp");
-            let port_mapping_udp = format!("{external_port}:{internal_port}/udp");
+        if let Some(pull_policy) = options.pull_policy {
+            run_container_args.push("--pull".to_string());
+            run_container_args.push(Self::get_pull_arg(pull_policy).to_string());
+        }
 
-            run_container_args.push("-p".to_string());
-            run_container_args.push(port_mapping_tcp);
-            run_container_args.push("-p".to_string());
-            run_container_args.push(port_mapping_udp);
+        if options.network_mode == Some(NetworkMode::Host) {
+            // The container shares the host ports, nothing is published
+            run_container_args.push("host".to_string());
+        } else {
+            run_container_args.push(Self::NETWORK_NAME.to_string());
+
+            let port_pair =
+                options
+                    .external_port
+                    .zip(options.internal_port)
+                    .map(|(external, internal)| PortMapping {
+                        external,
+                        internal,
+                        protocol: None,
+                    });
+
+            for port in port_pair.iter().chain(&options.ports) {
+                let protocols = match port.protocol {
+                    Some(protocol) => vec![protocol],
+                    None => vec![PortProtocol::Tcp, PortProtocol::Udp],
+                };
+
+                for protocol in protocols {
+                    run_container_args.push("-p".to_string());
+                    run_container_args.push(format!(
+                        "{}:{}/{}",
+                        port.external,
+                        port.internal,
+                        protocol.as_str()
+                    ));
+                }
+            }
         }
 
-        for (key, value) in envs {
+        for (key, value) in &options.envs {
             let env_str = format!("{key}={value}");
             run_container_args.push("-e".to_string());
             run_container_args.push(env_str);
         }
 
-        run_container_args.push(image);
+        if let Some(entrypoint) = &options.entrypoint {
+            run_container_args.push("--entrypoint".to_string());
+            run_container_args.push(entrypoint.clone());
+        }
+
+        run_container_args.push(options.image.clone());
 
-        if let Some(command) = command {
-            run_container_args.extend(command.split(' ').map(str::to_string));
+        if let Some(command) = &options.command {
+            run_container_args.extend(command.to_args());
         }
 
         run_container_args
@@ -153,9 +298,10 @@ impl ContainerEngine {
 
 #[cfg(test)]
 pub(crate) mod mocks {
-    use std::collections::HashMap;
-
     use mockall::mock;
+    use oct_config::ContainerCommand;
+
+    use super::RunContainerOptions;
 
     // As long as ContainerEngine implemnts Clone, we mock it using
     // mockall::mock macro, more info here:
@@ -165,16 +311,32 @@ pub(crate) mod mocks {
             pub(crate) fn run(
                 &self,
                 name: String,
-                image: String,
-                command: Option<String>,
-                external_port: Option<u32>,
-                internal_port: Option<u32>,
-                cpus: u32,
-                memory: u64,
-                envs: &HashMap<String, String>,
-            ) -> Result<(), Box<dyn std::error::Error>>;
+                options: &RunContainerOptions,
+            ) -> Result<String, Box<dyn std::error::Error>>;
 
             pub(crate) fn remove(&self, name: &str) -> Result<(), Box<dyn std::error::Error>>;
+
+            pub(crate) fn is_healthy(
+                &self,
+                name: &str,
+                command: &ContainerCommand,
+            ) -> Result<bool, Box<dyn std::error::Error>>;
+
+            pub(crate) fn exec(
+                &self,
+                name: &str,
+                args: &[String],
+            ) -> Result<std::process::Output, Box<dyn std::error::Error>>;
+
+            pub(crate) fn logs(
+                &self,
+                name: &str,
+                since: Option<String>,
+                tail: Option<u32>,
+                follow: bool,
+            ) -> Result<super::LogStream, Box<dyn std::error::Error>>;
+
+            pub(crate) fn is_available(&self) -> bool;
         }
 
         impl Clone for ContainerEngine {
@@ -206,7 +368,14 @@ mod tests {
     #[test]
     fn test_container_engine_run_success() {
         // Arrange
-        let mock_command_executor = get_command_executor_mock(0);
+        let mut mock_command_executor = CommandExecutor::default();
+        mock_command_executor.expect_execute().returning(|_| {
+            Ok(Output {
+                status: ExitStatus::from_raw(0),
+                stdout: b"3f2a9c1b7d\n".to_vec(),
+                stderr: Vec::new(),
+            })
+        });
 
         let container_engine = ContainerEngine {
             manager: ContainerManager::Podman,
@@ -216,17 +385,20 @@ mod tests {
         // Act
         let run_result = container_engine.run(
             "test".to_string(),
-            "ubuntu:latest".to_string(),
-            Some("echo hello".to_string()),
-            Some(80),
-            Some(8080),
-            1,
-            512,
-            &HashMap::from([("KEY".to_string(), "VALUE".to_string())]),
+            &RunContainerOptions {
+                image: "ubuntu:latest".to_string(),
+                command: Some(ContainerCommand::String("echo hello".to_string())),
+                external_port: Some(80),
+                internal_port: Some(8080),
+                cpus: 1,
+                memory: 512,
+                envs: HashMap::from([("KEY".to_string(), "VALUE".to_string())]),
+                ..Default::default()
+            },
         );
 
         // Assert
-        assert!(run_result.is_ok());
+        assert_eq!(run_result.expect("Failed to run container"), "3f2a9c1b7d");
     }
 
     #[test]
@@ -242,13 +414,15 @@ mod tests {
         // Act
         let run_result = container_engine.run(
             "test".to_string(),
-            "ubuntu:latest".to_string(),
-            Some("echo hello".to_string()),
-            Some(80),
-            Some(8080),
-            1,
-            512,
-            &HashMap::new(),
+            &RunContainerOptions {
+                image: "ubuntu:latest".to_string(),
+                command: Some(ContainerCommand::String("echo hello".to_string())),
+                external_port: Some(80),
+                internal_port: Some(8080),
+                cpus: 1,
+                memory: 512,
+                ..Default::default()
+            },
         );
 
         // Assert
@@ -289,6 +463,306 @@ mod tests {
         assert!(remove_result.is_err());
     }
 
+    #[test]
+    fn test_container_engine_is_healthy() {
+        // Arrange
+        let container_engine = ContainerEngine {
+            manager: ContainerManager::Podman,
+            executor: get_command_executor_mock(0),
+        };
+        let unhealthy_container_engine = ContainerEngine {
+            manager: ContainerManager::Podman,
+            executor: get_command_executor_mock(1),
+        };
+        let command = ContainerCommand::String("pg_isready".to_string());
+
+        // Act
+        let is_healthy = container_engine.is_healthy("test", &command);
+        let is_unhealthy = unhealthy_container_engine.is_healthy("test", &command);
+
+        // Assert
+        assert!(is_healthy.expect("Failed to run health check"));
+        assert!(!is_unhealthy.expect("Failed to run health check"));
+    }
+
+    #[test]
+    fn test_container_engine_exec_non_zero_exit() {
+        // Arrange
+        let mut mock_command_executor = CommandExecutor::default();
+        mock_command_executor.expect_execute().returning(|_| {
+            Ok(Output {
+                // Raw wait status keeps the exit code in the second byte
+                status: ExitStatus::from_raw(2 << 8),
+                stdout: Vec::new(),
+                stderr: b"migration failed".to_vec(),
+            })
+        });
+
+        let container_engine = ContainerEngine {
+            manager: ContainerManager::Podman,
+            executor: mock_command_executor,
+        };
+
+        // Act
+        let output = container_engine
+            .exec("test", &[String::from("migrate")])
+            .expect("Failed to run exec");
+
+        // Assert
+        assert_eq!(output.status.code(), Some(2));
+        assert_eq!(output.stderr, b"migration failed");
+    }
+
+    #[test]
+    fn test_container_engine_is_available() {
+        // Arrange
+        let container_engine = ContainerEngine {
+            manager: ContainerManager::Podman,
+            executor: get_command_executor_mock(0),
+        };
+
+        let mut missing_command_executor = CommandExecutor::default();
+        missing_command_executor
+            .expect_execute()
+            .returning(|_| Err(std::io::Error::from(std::io::ErrorKind::NotFound)));
+        let missing_container_engine = ContainerEngine {
+            manager: ContainerManager::Podman,
+            executor: missing_command_executor,
+        };
+
+        // Act & Assert
+        assert!(container_engine.is_available());
+        assert!(!missing_container_engine.is_available());
+    }
+
+    #[test]
+    fn test_build_run_container_args_string_command() {
+        // Act
+        let args = ContainerEngine::build_run_container_args(
+            "test".to_string(),
+            &RunContainerOptions {
+                image: "ubuntu:latest".to_string(),
+                command: Some(ContainerCommand::String("echo hello world".to_string())),
+                cpus: 1000,
+                memory: 512,
+                ..Default::default()
+            },
+        );
+
+        // Assert
+        assert_eq!(
+            args[args.len() - 4..],
+            ["ubuntu:latest", "echo", "hello", "world"]
+        );
+    }
+
+    #[test]
+    fn test_build_run_container_args_args_command() {
+        // Act
+        let args = ContainerEngine::build_run_container_args(
+            "test".to_string(),
+            &RunContainerOptions {
+                image: "ubuntu:latest".to_string(),
+                command: Some(ContainerCommand::Args(vec![
+                    "sh".to_string(),
+                    "-c".to_string(),
+                    "echo 'hello world' && sleep 1".to_string(),
+                ])),
+                cpus: 1000,
+                memory: 512,
+                ..Default::default()
+            },
+        );
+
+        // Assert
+        assert_eq!(
+            args[args.len() - 4..],
+            ["ubuntu:latest", "sh", "-c", "echo 'hello world' && sleep 1"]
+        );
+    }
+
+    #[test]
+    fn test_build_run_container_args_entrypoint() {
+        // Act
+        let args = ContainerEngine::build_run_container_args(
+            "test".to_string(),
+            &RunContainerOptions {
+                image: "postgres:16".to_string(),
+                entrypoint: Some("/usr/local/bin/wait-for-it.sh".to_string()),
+                command: Some(ContainerCommand::Args(vec![
+                    "db:5432".to_string(),
+                    "--".to_string(),
+                    "postgres".to_string(),
+                ])),
+                cpus: 1000,
+                memory: 512,
+                ..Default::default()
+            },
+        );
+
+        // Assert
+        assert_eq!(
+            args[args.len() - 6..],
+            [
+                "--entrypoint",
+                "/usr/local/bin/wait-for-it.sh",
+                "postgres:16",
+                "db:5432",
+                "--",
+                "postgres"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_run_container_args_entrypoint_without_command() {
+        // Act
+        let args = ContainerEngine::build_run_container_args(
+            "test".to_string(),
+            &RunContainerOptions {
+                image: "ubuntu:latest".to_string(),
+                entrypoint: Some("/bin/sh".to_string()),
+                cpus: 1000,
+                memory: 512,
+                ..Default::default()
+            },
+        );
+
+        // Assert
+        assert_eq!(
+            args[args.len() - 3..],
+            ["--entrypoint", "/bin/sh", "ubuntu:latest"]
+        );
+    }
+
+    #[test]
+    fn test_build_run_container_args_multiple_ports() {
+        // Act
+        let args = ContainerEngine::build_run_container_args(
+            "test".to_string(),
+            &RunContainerOptions {
+                image: "coredns/coredns:latest".to_string(),
+                external_port: Some(80),
+                internal_port: Some(8080),
+                ports: vec![
+                    PortMapping {
+                        external: 53,
+                        internal: 53,
+                        protocol: Some(PortProtocol::Udp),
+                    },
+                    PortMapping {
+                        external: 9153,
+                        internal: 9153,
+                        protocol: Some(PortProtocol::Tcp),
+                    },
+                ],
+                cpus: 1000,
+                memory: 512,
+                ..Default::default()
+            },
+        );
+
+        // Assert
+        assert_eq!(
+            args[args.len() - 10..],
+            [
+                "oct",
+                "-p",
+                "80:8080/tcp",
+                "-p",
+                "80:8080/udp",
+                "-p",
+                "53:53/udp",
+                "-p",
+                "9153:9153/tcp",
+                "coredns/coredns:latest",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_run_container_args_host_network() {
+        // Act
+        let args = ContainerEngine::build_run_container_args(
+            "test".to_string(),
+            &RunContainerOptions {
+                image: "nginx:latest".to_string(),
+                external_port: Some(80),
+                internal_port: Some(80),
+                ports: vec![PortMapping {
+                    external: 443,
+                    internal: 443,
+                    protocol: None,
+                }],
+                network_mode: Some(NetworkMode::Host),
+                cpus: 1000,
+                memory: 512,
+                ..Default::default()
+            },
+        );
+
+        // Assert
+        assert_eq!(
+            args[args.len() - 3..],
+            ["--network", "host", "nginx:latest"]
+        );
+        assert!(!args.contains(&"-p".to_string()));
+    }
+
+    #[test]
+    fn test_build_run_container_args_pull_policy() {
+        // Arrange
+        let get_args = |pull_policy| {
+            ContainerEngine::build_run_container_args(
+                "test".to_string(),
+                &RunContainerOptions {
+                    image: "nginx:latest".to_string(),
+                    pull_policy,
+                    cpus: 1000,
+                    memory: 512,
+                    ..Default::default()
+                },
+            )
+        };
+        let get_pull_arg = |args: &[String]| {
+            args.iter()
+                .position(|arg| arg == "--pull")
+                .map(|index| args[index + 1].clone())
+        };
+
+        // Act
+        let always_args = get_args(Some(PullPolicy::Always));
+        let if_not_present_args = get_args(Some(PullPolicy::IfNotPresent));
+        let never_args = get_args(Some(PullPolicy::Never));
+        let default_args = get_args(None);
+
+        // Assert
+        assert_eq!(get_pull_arg(&always_args).as_deref(), Some("always"));
+        assert_eq!(
+            get_pull_arg(&if_not_present_args).as_deref(),
+            Some("missing")
+        );
+        assert_eq!(get_pull_arg(&never_args).as_deref(), Some("never"));
+        assert_eq!(get_pull_arg(&default_args), None);
+    }
+
+    #[test]
+    fn test_build_logs_args() {
+        // Act & Assert
+        assert_eq!(
+            ContainerEngine::build_logs_args("app_1", None, None, false),
+            ["logs", "app_1"]
+        );
+        assert_eq!(
+            ContainerEngine::build_logs_args("app_1", Some("600s"), None, true),
+            ["logs", "--since", "600s", "--follow", "app_1"]
+        );
+        assert_eq!(
+            ContainerEngine::build_logs_args("app_1", None, Some(100), false),
+            ["logs", "--tail", "100", "app_1"]
+        );
+    }
+
     #[test]
     fn test_container_manager_as_str() {
         let container_manager = ContainerManager::Podman;
@@ -0,0 +1,289 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::prelude::*;
+
+const DEFAULT_LOG_PATH: &str = "/var/log/oct-ctl.log";
+
+/// 10 MiB
+const DEFAULT_LOG_MAX_SIZE: u64 = 10 * 1024 * 1024;
+
+const DEFAULT_LOG_MAX_FILES: usize = 5;
+
+/// Log file location and rotation limits, read from `OCT_CTL_LOG_PATH`,
+/// `OCT_CTL_LOG_MAX_SIZE` (bytes) and `OCT_CTL_LOG_MAX_FILES`
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct LogConfig {
+    pub(crate) path: PathBuf,
+    /// Size of the log file after which it is rotated
+    pub(crate) max_size: u64,
+    /// Number of rotated files kept next to the log file
+    pub(crate) max_files: usize,
+}
+
+impl LogConfig {
+    pub(crate) fn from_env(
+        get_env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let get_non_empty_env = |name: &str| get_env(name).filter(|value| !value.is_empty());
+
+        let max_size = match get_non_empty_env("OCT_CTL_LOG_MAX_SIZE") {
+            Some(max_size) => max_size
+                .parse()
+                .map_err(|e| format!("Invalid OCT_CTL_LOG_MAX_SIZE '{max_size}': {e}"))?,
+            None => DEFAULT_LOG_MAX_SIZE,
+        };
+        if max_size == 0 {
+            return Err("OCT_CTL_LOG_MAX_SIZE must be greater than 0".into());
+        }
+
+        let max_files = match get_non_empty_env("OCT_CTL_LOG_MAX_FILES") {
+            Some(max_files) => max_files
+                .parse()
+                .map_err(|e| format!("Invalid OCT_CTL_LOG_MAX_FILES '{max_files}': {e}"))?,
+            None => DEFAULT_LOG_MAX_FILES,
+        };
+
+        Ok(Self {
+            path: get_non_empty_env("OCT_CTL_LOG_PATH")
+                .map_or_else(|| PathBuf::from(DEFAULT_LOG_PATH), PathBuf::from),
+            max_size,
+            max_files,
+        })
+    }
+}
+
+/// Writing `write_size` bytes to a file of `current_size` bytes exceeds
+/// `max_size`, an empty file is never rotated so a single oversized line
+/// is still written
+fn should_rotate(current_size: u64, write_size: u64, max_size: u64) -> bool {
+    current_size > 0 && current_size + write_size > max_size
+}
+
+/// Path of the `index`th rotated file, `oct-ctl.log.1` is the newest one
+fn get_rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut rotated_path = path.as_os_str().to_owned();
+    rotated_path.push(format!(".{index}"));
+
+    PathBuf::from(rotated_path)
+}
+
+/// Log file renamed to `<path>.1` once it reaches the max size, the older
+/// files are shifted and the ones beyond `max_files` are removed
+pub(crate) struct RotatingFile {
+    config: LogConfig,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    pub(crate) fn open(config: LogConfig) -> std::io::Result<Self> {
+        let file = Self::open_file(&config.path)?;
+        let size = file.metadata()?.len();
+
+        Ok(Self { config, file, size })
+    }
+
+    fn open_file(path: &Path) -> std::io::Result<File> {
+        OpenOptions::new().append(true).create(true).open(path)
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file.flush()?;
+
+        if self.config.max_files == 0 {
+            std::fs::remove_file(&self.config.path)?;
+        } else {
+            let oldest_path = get_rotated_path(&self.config.path, self.config.max_files);
+            if oldest_path.exists() {
+                std::fs::remove_file(oldest_path)?;
+            }
+
+            for index in (1..self.config.max_files).rev() {
+                let rotated_path = get_rotated_path(&self.config.path, index);
+                if rotated_path.exists() {
+                    std::fs::rename(rotated_path, get_rotated_path(&self.config.path, index + 1))?;
+                }
+            }
+
+            std::fs::rename(&self.config.path, get_rotated_path(&self.config.path, 1))?;
+        }
+
+        self.file = Self::open_file(&self.config.path)?;
+        self.size = 0;
+
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if should_rotate(self.size, buf.len() as u64, self.config.max_size) {
+            self.rotate()?;
+        }
+
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Logs to stdout and as JSON lines to the rotating log file,
+/// the level is set by `RUST_LOG` and defaults to `info`
+///
+/// When the log file can't be opened, e.g. its directory is not writable,
+/// only stdout is used and a warning is logged
+pub(crate) fn init(config: LogConfig) {
+    let log_path = config.path.clone();
+    let (log_file, open_error) = match RotatingFile::open(config) {
+        Ok(log_file) => (Some(log_file), None),
+        Err(e) => (None, Some(e)),
+    };
+
+    // Tracing initialization code was inspired by
+    // https://github.com/tower-rs/tower-http/issues/296#issuecomment-1301108593
+    tracing_subscriber::registry()
+        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .with(tracing_subscriber::fmt::layer())
+        .with(log_file.map(|log_file| {
+            tracing_subscriber::fmt::layer()
+                .json()
+                .with_writer(Mutex::new(log_file))
+        }))
+        .init();
+
+    if let Some(e) = open_error {
+        log::warn!(
+            "Failed to open log file {}, logging to stdout only: {e}",
+            log_path.display()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn get_env(envs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let envs: HashMap<String, String> = envs
+            .iter()
+            .map(|(name, value)| ((*name).to_string(), (*value).to_string()))
+            .collect();
+
+        move |name| envs.get(name).cloned()
+    }
+
+    #[test]
+    fn test_should_rotate() {
+        // Assert
+        assert!(!should_rotate(0, 10, 100));
+        assert!(!should_rotate(90, 10, 100));
+        assert!(should_rotate(91, 10, 100));
+        assert!(!should_rotate(0, 200, 100));
+    }
+
+    #[test]
+    fn test_log_config_from_env() {
+        // Arrange
+        let envs = get_env(&[
+            ("OCT_CTL_LOG_PATH", "/tmp/oct-ctl.log"),
+            ("OCT_CTL_LOG_MAX_SIZE", "1024"),
+            ("OCT_CTL_LOG_MAX_FILES", "2"),
+        ]);
+
+        // Act
+        let log_config = LogConfig::from_env(envs).expect("Failed to read log config");
+
+        // Assert
+        assert_eq!(
+            log_config,
+            LogConfig {
+                path: PathBuf::from("/tmp/oct-ctl.log"),
+                max_size: 1024,
+                max_files: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_log_config_from_env_defaults() {
+        // Act
+        let log_config = LogConfig::from_env(get_env(&[("OCT_CTL_LOG_PATH", "")]))
+            .expect("Failed to read log config");
+
+        // Assert
+        assert_eq!(
+            log_config,
+            LogConfig {
+                path: PathBuf::from(DEFAULT_LOG_PATH),
+                max_size: DEFAULT_LOG_MAX_SIZE,
+                max_files: DEFAULT_LOG_MAX_FILES,
+            }
+        );
+    }
+
+    #[test]
+    fn test_log_config_from_env_invalid_max_size() {
+        // Act
+        let result = LogConfig::from_env(get_env(&[("OCT_CTL_LOG_MAX_SIZE", "10MB")]));
+
+        // Assert
+        assert_eq!(
+            result.expect_err("Expected invalid max size").to_string(),
+            "Invalid OCT_CTL_LOG_MAX_SIZE '10MB': invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn test_rotating_file_open_missing_dir() {
+        // Arrange
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+
+        // Act
+        let result = RotatingFile::open(LogConfig {
+            path: dir.path().join("missing").join("oct-ctl.log"),
+            max_size: DEFAULT_LOG_MAX_SIZE,
+            max_files: DEFAULT_LOG_MAX_FILES,
+        });
+
+        // Assert
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_rotating_file_rotates_at_max_size() {
+        // Arrange
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = dir.path().join("oct-ctl.log");
+        let mut rotating_file = RotatingFile::open(LogConfig {
+            path: path.clone(),
+            max_size: 10,
+            max_files: 2,
+        })
+        .expect("Failed to open log file");
+
+        // Act
+        for line in ["line 1\n", "line 2\n", "line 3\n", "line 4\n"] {
+            rotating_file
+                .write_all(line.as_bytes())
+                .expect("Failed to write log line");
+        }
+
+        // Assert
+        let read = |path: PathBuf| std::fs::read_to_string(path).expect("Failed to read log file");
+        assert_eq!(read(path.clone()), "line 4\n");
+        assert_eq!(read(get_rotated_path(&path, 1)), "line 3\n");
+        assert_eq!(read(get_rotated_path(&path, 2)), "line 2\n");
+        assert!(!get_rotated_path(&path, 3).exists());
+    }
+}
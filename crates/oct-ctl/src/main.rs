@@ -1,8 +1,13 @@
 mod container;
 mod executor;
+mod logging;
 mod service;
 
 #[tokio::main]
 async fn main() {
+    let log_config =
+        logging::LogConfig::from_env(|name| std::env::var(name).ok()).expect("Invalid log config");
+    logging::init(log_config);
+
     service::run().await;
 }
@@ -1,10 +1,11 @@
-use std::collections::HashMap;
-use std::fs::OpenOptions;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
 
-use axum::http::StatusCode;
+use axum::http::{StatusCode, header};
 use axum::response::IntoResponse;
 use axum::routing::{get, post};
-use axum::{Json, Router, extract};
+use axum::{Json, Router, extract, middleware};
 use oct_cloud::infra::graph::kahn_traverse;
 use oct_config::{Config, Node, StateBackend};
 use oct_orchestrator::{backend, user_state};
@@ -14,16 +15,36 @@ use tower_http::trace::{self, TraceLayer};
 
 #[cfg(not(test))]
 use crate::container::ContainerEngine;
+use crate::container::RunContainerOptions;
 #[cfg(test)]
 use crate::container::mocks::MockContainerEngine as ContainerEngine;
 
 pub(crate) async fn run() {
+    let token = std::env::var("OCT_CTL_TOKEN")
+        .ok()
+        .filter(|token| !token.is_empty());
+    if token.is_none() {
+        log::warn!("OCT_CTL_TOKEN is not set, the API accepts unauthenticated requests");
+    }
+
     let server_config = ServerConfig {
-        container_engine: ContainerEngine::default(),
+        container_engine: Arc::new(ContainerEngine::default()),
+        host_capacity: HostCapacity::detect(),
+        token,
+        user_state_lock: Arc::default(),
     };
 
     let app = prepare_router(server_config);
 
+    let tls_paths =
+        get_tls_paths(|name| std::env::var(name).ok()).expect("Invalid TLS configuration");
+
+    if let Some(tls_paths) = tls_paths {
+        serve_https(app, &tls_paths).await;
+
+        return;
+    }
+
     let listener = tokio::net::TcpListener::bind("0.0.0.0:31888")
         .await
         .expect("Failed to bind listener to 0.0.0.0:31888");
@@ -40,20 +61,67 @@ pub(crate) async fn run() {
         .expect("Failed to start server");
 }
 
-fn prepare_router(server_config: ServerConfig) -> Router {
-    let log_file = OpenOptions::new()
-        .append(true)
-        .create(true)
-        .open("/var/log/oct-ctl.log")
-        .expect("Failed to open log file");
+/// PEM certificate and private key paths of the HTTPS server
+#[derive(Debug, PartialEq, Eq)]
+struct TlsPaths {
+    cert_path: String,
+    key_path: String,
+}
+
+/// HTTPS is served when both `OCT_CTL_TLS_CERT_PATH` and
+/// `OCT_CTL_TLS_KEY_PATH` are set, plain HTTP when neither is set
+///
+/// Only one of them set is an error, so a typo doesn't silently serve the
+/// API over plain HTTP
+fn get_tls_paths(get_env: impl Fn(&str) -> Option<String>) -> Result<Option<TlsPaths>, String> {
+    let cert_path = get_env("OCT_CTL_TLS_CERT_PATH").filter(|path| !path.is_empty());
+    let key_path = get_env("OCT_CTL_TLS_KEY_PATH").filter(|path| !path.is_empty());
+
+    match (cert_path, key_path) {
+        (Some(cert_path), Some(key_path)) => Ok(Some(TlsPaths {
+            cert_path,
+            key_path,
+        })),
+        (None, None) => Ok(None),
+        (Some(_), None) => {
+            Err("OCT_CTL_TLS_CERT_PATH is set without OCT_CTL_TLS_KEY_PATH".to_string())
+        }
+        (None, Some(_)) => {
+            Err("OCT_CTL_TLS_KEY_PATH is set without OCT_CTL_TLS_CERT_PATH".to_string())
+        }
+    }
+}
+
+async fn serve_https(app: Router, tls_paths: &TlsPaths) {
+    let tls_config = axum_server::tls_rustls::RustlsConfig::from_pem_file(
+        &tls_paths.cert_path,
+        &tls_paths.key_path,
+    )
+    .await
+    .expect("Failed to load TLS certificate and key");
 
-    // Tracing initialization code was inspired by
-    // https://github.com/tower-rs/tower-http/issues/296#issuecomment-1301108593
-    tracing_subscriber::fmt().with_writer(log_file).init();
+    tracing::info!("Listening on https://0.0.0.0:31888");
 
+    axum_server::bind_rustls(
+        std::net::SocketAddr::from(([0, 0, 0, 0], 31888)),
+        tls_config,
+    )
+    .serve(app.into_make_service())
+    .await
+    .expect("Failed to start server");
+}
+
+fn prepare_router(server_config: ServerConfig) -> Router {
     Router::new()
         .route("/apply", post(apply))
         .route("/destroy", post(destroy))
+        .route("/remove-service", post(remove_service))
+        .route("/exec", post(exec))
+        .route("/logs", get(logs))
+        .route_layer(middleware::from_fn_with_state(
+            server_config.clone(),
+            check_token,
+        ))
         .route("/health-check", get(health_check))
         .layer(
             TraceLayer::new_for_http()
@@ -67,12 +135,151 @@ fn prepare_router(server_config: ServerConfig) -> Router {
 /// It is used as a Dependency Injection container.
 #[derive(Clone)]
 struct ServerConfig {
-    container_engine: ContainerEngine,
+    /// Shared with the blocking tasks of [`ServerConfig::run_blocking`]
+    container_engine: Arc<ContainerEngine>,
+    /// Resources of the host available for user services
+    host_capacity: HostCapacity,
+    /// Bearer token required by all the endpoints except `/health-check`,
+    /// `None` disables authentication
+    token: Option<String>,
+    /// Held by the endpoints during the user state load, change and save, so
+    /// concurrent requests do not overwrite each other's changes
+    ///
+    /// An apply holds it while it waits for the dependency health checks, up
+    /// to `interval * retries` per dependency, so a concurrent apply or
+    /// removal waits for it and may run out of the caller's `--timeout`
+    user_state_lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl ServerConfig {
+    /// Runs a `container_engine` call off the async workers, the `podman`
+    /// commands block until they exit
+    async fn run_blocking<T, F>(&self, call: F) -> Result<T, String>
+    where
+        T: Send + 'static,
+        F: FnOnce(&ContainerEngine) -> Result<T, Box<dyn std::error::Error>> + Send + 'static,
+    {
+        let container_engine = Arc::clone(&self.container_engine);
+
+        tokio::task::spawn_blocking(move || call(&container_engine).map_err(|err| err.to_string()))
+            .await
+            .unwrap_or_else(|err| Err(err.to_string()))
+    }
+}
+
+/// Rejects requests without the `Authorization: Bearer <token>` header
+/// matching the server token
+async fn check_token(
+    extract::State(server_config): extract::State<ServerConfig>,
+    request: extract::Request,
+    next: middleware::Next,
+) -> axum::response::Response {
+    let Some(token) = &server_config.token else {
+        return next.run(request).await;
+    };
+
+    let request_token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    if !request_token.is_some_and(|request_token| tokens_eq(request_token, token)) {
+        return (
+            StatusCode::UNAUTHORIZED,
+            String::from("Missing or invalid bearer token"),
+        )
+            .into_response();
+    }
+
+    next.run(request).await
+}
+
+/// Compares the tokens in time independent of where they differ,
+/// so the server token can't be guessed byte by byte from response times
+fn tokens_eq(left: &str, right: &str) -> bool {
+    left.len() == right.len()
+        && left
+            .bytes()
+            .zip(right.bytes())
+            .fold(0, |diff, (left_byte, right_byte)| {
+                diff | (left_byte ^ right_byte)
+            })
+            == 0
+}
+
+/// CPU millicores and memory in MB of the host
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct HostCapacity {
+    cpus: u32,
+    memory: u64,
+}
+
+impl HostCapacity {
+    /// Detects the number of CPUs and the total memory of the host
+    fn detect() -> Self {
+        let cpus = std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get);
+
+        let memory = std::fs::read_to_string("/proc/meminfo")
+            .ok()
+            .and_then(|meminfo| parse_mem_total(&meminfo))
+            .unwrap_or_else(|| {
+                log::warn!("Failed to detect host memory, the memory capacity is not limited");
+
+                u64::MAX
+            });
+
+        Self {
+            cpus: u32::try_from(cpus * 1000).unwrap_or(u32::MAX),
+            memory,
+        }
+    }
+}
+
+/// Parses `MemTotal` from `/proc/meminfo` content into MB
+fn parse_mem_total(meminfo: &str) -> Option<u64> {
+    let mem_total_kb = meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix("MemTotal:"))?
+        .trim()
+        .strip_suffix("kB")?
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+
+    Some(mem_total_kb / 1024)
 }
 
 #[derive(Serialize, Deserialize)]
 struct ApplyPayload {
     config: Config,
+
+    /// Recreate all services even if their configuration is unchanged
+    #[serde(default)]
+    force_recreate: bool,
+
+    /// Keep the running services missing in the config instead of removing
+    /// them, used to apply a subset of the services
+    #[serde(default)]
+    partial: bool,
+
+    /// Resolved values of the secret references in service envs.
+    /// Key - service name, Value - env name to secret value
+    #[serde(default)]
+    secret_envs: HashMap<String, HashMap<String, String>>,
+}
+
+/// Container of a running service
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+struct RunContainerResponse {
+    /// Container id assigned by the container manager
+    id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+struct ApplyResponse {
+    /// Key - service name, Value - service container
+    containers: HashMap<String, RunContainerResponse>,
 }
 
 /// Apply endpoint definition for Axum
@@ -87,152 +294,1939 @@ async fn apply(
         return (
             StatusCode::INTERNAL_SERVER_ERROR,
             String::from("Failed to get graph from request body"),
-        );
+        )
+            .into_response();
+    };
+
+    let state_backend = StateBackend::Local {
+        path: String::from(USER_STATE_FILE_PATH),
     };
 
-    let apply_result = apply_user_services_graph(&server_config, &services_graph);
+    let apply_result = apply_user_services_graph(
+        &server_config,
+        &state_backend,
+        &services_graph,
+        &payload.secret_envs,
+        payload.force_recreate,
+        payload.partial,
+    );
 
     match apply_result.await {
-        Ok(()) => (StatusCode::CREATED, "Success".to_string()),
+        Ok(container_ids) => {
+            let containers = container_ids
+                .into_iter()
+                .map(|(name, id)| (name, RunContainerResponse { id }))
+                .collect();
+
+            (StatusCode::CREATED, Json(ApplyResponse { containers })).into_response()
+        }
         Err(err) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Failed to apply services: {err}"),
-        ),
+        )
+            .into_response(),
     }
 }
 
 const USER_STATE_FILE_PATH: &str = "/var/log/oct-state.json";
 
+/// Names of the user services to change to get from the current user state
+/// to the requested services graph
+#[derive(Debug, Default, PartialEq, Eq)]
+struct UserServicesDiff {
+    to_create: Vec<String>,
+    /// Services to stop and run again
+    to_update: Vec<String>,
+    to_delete: Vec<String>,
+}
+
+/// Compares running services with the requested ones
+///
+/// A service is updated when its configuration differs from the running one,
+/// with `force_recreate` all the running services are updated. With `partial`
+/// no service is deleted
+fn get_user_services_to_create_and_delete(
+    running_services: &HashMap<String, oct_config::Service>,
+    services_graph: &Graph<Node, String>,
+    force_recreate: bool,
+    partial: bool,
+) -> UserServicesDiff {
+    let mut diff = UserServicesDiff::default();
+
+    for service in services_graph.node_weights() {
+        let Node::Resource(service) = service else {
+            continue;
+        };
+
+        match running_services.get(&service.name) {
+            None => diff.to_create.push(service.name.clone()),
+            Some(running_service) if force_recreate || running_service != service => {
+                diff.to_update.push(service.name.clone());
+            }
+            Some(_) => {}
+        }
+    }
+
+    for name in running_services.keys() {
+        let is_requested = services_graph
+            .node_weights()
+            .any(|node| matches!(node, Node::Resource(service) if &service.name == name));
+
+        if !is_requested && !partial {
+            diff.to_delete.push(name.clone());
+        }
+    }
+
+    diff.to_create.sort();
+    diff.to_update.sort();
+    diff.to_delete.sort();
+
+    diff
+}
+
 /// Applies user services graph
+///
+/// Only new and changed services are (re)started, services missing in the
+/// graph are removed unless `partial` is set. Returns container ids of the
+/// running services, or an error naming the services that failed to run once
+/// the state of the others is saved
+///
+/// `secret_envs` are passed to the containers only, the user state keeps the
+/// secret references of the graph services
 async fn apply_user_services_graph(
     server_config: &ServerConfig,
+    state_backend: &StateBackend,
     services_graph: &Graph<Node, String>,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let state_backend = StateBackend::Local {
-        path: String::from(USER_STATE_FILE_PATH),
+    secret_envs: &HashMap<String, HashMap<String, String>>,
+    force_recreate: bool,
+    partial: bool,
+) -> Result<HashMap<String, String>, Box<dyn std::error::Error + Send + Sync>> {
+    let _user_state_guard = server_config.user_state_lock.lock().await;
+
+    let user_state_backend = backend::get_state_backend::<user_state::UserState>(state_backend);
+
+    let (user_state, _loaded) = user_state_backend.load().await?;
+
+    let mut instance = user_state::Instance {
+        cpus: server_config.host_capacity.cpus,
+        memory: server_config.host_capacity.memory,
+        ..user_state::Instance::default()
     };
-    let user_state_backend = backend::get_state_backend::<user_state::UserState>(&state_backend);
+    for running_instance in user_state.instances.into_values() {
+        instance.services.extend(running_instance.services);
+        instance
+            .container_ids
+            .extend(running_instance.container_ids);
+    }
+
+    let diff = get_user_services_to_create_and_delete(
+        &instance.services,
+        services_graph,
+        force_recreate,
+        partial,
+    );
+
+    for name in &diff.to_delete {
+        log::info!("Removing service: {name}");
+
+        match remove_service_containers(server_config, instance.services.get(name), name).await {
+            Ok(()) => {
+                instance.remove_service(name);
+            }
+            Err(err) => log::error!("Failed to remove service {name}: {err}"),
+        }
+    }
 
     let sorted_graph = kahn_traverse(services_graph)?;
 
-    let mut services = HashMap::new();
+    let mut failed_services = HashSet::new();
+
     for node_index in &sorted_graph {
         if let Node::Resource(service) = &services_graph[*node_index] {
-            log::info!("Running service: {}", service.name);
-
-            let run_result = server_config.container_engine.run(
-                service.name.clone(),
-                service.image.clone(),
-                service.command.clone(),
-                service.external_port,
-                service.internal_port,
-                service.cpus,
-                service.memory,
-                &service.envs,
-            );
-
-            let Ok(()) = run_result else {
-                log::error!("Failed to run service: {}", service.name);
+            let is_update = diff.to_update.contains(&service.name);
 
+            if !is_update && !diff.to_create.contains(&service.name) {
                 continue;
-            };
+            }
+
+            if let Some(failed_dependency) = service
+                .depends_on
+                .iter()
+                .find(|dependency_name| failed_services.contains(*dependency_name))
+            {
+                log::error!(
+                    "Skipping service {}: dependency {failed_dependency} failed",
+                    service.name
+                );
+
+                failed_services.insert(service.name.clone());
+
+                continue;
+            }
 
-            services.insert(service.name.clone(), service.clone());
+            if let Err(err) = run_user_service(
+                server_config,
+                &mut instance,
+                service,
+                secret_envs.get(&service.name),
+                services_graph,
+                is_update,
+            )
+            .await
+            {
+                log::error!("Failed to run service {}: {err}", service.name);
+
+                failed_services.insert(service.name.clone());
+            }
         }
     }
 
-    let instance_state = user_state::Instance {
-        cpus: 0,
-        memory: 0,
-        services,
-    };
+    let container_ids = instance.container_ids.clone();
 
     let user_state = user_state::UserState {
-        instances: HashMap::from([(String::from("localhost"), instance_state)]),
+        instances: HashMap::from([(String::from("localhost"), instance)]),
+        oct_ctl_token: None,
+    };
+
+    user_state_backend.save(&user_state).await?;
+
+    if !failed_services.is_empty() {
+        let mut failed_services: Vec<String> = failed_services.into_iter().collect();
+        failed_services.sort();
+
+        return Err(format!("Failed to run services: {}", failed_services.join(", ")).into());
+    }
+
+    Ok(container_ids)
+}
+
+/// Removes the containers of all the replicas of the running service,
+/// a service missing in the state has a single container named after it
+async fn remove_service_containers(
+    server_config: &ServerConfig,
+    running_service: Option<&oct_config::Service>,
+    name: &str,
+) -> Result<(), String> {
+    let container_names = running_service.map_or_else(
+        || vec![name.to_string()],
+        oct_config::Service::container_names,
+    );
+
+    for container_name in container_names {
+        server_config
+            .run_blocking(move |container_engine| container_engine.remove(&container_name))
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Waits for the service dependencies and runs a container per service
+/// replica, an updated service is stopped first
+///
+/// When a replica fails to start the already started ones are removed
+async fn run_user_service(
+    server_config: &ServerConfig,
+    instance: &mut user_state::Instance,
+    service: &oct_config::Service,
+    secret_envs: Option<&HashMap<String, String>>,
+    services_graph: &Graph<Node, String>,
+    is_update: bool,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    wait_for_dependencies(server_config, service, services_graph).await?;
+
+    if is_update {
+        log::info!("Stopping service: {}", service.name);
+
+        remove_service_containers(
+            server_config,
+            instance.services.get(&service.name),
+            &service.name,
+        )
+        .await
+        .map_err(|err| format!("Failed to stop the running container: {err}"))?;
+
+        instance.remove_service(&service.name);
+    }
+
+    if !instance.can_fit(service) {
+        let (available_cpus, available_memory) = instance.available();
+
+        let (required_cpus, required_memory) = service
+            .total_resources()
+            .ok_or_else(|| format!("Resources of service '{}' overflow", service.name))?;
+
+        return Err(format!(
+            "Requested {required_cpus} millicores and {required_memory} MB, available \
+             {available_cpus} millicores and {available_memory} MB"
+        )
+        .into());
+    }
+
+    log::info!("Running service: {}", service.name);
+
+    let mut envs = service.envs.clone();
+    if let Some(secret_envs) = secret_envs {
+        envs.extend(secret_envs.clone());
+    }
+
+    let run_options = RunContainerOptions {
+        image: service.image.clone(),
+        entrypoint: service.entrypoint.clone(),
+        command: service.command.clone(),
+        external_port: service.external_port,
+        internal_port: service.internal_port,
+        ports: service.ports.clone(),
+        network_mode: service.network_mode,
+        pull_policy: service.pull_policy,
+        cpus: service.cpus,
+        memory: service.memory,
+        envs,
     };
 
-    user_state_backend.save(&user_state).await
+    let mut container_ids = HashMap::new();
+
+    for container_name in service.container_names() {
+        let run_name = container_name.clone();
+        let run_options = run_options.clone();
+        let run_result = server_config
+            .run_blocking(move |container_engine| container_engine.run(run_name, &run_options))
+            .await;
+
+        match run_result {
+            Ok(container_id) => {
+                container_ids.insert(container_name, container_id);
+            }
+            Err(err) => {
+                for started_container_name in container_ids.into_keys() {
+                    let remove_name = started_container_name.clone();
+                    let remove_result = server_config
+                        .run_blocking(move |container_engine| container_engine.remove(&remove_name))
+                        .await;
+
+                    if let Err(remove_err) = remove_result {
+                        log::error!(
+                            "Failed to remove container {started_container_name}: {remove_err}"
+                        );
+                    }
+                }
+
+                return Err(err.into());
+            }
+        }
+    }
+
+    instance
+        .services
+        .insert(service.name.clone(), service.clone());
+    instance.container_ids.extend(container_ids);
+
+    Ok(())
+}
+
+/// Waits until health checks of all the service dependencies pass,
+/// dependencies without health check are considered ready
+async fn wait_for_dependencies(
+    server_config: &ServerConfig,
+    service: &oct_config::Service,
+    services_graph: &Graph<Node, String>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    for dependency_name in &service.depends_on {
+        let dependency = services_graph.node_weights().find_map(|node| match node {
+            Node::Resource(dependency) if &dependency.name == dependency_name => Some(dependency),
+            _ => None,
+        });
+
+        let Some(dependency) = dependency else {
+            continue;
+        };
+        let Some(health_check) = &dependency.health_check else {
+            continue;
+        };
+
+        for container_name in dependency.container_names() {
+            wait_until_healthy(server_config, &container_name, health_check).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs service health check until it passes or retries are exhausted
+async fn wait_until_healthy(
+    server_config: &ServerConfig,
+    name: &str,
+    health_check: &oct_config::HealthCheck,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    for attempt in 1..=health_check.retries {
+        let health_check_name = name.to_string();
+        let health_check_command = health_check.command.clone();
+        let is_healthy = matches!(
+            server_config
+                .run_blocking(move |container_engine| {
+                    container_engine.is_healthy(&health_check_name, &health_check_command)
+                })
+                .await,
+            Ok(true)
+        );
+
+        if is_healthy {
+            log::info!("Service {name} is healthy");
+
+            return Ok(());
+        }
+
+        log::info!(
+            "Service {name} is not healthy yet, attempt {attempt}/{}",
+            health_check.retries
+        );
+
+        tokio::time::sleep(Duration::from_secs(health_check.interval)).await;
+    }
+
+    Err(format!(
+        "Dependency '{name}' is not healthy after {} checks",
+        health_check.retries
+    )
+    .into())
 }
 
 /// Destroy endpoint definition for Axum
 ///
-/// Temporary endpoint implementation to show the ability of `oct-ctl`
-/// to destroy cloud infra resources from the Leader node deployed via `apply`
-/// endpoint
-async fn destroy() -> impl IntoResponse {
+/// Stops the containers of all the user services on the host before its
+/// infrastructure is destroyed
+async fn destroy(extract::State(server_config): extract::State<ServerConfig>) -> impl IntoResponse {
     let state_backend = StateBackend::Local {
         path: String::from(USER_STATE_FILE_PATH),
     };
-    let user_state_backend = backend::get_state_backend::<user_state::UserState>(&state_backend);
 
-    let Ok((_state, _loaded)) = user_state_backend.load().await else {
-        return (
+    match remove_all_user_services(&server_config, &state_backend).await {
+        Ok(()) => (StatusCode::OK, String::from("Success")),
+        Err(err) => (
             StatusCode::INTERNAL_SERVER_ERROR,
-            String::from("Failed to load state"),
-        );
-    };
+            format!("Failed to destroy services: {err}"),
+        ),
+    }
+}
+
+/// Stops the containers of all the user services and removes the services
+/// from the user state, services failing to stop are kept in the state
+async fn remove_all_user_services(
+    server_config: &ServerConfig,
+    state_backend: &StateBackend,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let _user_state_guard = server_config.user_state_lock.lock().await;
+
+    let user_state_backend = backend::get_state_backend::<user_state::UserState>(state_backend);
+
+    let (mut user_state, _loaded) = user_state_backend.load().await?;
+
+    let mut failed_services = Vec::new();
+
+    for instance in user_state.instances.values_mut() {
+        let mut names: Vec<String> = instance.services.keys().cloned().collect();
+        names.sort();
+
+        for name in names {
+            log::info!("Removing service: {name}");
+
+            match remove_service_containers(server_config, instance.services.get(&name), &name)
+                .await
+            {
+                Ok(()) => {
+                    instance.remove_service(&name);
+                }
+                Err(err) => {
+                    log::error!("Failed to remove service {name}: {err}");
 
-    log::info!("Skipping containers removal in this version");
+                    failed_services.push(name);
+                }
+            }
+        }
+    }
+
+    user_state_backend.save(&user_state).await?;
+
+    if !failed_services.is_empty() {
+        return Err(format!("Failed to remove services: {}", failed_services.join(", ")).into());
+    }
 
-    (StatusCode::OK, String::from("Success"))
+    Ok(())
 }
 
-/// Health endpoint definition for Axum
-async fn health_check() -> impl IntoResponse {
-    (StatusCode::OK, "Success")
+#[derive(Serialize, Deserialize)]
+struct RemoveServicePayload {
+    name: String,
 }
 
-// TODO: Use parametrization and fixtures from
-//     https://github.com/la10736/rstest
-// TODO: Add integration tests
-#[cfg(test)]
-mod tests {
-    use axum::body::Body;
-    use axum::http::{Request, StatusCode};
-    use axum::routing::Router;
-    use tower::ServiceExt;
+/// Remove service endpoint definition for Axum
+///
+/// Stops a single service container and removes the service from the user
+/// state, the infrastructure is kept as is
+async fn remove_service(
+    extract::State(server_config): extract::State<ServerConfig>,
+    Json(payload): Json<RemoveServicePayload>,
+) -> impl IntoResponse {
+    let state_backend = StateBackend::Local {
+        path: String::from(USER_STATE_FILE_PATH),
+    };
 
-    use super::*;
+    match remove_user_service(&server_config, &state_backend, &payload.name).await {
+        Ok(()) => (StatusCode::OK, String::from("Success")),
+        Err(err) => err,
+    }
+}
 
-    fn get_container_engine_mock(is_ok: bool) -> ContainerEngine {
-        let mut container_engine_mock = ContainerEngine::default();
-        container_engine_mock
-            .expect_run()
-            .returning(
-                move |_, _, _, _, _, _, _, _| {
-                    if is_ok { Ok(()) } else { Err("error".into()) }
-                },
-            );
+/// Removes user service container and the service entry from the user state
+async fn remove_user_service(
+    server_config: &ServerConfig,
+    state_backend: &StateBackend,
+    name: &str,
+) -> Result<(), (StatusCode, String)> {
+    let _user_state_guard = server_config.user_state_lock.lock().await;
 
-        container_engine_mock
-            .expect_remove()
-            .returning(move |_| if is_ok { Ok(()) } else { Err("error".into()) });
+    let user_state_backend = backend::get_state_backend::<user_state::UserState>(state_backend);
 
-        container_engine_mock
-            .expect_clone()
-            .returning(move || get_container_engine_mock(is_ok));
+    let (mut user_state, _loaded) = user_state_backend.load().await.map_err(|err| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to load state: {err}"),
+        )
+    })?;
 
-        container_engine_mock
+    if !user_state.contains_service(name) {
+        return Err((StatusCode::NOT_FOUND, format!("Service '{name}' not found")));
     }
 
-    #[tokio::test]
-    async fn test_health_check() {
-        let server_config = ServerConfig {
-            container_engine: get_container_engine_mock(true),
-        };
-        let app = Router::new()
-            .route("/health-check", get(health_check))
-            .with_state(server_config);
+    log::info!("Removing service: {name}");
 
-        let response = app
-            .oneshot(
-                Request::get("/health-check")
-                    .body(Body::empty())
-                    .expect("Failed to prepare body"),
+    let running_service = user_state
+        .instances
+        .values()
+        .find_map(|instance| instance.services.get(name));
+
+    remove_service_containers(server_config, running_service, name)
+        .await
+        .map_err(|err| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to remove service '{name}': {err}"),
             )
-            .await
-            .expect("Failed to get response");
+        })?;
 
-        assert_eq!(response.status(), StatusCode::OK);
+    user_state.remove_service(name);
+
+    user_state_backend.save(&user_state).await.map_err(|err| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to save state: {err}"),
+        )
+    })
+}
+
+#[derive(Serialize, Deserialize)]
+struct ExecPayload {
+    /// Container name, `<service>-<index>` for a service with multiple
+    /// replicas
+    name: String,
+    args: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+struct ExecResponse {
+    stdout: String,
+    stderr: String,
+    /// `-1` when the command was terminated by a signal
+    exit_code: i32,
+}
+
+/// Exec endpoint definition for Axum
+///
+/// Runs a one-off command in the service container, a non-zero exit code
+/// is reported in the response body with 200 status
+async fn exec(
+    extract::State(server_config): extract::State<ServerConfig>,
+    Json(payload): Json<ExecPayload>,
+) -> impl IntoResponse {
+    let ExecPayload { name, args } = payload;
+
+    log::info!("Running {args:?} in container: {name}");
+
+    let exec_name = name.clone();
+    let output = server_config
+        .run_blocking(move |container_engine| container_engine.exec(&exec_name, &args))
+        .await;
+
+    match output {
+        Ok(output) => {
+            let exec_response = ExecResponse {
+                stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+                exit_code: output.status.code().unwrap_or(-1),
+            };
+
+            (StatusCode::OK, Json(exec_response)).into_response()
+        }
+        Err(err) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to exec in container '{name}': {err}"),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct LogsQuery {
+    /// Container name, `<service>-<index>` for a service with multiple
+    /// replicas
+    name: String,
+    /// Passed to `podman logs --since`, e.g. `600s`
+    since: Option<String>,
+    /// Passed to `podman logs --tail`, number of the last lines
+    tail: Option<u32>,
+    #[serde(default)]
+    follow: bool,
+}
+
+/// Logs endpoint definition for Axum
+///
+/// Streams the service container output as plain text, with `follow` the
+/// response stays open until the client disconnects
+async fn logs(
+    extract::State(server_config): extract::State<ServerConfig>,
+    extract::Query(query): extract::Query<LogsQuery>,
+) -> impl IntoResponse {
+    log::info!("Streaming logs of container: {}", query.name);
+
+    match server_config
+        .container_engine
+        .logs(&query.name, query.since, query.tail, query.follow)
+    {
+        Ok(stream) => (
+            [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+            axum::body::Body::from_stream(stream),
+        )
+            .into_response(),
+        Err(err) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to get logs of container '{}': {err}", query.name),
+        )
+            .into_response(),
+    }
+}
+
+/// Readiness of the host dependencies
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+struct HealthStatus {
+    /// Container manager is installed and responds
+    podman: bool,
+}
+
+/// Health endpoint definition for Axum
+///
+/// Responds with 503 until the host dependencies are installed, the VM boot
+/// script starts `oct-ctl` in the background while `podman` is still installing
+async fn health_check(
+    extract::State(server_config): extract::State<ServerConfig>,
+) -> impl IntoResponse {
+    let health_status = HealthStatus {
+        podman: server_config
+            .run_blocking(|container_engine| Ok(container_engine.is_available()))
+            .await
+            .unwrap_or(false),
+    };
+
+    let status_code = if health_status.podman {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (status_code, Json(health_status))
+}
+
+// TODO: Use parametrization and fixtures from
+//     https://github.com/la10736/rstest
+// TODO: Add integration tests
+#[cfg(test)]
+mod tests {
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::{ExitStatus, Output};
+
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use axum::routing::Router;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    const TEST_HOST_CAPACITY: HostCapacity = HostCapacity {
+        cpus: 2000,
+        memory: 1024,
+    };
+
+    fn get_container_engine_mock(is_ok: bool) -> ContainerEngine {
+        let mut container_engine_mock = ContainerEngine::default();
+        container_engine_mock.expect_run().returning(move |_, _| {
+            if is_ok {
+                Ok(String::from("container-id"))
+            } else {
+                Err("error".into())
+            }
+        });
+
+        container_engine_mock
+            .expect_remove()
+            .returning(move |_| if is_ok { Ok(()) } else { Err("error".into()) });
+
+        container_engine_mock
+            .expect_is_available()
+            .returning(move || is_ok);
+
+        container_engine_mock
+    }
+
+    fn get_service(name: &str) -> oct_config::Service {
+        oct_config::Service {
+            name: name.to_string(),
+            image: String::from("nginx:latest"),
+            cpus: 250,
+            memory: 64,
+            ..Default::default()
+        }
+    }
+
+    fn get_services_graph(services: Vec<oct_config::Service>) -> Graph<Node, String> {
+        let mut services_graph = Graph::new();
+        let root = services_graph.add_node(Node::Root);
+
+        for service in services {
+            let service_node = services_graph.add_node(Node::Resource(service));
+            services_graph.add_edge(root, service_node, String::new());
+        }
+
+        services_graph
+    }
+
+    async fn save_user_state_with_service(state_backend: &StateBackend, name: &str) {
+        let service = get_service(name);
+
+        let user_state = user_state::UserState {
+            instances: HashMap::from([(
+                String::from("localhost"),
+                user_state::Instance {
+                    cpus: 0,
+                    memory: 0,
+                    services: HashMap::from([(name.to_string(), service)]),
+                    container_ids: HashMap::from([(name.to_string(), format!("{name}-container"))]),
+                },
+            )]),
+            oct_ctl_token: None,
+        };
+
+        backend::get_state_backend::<user_state::UserState>(state_backend)
+            .save(&user_state)
+            .await
+            .expect("Failed to save user state");
+    }
+
+    async fn load_user_state(state_backend: &StateBackend) -> user_state::UserState {
+        let (user_state, _loaded) =
+            backend::get_state_backend::<user_state::UserState>(state_backend)
+                .load()
+                .await
+                .expect("Failed to load user state");
+
+        user_state
+    }
+
+    #[test]
+    fn test_get_user_services_to_create_and_delete() {
+        // Arrange
+        let mut changed_service = get_service("app_2");
+        changed_service.image = String::from("nginx:1.27");
+
+        let running_services = HashMap::from([
+            (String::from("app_1"), get_service("app_1")),
+            (String::from("app_2"), get_service("app_2")),
+            (String::from("app_3"), get_service("app_3")),
+        ]);
+        let services_graph = get_services_graph(vec![
+            get_service("app_1"),
+            changed_service,
+            get_service("app_4"),
+        ]);
+
+        // Act
+        let diff = get_user_services_to_create_and_delete(
+            &running_services,
+            &services_graph,
+            false,
+            false,
+        );
+
+        // Assert
+        assert_eq!(
+            diff,
+            UserServicesDiff {
+                to_create: vec![String::from("app_4")],
+                to_update: vec![String::from("app_2")],
+                to_delete: vec![String::from("app_3")],
+            }
+        );
+    }
+
+    #[test]
+    fn test_get_user_services_to_create_and_delete_force_recreate() {
+        // Arrange
+        let running_services = HashMap::from([
+            (String::from("app_1"), get_service("app_1")),
+            (String::from("app_2"), get_service("app_2")),
+        ]);
+        let services_graph = get_services_graph(vec![get_service("app_1"), get_service("app_2")]);
+
+        // Act
+        let diff =
+            get_user_services_to_create_and_delete(&running_services, &services_graph, true, false);
+
+        // Assert
+        assert_eq!(
+            diff,
+            UserServicesDiff {
+                to_create: vec![],
+                to_update: vec![String::from("app_1"), String::from("app_2")],
+                to_delete: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn test_get_user_services_to_create_and_delete_partial() {
+        // Arrange
+        let running_services = HashMap::from([
+            (String::from("app_1"), get_service("app_1")),
+            (String::from("app_2"), get_service("app_2")),
+        ]);
+        let services_graph = get_services_graph(vec![get_service("app_1"), get_service("app_3")]);
+
+        // Act
+        let diff =
+            get_user_services_to_create_and_delete(&running_services, &services_graph, false, true);
+
+        // Assert
+        assert_eq!(
+            diff,
+            UserServicesDiff {
+                to_create: vec![String::from("app_3")],
+                to_update: vec![],
+                to_delete: vec![],
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_apply_user_services_graph_skips_unchanged_services() {
+        // Arrange
+        let state_file = tempfile::NamedTempFile::new().expect("Failed to create a temp file");
+        let state_backend = StateBackend::Local {
+            path: state_file.path().display().to_string(),
+        };
+        save_user_state_with_service(&state_backend, "app_1").await;
+
+        let mut container_engine_mock = ContainerEngine::default();
+        container_engine_mock
+            .expect_run()
+            .withf(|name, _| name == "app_2")
+            .times(1)
+            .returning(|_, _| Ok(String::from("container-id")));
+        container_engine_mock.expect_remove().never();
+
+        let server_config = ServerConfig {
+            container_engine: Arc::new(container_engine_mock),
+            host_capacity: TEST_HOST_CAPACITY,
+            token: None,
+            user_state_lock: Arc::default(),
+        };
+        let services_graph = get_services_graph(vec![get_service("app_1"), get_service("app_2")]);
+
+        // Act
+        let result = apply_user_services_graph(
+            &server_config,
+            &state_backend,
+            &services_graph,
+            &HashMap::new(),
+            false,
+            false,
+        )
+        .await;
+
+        // Assert
+        let expected_container_ids = HashMap::from([
+            (String::from("app_1"), String::from("app_1-container")),
+            (String::from("app_2"), String::from("container-id")),
+        ]);
+        assert_eq!(
+            result.expect("Failed to apply services"),
+            expected_container_ids
+        );
+
+        let user_state = load_user_state(&state_backend).await;
+        assert!(user_state.contains_service("app_1"));
+        assert!(user_state.contains_service("app_2"));
+        assert_eq!(
+            user_state.instances["localhost"].container_ids,
+            expected_container_ids
+        );
+    }
+
+    #[tokio::test]
+    async fn test_apply_user_services_graph_exceeds_capacity() {
+        // Arrange
+        let state_file = tempfile::NamedTempFile::new().expect("Failed to create a temp file");
+        let state_backend = StateBackend::Local {
+            path: state_file.path().display().to_string(),
+        };
+        save_user_state_with_service(&state_backend, "app_1").await;
+
+        let mut container_engine_mock = ContainerEngine::default();
+        container_engine_mock.expect_run().never();
+        container_engine_mock.expect_remove().never();
+
+        let server_config = ServerConfig {
+            container_engine: Arc::new(container_engine_mock),
+            host_capacity: HostCapacity {
+                cpus: 400,
+                memory: 1024,
+            },
+            token: None,
+            user_state_lock: Arc::default(),
+        };
+        let services_graph = get_services_graph(vec![get_service("app_1"), get_service("app_2")]);
+
+        // Act
+        let result = apply_user_services_graph(
+            &server_config,
+            &state_backend,
+            &services_graph,
+            &HashMap::new(),
+            false,
+            false,
+        )
+        .await;
+
+        // Assert
+        assert_eq!(
+            result
+                .expect_err("Expected failed services error")
+                .to_string(),
+            "Failed to run services: app_2"
+        );
+
+        let user_state = load_user_state(&state_backend).await;
+        assert!(user_state.contains_service("app_1"));
+        assert!(!user_state.contains_service("app_2"));
+        assert_eq!(user_state.instances["localhost"].available(), (150, 960));
+    }
+
+    #[tokio::test]
+    async fn test_apply_user_services_graph_runs_replicas() {
+        // Arrange
+        let state_file = tempfile::NamedTempFile::new().expect("Failed to create a temp file");
+        let state_backend = StateBackend::Local {
+            path: state_file.path().display().to_string(),
+        };
+
+        let mut container_engine_mock = ContainerEngine::default();
+        container_engine_mock
+            .expect_run()
+            .times(2)
+            .returning(|name, _| Ok(format!("{name}-container")));
+        container_engine_mock.expect_remove().never();
+
+        let server_config = ServerConfig {
+            container_engine: Arc::new(container_engine_mock),
+            host_capacity: TEST_HOST_CAPACITY,
+            token: None,
+            user_state_lock: Arc::default(),
+        };
+        let services_graph = get_services_graph(vec![oct_config::Service {
+            replicas: 2,
+            ..get_service("app_1")
+        }]);
+
+        // Act
+        let result = apply_user_services_graph(
+            &server_config,
+            &state_backend,
+            &services_graph,
+            &HashMap::new(),
+            false,
+            false,
+        )
+        .await;
+
+        // Assert
+        let expected_container_ids = HashMap::from([
+            (String::from("app_1-0"), String::from("app_1-0-container")),
+            (String::from("app_1-1"), String::from("app_1-1-container")),
+        ]);
+        assert_eq!(
+            result.expect("Failed to apply services"),
+            expected_container_ids
+        );
+
+        let user_state = load_user_state(&state_backend).await;
+        assert!(user_state.contains_service("app_1"));
+        assert_eq!(user_state.instances["localhost"].reserved(), (500, 128));
+    }
+
+    #[tokio::test]
+    async fn test_apply_user_services_graph_keeps_secret_references_in_state() {
+        // Arrange
+        let state_file = tempfile::NamedTempFile::new().expect("Failed to create a temp file");
+        let state_backend = StateBackend::Local {
+            path: state_file.path().display().to_string(),
+        };
+
+        let mut container_engine_mock = ContainerEngine::default();
+        container_engine_mock
+            .expect_run()
+            .withf(|_, options| {
+                options.envs.get("DB_PASSWORD").map(String::as_str) == Some("s3cr3t")
+                    && options.envs.get("LOG_LEVEL").map(String::as_str) == Some("info")
+            })
+            .times(1)
+            .returning(|_, _| Ok(String::from("app_1-container")));
+
+        let server_config = ServerConfig {
+            container_engine: Arc::new(container_engine_mock),
+            host_capacity: TEST_HOST_CAPACITY,
+            token: None,
+            user_state_lock: Arc::default(),
+        };
+        let service = oct_config::Service {
+            envs: HashMap::from([
+                (
+                    String::from("DB_PASSWORD"),
+                    String::from("secret://secretsmanager/db?key=password"),
+                ),
+                (String::from("LOG_LEVEL"), String::from("info")),
+            ]),
+            ..get_service("app_1")
+        };
+        let services_graph = get_services_graph(vec![service.clone()]);
+        let secret_envs = HashMap::from([(
+            String::from("app_1"),
+            HashMap::from([(String::from("DB_PASSWORD"), String::from("s3cr3t"))]),
+        )]);
+
+        // Act
+        let result = apply_user_services_graph(
+            &server_config,
+            &state_backend,
+            &services_graph,
+            &secret_envs,
+            false,
+            false,
+        )
+        .await;
+
+        // Assert
+        assert!(result.is_ok());
+
+        let user_state = load_user_state(&state_backend).await;
+        assert_eq!(user_state.instances["localhost"].services["app_1"], service);
+
+        let state_content =
+            std::fs::read_to_string(state_file.path()).expect("Failed to read user state");
+        assert!(!state_content.contains("s3cr3t"));
+    }
+
+    #[tokio::test]
+    async fn test_apply_user_services_graph_failed_replica_removes_started_ones() {
+        // Arrange
+        let state_file = tempfile::NamedTempFile::new().expect("Failed to create a temp file");
+        let state_backend = StateBackend::Local {
+            path: state_file.path().display().to_string(),
+        };
+
+        let mut container_engine_mock = ContainerEngine::default();
+        container_engine_mock
+            .expect_run()
+            .times(2)
+            .returning(|name, _| {
+                if name == "app_1-1" {
+                    return Err("Failed to pull image".into());
+                }
+
+                Ok(format!("{name}-container"))
+            });
+        container_engine_mock
+            .expect_remove()
+            .withf(|name| name == "app_1-0")
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let server_config = ServerConfig {
+            container_engine: Arc::new(container_engine_mock),
+            host_capacity: TEST_HOST_CAPACITY,
+            token: None,
+            user_state_lock: Arc::default(),
+        };
+        let services_graph = get_services_graph(vec![oct_config::Service {
+            replicas: 2,
+            ..get_service("app_1")
+        }]);
+
+        // Act
+        let result = apply_user_services_graph(
+            &server_config,
+            &state_backend,
+            &services_graph,
+            &HashMap::new(),
+            false,
+            false,
+        )
+        .await;
+
+        // Assert
+        assert_eq!(
+            result
+                .expect_err("Expected failed services error")
+                .to_string(),
+            "Failed to run services: app_1"
+        );
+
+        let user_state = load_user_state(&state_backend).await;
+        assert!(!user_state.contains_service("app_1"));
+    }
+
+    #[test]
+    fn test_parse_mem_total() {
+        // Arrange
+        let meminfo = "MemTotal:        8039600 kB\nMemFree:         1204880 kB\n";
+
+        // Act & Assert
+        assert_eq!(parse_mem_total(meminfo), Some(7851));
+        assert_eq!(parse_mem_total("MemFree: 1204880 kB"), None);
+    }
+
+    #[tokio::test]
+    async fn test_apply_user_services_graph_force_recreate() {
+        // Arrange
+        let state_file = tempfile::NamedTempFile::new().expect("Failed to create a temp file");
+        let state_backend = StateBackend::Local {
+            path: state_file.path().display().to_string(),
+        };
+        save_user_state_with_service(&state_backend, "app_1").await;
+
+        let mut container_engine_mock = ContainerEngine::default();
+        container_engine_mock
+            .expect_remove()
+            .withf(|name| name == "app_1")
+            .times(1)
+            .returning(|_| Ok(()));
+        container_engine_mock
+            .expect_run()
+            .withf(|name, _| name == "app_1")
+            .times(1)
+            .returning(|_, _| Ok(String::from("container-id")));
+
+        let server_config = ServerConfig {
+            container_engine: Arc::new(container_engine_mock),
+            host_capacity: TEST_HOST_CAPACITY,
+            token: None,
+            user_state_lock: Arc::default(),
+        };
+        let services_graph = get_services_graph(vec![get_service("app_1")]);
+
+        // Act
+        let result = apply_user_services_graph(
+            &server_config,
+            &state_backend,
+            &services_graph,
+            &HashMap::new(),
+            true,
+            false,
+        )
+        .await;
+
+        // Assert
+        assert!(result.is_ok());
+
+        let user_state = load_user_state(&state_backend).await;
+        assert!(user_state.contains_service("app_1"));
+    }
+
+    fn get_dependent_services_graph() -> Graph<Node, String> {
+        let mut db_service = get_service("db");
+        db_service.health_check = Some(oct_config::HealthCheck {
+            command: oct_config::ContainerCommand::String(String::from("pg_isready")),
+            interval: 0,
+            retries: 3,
+        });
+
+        let mut app_service = get_service("app");
+        app_service.depends_on = vec![String::from("db")];
+
+        let mut services_graph = Graph::new();
+        let root = services_graph.add_node(Node::Root);
+        let db = services_graph.add_node(Node::Resource(db_service));
+        let app = services_graph.add_node(Node::Resource(app_service));
+        services_graph.extend_with_edges([
+            (root, db, String::new()),
+            (root, app, String::new()),
+            (db, app, String::new()),
+        ]);
+
+        services_graph
+    }
+
+    #[tokio::test]
+    async fn test_apply_user_services_graph_waits_for_healthy_dependency() {
+        // Arrange
+        let state_file = tempfile::NamedTempFile::new().expect("Failed to create a temp file");
+        let state_backend = StateBackend::Local {
+            path: state_file.path().display().to_string(),
+        };
+
+        let mut sequence = mockall::Sequence::new();
+        let mut container_engine_mock = ContainerEngine::default();
+        container_engine_mock
+            .expect_run()
+            .withf(|name, _| name == "db")
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|_, _| Ok(String::from("container-id")));
+        container_engine_mock
+            .expect_is_healthy()
+            .withf(|name, _| name == "db")
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|_, _| Ok(false));
+        container_engine_mock
+            .expect_is_healthy()
+            .withf(|name, _| name == "db")
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|_, _| Ok(true));
+        container_engine_mock
+            .expect_run()
+            .withf(|name, _| name == "app")
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|_, _| Ok(String::from("container-id")));
+
+        let server_config = ServerConfig {
+            container_engine: Arc::new(container_engine_mock),
+            host_capacity: TEST_HOST_CAPACITY,
+            token: None,
+            user_state_lock: Arc::default(),
+        };
+        let services_graph = get_dependent_services_graph();
+
+        // Act
+        let result = apply_user_services_graph(
+            &server_config,
+            &state_backend,
+            &services_graph,
+            &HashMap::new(),
+            false,
+            false,
+        )
+        .await;
+
+        // Assert
+        assert!(result.is_ok());
+
+        let user_state = load_user_state(&state_backend).await;
+        assert!(user_state.contains_service("db"));
+        assert!(user_state.contains_service("app"));
+    }
+
+    #[tokio::test]
+    async fn test_apply_user_services_graph_unhealthy_dependency() {
+        // Arrange
+        let state_file = tempfile::NamedTempFile::new().expect("Failed to create a temp file");
+        let state_backend = StateBackend::Local {
+            path: state_file.path().display().to_string(),
+        };
+
+        let mut container_engine_mock = ContainerEngine::default();
+        container_engine_mock
+            .expect_run()
+            .withf(|name, _| name == "db")
+            .times(1)
+            .returning(|_, _| Ok(String::from("container-id")));
+        container_engine_mock
+            .expect_is_healthy()
+            .times(3)
+            .returning(|_, _| Ok(false));
+
+        let server_config = ServerConfig {
+            container_engine: Arc::new(container_engine_mock),
+            host_capacity: TEST_HOST_CAPACITY,
+            token: None,
+            user_state_lock: Arc::default(),
+        };
+        let services_graph = get_dependent_services_graph();
+
+        // Act
+        let result = apply_user_services_graph(
+            &server_config,
+            &state_backend,
+            &services_graph,
+            &HashMap::new(),
+            false,
+            false,
+        )
+        .await;
+
+        // Assert
+        assert_eq!(
+            result
+                .expect_err("Expected failed services error")
+                .to_string(),
+            "Failed to run services: app"
+        );
+
+        let user_state = load_user_state(&state_backend).await;
+        assert!(user_state.contains_service("db"));
+        assert!(!user_state.contains_service("app"));
+    }
+
+    /// `web` depends on `api` which depends on `db`, the nodes are added
+    /// in the reverse order of the dependencies
+    fn get_dependency_chain_graph() -> Graph<Node, String> {
+        let mut api_service = get_service("api");
+        api_service.depends_on = vec![String::from("db")];
+        let mut web_service = get_service("web");
+        web_service.depends_on = vec![String::from("api")];
+
+        let mut services_graph = Graph::new();
+        let root = services_graph.add_node(Node::Root);
+        let web = services_graph.add_node(Node::Resource(web_service));
+        let api = services_graph.add_node(Node::Resource(api_service));
+        let db = services_graph.add_node(Node::Resource(get_service("db")));
+        services_graph.extend_with_edges([
+            (root, db, String::new()),
+            (db, api, String::new()),
+            (api, web, String::new()),
+        ]);
+
+        services_graph
+    }
+
+    #[tokio::test]
+    async fn test_apply_user_services_graph_dependency_chain_order() {
+        // Arrange
+        let state_file = tempfile::NamedTempFile::new().expect("Failed to create a temp file");
+        let state_backend = StateBackend::Local {
+            path: state_file.path().display().to_string(),
+        };
+
+        let mut sequence = mockall::Sequence::new();
+        let mut container_engine_mock = ContainerEngine::default();
+        for service_name in ["db", "api", "web"] {
+            container_engine_mock
+                .expect_run()
+                .withf(move |name, _| name == service_name)
+                .times(1)
+                .in_sequence(&mut sequence)
+                .returning(|_, _| Ok(String::from("container-id")));
+        }
+
+        let server_config = ServerConfig {
+            container_engine: Arc::new(container_engine_mock),
+            host_capacity: TEST_HOST_CAPACITY,
+            token: None,
+            user_state_lock: Arc::default(),
+        };
+        let services_graph = get_dependency_chain_graph();
+
+        // Act
+        let result = apply_user_services_graph(
+            &server_config,
+            &state_backend,
+            &services_graph,
+            &HashMap::new(),
+            false,
+            false,
+        )
+        .await;
+
+        // Assert
+        assert!(result.is_ok());
+
+        let user_state = load_user_state(&state_backend).await;
+        assert!(user_state.contains_service("db"));
+        assert!(user_state.contains_service("api"));
+        assert!(user_state.contains_service("web"));
+    }
+
+    #[tokio::test]
+    async fn test_apply_user_services_graph_skips_dependents_of_failed_service() {
+        // Arrange
+        let state_file = tempfile::NamedTempFile::new().expect("Failed to create a temp file");
+        let state_backend = StateBackend::Local {
+            path: state_file.path().display().to_string(),
+        };
+
+        let mut container_engine_mock = ContainerEngine::default();
+        container_engine_mock
+            .expect_run()
+            .withf(|name, _| name == "db")
+            .times(1)
+            .returning(|_, _| Err("Failed to pull image".into()));
+        container_engine_mock
+            .expect_run()
+            .withf(|name, _| name == "api" || name == "web")
+            .never();
+
+        let server_config = ServerConfig {
+            container_engine: Arc::new(container_engine_mock),
+            host_capacity: TEST_HOST_CAPACITY,
+            token: None,
+            user_state_lock: Arc::default(),
+        };
+        let services_graph = get_dependency_chain_graph();
+
+        // Act
+        let result = apply_user_services_graph(
+            &server_config,
+            &state_backend,
+            &services_graph,
+            &HashMap::new(),
+            false,
+            false,
+        )
+        .await;
+
+        // Assert
+        assert_eq!(
+            result
+                .expect_err("Expected failed services error")
+                .to_string(),
+            "Failed to run services: api, db, web"
+        );
+
+        let user_state = load_user_state(&state_backend).await;
+        assert!(!user_state.contains_service("db"));
+        assert!(!user_state.contains_service("api"));
+        assert!(!user_state.contains_service("web"));
+    }
+
+    #[tokio::test]
+    async fn test_remove_user_service_success() {
+        // Arrange
+        let state_file = tempfile::NamedTempFile::new().expect("Failed to create a temp file");
+        let state_backend = StateBackend::Local {
+            path: state_file.path().display().to_string(),
+        };
+        save_user_state_with_service(&state_backend, "app_1").await;
+
+        let server_config = ServerConfig {
+            container_engine: Arc::new(get_container_engine_mock(true)),
+            host_capacity: TEST_HOST_CAPACITY,
+            token: None,
+            user_state_lock: Arc::default(),
+        };
+
+        // Act
+        let result = remove_user_service(&server_config, &state_backend, "app_1").await;
+
+        // Assert
+        assert!(result.is_ok());
+
+        let user_state = load_user_state(&state_backend).await;
+        assert!(!user_state.contains_service("app_1"));
+    }
+
+    #[tokio::test]
+    async fn test_remove_user_service_not_found() {
+        // Arrange
+        let state_file = tempfile::NamedTempFile::new().expect("Failed to create a temp file");
+        let state_backend = StateBackend::Local {
+            path: state_file.path().display().to_string(),
+        };
+        save_user_state_with_service(&state_backend, "app_1").await;
+
+        let server_config = ServerConfig {
+            container_engine: Arc::new(get_container_engine_mock(true)),
+            host_capacity: TEST_HOST_CAPACITY,
+            token: None,
+            user_state_lock: Arc::default(),
+        };
+
+        // Act
+        let result = remove_user_service(&server_config, &state_backend, "app_2").await;
+
+        // Assert
+        assert_eq!(
+            result,
+            Err((
+                StatusCode::NOT_FOUND,
+                String::from("Service 'app_2' not found")
+            ))
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_remove_user_service_concurrent() {
+        // Arrange
+        let state_file = tempfile::NamedTempFile::new().expect("Failed to create a temp file");
+        let state_backend = StateBackend::Local {
+            path: state_file.path().display().to_string(),
+        };
+
+        let names: Vec<String> = (1..=8).map(|index| format!("app_{index}")).collect();
+        let user_state = user_state::UserState {
+            instances: HashMap::from([(
+                String::from("localhost"),
+                user_state::Instance {
+                    cpus: 0,
+                    memory: 0,
+                    services: names
+                        .iter()
+                        .map(|name| (name.clone(), get_service(name)))
+                        .collect(),
+                    container_ids: names
+                        .iter()
+                        .map(|name| (name.clone(), format!("{name}-container")))
+                        .collect(),
+                },
+            )]),
+            oct_ctl_token: None,
+        };
+        backend::get_state_backend::<user_state::UserState>(&state_backend)
+            .save(&user_state)
+            .await
+            .expect("Failed to save user state");
+
+        let server_config = ServerConfig {
+            container_engine: Arc::new(get_container_engine_mock(true)),
+            host_capacity: TEST_HOST_CAPACITY,
+            token: None,
+            user_state_lock: Arc::default(),
+        };
+
+        // Act
+        let handles: Vec<_> = names
+            .iter()
+            .map(|name| {
+                let server_config = server_config.clone();
+                let state_backend = state_backend.clone();
+                let name = name.clone();
+
+                tokio::spawn(async move {
+                    remove_user_service(&server_config, &state_backend, &name).await
+                })
+            })
+            .collect();
+
+        // Assert
+        for handle in handles {
+            let result = handle.await.expect("Failed to join task");
+            assert!(result.is_ok());
+        }
+
+        let user_state = load_user_state(&state_backend).await;
+        for name in &names {
+            assert!(!user_state.contains_service(name));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remove_user_service_container_removal_fails() {
+        // Arrange
+        let state_file = tempfile::NamedTempFile::new().expect("Failed to create a temp file");
+        let state_backend = StateBackend::Local {
+            path: state_file.path().display().to_string(),
+        };
+        save_user_state_with_service(&state_backend, "app_1").await;
+
+        let server_config = ServerConfig {
+            container_engine: Arc::new(get_container_engine_mock(false)),
+            host_capacity: TEST_HOST_CAPACITY,
+            token: None,
+            user_state_lock: Arc::default(),
+        };
+
+        // Act
+        let result = remove_user_service(&server_config, &state_backend, "app_1").await;
+
+        // Assert
+        assert_eq!(
+            result.map_err(|(status, _)| status),
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        );
+
+        let user_state = load_user_state(&state_backend).await;
+        assert!(user_state.contains_service("app_1"));
+    }
+
+    #[tokio::test]
+    async fn test_remove_all_user_services() {
+        // Arrange
+        let state_file = tempfile::NamedTempFile::new().expect("Failed to create a temp file");
+        let state_backend = StateBackend::Local {
+            path: state_file.path().display().to_string(),
+        };
+
+        let app_2 = oct_config::Service {
+            replicas: 2,
+            ..get_service("app_2")
+        };
+        let user_state = user_state::UserState {
+            instances: HashMap::from([(
+                String::from("localhost"),
+                user_state::Instance {
+                    cpus: 0,
+                    memory: 0,
+                    services: HashMap::from([
+                        (String::from("app_1"), get_service("app_1")),
+                        (String::from("app_2"), app_2),
+                    ]),
+                    container_ids: HashMap::from([
+                        (String::from("app_1"), String::from("app_1-container")),
+                        (String::from("app_2-0"), String::from("app_2-0-container")),
+                        (String::from("app_2-1"), String::from("app_2-1-container")),
+                    ]),
+                },
+            )]),
+            oct_ctl_token: None,
+        };
+        backend::get_state_backend::<user_state::UserState>(&state_backend)
+            .save(&user_state)
+            .await
+            .expect("Failed to save user state");
+
+        let mut container_engine_mock = ContainerEngine::default();
+        for container_name in ["app_1", "app_2-0", "app_2-1"] {
+            container_engine_mock
+                .expect_remove()
+                .withf(move |name| name == container_name)
+                .times(1)
+                .returning(|_| Ok(()));
+        }
+
+        let server_config = ServerConfig {
+            container_engine: Arc::new(container_engine_mock),
+            host_capacity: TEST_HOST_CAPACITY,
+            token: None,
+            user_state_lock: Arc::default(),
+        };
+
+        // Act
+        let result = remove_all_user_services(&server_config, &state_backend).await;
+
+        // Assert
+        assert!(result.is_ok());
+
+        let user_state = load_user_state(&state_backend).await;
+        let instance = &user_state.instances["localhost"];
+        assert!(instance.services.is_empty());
+        assert!(instance.container_ids.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_remove_all_user_services_container_removal_fails() {
+        // Arrange
+        let state_file = tempfile::NamedTempFile::new().expect("Failed to create a temp file");
+        let state_backend = StateBackend::Local {
+            path: state_file.path().display().to_string(),
+        };
+        save_user_state_with_service(&state_backend, "app_1").await;
+
+        let server_config = ServerConfig {
+            container_engine: Arc::new(get_container_engine_mock(false)),
+            host_capacity: TEST_HOST_CAPACITY,
+            token: None,
+            user_state_lock: Arc::default(),
+        };
+
+        // Act
+        let result = remove_all_user_services(&server_config, &state_backend).await;
+
+        // Assert
+        assert_eq!(
+            result.expect_err("Expected removal error").to_string(),
+            "Failed to remove services: app_1"
+        );
+
+        let user_state = load_user_state(&state_backend).await;
+        assert!(user_state.contains_service("app_1"));
+    }
+
+    fn get_exec_container_engine_mock() -> ContainerEngine {
+        let mut container_engine_mock = ContainerEngine::default();
+        container_engine_mock
+            .expect_exec()
+            .withf(|name, args| name == "app_1" && *args == [String::from("ls"), String::from("/")])
+            .returning(|_, _| {
+                Ok(Output {
+                    status: ExitStatus::from_raw(1 << 8),
+                    stdout: b"bin\n".to_vec(),
+                    stderr: b"ls: /root: Permission denied\n".to_vec(),
+                })
+            });
+        container_engine_mock
+    }
+
+    #[test]
+    fn test_get_tls_paths() {
+        // Arrange
+        let envs = HashMap::from([
+            ("OCT_CTL_TLS_CERT_PATH", "/etc/oct-ctl/cert.pem"),
+            ("OCT_CTL_TLS_KEY_PATH", "/etc/oct-ctl/key.pem"),
+        ]);
+
+        // Act
+        let tls_paths = get_tls_paths(|name| envs.get(name).map(ToString::to_string));
+
+        // Assert
+        assert_eq!(
+            tls_paths,
+            Ok(Some(TlsPaths {
+                cert_path: String::from("/etc/oct-ctl/cert.pem"),
+                key_path: String::from("/etc/oct-ctl/key.pem"),
+            }))
+        );
+    }
+
+    #[test]
+    fn test_get_tls_paths_without_envs() {
+        // Act
+        let tls_paths = get_tls_paths(|_| None);
+
+        // Assert
+        assert_eq!(tls_paths, Ok(None));
+    }
+
+    #[test]
+    fn test_get_tls_paths_with_one_env() {
+        // Arrange
+        let cert_envs = HashMap::from([("OCT_CTL_TLS_CERT_PATH", "/etc/oct-ctl/cert.pem")]);
+        let key_envs = HashMap::from([("OCT_CTL_TLS_KEY_PATH", "/etc/oct-ctl/key.pem")]);
+
+        // Act
+        let cert_only = get_tls_paths(|name| cert_envs.get(name).map(ToString::to_string));
+        let key_only = get_tls_paths(|name| key_envs.get(name).map(ToString::to_string));
+
+        // Assert
+        assert_eq!(
+            cert_only,
+            Err("OCT_CTL_TLS_CERT_PATH is set without OCT_CTL_TLS_KEY_PATH".to_string())
+        );
+        assert_eq!(
+            key_only,
+            Err("OCT_CTL_TLS_KEY_PATH is set without OCT_CTL_TLS_CERT_PATH".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_exec_non_zero_exit() {
+        // Arrange
+        let server_config = ServerConfig {
+            container_engine: Arc::new(get_exec_container_engine_mock()),
+            host_capacity: TEST_HOST_CAPACITY,
+            token: None,
+            user_state_lock: Arc::default(),
+        };
+        let app = Router::new()
+            .route("/exec", post(exec))
+            .with_state(server_config);
+
+        // Act
+        let response = app
+            .oneshot(
+                Request::post("/exec")
+                    .header("Content-Type", "application/json")
+                    .body(Body::from(r#"{"name": "app_1", "args": ["ls", "/"]}"#))
+                    .expect("Failed to prepare body"),
+            )
+            .await
+            .expect("Failed to get response");
+
+        // Assert
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("Failed to read body");
+        assert_eq!(
+            String::from_utf8(body.to_vec()).expect("Body is not UTF-8"),
+            r#"{"stdout":"bin\n","stderr":"ls: /root: Permission denied\n","exit_code":1}"#
+        );
+    }
+
+    fn get_logs_container_engine_mock() -> ContainerEngine {
+        let mut container_engine_mock = ContainerEngine::default();
+        container_engine_mock
+            .expect_logs()
+            .withf(|name, since, tail, follow| {
+                name == "app_1" && since.as_deref() == Some("600s") && tail.is_none() && *follow
+            })
+            .returning(|_, _, _, _| {
+                let chunks = vec![
+                    Ok(axum::body::Bytes::from("Listening on :80\n")),
+                    Ok(axum::body::Bytes::from("GET / 200\n")),
+                ];
+
+                Ok(Box::pin(tokio_stream::iter(chunks)))
+            });
+        container_engine_mock
+    }
+
+    #[tokio::test]
+    async fn test_logs_since_follow() {
+        // Arrange
+        let server_config = ServerConfig {
+            container_engine: Arc::new(get_logs_container_engine_mock()),
+            host_capacity: TEST_HOST_CAPACITY,
+            token: None,
+            user_state_lock: Arc::default(),
+        };
+        let app = Router::new()
+            .route("/logs", get(logs))
+            .with_state(server_config);
+
+        // Act
+        let response = app
+            .oneshot(
+                Request::get("/logs?name=app_1&since=600s&follow=true")
+                    .body(Body::empty())
+                    .expect("Failed to prepare body"),
+            )
+            .await
+            .expect("Failed to get response");
+
+        // Assert
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("Failed to read body");
+        assert_eq!(
+            String::from_utf8(body.to_vec()).expect("Body is not UTF-8"),
+            "Listening on :80\nGET / 200\n"
+        );
+    }
+
+    async fn get_exec_status_with_token(authorization: Option<&str>) -> StatusCode {
+        let server_config = ServerConfig {
+            container_engine: Arc::new(get_exec_container_engine_mock()),
+            host_capacity: TEST_HOST_CAPACITY,
+            token: Some(String::from("secret-token")),
+            user_state_lock: Arc::default(),
+        };
+        let app = Router::new()
+            .route("/exec", post(exec))
+            .route_layer(middleware::from_fn_with_state(
+                server_config.clone(),
+                check_token,
+            ))
+            .with_state(server_config);
+
+        let mut request = Request::post("/exec").header("Content-Type", "application/json");
+        if let Some(authorization) = authorization {
+            request = request.header("Authorization", authorization);
+        }
+
+        app.oneshot(
+            request
+                .body(Body::from(r#"{"name": "app_1", "args": ["ls", "/"]}"#))
+                .expect("Failed to prepare body"),
+        )
+        .await
+        .expect("Failed to get response")
+        .status()
+    }
+
+    #[tokio::test]
+    async fn test_check_token_accepts_valid_token() {
+        // Act
+        let status = get_exec_status_with_token(Some("Bearer secret-token")).await;
+
+        // Assert
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_check_token_rejects_missing_token() {
+        // Act
+        let status = get_exec_status_with_token(None).await;
+
+        // Assert
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_check_token_rejects_invalid_token() {
+        // Act
+        let status = get_exec_status_with_token(Some("Bearer wrong-token")).await;
+
+        // Assert
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_tokens_eq() {
+        // Act & Assert
+        assert!(tokens_eq("secret-token", "secret-token"));
+        assert!(!tokens_eq("secret-token", "secret-tokem"));
+        assert!(!tokens_eq("secret-token", "secret"));
+        assert!(!tokens_eq("", "secret-token"));
+    }
+
+    async fn get_health_check_response(is_ok: bool) -> (StatusCode, String) {
+        let server_config = ServerConfig {
+            container_engine: Arc::new(get_container_engine_mock(is_ok)),
+            host_capacity: TEST_HOST_CAPACITY,
+            token: None,
+            user_state_lock: Arc::default(),
+        };
+        let app = Router::new()
+            .route("/health-check", get(health_check))
+            .with_state(server_config);
+
+        let response = app
+            .oneshot(
+                Request::get("/health-check")
+                    .body(Body::empty())
+                    .expect("Failed to prepare body"),
+            )
+            .await
+            .expect("Failed to get response");
+
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("Failed to read body");
+
+        (
+            status,
+            String::from_utf8(body.to_vec()).expect("Body is not UTF-8"),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_health_check() {
+        // Act
+        let (status, body) = get_health_check_response(true).await;
+
+        // Assert
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, r#"{"podman":true}"#);
+    }
+
+    #[tokio::test]
+    async fn test_health_check_podman_not_available() {
+        // Act
+        let (status, body) = get_health_check_response(false).await;
+
+        // Assert
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body, r#"{"podman":false}"#);
     }
 }
@@ -1,7 +1,10 @@
 use std::fs;
+use std::path::{Path, PathBuf};
 
+/// Re-exported to implement [`StateBackend`] outside of this crate
+pub use async_trait::async_trait;
 use oct_cloud::aws::resource::S3Bucket;
-use oct_cloud::resource::Resource;
+use oct_cloud::resource::{Resource, Retry, RetryPolicy};
 
 /// Creates a state backend based on the configuration.
 ///
@@ -24,6 +27,16 @@ where
     }
 }
 
+/// Storage for the infra and user states
+///
+/// Built-in implementations are selected by [`get_state_backend`] from the
+/// config, custom storages (e.g. `DynamoDB` or GCS) can implement this trait
+/// using the re-exported [`async_trait`] macro:
+///
+/// ```ignore
+/// #[oct_orchestrator::backend::async_trait]
+/// impl<T: Send + Sync + 'static> StateBackend<T> for MyStateBackend { ... }
+/// ```
 #[async_trait::async_trait]
 pub trait StateBackend<T: 'static>: Send + Sync {
     /// Saves state to a backend
@@ -41,6 +54,9 @@ pub(crate) struct LocalStateBackend<T> {
     _marker: std::marker::PhantomData<T>,
 
     file_path: String,
+    /// Moves the temporary file over the state file, replaced in tests to
+    /// simulate a failed rename
+    rename: fn(&Path, &Path) -> std::io::Result<()>,
 }
 
 impl<T> LocalStateBackend<T> {
@@ -49,17 +65,53 @@ impl<T> LocalStateBackend<T> {
             _marker: std::marker::PhantomData,
 
             file_path: file_path.to_string(),
+            rename: |from, to| fs::rename(from, to),
         }
     }
 }
 
+/// Writes `contents` to a new temporary file next to `path`, a unique name
+/// keeps concurrent saves from writing to the same file
+fn write_temp_file(path: &Path, contents: &str) -> std::io::Result<PathBuf> {
+    let mut temp_file_path = path.as_os_str().to_owned();
+    temp_file_path.push(format!(".{}.tmp", uuid::Uuid::new_v4()));
+    let temp_file_path = PathBuf::from(temp_file_path);
+
+    if let Err(e) = fs::write(&temp_file_path, contents) {
+        let _ = fs::remove_file(&temp_file_path);
+
+        return Err(e);
+    }
+
+    Ok(temp_file_path)
+}
+
 #[async_trait::async_trait]
 impl<T> StateBackend<T> for LocalStateBackend<T>
 where
     T: serde::Serialize + serde::de::DeserializeOwned + Send + Sync + Default + 'static,
 {
+    /// Writes the state to a temporary file next to the state file and
+    /// renames it over the state file, so a reader never sees a partially
+    /// written state and a crash keeps the previous one, the missing parent
+    /// directories are created
     async fn save(&self, state: &T) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        fs::write(&self.file_path, serde_json::to_string_pretty(state)?)?;
+        let file_path = Path::new(&self.file_path);
+
+        if let Some(parent) = file_path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)?;
+        }
+
+        let temp_file_path = write_temp_file(file_path, &serde_json::to_string_pretty(state)?)?;
+
+        if let Err(e) = (self.rename)(&temp_file_path, file_path) {
+            let _ = fs::remove_file(&temp_file_path);
+
+            return Err(e.into());
+        }
 
         Ok(())
     }
@@ -109,11 +161,13 @@ where
     T: serde::Serialize + serde::de::DeserializeOwned + Send + Sync + Default + 'static,
 {
     async fn save(&self, state: &T) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let mut s3_bucket = S3Bucket::new(self.region.clone(), self.bucket.clone()).await;
+        let s3_bucket = S3Bucket::new(self.region.clone(), self.bucket.clone()).await;
+        let mut s3_bucket = Retry::new(s3_bucket, RetryPolicy::default());
         s3_bucket.create().await?;
 
+        let data = serde_json::to_vec(state)?;
         s3_bucket
-            .put_object(&self.key, serde_json::to_vec(state)?)
+            .run(|s3_bucket| s3_bucket.put_object(&self.key, data.clone()))
             .await?;
 
         Ok(())
@@ -131,12 +185,15 @@ where
     }
 
     async fn remove(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let mut s3_bucket = S3Bucket::new(self.region.clone(), self.bucket.clone()).await;
+        let s3_bucket = S3Bucket::new(self.region.clone(), self.bucket.clone()).await;
+        let mut s3_bucket = Retry::new(s3_bucket, RetryPolicy::default());
 
         // For now we expect to have only one file in the bucket
         // If there are multiple files, the state is corrupted and bucket
         // will not be deleted
-        s3_bucket.delete_object(&self.key).await?;
+        s3_bucket
+            .run(|s3_bucket| s3_bucket.delete_object(&self.key))
+            .await?;
 
         s3_bucket.destroy().await?;
 
@@ -147,16 +204,90 @@ where
 #[cfg(test)]
 mod tests {
     use std::io::Write;
+    use std::sync::Mutex;
 
     use serde::{Deserialize, Serialize};
 
     use super::*;
 
-    #[derive(Debug, Serialize, Deserialize, Default, PartialEq, Eq)]
+    #[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq, Eq)]
     struct TestState {
         value: String,
     }
 
+    /// Custom backend implemented only against the public trait
+    #[derive(Default)]
+    struct InMemoryStateBackend {
+        state: Mutex<Option<TestState>>,
+    }
+
+    #[async_trait]
+    impl StateBackend<TestState> for InMemoryStateBackend {
+        async fn save(
+            &self,
+            state: &TestState,
+        ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            *self.state.lock().map_err(|e| e.to_string())? = Some(state.clone());
+
+            Ok(())
+        }
+
+        async fn load(
+            &self,
+        ) -> Result<(TestState, bool), Box<dyn std::error::Error + Send + Sync>> {
+            let state = self.state.lock().map_err(|e| e.to_string())?.clone();
+
+            Ok(match state {
+                Some(state) => (state, true),
+                None => (TestState::default(), false),
+            })
+        }
+
+        async fn remove(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            *self.state.lock().map_err(|e| e.to_string())? = None;
+
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_custom_state_backend_as_trait_object() {
+        // Arrange
+        let state_backend: Box<dyn StateBackend<TestState> + Send + Sync> =
+            Box::new(InMemoryStateBackend::default());
+        let state = TestState {
+            value: "test".to_string(),
+        };
+
+        // Act
+        let (_, loaded_before_save) = state_backend
+            .load()
+            .await
+            .expect("Failed to load from state backend");
+        state_backend
+            .save(&state)
+            .await
+            .expect("Failed to save to state backend");
+        let (loaded_state, loaded_after_save) = state_backend
+            .load()
+            .await
+            .expect("Failed to load from state backend");
+        state_backend
+            .remove()
+            .await
+            .expect("Failed to remove from state backend");
+        let (_, loaded_after_remove) = state_backend
+            .load()
+            .await
+            .expect("Failed to load from state backend");
+
+        // Assert
+        assert!(!loaded_before_save);
+        assert!(loaded_after_save);
+        assert_eq!(loaded_state, state);
+        assert!(!loaded_after_remove);
+    }
+
     #[tokio::test]
     async fn test_state_new_exists() {
         // Arrange
@@ -239,6 +370,117 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_local_state_backend_save_replaces_state_file() {
+        // Arrange
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let state_file_path = dir.path().join("state.json");
+        let state_backend = LocalStateBackend::<TestState>::new(
+            state_file_path
+                .to_str()
+                .expect("Failed to convert path to str"),
+        );
+
+        // Act
+        for value in ["old", "new"] {
+            state_backend
+                .save(&TestState {
+                    value: value.to_string(),
+                })
+                .await
+                .expect("Failed to save to state file");
+        }
+
+        // Assert
+        let (state, loaded) = state_backend
+            .load()
+            .await
+            .expect("Failed to load from state backend");
+        assert!(loaded);
+        assert_eq!(state.value, "new");
+
+        let file_names: Vec<_> = fs::read_dir(dir.path())
+            .expect("Failed to read temp dir")
+            .map(|entry| entry.expect("Failed to read dir entry").file_name())
+            .collect();
+        assert_eq!(file_names, vec!["state.json"]);
+    }
+
+    #[tokio::test]
+    async fn test_local_state_backend_save_creates_parent_dirs() {
+        // Arrange
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let state_file_path = dir.path().join("states").join("prod").join("state.json");
+        let state_backend = LocalStateBackend::<TestState>::new(
+            state_file_path
+                .to_str()
+                .expect("Failed to convert path to str"),
+        );
+
+        // Act
+        state_backend
+            .save(&TestState {
+                value: "test".to_string(),
+            })
+            .await
+            .expect("Failed to save to state file");
+
+        // Assert
+        let (state, loaded) = state_backend
+            .load()
+            .await
+            .expect("Failed to load from state backend");
+        assert!(loaded);
+        assert_eq!(state.value, "test");
+    }
+
+    #[tokio::test]
+    async fn test_local_state_backend_failed_rename_keeps_state() {
+        // Arrange
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let state_file_path = dir.path().join("state.json");
+        let state_file_path = state_file_path
+            .to_str()
+            .expect("Failed to convert path to str");
+        LocalStateBackend::<TestState>::new(state_file_path)
+            .save(&TestState {
+                value: "old".to_string(),
+            })
+            .await
+            .expect("Failed to save to state file");
+
+        let state_backend = LocalStateBackend::<TestState> {
+            rename: |_, _| Err(std::io::Error::other("disk is full")),
+            ..LocalStateBackend::new(state_file_path)
+        };
+
+        // Act
+        let result = state_backend
+            .save(&TestState {
+                value: "new".to_string(),
+            })
+            .await;
+
+        // Assert
+        assert_eq!(
+            result.expect_err("Expected rename error").to_string(),
+            "disk is full"
+        );
+
+        let (state, loaded) = state_backend
+            .load()
+            .await
+            .expect("Failed to load from state backend");
+        assert!(loaded);
+        assert_eq!(state.value, "old");
+
+        let file_names: Vec<_> = fs::read_dir(dir.path())
+            .expect("Failed to read temp dir")
+            .map(|entry| entry.expect("Failed to read dir entry").file_name())
+            .collect();
+        assert_eq!(file_names, vec!["state.json"]);
+    }
+
     #[test]
     fn test_s3_backend_new() {
         let state_backend = S3StateBackend::<TestState>::new("region", "bucket", "key");
@@ -0,0 +1,179 @@
+use std::path::Path;
+use std::process::Command;
+
+use oct_config::Service;
+
+/// Builds service images locally without pushing them
+pub(crate) trait ImageBuilder: Send + Sync {
+    fn build(
+        &self,
+        dockerfile_path: &Path,
+        image: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Builds images with `docker build`, the Dockerfile directory is the build
+/// context
+pub(crate) struct DockerImageBuilder;
+
+impl ImageBuilder for DockerImageBuilder {
+    fn build(
+        &self,
+        dockerfile_path: &Path,
+        image: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let context_path = dockerfile_path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+
+        let output = Command::new("docker")
+            .arg("build")
+            .arg("--file")
+            .arg(dockerfile_path)
+            .args(["--tag", image])
+            .arg(context_path)
+            .output()?;
+
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Builds the images of all the services with a Dockerfile, failing on the
+/// first broken build so no infrastructure is provisioned for it
+///
+/// `dockerfile_path` is relative to the `config_dir`, not to the current
+/// directory
+pub(crate) fn check_builds(
+    services: &[Service],
+    config_dir: &Path,
+    builder: &dyn ImageBuilder,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    for service in services {
+        let Some(dockerfile_path) = &service.dockerfile_path else {
+            continue;
+        };
+
+        log::info!("Building image of service {}", service.name);
+
+        builder
+            .build(&config_dir.join(dockerfile_path), &service.image)
+            .map_err(|e| format!("Failed to build image of service {}: {e}", service.name))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::path::PathBuf;
+    use std::sync::Mutex;
+
+    use super::*;
+
+    /// Records the built images with their Dockerfile and fails the build
+    /// of `failing_image`
+    #[derive(Default)]
+    pub(crate) struct MockImageBuilder {
+        pub(crate) failing_image: Option<String>,
+        pub(crate) built_images: Mutex<Vec<String>>,
+        pub(crate) dockerfile_paths: Mutex<Vec<PathBuf>>,
+    }
+
+    impl ImageBuilder for MockImageBuilder {
+        fn build(
+            &self,
+            dockerfile_path: &Path,
+            image: &str,
+        ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            self.built_images
+                .lock()
+                .expect("Failed to lock built images")
+                .push(image.to_string());
+            self.dockerfile_paths
+                .lock()
+                .expect("Failed to lock Dockerfile paths")
+                .push(dockerfile_path.to_path_buf());
+
+            if self.failing_image.as_deref() == Some(image) {
+                return Err("COPY failed: file not found".into());
+            }
+
+            Ok(())
+        }
+    }
+
+    fn get_service(name: &str, dockerfile_path: Option<&str>) -> Service {
+        Service {
+            name: name.to_string(),
+            image: format!("{name}:latest"),
+            dockerfile_path: dockerfile_path.map(str::to_string),
+            cpus: 250,
+            memory: 64,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_check_builds_skips_services_without_dockerfile() {
+        // Arrange
+        let services = vec![
+            get_service("api", Some("api/Dockerfile")),
+            get_service("db", None),
+        ];
+        let builder = MockImageBuilder::default();
+
+        // Act
+        let result = check_builds(&services, Path::new("/project"), &builder);
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(
+            *builder
+                .built_images
+                .lock()
+                .expect("Failed to lock built images"),
+            vec![String::from("api:latest")]
+        );
+        assert_eq!(
+            *builder
+                .dockerfile_paths
+                .lock()
+                .expect("Failed to lock Dockerfile paths"),
+            vec![PathBuf::from("/project/api/Dockerfile")]
+        );
+    }
+
+    #[test]
+    fn test_check_builds_fails_fast() {
+        // Arrange
+        let services = vec![
+            get_service("api", Some("api/Dockerfile")),
+            get_service("web", Some("web/Dockerfile")),
+        ];
+        let builder = MockImageBuilder {
+            failing_image: Some(String::from("api:latest")),
+            ..MockImageBuilder::default()
+        };
+
+        // Act
+        let result = check_builds(&services, Path::new("."), &builder);
+
+        // Assert
+        assert_eq!(
+            result.expect_err("Expected build error").to_string(),
+            "Failed to build image of service api: COPY failed: file not found"
+        );
+        assert_eq!(
+            *builder
+                .built_images
+                .lock()
+                .expect("Failed to lock built images"),
+            vec![String::from("api:latest")]
+        );
+    }
+}
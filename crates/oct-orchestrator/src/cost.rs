@@ -0,0 +1,106 @@
+/// Hours in an average month used for the monthly estimate
+const HOURS_PER_MONTH: f64 = 730.0;
+
+/// On-demand Linux hourly prices in USD, the same in `us-east-1` and
+/// `us-west-2`
+const US_HOURLY_PRICES: [(&str, f64); 22] = [
+    ("t2.nano", 0.0058),
+    ("t2.micro", 0.0116),
+    ("t2.small", 0.023),
+    ("t2.medium", 0.0464),
+    ("t2.large", 0.0928),
+    ("t2.xlarge", 0.1856),
+    ("t2.2xlarge", 0.3712),
+    ("t3.nano", 0.0052),
+    ("t3.micro", 0.0104),
+    ("t3.small", 0.0208),
+    ("t3.medium", 0.0416),
+    ("t3.large", 0.0832),
+    ("t3.xlarge", 0.1664),
+    ("t3.2xlarge", 0.3328),
+    ("m5.large", 0.096),
+    ("m5.xlarge", 0.192),
+    ("m5.2xlarge", 0.384),
+    ("m5.4xlarge", 0.768),
+    ("c5.large", 0.085),
+    ("c5.xlarge", 0.17),
+    ("c5.2xlarge", 0.34),
+    ("c5.4xlarge", 0.68),
+];
+
+/// Hourly price of the instance type in the region, `None` when the region
+/// or the instance type is missing in the price table
+fn get_hourly_price(region: &str, instance_type: &str) -> Option<f64> {
+    let prices = match region {
+        "us-east-1" | "us-west-2" => &US_HOURLY_PRICES,
+        _ => return None,
+    };
+
+    prices
+        .iter()
+        .find(|(name, _)| *name == instance_type)
+        .map(|(_, price)| *price)
+}
+
+/// Rough monthly cost of running the `instance_types` VMs all month,
+/// `None` when any of them has no known price
+pub(crate) fn estimate_monthly_cost(instance_types: &[&str], region: &str) -> Option<f64> {
+    instance_types
+        .iter()
+        .map(|instance_type| get_hourly_price(region, instance_type))
+        .sum::<Option<f64>>()
+        .map(|hourly_price| hourly_price * HOURS_PER_MONTH)
+}
+
+/// Readable monthly cost estimate of the VMs
+pub(crate) fn format_cost_estimate(instance_types: &[&str], region: &str) -> String {
+    let estimate = match estimate_monthly_cost(instance_types, region) {
+        Some(monthly_cost) => format!("${monthly_cost:.2}"),
+        None => String::from("unknown"),
+    };
+
+    format!(
+        "Estimated monthly cost: {estimate} ({} instance(s) in {region} × {HOURS_PER_MONTH} h)\n\
+         Data transfer, EBS volumes and ECR storage are not included\n",
+        instance_types.len()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_monthly_cost() {
+        // Act
+        let monthly_cost = estimate_monthly_cost(&["t2.micro", "t2.micro"], "us-west-2");
+
+        // Assert
+        let monthly_cost = monthly_cost.expect("Failed to estimate cost");
+        assert!((monthly_cost - 16.936).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_format_cost_estimate() {
+        // Act
+        let output = format_cost_estimate(&["t2.micro", "t2.micro"], "us-west-2");
+
+        // Assert
+        assert_eq!(
+            output,
+            "Estimated monthly cost: $16.94 (2 instance(s) in us-west-2 × 730 h)\n\
+             Data transfer, EBS volumes and ECR storage are not included\n"
+        );
+    }
+
+    #[test]
+    fn test_format_cost_estimate_unknown_price() {
+        // Act
+        let unknown_type_output = format_cost_estimate(&["t2.micro", "x9.huge"], "us-west-2");
+        let unknown_region_output = format_cost_estimate(&["t2.micro"], "eu-central-1");
+
+        // Assert
+        assert!(unknown_type_output.starts_with("Estimated monthly cost: unknown"));
+        assert!(unknown_region_output.starts_with("Estimated monthly cost: unknown"));
+    }
+}
@@ -1,9 +1,108 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use oct_cloud::aws::ami::AmiResolver;
+use oct_cloud::aws::secrets::{SecretReference, SecretsResolver};
 use oct_cloud::aws::types::InstanceType;
 use oct_cloud::infra;
 use petgraph::Graph;
+use tokio::sync::mpsc;
 
 pub mod backend;
+mod build;
+mod cost;
+mod doctor;
+mod metrics;
+mod notify;
+mod rolling;
+mod teardown;
 pub mod user_state;
+mod watch;
+
+/// Default time limit for a single deployment step
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Region used when neither CLI, environment nor config sets one
+pub const DEFAULT_REGION: &str = "us-west-2";
+
+/// Options of the [`OrchestratorWithGraph::genesis_with_options`] step
+#[derive(Debug, Clone)]
+pub struct GenesisOptions {
+    /// Time limit for the infra deployment
+    pub timeout: Duration,
+    /// Deploy only the shared networking without VMs when no services are
+    /// configured, otherwise genesis fails
+    pub allow_no_services: bool,
+    /// Build the service images locally before provisioning any infra and
+    /// fail if any build fails, the images are not pushed
+    pub build_check: bool,
+    /// Directory the service `dockerfile_path`s are relative to, see
+    /// [`get_config_dir`]
+    pub config_dir: PathBuf,
+    /// Leader VM instance type used instead of the one selected from the
+    /// service resources
+    pub instance_type: Option<InstanceType>,
+}
+
+impl Default for GenesisOptions {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_TIMEOUT,
+            allow_no_services: false,
+            build_check: false,
+            config_dir: PathBuf::from("."),
+            instance_type: None,
+        }
+    }
+}
+
+/// Options of the [`OrchestratorWithGraph::apply_with_options`] step
+#[derive(Debug, Clone, Copy)]
+pub struct ApplyOptions {
+    /// Time limit for waiting for `oct-ctl`
+    pub timeout: Duration,
+    /// Recreate all services even if their configuration is unchanged
+    pub force_recreate: bool,
+    /// Keep the running services missing in the config, used to apply
+    /// a subset of the services
+    pub partial: bool,
+}
+
+impl Default for ApplyOptions {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_TIMEOUT,
+            force_recreate: false,
+            partial: false,
+        }
+    }
+}
+
+/// Options of the [`OrchestratorWithGraph::destroy_with_options`] step
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DestroyOptions {
+    /// Destroy independent resources concurrently
+    pub parallel: bool,
+}
+
+/// States printed by [`OrchestratorWithGraph::show_state`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateScope {
+    All,
+    Infra,
+    User,
+}
+
+/// Result of [`OrchestratorWithGraph::doctor`]
+#[derive(Debug)]
+pub struct DoctorReport {
+    /// Readable pass/fail line per check
+    pub checklist: String,
+    /// All the checks passed
+    pub passed: bool,
+}
 
 pub struct OrchestratorWithGraph;
 
@@ -13,25 +112,137 @@ impl OrchestratorWithGraph {
         &self,
         config: &oct_config::Config,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.genesis_with_timeout(config, DEFAULT_TIMEOUT).await
+    }
+
+    /// Same as [`Self::genesis`] but aborts the infra deployment after
+    /// `timeout`, saving the resources created so far to the state
+    pub async fn genesis_with_timeout(
+        &self,
+        config: &oct_config::Config,
+        timeout: Duration,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let options = GenesisOptions {
+            timeout,
+            ..GenesisOptions::default()
+        };
+
+        self.genesis_with_options(config, options).await
+    }
+
+    /// Same as [`Self::genesis`] but with the explicit [`GenesisOptions`]
+    ///
+    /// Deployment timing metrics are emitted to `Project.metrics` when it is
+    /// configured
+    pub async fn genesis_with_options(
+        &self,
+        config: &oct_config::Config,
+        options: GenesisOptions,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.genesis_with_image_builder(config, options, &build::DockerImageBuilder)
+            .await
+    }
+
+    async fn genesis_with_image_builder(
+        &self,
+        config: &oct_config::Config,
+        options: GenesisOptions,
+        image_builder: &dyn build::ImageBuilder,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        check_provider(config.project.provider)?;
+
+        if options.build_check {
+            build::check_builds(&config.project.services, &options.config_dir, image_builder)?;
+        }
+
         let infra_state_backend =
             backend::get_state_backend::<infra::state::State>(&config.project.state_backend);
+        // Resources imported before genesis stay in the new state
+        let (previous_state, _loaded) = infra_state_backend.load().await?;
 
         // In the current version there is only one Leader node which serves
         // all user services, so it's okay to get instance type from the user services
         // graph
         let user_services_graph = config.to_graph().map_err(|e| e.to_string())?;
-        let instance_type = get_instance_type(&user_services_graph)?;
+        let instance_type = get_genesis_instance_type(
+            &user_services_graph,
+            options.allow_no_services,
+            options.instance_type,
+        )?;
 
-        let genesis_spec_graph = infra::graph::GraphManager::get_genesis_graph(instance_type);
+        let oct_ctl_token = match instance_type {
+            Some(_) => Some(get_or_create_oct_ctl_token(config).await?),
+            None => None,
+        };
 
-        let infra_graph_manager = infra::graph::GraphManager::new().await;
-        let (resource_graph, _vm) = infra_graph_manager
-            .deploy_genesis_graph(&genesis_spec_graph)
-            .await?;
+        let leader_vm = match instance_type {
+            Some(instance_type) => {
+                let ami_resolver = AmiResolver::new(get_region(config).to_string()).await;
 
-        let state = infra::state::State::from_graph(&resource_graph);
+                Some(infra::graph::LeaderVmSpec {
+                    instance_type,
+                    ami: ami_resolver.resolve().await?,
+                })
+            }
+            None => None,
+        };
+
+        let existing_network = config
+            .project
+            .vpc_id
+            .as_ref()
+            .zip(config.project.subnet_ids.first())
+            .map(|(vpc_id, subnet_id)| infra::graph::ExistingNetworkSpec {
+                vpc_id: vpc_id.clone(),
+                subnet_id: subnet_id.clone(),
+            });
+
+        let genesis_spec_graph = infra::graph::GraphManager::get_genesis_graph(
+            leader_vm,
+            config.project.oct_ctl_sha256.as_deref(),
+            oct_ctl_token.as_deref(),
+            get_region(config),
+            existing_network.as_ref(),
+            &get_project_infra_spec(config),
+        );
+
+        let infra_graph_manager = infra::graph::GraphManager::new(get_region(config)).await;
+
+        let started_at = std::time::Instant::now();
+
+        let mut resource_graph = Graph::new();
+        let (events_sender, events_receiver) = mpsc::channel(16);
+        let (deploy_result, deploy_metrics) = tokio::join!(
+            with_timeout(
+                options.timeout,
+                infra_graph_manager.deploy_genesis_graph_into(
+                    &genesis_spec_graph,
+                    &mut resource_graph,
+                    Some(events_sender),
+                ),
+            ),
+            metrics::DeployMetrics::collect(events_receiver),
+        );
+
+        if let Some(metrics_backend) = &config.project.metrics {
+            let samples = deploy_metrics.samples(
+                &config.project.name,
+                started_at.elapsed(),
+                deploy_result.is_ok(),
+            );
+
+            metrics::emit(
+                metrics::get_metrics_sink(metrics_backend).as_ref(),
+                &samples,
+            );
+        }
+
+        let mut state = infra::state::State::from_graph(&resource_graph);
+        state.merge_previous(previous_state);
         let () = infra_state_backend.save(&state).await?;
 
+        let _vm = deploy_result?;
+
         Ok(())
     }
 
@@ -39,6 +250,63 @@ impl OrchestratorWithGraph {
         &self,
         config: &oct_config::Config,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.apply_with_timeout(config, DEFAULT_TIMEOUT).await
+    }
+
+    /// Same as [`Self::apply`] but aborts waiting for `oct-ctl` after `timeout`
+    pub async fn apply_with_timeout(
+        &self,
+        config: &oct_config::Config,
+        timeout: Duration,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let options = ApplyOptions {
+            timeout,
+            ..ApplyOptions::default()
+        };
+
+        self.apply_with_options(config, options).await
+    }
+
+    /// Same as [`Self::apply`] but with the explicit [`ApplyOptions`]
+    ///
+    /// The result is posted to `Project.notify_webhook` when it is configured
+    pub async fn apply_with_options(
+        &self,
+        config: &oct_config::Config,
+        options: ApplyOptions,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.apply_with_rollout(config, options, &rolling::OctCtlRollout)
+            .await
+    }
+
+    async fn apply_with_rollout(
+        &self,
+        config: &oct_config::Config,
+        options: ApplyOptions,
+        rollout: &dyn rolling::Rollout,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let started_at = std::time::Instant::now();
+
+        let result = self.apply_services(config, options, rollout).await;
+
+        if let Some(url) = &config.project.notify_webhook {
+            let notification =
+                notify::DeployNotification::new(config, started_at.elapsed(), &result);
+
+            notify::send(url, &notification).await;
+        }
+
+        result
+    }
+
+    async fn apply_services(
+        &self,
+        config: &oct_config::Config,
+        options: ApplyOptions,
+        rollout: &dyn rolling::Rollout,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        check_provider(config.project.provider)?;
+
         let infra_state_backend =
             backend::get_state_backend::<infra::state::State>(&config.project.state_backend);
         let (infra_state, _loaded) = infra_state_backend.load().await?;
@@ -46,16 +314,116 @@ impl OrchestratorWithGraph {
         let vms = infra_state.get_vms();
         let leader_vm = vms.first().ok_or("No VMs available")?;
 
-        let oct_ctl_client = oct_ctl_sdk::Client::new(leader_vm.public_ip.clone());
-        let () = oct_ctl_client.apply(config.clone()).await?;
+        let secret_envs = resolve_secrets(config).await?;
+        let updater = rollout.updater(config, &secret_envs, options);
 
-        Ok(())
+        // Instances behind a load balancer are updated one by one,
+        // so the others keep serving the requests
+        if let Some(load_balancer) = infra_state.get_load_balancer() {
+            let target_group = rollout
+                .target_group(get_state_region(&infra_state, config), load_balancer)
+                .await;
+
+            return rolling::rolling_update(&vms, target_group.as_ref(), updater.as_ref()).await;
+        }
+
+        updater.update(leader_vm).await
     }
 
-    pub async fn destroy(
+    /// Applies the services, then re-applies them whenever one of
+    /// `config_paths` or a service Dockerfile directory changes, until the
+    /// process is stopped
+    ///
+    /// Changes are debounced and trigger a redeploy only when at least one
+    /// service is affected, `load_config` reloads the config after each change.
+    /// The images of the affected services with a Dockerfile are rebuilt and
+    /// only the affected services are applied
+    pub async fn watch_apply<F>(
+        &self,
+        config_paths: &[String],
+        options: ApplyOptions,
+        load_config: F,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+    where
+        F: Fn() -> Result<oct_config::Config, Box<dyn std::error::Error + Send + Sync>>,
+    {
+        watch::watch_apply(
+            self,
+            config_paths,
+            options,
+            load_config,
+            &build::DockerImageBuilder,
+        )
+        .await
+    }
+
+    /// Stops a single user service, keeping the infrastructure and the other
+    /// services running
+    pub async fn destroy_service(
+        &self,
+        config: &oct_config::Config,
+        service_name: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        check_provider(config.project.provider)?;
+
+        let infra_state_backend =
+            backend::get_state_backend::<infra::state::State>(&config.project.state_backend);
+        let (infra_state, _loaded) = infra_state_backend.load().await?;
+
+        let vms = infra_state.get_vms();
+        let leader_vm = vms.first().ok_or("No VMs available")?;
+
+        let oct_ctl_client = get_oct_ctl_client(config, leader_vm.public_ip.clone()).await?;
+        match oct_ctl_client.remove_service(service_name).await? {
+            oct_ctl_sdk::RemoveOutcome::Removed => Ok(()),
+            oct_ctl_sdk::RemoveOutcome::NotFound => {
+                Err(format!("Service '{service_name}' is not found").into())
+            }
+        }
+    }
+
+    /// Runs a one-off command in a running service container, e.g. a
+    /// database migration, see [`select_container`] for the `replica` values
+    pub async fn exec_service(
+        &self,
+        config: &oct_config::Config,
+        service_name: &str,
+        replica: Option<u32>,
+        args: &[String],
+    ) -> Result<oct_ctl_sdk::ExecOutput, Box<dyn std::error::Error + Send + Sync>> {
+        check_provider(config.project.provider)?;
+
+        let container_name = select_container(config, service_name, replica)?;
+
+        let infra_state_backend =
+            backend::get_state_backend::<infra::state::State>(&config.project.state_backend);
+        let (infra_state, _loaded) = infra_state_backend.load().await?;
+
+        let vms = infra_state.get_vms();
+        let leader_vm = vms.first().ok_or("No VMs available")?;
+
+        let oct_ctl_client = get_oct_ctl_client(config, leader_vm.public_ip.clone()).await?;
+
+        oct_ctl_client.exec(&container_name, args).await
+    }
+
+    /// Writes the service container logs to `output`, `follow` streams the
+    /// new output until the connection is closed, see [`select_container`]
+    /// for the `replica` values
+    pub async fn service_logs(
         &self,
         config: &oct_config::Config,
+        service_name: &str,
+        replica: Option<u32>,
+        since: Option<Duration>,
+        tail: Option<u32>,
+        follow: bool,
+        output: &mut (impl std::io::Write + Send),
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        check_provider(config.project.provider)?;
+
+        let container_name = select_container(config, service_name, replica)?;
+
         let infra_state_backend =
             backend::get_state_backend::<infra::state::State>(&config.project.state_backend);
         let (infra_state, _loaded) = infra_state_backend.load().await?;
@@ -63,35 +431,608 @@ impl OrchestratorWithGraph {
         let vms = infra_state.get_vms();
         let leader_vm = vms.first().ok_or("No VMs available")?;
 
-        let oct_ctl_client = oct_ctl_sdk::Client::new(leader_vm.public_ip.clone());
-        let () = oct_ctl_client.destroy().await?;
+        let oct_ctl_client = get_oct_ctl_client(config, leader_vm.public_ip.clone()).await?;
+
+        oct_ctl_client
+            .get_container_logs(&container_name, since, tail, follow, output)
+            .await
+    }
+
+    pub async fn destroy(
+        &self,
+        config: &oct_config::Config,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.destroy_with_options(config, DestroyOptions::default())
+            .await
+    }
+
+    /// Same as [`Self::destroy`] with configurable [`DestroyOptions`]
+    pub async fn destroy_with_options(
+        &self,
+        config: &oct_config::Config,
+        options: DestroyOptions,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        check_provider(config.project.provider)?;
+
+        let infra_state_backend =
+            backend::get_state_backend::<infra::state::State>(&config.project.state_backend);
+        let (infra_state, loaded) = infra_state_backend.load().await?;
+
+        if !loaded {
+            return Err("Infra state is not found".into());
+        }
+
+        let graph_manager =
+            infra::graph::GraphManager::new(get_state_region(&infra_state, config)).await;
+
+        // User containers are stopped on every instance first, so none is
+        // left running when an instance outlives the teardown
+        let stopper = teardown::OctCtlServicesStopper { config };
+        let destroyer = teardown::GraphInfraDestroyer {
+            graph_manager: &graph_manager,
+            infra_state: &infra_state,
+            infra_state_backend: infra_state_backend.as_ref(),
+            parallel: options.parallel,
+        };
+
+        teardown::teardown(&infra_state.get_vms(), &stopper, &destroyer).await
+    }
+
+    /// Returns the VMs genesis would deploy with their rough monthly cost,
+    /// nothing is deployed
+    pub fn plan(
+        &self,
+        config: &oct_config::Config,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let user_services_graph = config.to_graph()?;
+        let instance_type = get_genesis_instance_type(&user_services_graph, true, None)?;
+
+        Ok(format_plan(instance_type, get_region(config)))
+    }
+
+    /// Checks the local prerequisites of the deployments: a container
+    /// manager, the AWS CLI, resolvable AWS credentials and network access
+    /// to AWS in `region`
+    pub async fn doctor(&self, region: &str) -> DoctorReport {
+        let results = doctor::run_checks(
+            &doctor::SystemCommandRunner,
+            &doctor::AwsCredentialsResolver,
+            region,
+        )
+        .await;
+
+        DoctorReport {
+            checklist: doctor::format_checklist(&results),
+            passed: results.iter().all(|result| result.passed),
+        }
+    }
+
+    /// Returns a readable summary of the deployed infra and user states
+    pub async fn show_state(
+        &self,
+        config: &oct_config::Config,
+        scope: StateScope,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let mut output = String::new();
+
+        if scope != StateScope::User {
+            let infra_state_backend =
+                backend::get_state_backend::<infra::state::State>(&config.project.state_backend);
+            let (infra_state, loaded) = infra_state_backend.load().await?;
+
+            output.push_str(&format_infra_state(&infra_state, loaded));
+        }
+
+        if scope != StateScope::Infra {
+            let user_state_backend = backend::get_state_backend::<user_state::UserState>(
+                &config.project.user_state_backend,
+            );
+            let (user_state, loaded) = user_state_backend.load().await?;
+
+            output.push_str(&format_user_state(&user_state, loaded));
+        }
+
+        Ok(output)
+    }
+
+    /// Removes the resource from the infra state without destroying it,
+    /// used when the resource was deleted outside of `oct`
+    pub async fn remove_state_resource(
+        &self,
+        config: &oct_config::Config,
+        name: &str,
+        force: bool,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let infra_state_backend =
+            backend::get_state_backend::<infra::state::State>(&config.project.state_backend);
+        let (mut infra_state, loaded) = infra_state_backend.load().await?;
+
+        if !loaded {
+            return Err("Infra state is not found".into());
+        }
+
+        infra_state.remove_resource(name, force)?;
+        infra_state_backend.save(&infra_state).await?;
+
+        Ok(())
+    }
+
+    /// Describes a resource created outside of `oct` and adds it to the infra
+    /// state with the given dependencies, so the next deployments manage it
+    ///
+    /// Returns the state name of the imported resource
+    pub async fn import_state_resource(
+        &self,
+        config: &oct_config::Config,
+        resource_type: &str,
+        id: &str,
+        dependencies: Vec<String>,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let infra_state_backend =
+            backend::get_state_backend::<infra::state::State>(&config.project.state_backend);
+        let (mut infra_state, _loaded) = infra_state_backend.load().await?;
+
+        let region = get_state_region(&infra_state, config).to_string();
+        let graph_manager = infra::graph::GraphManager::new(&region).await;
+        let resource = graph_manager
+            .describe_resource(resource_type, id, &region)
+            .await?;
+
+        let name = infra_state.insert_resource(resource, dependencies)?;
+        infra_state_backend.save(&infra_state).await?;
+
+        Ok(name)
+    }
+
+    /// Resolves the public IP of a deployed instance from the infra state,
+    /// see [`select_instance`] for the accepted `target` values
+    pub async fn resolve_instance_ip(
+        &self,
+        config: &oct_config::Config,
+        target: Option<&str>,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let infra_state_backend =
+            backend::get_state_backend::<infra::state::State>(&config.project.state_backend);
+        let (infra_state, loaded) = infra_state_backend.load().await?;
+
+        if !loaded {
+            return Err("Infra state is not found".into());
+        }
+
+        let public_ips: Vec<String> = infra_state
+            .get_vms()
+            .into_iter()
+            .map(|vm| vm.public_ip)
+            .collect();
+
+        select_instance(&public_ips, target)
+    }
+}
+
+/// Selects the container of a service replica by its index, the index may
+/// be omitted for a service with a single replica
+fn select_container(
+    config: &oct_config::Config,
+    service_name: &str,
+    replica: Option<u32>,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let services = config.services_by_name()?;
+    let service = services
+        .get(service_name)
+        .ok_or_else(|| format!("Service '{service_name}' is not found in config"))?;
+
+    let container_names = service.container_names();
+
+    let index = match replica {
+        Some(replica) => usize::try_from(replica)?,
+        None if container_names.len() == 1 => 0,
+        None => {
+            return Err(format!(
+                "Service '{service_name}' has {} replicas, select one by its index",
+                container_names.len()
+            )
+            .into());
+        }
+    };
+
+    match container_names.get(index) {
+        Some(container_name) => Ok(container_name.clone()),
+        None => Err(format!(
+            "Service '{service_name}' has no replica {index}, replicas are 0..{}",
+            container_names.len()
+        )
+        .into()),
+    }
+}
+
+/// Selects an instance by its index, full IP or unique IP prefix,
+/// the only instance is selected when `target` is not set
+///
+/// Available instances are listed in the error when the target is
+/// ambiguous or does not match any instance
+fn select_instance(
+    public_ips: &[String],
+    target: Option<&str>,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    if public_ips.is_empty() {
+        return Err("No instances are deployed".into());
+    }
+
+    let Some(target) = target else {
+        return match public_ips {
+            [public_ip] => Ok(public_ip.clone()),
+            _ => Err(format!(
+                "Multiple instances are deployed, pass an index or IP:\n{}",
+                format_instance_list(public_ips.iter().enumerate())
+            )
+            .into()),
+        };
+    };
+
+    if let Ok(index) = target.parse::<usize>() {
+        return public_ips.get(index).cloned().ok_or_else(|| {
+            format!(
+                "Instance index {index} is out of range, available instances:\n{}",
+                format_instance_list(public_ips.iter().enumerate())
+            )
+            .into()
+        });
+    }
+
+    if public_ips.iter().any(|public_ip| public_ip == target) {
+        return Ok(target.to_string());
+    }
+
+    let matches: Vec<(usize, &String)> = public_ips
+        .iter()
+        .enumerate()
+        .filter(|(_, public_ip)| public_ip.starts_with(target))
+        .collect();
+
+    match matches.as_slice() {
+        [(_, public_ip)] => Ok((*public_ip).clone()),
+        [] => Err(format!(
+            "Instance '{target}' is not found, available instances:\n{}",
+            format_instance_list(public_ips.iter().enumerate())
+        )
+        .into()),
+        _ => Err(format!(
+            "Instance '{target}' is ambiguous, matching instances:\n{}",
+            format_instance_list(matches)
+        )
+        .into()),
+    }
+}
+
+/// Formats instances as `  <index> <ip>` lines, `index` is the position of
+/// the instance among all the deployed ones
+fn format_instance_list<'a>(instances: impl IntoIterator<Item = (usize, &'a String)>) -> String {
+    instances
+        .into_iter()
+        .map(|(index, public_ip)| format!("  {index} {public_ip}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Formats VPCs and VMs of the infra state
+fn format_infra_state(infra_state: &infra::state::State, loaded: bool) -> String {
+    if !loaded {
+        return String::from("Infra state: not found\n");
+    }
+
+    let mut output = String::from("Infra state:\n");
+
+    for vpc in infra_state.get_vpcs() {
+        output.push_str(&format!(
+            "  VPC {} ({}, {})\n",
+            vpc.id, vpc.region, vpc.cidr_block
+        ));
+    }
+
+    for vm in infra_state.get_vms() {
+        output.push_str(&format!(
+            "  VM {} {} {}\n",
+            vm.id,
+            vm.public_ip,
+            vm.instance_type.as_str()
+        ));
+    }
+
+    output
+}
+
+/// Returns the `oct-ctl` bearer token stored in the user state, generating
+/// and storing a new one only when there is none. The token is passed to the
+/// Leader VM in its user data, reusing it keeps a VM that survived a failed
+/// or repeated genesis reachable
+async fn get_or_create_oct_ctl_token(
+    config: &oct_config::Config,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let user_state_backend =
+        backend::get_state_backend::<user_state::UserState>(&config.project.user_state_backend);
+    let (mut user_state, _loaded) = user_state_backend.load().await?;
+
+    if let Some(oct_ctl_token) = user_state.oct_ctl_token {
+        return Ok(oct_ctl_token);
+    }
+
+    let oct_ctl_token = uuid::Uuid::new_v4().simple().to_string();
+    user_state.oct_ctl_token = Some(oct_ctl_token.clone());
+
+    user_state_backend.save(&user_state).await?;
+
+    Ok(oct_ctl_token)
+}
+
+/// `oct-ctl` client authenticated with the token stored in the user state,
+/// VMs created before the token was introduced accept requests without it
+///
+/// The client talks plain HTTP: the VMs are not provisioned with an
+/// `oct-ctl` TLS certificate yet, so there is no CA to pin with
+/// `oct_ctl_sdk::Client::new_https`
+async fn get_oct_ctl_client(
+    config: &oct_config::Config,
+    public_ip: String,
+) -> Result<oct_ctl_sdk::Client, Box<dyn std::error::Error + Send + Sync>> {
+    let user_state_backend =
+        backend::get_state_backend::<user_state::UserState>(&config.project.user_state_backend);
+    let (user_state, _loaded) = user_state_backend.load().await?;
+
+    let oct_ctl_client = oct_ctl_sdk::Client::new(public_ip);
+
+    Ok(match user_state.oct_ctl_token {
+        Some(oct_ctl_token) => oct_ctl_client.with_token(oct_ctl_token),
+        None => oct_ctl_client,
+    })
+}
+
+/// Formats the planned Leader VM and the cost estimate
+fn format_plan(instance_type: Option<InstanceType>, region: &str) -> String {
+    let Some(instance_type) = instance_type else {
+        return String::from("Plan: no VMs, only the shared networking is deployed\n");
+    };
+
+    format!(
+        concat!("Plan:\n", "  Leader VM {} in {}\n", "{}"),
+        instance_type.as_str(),
+        region,
+        cost::format_cost_estimate(&[instance_type.as_str()], region)
+    )
+}
+
+/// Formats instances and their services of the user state, sorted by name
+fn format_user_state(user_state: &user_state::UserState, loaded: bool) -> String {
+    if !loaded {
+        return String::from("User state: not found\n");
+    }
+
+    let mut output = String::from("User state:\n");
+
+    let mut instances: Vec<_> = user_state.instances.iter().collect();
+    instances.sort_by_key(|(public_ip, _)| *public_ip);
+
+    for (public_ip, instance) in instances {
+        output.push_str(&format!(
+            "  Instance {public_ip} ({} millicores, {} MB)\n",
+            instance.cpus, instance.memory
+        ));
+
+        let mut services: Vec<_> = instance.services.values().collect();
+        services.sort_by_key(|service| &service.name);
 
-        let mut resource_graph = infra_state.to_graph();
+        for service in services {
+            output.push_str(&format!(
+                "    {} {} ({} millicores, {} MB)\n",
+                service.name, service.image, service.cpus, service.memory
+            ));
 
-        let graph_manager = infra::graph::GraphManager::new().await;
-        let destroy_result = graph_manager.destroy(&mut resource_graph).await;
+            if let Some(description) = &service.description {
+                output.push_str(&format!("      {description}\n"));
+            }
 
-        match destroy_result {
-            Ok(()) => {
-                infra_state_backend.remove().await?;
+            let mut labels: Vec<_> = service.labels.iter().collect();
+            labels.sort();
 
-                Ok(())
+            for (key, value) in labels {
+                output.push_str(&format!("      {key}={value}\n"));
             }
-            Err(e) => {
-                log::error!("Failed to destroy: {e}");
+        }
+    }
+
+    output
+}
+
+/// Directory of the first config file, service `dockerfile_path`s are
+/// relative to it, the current directory for a config read from stdin
+pub fn get_config_dir<P: AsRef<Path>>(config_paths: &[P]) -> &Path {
+    config_paths
+        .first()
+        .and_then(|config_path| config_path.as_ref().parent())
+        .filter(|config_dir| !config_dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."))
+}
 
-                let current_infra_state = infra::state::State::from_graph(&resource_graph);
+/// Resolves deployment region, the precedence is
+/// CLI flag > `AWS_REGION` env > config > [`DEFAULT_REGION`]
+pub fn resolve_region(flag: Option<&str>, env: Option<&str>, config: Option<&str>) -> String {
+    flag.or(env)
+        .or(config)
+        .unwrap_or(DEFAULT_REGION)
+        .to_string()
+}
+
+/// Returns project region from config or [`DEFAULT_REGION`]
+fn get_region(config: &oct_config::Config) -> &str {
+    config.project.region.as_deref().unwrap_or(DEFAULT_REGION)
+}
 
-                if let Err(save_err) = infra_state_backend.save(&current_infra_state).await {
-                    return Err(format!(
-                        "Destruction failed: {e}. Additionally, failed to save state: {save_err}"
-                    )
-                    .into());
-                }
+/// Project infra deployed by genesis next to the Leader VM
+fn get_project_infra_spec(config: &oct_config::Config) -> infra::graph::ProjectInfraSpec {
+    let domain = config
+        .project
+        .domain
+        .clone()
+        .map(|name| infra::graph::DomainSpec {
+            name,
+            ttl: config
+                .project
+                .dns_ttl
+                .unwrap_or(infra::graph::DomainSpec::DEFAULT_TTL),
+            wildcard: config.project.dns_wildcard,
+        });
 
-                Err(format!("Partial destruction: {e}. Remaining resources saved to state.").into())
+    let ecrs = config
+        .project
+        .ecr_repositories()
+        .into_iter()
+        .map(|name| infra::resource::EcrSpec {
+            name,
+            keep_last_images: config.project.ecr_keep_last_images,
+        })
+        .collect();
+
+    infra::graph::ProjectInfraSpec {
+        domain,
+        ecrs,
+        load_balancer_port: config.project.load_balancer_port(),
+    }
+}
+
+/// Returns region of the deployed infrastructure, the region recorded in the
+/// state wins so a changed or defaulted `Project.region` does not point the
+/// destroy at another region
+fn get_state_region<'a>(
+    infra_state: &'a infra::state::State,
+    config: &'a oct_config::Config,
+) -> &'a str {
+    infra_state
+        .get_region()
+        .unwrap_or_else(|| get_region(config))
+}
+
+/// Awaits the future, failing with a timeout error if it does not complete
+/// within `timeout`
+async fn with_timeout<T>(
+    timeout: Duration,
+    future: impl Future<Output = Result<T, Box<dyn std::error::Error + Send + Sync>>>,
+) -> Result<T, Box<dyn std::error::Error + Send + Sync>> {
+    match tokio::time::timeout(timeout, future).await {
+        Ok(result) => result,
+        Err(_) => Err(format!("Operation timed out after {timeout:?}").into()),
+    }
+}
+
+/// Resolves the secret references in service envs, AWS is called only if
+/// there are references
+///
+/// Returns only the resolved secret envs, key - service name, the config
+/// keeps the references so the values are not stored in the user state
+async fn resolve_secrets(
+    config: &oct_config::Config,
+) -> Result<HashMap<String, HashMap<String, String>>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut secret_references = HashMap::new();
+    for service in &config.project.services {
+        let mut service_references = HashMap::new();
+
+        for (name, value) in &service.envs {
+            if SecretReference::parse(value)?.is_some() {
+                service_references.insert(name.clone(), value.clone());
             }
         }
+
+        if !service_references.is_empty() {
+            secret_references.insert(service.name.clone(), service_references);
+        }
+    }
+
+    if secret_references.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let secrets_resolver = SecretsResolver::new(get_region(config).to_string()).await;
+
+    let mut secret_envs = HashMap::with_capacity(secret_references.len());
+    for (service_name, references) in secret_references {
+        let resolved_envs = secrets_resolver.resolve_envs(&references).await?;
+
+        secret_envs.insert(service_name, resolved_envs);
+    }
+
+    Ok(secret_envs)
+}
+
+/// Checks that the orchestrator can deploy to the configured cloud provider
+fn check_provider(
+    provider: oct_config::CloudProvider,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    match provider {
+        oct_config::CloudProvider::Aws => Ok(()),
+        oct_config::CloudProvider::Gcp => {
+            Err("GCP provider is not supported for deployments yet".into())
+        }
+    }
+}
+
+/// Checks that a replica of each user service fits into the largest available
+/// instance type
+fn validate_services_capacity(
+    services_graph: &Graph<oct_config::Node, String>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let max_capacity = InstanceType::largest().get_info();
+
+    for node in services_graph.node_weights() {
+        let oct_config::Node::Resource(service) = node else {
+            continue;
+        };
+
+        if service.cpus > max_capacity.cpus {
+            return Err(format!(
+                "Service '{}' requires {} vCPU which exceeds max instance capacity of {} vCPU",
+                service.name,
+                f64::from(service.cpus) / 1000.0,
+                f64::from(max_capacity.cpus) / 1000.0,
+            )
+            .into());
+        }
+
+        if service.memory > max_capacity.memory {
+            return Err(format!(
+                "Service '{}' requires {} MB of memory which exceeds max instance capacity of \
+                 {} MB",
+                service.name, service.memory, max_capacity.memory,
+            )
+            .into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Instance type of the Leader node, `None` when no services are configured
+/// and `allow_no_services` is set so genesis creates no VM
+///
+/// `instance_type_override` wins over the instance type selected from the
+/// service resources
+fn get_genesis_instance_type(
+    services_graph: &Graph<oct_config::Node, String>,
+    allow_no_services: bool,
+    instance_type_override: Option<InstanceType>,
+) -> Result<Option<InstanceType>, Box<dyn std::error::Error + Send + Sync>> {
+    let has_services = services_graph
+        .node_weights()
+        .any(|node| matches!(node, oct_config::Node::Resource(_)));
+
+    match (has_services, allow_no_services) {
+        (true, _) => match instance_type_override {
+            Some(instance_type) => {
+                log::info!("Using instance type {} override", instance_type.as_str());
+
+                Ok(Some(instance_type))
+            }
+            None => Ok(Some(get_instance_type(services_graph)?)),
+        },
+        (false, true) => Ok(None),
+        (false, false) => Err("No services configured".into()),
     }
 }
 
@@ -99,6 +1040,8 @@ impl OrchestratorWithGraph {
 fn get_instance_type(
     services_graph: &Graph<oct_config::Node, String>,
 ) -> Result<InstanceType, Box<dyn std::error::Error + Send + Sync>> {
+    validate_services_capacity(services_graph)?;
+
     let sorted_graph = infra::graph::kahn_traverse(services_graph)?;
 
     let (total_services_cpus, total_services_memory) = sorted_graph
@@ -110,9 +1053,13 @@ fn get_instance_type(
 
             None
         })
-        .fold((0u32, 0u64), |(cpus, mem), service| {
-            (cpus + service.cpus, mem + service.memory)
-        });
+        .try_fold((0u32, 0u64), |(cpus, mem), service| {
+            let (service_cpus, service_memory) = service
+                .total_resources()
+                .ok_or_else(|| format!("Resources of service '{}' overflow", service.name))?;
+
+            Ok::<_, String>((cpus + service_cpus, mem + service_memory))
+        })?;
 
     let instance_type = InstanceType::from_resources(total_services_cpus, total_services_memory);
 
@@ -123,4 +1070,766 @@ fn get_instance_type(
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use std::collections::HashMap;
+
+    use oct_config::{Config, Project, Service, StateBackend};
+
+    use super::*;
+
+    fn get_config(services: Vec<Service>) -> Config {
+        Config {
+            project: Project {
+                name: "test".to_string(),
+                state_backend: StateBackend::Local {
+                    path: "state.json".to_string(),
+                },
+                user_state_backend: StateBackend::Local {
+                    path: "user_state.json".to_string(),
+                },
+                services,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn get_service(name: &str, cpus: u32, memory: u64) -> Service {
+        Service {
+            name: name.to_string(),
+            image: "nginx:latest".to_string(),
+            cpus,
+            memory,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_get_config_dir() {
+        // Act & Assert
+        assert_eq!(
+            get_config_dir(&["project/oct.toml", "project/prod.toml"]),
+            Path::new("project")
+        );
+        assert_eq!(get_config_dir(&["oct.toml"]), Path::new("."));
+        assert_eq!(get_config_dir(&["-"]), Path::new("."));
+        assert_eq!(get_config_dir::<&str>(&[]), Path::new("."));
+    }
+
+    #[test]
+    fn test_resolve_region_precedence() {
+        // Act & Assert
+        assert_eq!(
+            resolve_region(Some("eu-west-1"), Some("eu-central-1"), Some("us-east-1")),
+            "eu-west-1"
+        );
+        assert_eq!(
+            resolve_region(None, Some("eu-central-1"), Some("us-east-1")),
+            "eu-central-1"
+        );
+        assert_eq!(resolve_region(None, None, Some("us-east-1")), "us-east-1");
+        assert_eq!(resolve_region(None, None, None), DEFAULT_REGION);
+    }
+
+    #[test]
+    fn test_get_project_infra_spec_domain() {
+        // Arrange
+        let mut config = get_config(vec![get_service("app_1", 250, 64)]);
+        let default_config = config.clone();
+        config.project.domain = Some(String::from("example.com"));
+        config.project.dns_ttl = Some(300);
+        config.project.dns_wildcard = true;
+
+        // Act
+        let project_infra = get_project_infra_spec(&config);
+        let default_project_infra = get_project_infra_spec(&default_config);
+
+        // Assert
+        assert_eq!(
+            project_infra.domain,
+            Some(infra::graph::DomainSpec {
+                name: String::from("example.com"),
+                ttl: 300,
+                wildcard: true,
+            })
+        );
+        assert_eq!(default_project_infra.domain, None);
+    }
+
+    #[test]
+    fn test_get_project_infra_spec_ecr_per_service() {
+        // Arrange
+        let mut config = get_config(vec![
+            Service {
+                dockerfile_path: Some(String::from("api/Dockerfile")),
+                ..get_service("api", 250, 64)
+            },
+            get_service("cache", 250, 64),
+        ]);
+        let shared_ecr_config = config.clone();
+        config.project.ecr_per_service = true;
+        config.project.ecr_keep_last_images = Some(10);
+
+        // Act
+        let project_infra = get_project_infra_spec(&config);
+        let shared_ecr_project_infra = get_project_infra_spec(&shared_ecr_config);
+
+        // Assert
+        assert_eq!(
+            project_infra.ecrs,
+            vec![infra::resource::EcrSpec {
+                name: String::from("api"),
+                keep_last_images: Some(10),
+            }]
+        );
+        assert!(shared_ecr_project_infra.ecrs.is_empty());
+    }
+
+    #[test]
+    fn test_get_project_infra_spec_load_balancer() {
+        // Arrange
+        let mut config = get_config(vec![Service {
+            external_port: Some(8080),
+            ..get_service("app_1", 250, 64)
+        }]);
+        let default_config = config.clone();
+        config.project.load_balancer = true;
+
+        // Act
+        let project_infra = get_project_infra_spec(&config);
+        let default_project_infra = get_project_infra_spec(&default_config);
+
+        // Assert
+        assert_eq!(project_infra.load_balancer_port, Some(8080));
+        assert_eq!(default_project_infra.load_balancer_port, None);
+    }
+
+    #[test]
+    fn test_format_user_state() {
+        // Arrange
+        let user_state = user_state::UserState {
+            instances: HashMap::from([(
+                String::from("1.2.3.4"),
+                user_state::Instance {
+                    cpus: 2000,
+                    memory: 1024,
+                    services: HashMap::from([
+                        (String::from("app_2"), get_service("app_2", 500, 128)),
+                        (
+                            String::from("app_1"),
+                            Service {
+                                description: Some(String::from("Frontend")),
+                                labels: HashMap::from([(
+                                    String::from("team"),
+                                    String::from("web"),
+                                )]),
+                                ..get_service("app_1", 250, 64)
+                            },
+                        ),
+                    ]),
+                    container_ids: HashMap::new(),
+                },
+            )]),
+            oct_ctl_token: None,
+        };
+
+        // Act
+        let output = format_user_state(&user_state, true);
+
+        // Assert
+        assert_eq!(
+            output,
+            "User state:\n  Instance 1.2.3.4 (2000 millicores, 1024 MB)\n    app_1 nginx:latest \
+             (250 millicores, 64 MB)\n      Frontend\n      team=web\n    app_2 nginx:latest (500 millicores, 128 MB)\n"
+        );
+    }
+
+    #[test]
+    fn test_format_states_not_found() {
+        // Act & Assert
+        assert_eq!(
+            format_infra_state(&infra::state::State::default(), false),
+            "Infra state: not found\n"
+        );
+        assert_eq!(
+            format_user_state(&user_state::UserState::default(), false),
+            "User state: not found\n"
+        );
+    }
+
+    #[test]
+    fn test_select_container() {
+        // Arrange
+        let config = get_config(vec![
+            get_service("app", 1000, 1024),
+            Service {
+                replicas: 2,
+                ..get_service("worker", 1000, 1024)
+            },
+        ]);
+
+        // Act & Assert
+        assert_eq!(
+            select_container(&config, "app", None).expect("Failed to select the only replica"),
+            "app"
+        );
+        assert_eq!(
+            select_container(&config, "worker", Some(1)).expect("Failed to select by index"),
+            "worker-1"
+        );
+        assert_eq!(
+            select_container(&config, "worker", None)
+                .expect_err("Expected multiple replicas error")
+                .to_string(),
+            "Service 'worker' has 2 replicas, select one by its index"
+        );
+        assert_eq!(
+            select_container(&config, "worker", Some(2))
+                .expect_err("Expected out of range error")
+                .to_string(),
+            "Service 'worker' has no replica 2, replicas are 0..2"
+        );
+        assert_eq!(
+            select_container(&config, "db", None)
+                .expect_err("Expected unknown service error")
+                .to_string(),
+            "Service 'db' is not found in config"
+        );
+    }
+
+    #[test]
+    fn test_select_instance() {
+        // Arrange
+        let public_ips = vec![
+            String::from("10.0.1.5"),
+            String::from("10.0.2.7"),
+            String::from("192.168.0.1"),
+        ];
+
+        // Act & Assert
+        assert_eq!(
+            select_instance(&public_ips, Some("1")).expect("Failed to select by index"),
+            "10.0.2.7"
+        );
+        assert_eq!(
+            select_instance(&public_ips, Some("10.0.1.5")).expect("Failed to select by IP"),
+            "10.0.1.5"
+        );
+        assert_eq!(
+            select_instance(&public_ips, Some("192.")).expect("Failed to select by prefix"),
+            "192.168.0.1"
+        );
+        assert_eq!(
+            select_instance(&public_ips[..1], None).expect("Failed to select the only instance"),
+            "10.0.1.5"
+        );
+    }
+
+    #[test]
+    fn test_select_instance_errors() {
+        // Arrange
+        let public_ips = vec![String::from("10.0.1.5"), String::from("10.0.2.7")];
+
+        // Act & Assert
+        assert_eq!(
+            select_instance(&public_ips, Some("10.0."))
+                .expect_err("Expected ambiguous instance error")
+                .to_string(),
+            "Instance '10.0.' is ambiguous, matching instances:\n  0 10.0.1.5\n  1 10.0.2.7"
+        );
+        assert_eq!(
+            select_instance(
+                &[
+                    String::from("192.168.0.1"),
+                    String::from("10.0.1.5"),
+                    String::from("10.0.2.7"),
+                ],
+                Some("10.0.")
+            )
+            .expect_err("Expected ambiguous instance error")
+            .to_string(),
+            "Instance '10.0.' is ambiguous, matching instances:\n  1 10.0.1.5\n  2 10.0.2.7"
+        );
+        assert_eq!(
+            select_instance(&public_ips, None)
+                .expect_err("Expected multiple instances error")
+                .to_string(),
+            "Multiple instances are deployed, pass an index or IP:\n  0 10.0.1.5\n  1 10.0.2.7"
+        );
+        assert_eq!(
+            select_instance(&public_ips, Some("5"))
+                .expect_err("Expected out of range error")
+                .to_string(),
+            "Instance index 5 is out of range, available instances:\n  0 10.0.1.5\n  1 10.0.2.7"
+        );
+        assert_eq!(
+            select_instance(&public_ips, Some("172.16.0.1"))
+                .expect_err("Expected not found error")
+                .to_string(),
+            "Instance '172.16.0.1' is not found, available instances:\n  0 10.0.1.5\n  1 10.0.2.7"
+        );
+        assert_eq!(
+            select_instance(&[], None)
+                .expect_err("Expected no instances error")
+                .to_string(),
+            "No instances are deployed"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_with_timeout_completes() {
+        // Act
+        let result = with_timeout(Duration::from_secs(5), async { Ok(42) }).await;
+
+        // Assert
+        assert_eq!(result.expect("Failed to complete future"), 42);
+    }
+
+    #[tokio::test]
+    async fn test_with_timeout_expires() {
+        // Arrange
+        let deploy = async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+
+            Ok(())
+        };
+
+        // Act
+        let result = with_timeout(Duration::from_millis(10), deploy).await;
+
+        // Assert
+        assert_eq!(
+            result.expect_err("Expected timeout error").to_string(),
+            "Operation timed out after 10ms"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_secrets_without_references() {
+        // Arrange
+        let mut service = get_service("app", 1000, 1024);
+        service
+            .envs
+            .insert(String::from("LOG_LEVEL"), String::from("info"));
+        let config = get_config(vec![service]);
+
+        // Act
+        let secret_envs = resolve_secrets(&config).await;
+
+        // Assert
+        assert!(secret_envs.expect("Failed to resolve secrets").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_secrets_invalid_reference() {
+        // Arrange
+        let mut service = get_service("app", 1000, 1024);
+        service.envs.insert(
+            String::from("DB_PASSWORD"),
+            String::from("secret://vault/db"),
+        );
+        let config = get_config(vec![service]);
+
+        // Act
+        let secret_envs = resolve_secrets(&config).await;
+
+        // Assert
+        assert_eq!(
+            secret_envs
+                .expect_err("Expected invalid reference error")
+                .to_string(),
+            "Unsupported secret reference 'secret://vault/db'"
+        );
+    }
+
+    #[test]
+    fn test_check_provider_aws() {
+        // Act
+        let result = check_provider(oct_config::CloudProvider::Aws);
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_genesis_gcp_provider_not_supported() {
+        // Arrange
+        let mut config = get_config(vec![get_service("app", 1000, 1024)]);
+        config.project.provider = oct_config::CloudProvider::Gcp;
+
+        // Act
+        let result = OrchestratorWithGraph.genesis(&config).await;
+
+        // Assert
+        assert_eq!(
+            result
+                .expect_err("Expected unsupported provider error")
+                .to_string(),
+            "GCP provider is not supported for deployments yet"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_genesis_build_check_failure_aborts_before_provisioning() {
+        // Arrange
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let state_path = dir.path().join("state.json");
+
+        let mut config = get_config(vec![Service {
+            dockerfile_path: Some(String::from("Dockerfile")),
+            ..get_service("app", 1000, 1024)
+        }]);
+        config.project.state_backend = StateBackend::Local {
+            path: state_path.to_string_lossy().to_string(),
+        };
+
+        let options = GenesisOptions {
+            build_check: true,
+            ..GenesisOptions::default()
+        };
+        let image_builder = build::tests::MockImageBuilder {
+            failing_image: Some(String::from("nginx:latest")),
+            ..build::tests::MockImageBuilder::default()
+        };
+
+        // Act
+        let result = OrchestratorWithGraph
+            .genesis_with_image_builder(&config, options, &image_builder)
+            .await;
+
+        // Assert
+        assert_eq!(
+            result.expect_err("Expected build error").to_string(),
+            "Failed to build image of service app: COPY failed: file not found"
+        );
+        // Genesis saves the state after any infra deployment attempt
+        assert!(!state_path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_apply_with_load_balancer_updates_instances_one_by_one() {
+        // Arrange
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+
+        let mut config = get_config(vec![get_service("app", 1000, 1024)]);
+        config.project.state_backend = StateBackend::Local {
+            path: dir.path().join("state.json").to_string_lossy().to_string(),
+        };
+
+        let mut graph = Graph::new();
+        let root = graph.add_node(infra::resource::Node::Root);
+        for vm_id in ["vm-1", "vm-2"] {
+            let vm = graph.add_node(infra::resource::Node::Resource(
+                infra::resource::ResourceType::Vm(rolling::tests::get_vm(vm_id)),
+            ));
+            graph.add_edge(root, vm, String::new());
+        }
+        let load_balancer = graph.add_node(infra::resource::Node::Resource(
+            infra::resource::ResourceType::LoadBalancer(infra::resource::LoadBalancer {
+                name: String::from("load-balancer-1"),
+                arn: String::from("lb-arn"),
+                dns_name: String::from("lb-1.elb.amazonaws.com"),
+                target_group_arn: String::from("tg-arn"),
+                listener_arn: String::from("listener-arn"),
+                target_port: 8080,
+                instance_ids: vec![String::from("vm-1"), String::from("vm-2")],
+            }),
+        ));
+        graph.add_edge(root, load_balancer, String::new());
+
+        backend::get_state_backend::<infra::state::State>(&config.project.state_backend)
+            .save(&infra::state::State::from_graph(&graph))
+            .await
+            .expect("Failed to save infra state");
+
+        let rollout = rolling::tests::MockRollingUpdate::default();
+
+        // Act
+        let result = OrchestratorWithGraph
+            .apply_with_rollout(&config, ApplyOptions::default(), &rollout)
+            .await;
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(
+            rollout.calls(),
+            vec![
+                "target_group load-balancer-1",
+                "deregister vm-1",
+                "update vm-1",
+                "wait_for_health vm-1",
+                "register vm-1",
+                "deregister vm-2",
+                "update vm-2",
+                "wait_for_health vm-2",
+                "register vm-2",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_apply_without_load_balancer_updates_leader() {
+        // Arrange
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+
+        let mut config = get_config(vec![get_service("app", 1000, 1024)]);
+        config.project.state_backend = StateBackend::Local {
+            path: dir.path().join("state.json").to_string_lossy().to_string(),
+        };
+
+        let mut graph = Graph::new();
+        let root = graph.add_node(infra::resource::Node::Root);
+        let vm = graph.add_node(infra::resource::Node::Resource(
+            infra::resource::ResourceType::Vm(rolling::tests::get_vm("vm-1")),
+        ));
+        graph.add_edge(root, vm, String::new());
+
+        backend::get_state_backend::<infra::state::State>(&config.project.state_backend)
+            .save(&infra::state::State::from_graph(&graph))
+            .await
+            .expect("Failed to save infra state");
+
+        let rollout = rolling::tests::MockRollingUpdate::default();
+
+        // Act
+        let result = OrchestratorWithGraph
+            .apply_with_rollout(&config, ApplyOptions::default(), &rollout)
+            .await;
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(rollout.calls(), vec!["update vm-1"]);
+    }
+
+    #[test]
+    fn test_get_state_region() {
+        // Arrange
+        let mut graph = Graph::new();
+        let root = graph.add_node(infra::resource::Node::Root);
+        let vpc = graph.add_node(infra::resource::Node::Resource(
+            infra::resource::ResourceType::Vpc(infra::resource::Vpc {
+                id: String::from("vpc-id"),
+                region: String::from("eu-central-1"),
+                cidr_block: String::from("10.0.0.0/16"),
+                name: String::from("vpc-1"),
+                existing: false,
+            }),
+        ));
+        graph.add_edge(root, vpc, String::new());
+
+        let infra_state: infra::state::State = serde_json::from_str(
+            &serde_json::to_string(&infra::state::State::from_graph(&graph))
+                .expect("Failed to serialize state"),
+        )
+        .expect("Failed to deserialize state");
+
+        let mut config = get_config(vec![]);
+        config.project.region = Some(String::from("us-east-1"));
+
+        // Act
+        let region = get_state_region(&infra_state, &config);
+        let region_without_state = get_state_region(&infra::state::State::default(), &config);
+
+        // Assert
+        assert_eq!(region, "eu-central-1");
+        assert_eq!(region_without_state, "us-east-1");
+    }
+
+    #[tokio::test]
+    async fn test_get_or_create_oct_ctl_token_keeps_existing_token() {
+        // Arrange
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+
+        let mut config = get_config(vec![]);
+        config.project.user_state_backend = StateBackend::Local {
+            path: dir
+                .path()
+                .join("user_state.json")
+                .to_string_lossy()
+                .to_string(),
+        };
+
+        // Act
+        let first_token = get_or_create_oct_ctl_token(&config)
+            .await
+            .expect("Failed to create token");
+        let second_token = get_or_create_oct_ctl_token(&config)
+            .await
+            .expect("Failed to get token");
+
+        // Assert
+        assert_eq!(first_token, second_token);
+        assert_eq!(first_token.len(), 32);
+
+        let (user_state, _loaded) =
+            backend::get_state_backend::<user_state::UserState>(&config.project.user_state_backend)
+                .load()
+                .await
+                .expect("Failed to load user state");
+        assert_eq!(user_state.oct_ctl_token, Some(second_token));
+    }
+
+    #[test]
+    fn test_get_genesis_instance_type_no_services() {
+        // Arrange
+        let services_graph = get_config(vec![])
+            .to_graph()
+            .expect("Failed to build services graph");
+
+        // Act
+        let instance_type = get_genesis_instance_type(&services_graph, false, None);
+        let networking_only_instance_type = get_genesis_instance_type(&services_graph, true, None);
+
+        // Assert
+        assert_eq!(
+            instance_type
+                .expect_err("Expected no services error")
+                .to_string(),
+            "No services configured"
+        );
+        assert_eq!(
+            networking_only_instance_type.expect("Failed to get instance type"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_get_genesis_instance_type_with_services() {
+        // Arrange
+        let services_graph = get_config(vec![get_service("app", 1000, 1024)])
+            .to_graph()
+            .expect("Failed to build services graph");
+
+        // Act
+        let instance_type = get_genesis_instance_type(&services_graph, true, None);
+
+        // Assert
+        assert_eq!(
+            instance_type.expect("Failed to get instance type"),
+            Some(InstanceType::T3Micro)
+        );
+    }
+
+    #[test]
+    fn test_format_plan() {
+        // Act
+        let output = format_plan(Some(InstanceType::T3Micro), "us-west-2");
+        let networking_only_output = format_plan(None, "us-west-2");
+
+        // Assert
+        assert_eq!(
+            output,
+            "Plan:\n  Leader VM t3.micro in us-west-2\n\
+             Estimated monthly cost: $7.59 (1 instance(s) in us-west-2 × 730 h)\n\
+             Data transfer, EBS volumes and ECR storage are not included\n"
+        );
+        assert_eq!(
+            networking_only_output,
+            "Plan: no VMs, only the shared networking is deployed\n"
+        );
+    }
+
+    #[test]
+    fn test_get_genesis_instance_type_override() {
+        // Arrange
+        let services_graph = get_config(vec![get_service("app", 1000, 1024)])
+            .to_graph()
+            .expect("Failed to build services graph");
+
+        // Act
+        let instance_type =
+            get_genesis_instance_type(&services_graph, false, Some(InstanceType::T3Small));
+
+        // Assert
+        assert_eq!(
+            instance_type.expect("Failed to get instance type"),
+            Some(InstanceType::T3Small)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_genesis_no_services() {
+        // Arrange
+        let config = get_config(vec![]);
+
+        // Act
+        let result = OrchestratorWithGraph.genesis(&config).await;
+
+        // Assert
+        assert_eq!(
+            result.expect_err("Expected no services error").to_string(),
+            "No services configured"
+        );
+    }
+
+    #[test]
+    fn test_get_instance_type_service_fits() {
+        // Arrange
+        let config = get_config(vec![get_service("app", 1000, 1024)]);
+        let services_graph = config.to_graph().expect("Failed to build services graph");
+
+        // Act
+        let instance_type = get_instance_type(&services_graph);
+
+        // Assert
+        assert_eq!(
+            instance_type.expect("Failed to get instance type"),
+            InstanceType::T3Micro
+        );
+    }
+
+    #[test]
+    fn test_get_instance_type_service_replicas() {
+        // Arrange
+        let config = get_config(vec![Service {
+            replicas: 2,
+            ..get_service("app", 1000, 1024)
+        }]);
+        let services_graph = config.to_graph().expect("Failed to build services graph");
+
+        // Act
+        let instance_type = get_instance_type(&services_graph);
+
+        // Assert
+        assert_eq!(
+            instance_type.expect("Failed to get instance type"),
+            InstanceType::T3Small
+        );
+    }
+
+    #[test]
+    fn test_get_instance_type_service_exceeds_max_cpus() {
+        // Arrange
+        let config = get_config(vec![get_service("app", 10000, 1024)]);
+        let services_graph = config.to_graph().expect("Failed to build services graph");
+
+        // Act
+        let instance_type = get_instance_type(&services_graph);
+
+        // Assert
+        assert_eq!(
+            instance_type
+                .expect_err("Expected capacity validation error")
+                .to_string(),
+            "Service 'app' requires 10 vCPU which exceeds max instance capacity of 8 vCPU"
+        );
+    }
+
+    #[test]
+    fn test_get_instance_type_service_exceeds_max_memory() {
+        // Arrange
+        let config = get_config(vec![get_service("app", 1000, 1_000_000)]);
+        let services_graph = config.to_graph().expect("Failed to build services graph");
+
+        // Act
+        let instance_type = get_instance_type(&services_graph);
+
+        // Assert
+        assert_eq!(
+            instance_type
+                .expect_err("Expected capacity validation error")
+                .to_string(),
+            "Service 'app' requires 1000000 MB of memory which exceeds max instance capacity \
+             of 32768 MB"
+        );
+    }
+}
@@ -0,0 +1,422 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::net::UdpSocket;
+use std::time::{Duration, Instant};
+
+use oct_cloud::infra::graph::DeployEvent;
+use oct_config::MetricsBackend;
+use tokio::sync::mpsc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MetricKind {
+    Gauge,
+    Counter,
+}
+
+/// Single metric value with its labels
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Sample {
+    pub(crate) name: &'static str,
+    pub(crate) kind: MetricKind,
+    pub(crate) labels: Vec<(&'static str, String)>,
+    pub(crate) value: f64,
+}
+
+/// Destination of the deployment metrics configured in `Project.metrics`
+pub(crate) trait MetricsSink {
+    fn emit(&self, samples: &[Sample]) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
+
+pub(crate) fn get_metrics_sink(backend: &MetricsBackend) -> Box<dyn MetricsSink> {
+    match backend {
+        MetricsBackend::PrometheusTextfile { path } => {
+            Box::new(PrometheusTextfileSink { path: path.clone() })
+        }
+        MetricsBackend::Statsd { address } => Box::new(StatsdSink {
+            address: address.clone(),
+        }),
+    }
+}
+
+/// Sends the samples to the sink, failures are only logged so an unavailable
+/// metrics destination does not fail the deployment
+pub(crate) fn emit(sink: &dyn MetricsSink, samples: &[Sample]) {
+    if let Err(e) = sink.emit(samples) {
+        log::warn!("Failed to emit deployment metrics: {e}");
+    }
+}
+
+/// Writes the samples to a file read by the node exporter textfile collector
+///
+/// The file is replaced on each deployment, a temporary file is renamed
+/// so the collector never reads a partially written file
+struct PrometheusTextfileSink {
+    path: String,
+}
+
+impl MetricsSink for PrometheusTextfileSink {
+    fn emit(&self, samples: &[Sample]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let tmp_path = format!("{}.tmp", self.path);
+
+        std::fs::write(&tmp_path, format_prometheus(samples))?;
+        std::fs::rename(&tmp_path, &self.path)?;
+
+        Ok(())
+    }
+}
+
+/// Sends the samples to a StatsD server over UDP, labels are sent as
+/// DogStatsD tags
+struct StatsdSink {
+    address: String,
+}
+
+impl MetricsSink for StatsdSink {
+    fn emit(&self, samples: &[Sample]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let socket = UdpSocket::bind("0.0.0.0:0")?;
+
+        for sample in samples {
+            socket.send_to(format_statsd(sample).as_bytes(), &self.address)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn format_prometheus(samples: &[Sample]) -> String {
+    let mut output = String::new();
+    let mut typed_names: Vec<&str> = vec![];
+
+    for sample in samples {
+        if !typed_names.contains(&sample.name) {
+            let kind = match sample.kind {
+                MetricKind::Gauge => "gauge",
+                MetricKind::Counter => "counter",
+            };
+            let _ = writeln!(output, "# TYPE {} {kind}", sample.name);
+
+            typed_names.push(sample.name);
+        }
+
+        let labels = sample
+            .labels
+            .iter()
+            .map(|(name, value)| {
+                let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+
+                format!("{name}=\"{value}\"")
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let _ = writeln!(output, "{}{{{labels}}} {}", sample.name, sample.value);
+    }
+
+    output
+}
+
+fn format_statsd(sample: &Sample) -> String {
+    let kind = match sample.kind {
+        MetricKind::Gauge => "g",
+        MetricKind::Counter => "c",
+    };
+
+    let tags = sample
+        .labels
+        .iter()
+        .map(|(name, value)| format!("{name}:{value}"))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    format!("{}:{}|{kind}|#{tags}", sample.name, sample.value)
+}
+
+/// Creation latency and outcome counters of a single resource type
+#[derive(Debug, Default)]
+struct ResourceTypeMetrics {
+    create_duration: Duration,
+    created: u32,
+    failed: u32,
+}
+
+/// Per resource type metrics collected from the [`DeployEvent`]s
+#[derive(Debug, Default)]
+pub(crate) struct DeployMetrics {
+    started_at: HashMap<String, Instant>,
+    resource_types: BTreeMap<String, ResourceTypeMetrics>,
+}
+
+impl DeployMetrics {
+    /// Records events until all the senders are dropped
+    pub(crate) async fn collect(mut events: mpsc::Receiver<DeployEvent>) -> Self {
+        let mut metrics = Self::default();
+
+        while let Some(event) = events.recv().await {
+            metrics.record(&event, Instant::now());
+        }
+
+        metrics
+    }
+
+    fn record(&mut self, event: &DeployEvent, at: Instant) {
+        match event {
+            DeployEvent::ResourceCreateStarted { name } => {
+                self.started_at.insert(name.clone(), at);
+            }
+            DeployEvent::ResourceCreated { name } => self.finish(name, at).created += 1,
+            DeployEvent::ResourceFailed { name, .. } => self.finish(name, at).failed += 1,
+        }
+    }
+
+    /// Adds the resource creation latency to its type metrics,
+    /// the type is the first word of the event resource name, e.g. `Vpc`
+    fn finish(&mut self, name: &str, at: Instant) -> &mut ResourceTypeMetrics {
+        let create_duration = self
+            .started_at
+            .remove(name)
+            .map_or(Duration::ZERO, |started_at| at.duration_since(started_at));
+
+        let resource_type = name.split_whitespace().next().unwrap_or(name);
+
+        let metrics = self
+            .resource_types
+            .entry(resource_type.to_string())
+            .or_default();
+        metrics.create_duration += create_duration;
+
+        metrics
+    }
+
+    pub(crate) fn samples(&self, project: &str, duration: Duration, success: bool) -> Vec<Sample> {
+        let project_labels = vec![("project", project.to_string())];
+
+        let mut samples = vec![
+            Sample {
+                name: "oct_deploy_duration_seconds",
+                kind: MetricKind::Gauge,
+                labels: project_labels.clone(),
+                value: duration.as_secs_f64(),
+            },
+            Sample {
+                name: "oct_deploy_success",
+                kind: MetricKind::Gauge,
+                labels: project_labels,
+                value: if success { 1.0 } else { 0.0 },
+            },
+        ];
+
+        samples.extend(self.resource_type_samples(
+            project,
+            "oct_resource_create_duration_seconds",
+            MetricKind::Gauge,
+            |metrics| metrics.create_duration.as_secs_f64(),
+        ));
+        samples.extend(self.resource_type_samples(
+            project,
+            "oct_resources_created_total",
+            MetricKind::Counter,
+            |metrics| f64::from(metrics.created),
+        ));
+        samples.extend(self.resource_type_samples(
+            project,
+            "oct_resources_failed_total",
+            MetricKind::Counter,
+            |metrics| f64::from(metrics.failed),
+        ));
+
+        samples
+    }
+
+    /// Samples of a single metric, one per resource type
+    fn resource_type_samples(
+        &self,
+        project: &str,
+        name: &'static str,
+        kind: MetricKind,
+        value: fn(&ResourceTypeMetrics) -> f64,
+    ) -> Vec<Sample> {
+        self.resource_types
+            .iter()
+            .map(|(resource_type, metrics)| Sample {
+                name,
+                kind,
+                labels: vec![
+                    ("project", project.to_string()),
+                    ("resource_type", resource_type.clone()),
+                ],
+                value: value(metrics),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct MockMetricsSink {
+        samples: Mutex<Vec<Sample>>,
+    }
+
+    impl MetricsSink for MockMetricsSink {
+        fn emit(&self, samples: &[Sample]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            self.samples
+                .lock()
+                .expect("Failed to lock samples")
+                .extend_from_slice(samples);
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_deploy_metrics_emit_deploy_duration() {
+        // Arrange
+        let sink = MockMetricsSink::default();
+
+        let started_at = Instant::now();
+        let mut deploy_metrics = DeployMetrics::default();
+        deploy_metrics.record(
+            &DeployEvent::ResourceCreateStarted {
+                name: String::from("Vpc vpc-1"),
+            },
+            started_at,
+        );
+        deploy_metrics.record(
+            &DeployEvent::ResourceCreated {
+                name: String::from("Vpc vpc-1"),
+            },
+            started_at + Duration::from_secs(3),
+        );
+        deploy_metrics.record(
+            &DeployEvent::ResourceCreateStarted {
+                name: String::from("Subnet subnet-1"),
+            },
+            started_at,
+        );
+        deploy_metrics.record(
+            &DeployEvent::ResourceFailed {
+                name: String::from("Subnet subnet-1"),
+                error: String::from("InvalidParameterValue"),
+            },
+            started_at + Duration::from_secs(1),
+        );
+
+        // Act
+        emit(
+            &sink,
+            &deploy_metrics.samples("test", Duration::from_secs(5), false),
+        );
+
+        // Assert
+        let samples = sink.samples.lock().expect("Failed to lock samples");
+        assert_eq!(
+            samples.first(),
+            Some(&Sample {
+                name: "oct_deploy_duration_seconds",
+                kind: MetricKind::Gauge,
+                labels: vec![("project", String::from("test"))],
+                value: 5.0,
+            })
+        );
+        assert!(samples.contains(&Sample {
+            name: "oct_resource_create_duration_seconds",
+            kind: MetricKind::Gauge,
+            labels: vec![
+                ("project", String::from("test")),
+                ("resource_type", String::from("Vpc")),
+            ],
+            value: 3.0,
+        }));
+        assert!(samples.contains(&Sample {
+            name: "oct_resources_failed_total",
+            kind: MetricKind::Counter,
+            labels: vec![
+                ("project", String::from("test")),
+                ("resource_type", String::from("Subnet")),
+            ],
+            value: 1.0,
+        }));
+    }
+
+    #[test]
+    fn test_format_prometheus() {
+        // Arrange
+        let samples = vec![
+            Sample {
+                name: "oct_resources_created_total",
+                kind: MetricKind::Counter,
+                labels: vec![
+                    ("project", String::from("test")),
+                    ("resource_type", String::from("Subnet")),
+                ],
+                value: 2.0,
+            },
+            Sample {
+                name: "oct_resources_created_total",
+                kind: MetricKind::Counter,
+                labels: vec![
+                    ("project", String::from("test")),
+                    ("resource_type", String::from("Vpc")),
+                ],
+                value: 1.0,
+            },
+        ];
+
+        // Act
+        let output = format_prometheus(&samples);
+
+        // Assert
+        assert_eq!(
+            output,
+            "# TYPE oct_resources_created_total counter\n\
+             oct_resources_created_total{project=\"test\",resource_type=\"Subnet\"} 2\n\
+             oct_resources_created_total{project=\"test\",resource_type=\"Vpc\"} 1\n"
+        );
+    }
+
+    #[test]
+    fn test_format_statsd() {
+        // Arrange
+        let sample = Sample {
+            name: "oct_deploy_duration_seconds",
+            kind: MetricKind::Gauge,
+            labels: vec![("project", String::from("test"))],
+            value: 1.5,
+        };
+
+        // Act
+        let output = format_statsd(&sample);
+
+        // Assert
+        assert_eq!(output, "oct_deploy_duration_seconds:1.5|g|#project:test");
+    }
+
+    #[test]
+    fn test_prometheus_textfile_sink_emit() {
+        // Arrange
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = dir.path().join("oct.prom");
+
+        let sink = PrometheusTextfileSink {
+            path: path.to_string_lossy().to_string(),
+        };
+
+        // Act
+        let result =
+            sink.emit(&DeployMetrics::default().samples("test", Duration::from_secs(2), true));
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(
+            std::fs::read_to_string(path).expect("Failed to read metrics file"),
+            "# TYPE oct_deploy_duration_seconds gauge\n\
+             oct_deploy_duration_seconds{project=\"test\"} 2\n\
+             # TYPE oct_deploy_success gauge\n\
+             oct_deploy_success{project=\"test\"} 1\n"
+        );
+    }
+}
@@ -0,0 +1,134 @@
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// Time limit for the webhook request, a slow webhook must not delay the CLI
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Deployment result sent to `Project.notify_webhook`
+///
+/// `oct-ctl` applies all the services in one request and doesn't report
+/// per-service results, so only the outcome of the whole deployment is sent
+#[derive(Debug, Serialize)]
+pub(crate) struct DeployNotification {
+    project: String,
+    success: bool,
+    duration_secs: f64,
+    error: Option<String>,
+}
+
+impl DeployNotification {
+    pub(crate) fn new(
+        config: &oct_config::Config,
+        duration: Duration,
+        result: &Result<(), Box<dyn std::error::Error + Send + Sync>>,
+    ) -> Self {
+        Self {
+            project: config.project.name.clone(),
+            success: result.is_ok(),
+            duration_secs: duration.as_secs_f64(),
+            error: result.as_ref().err().map(ToString::to_string),
+        }
+    }
+}
+
+/// Posts the notification to the webhook, failures are only logged so an
+/// unreachable webhook does not fail the deployment
+pub(crate) async fn send(url: &str, notification: &DeployNotification) {
+    let response = reqwest::Client::new()
+        .post(url)
+        .json(notification)
+        .timeout(WEBHOOK_TIMEOUT)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status);
+
+    match response {
+        Ok(_) => log::info!("Deployment notification is sent to {url}"),
+        Err(e) => log::warn!("Failed to send deployment notification to {url}: {e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use oct_config::{Config, Project, Service, StateBackend};
+
+    use super::*;
+
+    fn get_config() -> Config {
+        Config {
+            project: Project {
+                name: String::from("test"),
+                state_backend: StateBackend::Local {
+                    path: String::from("state.json"),
+                },
+                user_state_backend: StateBackend::Local {
+                    path: String::from("user_state.json"),
+                },
+                services: vec![Service {
+                    name: String::from("app_1"),
+                    image: String::from("nginx:latest"),
+                    cpus: 250,
+                    memory: 64,
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_success_notification() {
+        // Arrange
+        let mut server = mockito::Server::new_async().await;
+        let webhook_mock = server
+            .mock("POST", "/hook")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "project": "test",
+                "success": true,
+                "duration_secs": 1.5,
+                "error": null,
+            })))
+            .with_status(200)
+            .create_async()
+            .await;
+
+        let notification =
+            DeployNotification::new(&get_config(), Duration::from_millis(1500), &Ok(()));
+
+        // Act
+        send(&format!("{}/hook", server.url()), &notification).await;
+
+        // Assert
+        webhook_mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_send_failure_notification() {
+        // Arrange
+        let mut server = mockito::Server::new_async().await;
+        let webhook_mock = server
+            .mock("POST", "/hook")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "project": "test",
+                "success": false,
+                "duration_secs": 2.0,
+                "error": "No VMs available",
+            })))
+            .with_status(500)
+            .create_async()
+            .await;
+
+        let notification = DeployNotification::new(
+            &get_config(),
+            Duration::from_secs(2),
+            &Err("No VMs available".into()),
+        );
+
+        // Act
+        send(&format!("{}/hook", server.url()), &notification).await;
+
+        // Assert
+        webhook_mock.assert_async().await;
+    }
+}
@@ -0,0 +1,367 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use oct_cloud::infra::graph::GraphManager;
+use oct_cloud::infra::resource::{LoadBalancer, Vm};
+
+use crate::{ApplyOptions, get_oct_ctl_client, with_timeout};
+
+/// Load balancer target group routing the requests to the instances
+#[async_trait::async_trait]
+pub(crate) trait TargetGroup: Send + Sync {
+    async fn deregister(
+        &self,
+        instance_id: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    async fn register(
+        &self,
+        instance_id: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Updates the services of a single instance
+#[async_trait::async_trait]
+pub(crate) trait InstanceUpdater: Send + Sync {
+    async fn update(&self, vm: &Vm) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Waits until the updated services of the instance are healthy
+    async fn wait_for_health(
+        &self,
+        vm: &Vm,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Creates the target group and the updater of an apply, the target group is
+/// created only when the infra state has a load balancer
+#[async_trait::async_trait]
+pub(crate) trait Rollout: Send + Sync {
+    async fn target_group(&self, region: &str, load_balancer: LoadBalancer)
+    -> Box<dyn TargetGroup>;
+
+    fn updater<'a>(
+        &self,
+        config: &'a oct_config::Config,
+        secret_envs: &'a HashMap<String, HashMap<String, String>>,
+        options: ApplyOptions,
+    ) -> Box<dyn InstanceUpdater + 'a>;
+}
+
+/// Updates the instances one by one, an instance is out of the target group
+/// from before its update until its services are healthy, so the requests
+/// never reach a starting container
+///
+/// A failed instance stays deregistered and stops the update, the instances
+/// not updated yet keep serving the requests
+pub(crate) async fn rolling_update(
+    vms: &[Vm],
+    target_group: &dyn TargetGroup,
+    updater: &dyn InstanceUpdater,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    for vm in vms {
+        log::info!("Updating instance {}", vm.id);
+
+        target_group.deregister(&vm.id).await?;
+
+        updater
+            .update(vm)
+            .await
+            .map_err(|e| format!("Failed to update instance {}: {e}", vm.id))?;
+        updater.wait_for_health(vm).await?;
+
+        target_group.register(&vm.id).await?;
+
+        log::info!("Instance {} is updated", vm.id);
+    }
+
+    Ok(())
+}
+
+/// Rollout of the deployed load balancer target group and `oct-ctl`
+pub(crate) struct OctCtlRollout;
+
+#[async_trait::async_trait]
+impl Rollout for OctCtlRollout {
+    async fn target_group(
+        &self,
+        region: &str,
+        load_balancer: LoadBalancer,
+    ) -> Box<dyn TargetGroup> {
+        Box::new(LoadBalancerTargetGroup {
+            graph_manager: GraphManager::new(region).await,
+            load_balancer,
+        })
+    }
+
+    fn updater<'a>(
+        &self,
+        config: &'a oct_config::Config,
+        secret_envs: &'a HashMap<String, HashMap<String, String>>,
+        options: ApplyOptions,
+    ) -> Box<dyn InstanceUpdater + 'a> {
+        Box::new(OctCtlUpdater {
+            config,
+            secret_envs,
+            options,
+        })
+    }
+}
+
+/// Target group of the deployed load balancer
+pub(crate) struct LoadBalancerTargetGroup {
+    pub(crate) graph_manager: GraphManager,
+    pub(crate) load_balancer: LoadBalancer,
+}
+
+#[async_trait::async_trait]
+impl TargetGroup for LoadBalancerTargetGroup {
+    async fn deregister(
+        &self,
+        instance_id: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.graph_manager
+            .deregister_load_balancer_targets(&self.load_balancer, vec![instance_id.to_string()])
+            .await
+    }
+
+    async fn register(
+        &self,
+        instance_id: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.graph_manager
+            .register_load_balancer_targets(&self.load_balancer, vec![instance_id.to_string()])
+            .await
+    }
+}
+
+/// Applies the services with `oct-ctl` of the instance and runs their
+/// health checks through it in every replica container, services without a
+/// health check are healthy once applied
+pub(crate) struct OctCtlUpdater<'a> {
+    pub(crate) config: &'a oct_config::Config,
+    /// Resolved secret envs, key - service name
+    pub(crate) secret_envs: &'a HashMap<String, HashMap<String, String>>,
+    pub(crate) options: ApplyOptions,
+}
+
+#[async_trait::async_trait]
+impl InstanceUpdater for OctCtlUpdater<'_> {
+    async fn update(&self, vm: &Vm) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let oct_ctl_client = get_oct_ctl_client(self.config, vm.public_ip.clone()).await?;
+        let apply_response = with_timeout(
+            self.options.timeout,
+            oct_ctl_client.apply(
+                self.config.clone(),
+                self.secret_envs.clone(),
+                self.options.force_recreate,
+                self.options.partial,
+            ),
+        )
+        .await?;
+
+        for (name, container) in &apply_response.containers {
+            log::info!("Service {name} runs in container {}", container.id);
+        }
+
+        Ok(())
+    }
+
+    async fn wait_for_health(
+        &self,
+        vm: &Vm,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let oct_ctl_client = get_oct_ctl_client(self.config, vm.public_ip.clone()).await?;
+
+        for service in &self.config.project.services {
+            let Some(health_check) = &service.health_check else {
+                continue;
+            };
+
+            for container_name in service.container_names() {
+                let mut is_healthy = false;
+
+                for attempt in 1..=health_check.retries {
+                    let exec_output = oct_ctl_client
+                        .exec(&container_name, &health_check.command.to_args())
+                        .await?;
+
+                    if exec_output.exit_code == 0 {
+                        is_healthy = true;
+
+                        break;
+                    }
+
+                    log::info!(
+                        "Service {container_name} is not healthy yet, attempt {attempt}/{}",
+                        health_check.retries
+                    );
+
+                    tokio::time::sleep(Duration::from_secs(health_check.interval)).await;
+                }
+
+                if !is_healthy {
+                    return Err(format!(
+                        "Service '{container_name}' is not healthy on instance {} after {} checks",
+                        vm.id, health_check.retries
+                    )
+                    .into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use oct_cloud::aws::types::InstanceType;
+
+    use super::*;
+
+    /// Records the target group and updater calls in order, `update` fails
+    /// for `failing_instance_id`
+    ///
+    /// Clones share the recorded calls, so the rollout hands out clones
+    #[derive(Clone, Default)]
+    pub(crate) struct MockRollingUpdate {
+        pub(crate) failing_instance_id: Option<String>,
+        pub(crate) calls: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl MockRollingUpdate {
+        fn record(&self, call: String) {
+            self.calls.lock().expect("Failed to lock calls").push(call);
+        }
+
+        pub(crate) fn calls(&self) -> Vec<String> {
+            self.calls.lock().expect("Failed to lock calls").clone()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl TargetGroup for MockRollingUpdate {
+        async fn deregister(
+            &self,
+            instance_id: &str,
+        ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            self.record(format!("deregister {instance_id}"));
+
+            Ok(())
+        }
+
+        async fn register(
+            &self,
+            instance_id: &str,
+        ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            self.record(format!("register {instance_id}"));
+
+            Ok(())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl InstanceUpdater for MockRollingUpdate {
+        async fn update(&self, vm: &Vm) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            self.record(format!("update {}", vm.id));
+
+            if self.failing_instance_id.as_deref() == Some(vm.id.as_str()) {
+                return Err("Failed to pull image".into());
+            }
+
+            Ok(())
+        }
+
+        async fn wait_for_health(
+            &self,
+            vm: &Vm,
+        ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            self.record(format!("wait_for_health {}", vm.id));
+
+            Ok(())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Rollout for MockRollingUpdate {
+        async fn target_group(
+            &self,
+            _region: &str,
+            load_balancer: LoadBalancer,
+        ) -> Box<dyn TargetGroup> {
+            self.record(format!("target_group {}", load_balancer.name));
+
+            Box::new(self.clone())
+        }
+
+        fn updater<'a>(
+            &self,
+            _config: &'a oct_config::Config,
+            _secret_envs: &'a HashMap<String, HashMap<String, String>>,
+            _options: ApplyOptions,
+        ) -> Box<dyn InstanceUpdater + 'a> {
+            Box::new(self.clone())
+        }
+    }
+
+    pub(crate) fn get_vm(id: &str) -> Vm {
+        Vm {
+            id: id.to_string(),
+            public_ip: String::from("1.2.3.4"),
+            instance_type: InstanceType::T3Micro,
+            ami: String::from("ami-123"),
+            user_data: String::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rolling_update_order() {
+        // Arrange
+        let rolling_update_mock = MockRollingUpdate::default();
+        let vms = [get_vm("vm-1"), get_vm("vm-2")];
+
+        // Act
+        let result = rolling_update(&vms, &rolling_update_mock, &rolling_update_mock).await;
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(
+            rolling_update_mock.calls(),
+            vec![
+                "deregister vm-1",
+                "update vm-1",
+                "wait_for_health vm-1",
+                "register vm-1",
+                "deregister vm-2",
+                "update vm-2",
+                "wait_for_health vm-2",
+                "register vm-2",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rolling_update_failed_instance_stays_deregistered() {
+        // Arrange
+        let rolling_update_mock = MockRollingUpdate {
+            failing_instance_id: Some(String::from("vm-1")),
+            ..MockRollingUpdate::default()
+        };
+        let vms = [get_vm("vm-1"), get_vm("vm-2")];
+
+        // Act
+        let result = rolling_update(&vms, &rolling_update_mock, &rolling_update_mock).await;
+
+        // Assert
+        assert_eq!(
+            result.expect_err("Expected update error").to_string(),
+            "Failed to update instance vm-1: Failed to pull image"
+        );
+        assert_eq!(
+            rolling_update_mock.calls(),
+            vec!["deregister vm-1", "update vm-1"]
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use oct_cloud::infra;
+use oct_cloud::infra::graph::GraphManager;
+use oct_cloud::infra::resource::Vm;
+
+use crate::backend::StateBackend;
+use crate::get_oct_ctl_client;
+
+/// Stops the user service containers of an instance
+#[async_trait::async_trait]
+pub(crate) trait ServicesStopper: Send + Sync {
+    async fn stop_services(&self, vm: &Vm) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Destroys the deployed infrastructure
+#[async_trait::async_trait]
+pub(crate) trait InfraDestroyer: Send + Sync {
+    async fn destroy_infra(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Stops the user services of all the instances, then destroys the infra
+///
+/// An instance failing to stop its services stops the teardown before any
+/// infra is destroyed, so no container is left running on a kept instance
+pub(crate) async fn teardown(
+    vms: &[Vm],
+    stopper: &dyn ServicesStopper,
+    destroyer: &dyn InfraDestroyer,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    for vm in vms {
+        log::info!("Stopping services on instance {}", vm.id);
+
+        stopper
+            .stop_services(vm)
+            .await
+            .map_err(|e| format!("Failed to stop services on instance {}: {e}", vm.id))?;
+    }
+
+    destroyer.destroy_infra().await
+}
+
+/// Stops all the services of the instance with a single `oct-ctl` request
+pub(crate) struct OctCtlServicesStopper<'a> {
+    pub(crate) config: &'a oct_config::Config,
+}
+
+#[async_trait::async_trait]
+impl ServicesStopper for OctCtlServicesStopper<'_> {
+    async fn stop_services(&self, vm: &Vm) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let oct_ctl_client = get_oct_ctl_client(self.config, vm.public_ip.clone()).await?;
+
+        oct_ctl_client.destroy().await
+    }
+}
+
+/// Destroys the resources of the infra state, the state is removed after a
+/// full destruction and keeps the remaining resources after a partial one
+pub(crate) struct GraphInfraDestroyer<'a> {
+    pub(crate) graph_manager: &'a GraphManager,
+    pub(crate) infra_state: &'a infra::state::State,
+    pub(crate) infra_state_backend: &'a dyn StateBackend<infra::state::State>,
+    /// Destroys independent resources concurrently
+    pub(crate) parallel: bool,
+}
+
+#[async_trait::async_trait]
+impl InfraDestroyer for GraphInfraDestroyer<'_> {
+    async fn destroy_infra(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut resource_graph = self.infra_state.to_graph();
+
+        let destroy_result = if self.parallel {
+            self.graph_manager
+                .destroy_parallel(&mut resource_graph)
+                .await
+        } else {
+            self.graph_manager.destroy(&mut resource_graph).await
+        };
+
+        match destroy_result {
+            Ok(()) => {
+                self.infra_state_backend.remove().await?;
+
+                Ok(())
+            }
+            Err(e) => {
+                log::error!("Failed to destroy: {e}");
+
+                let current_infra_state = infra::state::State::from_graph(&resource_graph);
+
+                if let Err(save_err) = self.infra_state_backend.save(&current_infra_state).await {
+                    return Err(format!(
+                        "Destruction failed: {e}. Additionally, failed to save state: {save_err}"
+                    )
+                    .into());
+                }
+
+                Err(format!("Partial destruction: {e}. Remaining resources saved to state.").into())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use oct_cloud::aws::types::InstanceType;
+
+    use super::*;
+
+    /// Records the stopper and destroyer calls in order, `stop_services`
+    /// fails for `failing_instance_id`
+    #[derive(Default)]
+    struct MockTeardown {
+        failing_instance_id: Option<String>,
+        calls: Mutex<Vec<String>>,
+    }
+
+    impl MockTeardown {
+        fn record(&self, call: String) {
+            self.calls.lock().expect("Failed to lock calls").push(call);
+        }
+
+        fn calls(&self) -> Vec<String> {
+            self.calls.lock().expect("Failed to lock calls").clone()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ServicesStopper for MockTeardown {
+        async fn stop_services(
+            &self,
+            vm: &Vm,
+        ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            self.record(format!("stop_services {}", vm.id));
+
+            if self.failing_instance_id.as_deref() == Some(vm.id.as_str()) {
+                return Err("Connection refused".into());
+            }
+
+            Ok(())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl InfraDestroyer for MockTeardown {
+        async fn destroy_infra(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            self.record(String::from("destroy_infra"));
+
+            Ok(())
+        }
+    }
+
+    fn get_vm(id: &str) -> Vm {
+        Vm {
+            id: id.to_string(),
+            public_ip: String::from("1.2.3.4"),
+            instance_type: InstanceType::T3Micro,
+            ami: String::from("ami-123"),
+            user_data: String::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_teardown_stops_services_before_infra() {
+        // Arrange
+        let teardown_mock = MockTeardown::default();
+        let vms = [get_vm("vm-1"), get_vm("vm-2")];
+
+        // Act
+        let result = teardown(&vms, &teardown_mock, &teardown_mock).await;
+
+        // Assert
+        assert!(result.is_ok());
+        assert_eq!(
+            teardown_mock.calls(),
+            vec!["stop_services vm-1", "stop_services vm-2", "destroy_infra"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_teardown_failed_instance_keeps_infra() {
+        // Arrange
+        let teardown_mock = MockTeardown {
+            failing_instance_id: Some(String::from("vm-1")),
+            ..MockTeardown::default()
+        };
+        let vms = [get_vm("vm-1"), get_vm("vm-2")];
+
+        // Act
+        let result = teardown(&vms, &teardown_mock, &teardown_mock).await;
+
+        // Assert
+        assert_eq!(
+            result.expect_err("Expected stop error").to_string(),
+            "Failed to stop services on instance vm-1: Connection refused"
+        );
+        assert_eq!(teardown_mock.calls(), vec!["stop_services vm-1"]);
+    }
+}
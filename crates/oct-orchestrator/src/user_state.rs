@@ -6,6 +6,10 @@ use serde::{Deserialize, Serialize};
 pub struct UserState {
     /// Key - public IP, Value - instance
     pub instances: HashMap<String, Instance>,
+
+    /// Bearer token of the `oct-ctl` API generated at genesis
+    #[serde(default)]
+    pub oct_ctl_token: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Eq, PartialEq)]
@@ -15,6 +19,227 @@ pub struct Instance {
     /// Memory available on instance
     pub memory: u64,
 
-    /// Services running on instance
+    /// Services running on instance, each service replica reserves its
+    /// `cpus` and `memory`
     pub services: HashMap<String, oct_config::Service>,
+
+    /// Key - service container name, Value - id of the container assigned by
+    /// the container manager
+    #[serde(default)]
+    pub container_ids: HashMap<String, String>,
+}
+
+impl Instance {
+    /// CPU millicores and memory in MB reserved by the running services
+    pub fn reserved(&self) -> (u32, u64) {
+        self.services
+            .values()
+            .fold((0, 0), |(cpus, memory), service| {
+                let (service_cpus, service_memory) =
+                    service.total_resources().unwrap_or((u32::MAX, u64::MAX));
+
+                (
+                    cpus.saturating_add(service_cpus),
+                    memory.saturating_add(service_memory),
+                )
+            })
+    }
+
+    /// CPU millicores and memory in MB left for new services
+    pub fn available(&self) -> (u32, u64) {
+        let (reserved_cpus, reserved_memory) = self.reserved();
+
+        (
+            self.cpus.saturating_sub(reserved_cpus),
+            self.memory.saturating_sub(reserved_memory),
+        )
+    }
+
+    /// Removes the service and the container ids of its replicas
+    pub fn remove_service(&mut self, name: &str) -> Option<oct_config::Service> {
+        let service = self.services.remove(name);
+
+        let container_names = service.as_ref().map_or_else(
+            || vec![name.to_string()],
+            oct_config::Service::container_names,
+        );
+        for container_name in container_names {
+            self.container_ids.remove(&container_name);
+        }
+
+        service
+    }
+
+    /// Checks whether all the service replicas fit into the remaining capacity
+    pub fn can_fit(&self, service: &oct_config::Service) -> bool {
+        let (available_cpus, available_memory) = self.available();
+        let Some((required_cpus, required_memory)) = service.total_resources() else {
+            return false;
+        };
+
+        required_cpus <= available_cpus && required_memory <= available_memory
+    }
+}
+
+impl UserState {
+    /// Checks whether the service is running on any instance
+    pub fn contains_service(&self, name: &str) -> bool {
+        self.instances
+            .values()
+            .any(|instance| instance.services.contains_key(name))
+    }
+
+    /// Removes the service from the instance it is running on
+    pub fn remove_service(&mut self, name: &str) -> Option<oct_config::Service> {
+        self.instances
+            .values_mut()
+            .find_map(|instance| instance.remove_service(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_user_state() -> UserState {
+        let service = oct_config::Service {
+            name: String::from("app_1"),
+            image: String::from("nginx:latest"),
+            cpus: 250,
+            memory: 64,
+            ..Default::default()
+        };
+
+        UserState {
+            instances: HashMap::from([(
+                String::from("localhost"),
+                Instance {
+                    cpus: 2000,
+                    memory: 1024,
+                    services: HashMap::from([(String::from("app_1"), service)]),
+                    container_ids: HashMap::from([(
+                        String::from("app_1"),
+                        String::from("container-1"),
+                    )]),
+                },
+            )]),
+            oct_ctl_token: None,
+        }
+    }
+
+    #[test]
+    fn test_user_state_remove_service() {
+        // Arrange
+        let mut user_state = get_user_state();
+
+        // Act
+        let removed_service = user_state.remove_service("app_1");
+
+        // Assert
+        assert_eq!(
+            removed_service.map(|service| service.name),
+            Some(String::from("app_1"))
+        );
+        assert!(!user_state.contains_service("app_1"));
+        assert!(user_state.instances.contains_key("localhost"));
+        assert!(user_state.instances["localhost"].container_ids.is_empty());
+    }
+
+    #[test]
+    fn test_user_state_remove_missing_service() {
+        // Arrange
+        let mut user_state = get_user_state();
+
+        // Act
+        let removed_service = user_state.remove_service("missing");
+
+        // Assert
+        assert!(removed_service.is_none());
+        assert!(user_state.contains_service("app_1"));
+    }
+
+    #[test]
+    fn test_instance_reservation_accounting() {
+        // Arrange
+        let mut user_state = get_user_state();
+        let mut app_2 = user_state.instances["localhost"].services["app_1"].clone();
+        app_2.name = String::from("app_2");
+        app_2.cpus = 1500;
+        app_2.memory = 512;
+
+        // Act
+        let instance = user_state
+            .instances
+            .get_mut("localhost")
+            .expect("Instance is missing");
+        let available_before = instance.available();
+        let can_fit_before = instance.can_fit(&app_2);
+
+        instance.services.insert(app_2.name.clone(), app_2.clone());
+        let available_after_add = instance.available();
+        let can_fit_again = instance.can_fit(&app_2);
+
+        user_state.remove_service("app_1");
+        let available_after_remove = user_state.instances["localhost"].available();
+
+        // Assert
+        assert_eq!(available_before, (1750, 960));
+        assert!(can_fit_before);
+        assert_eq!(available_after_add, (250, 448));
+        assert!(!can_fit_again);
+        assert_eq!(available_after_remove, (500, 512));
+    }
+
+    #[test]
+    fn test_instance_replicas_accounting() {
+        // Arrange
+        let mut user_state = get_user_state();
+        let instance = user_state
+            .instances
+            .get_mut("localhost")
+            .expect("Instance is missing");
+        let mut app_2 = instance.services["app_1"].clone();
+        app_2.name = String::from("app_2");
+        app_2.cpus = 500;
+        app_2.replicas = 3;
+
+        // Act
+        let can_fit = instance.can_fit(&app_2);
+
+        instance.services.insert(app_2.name.clone(), app_2.clone());
+        instance.container_ids.extend([
+            (String::from("app_2-0"), String::from("container-2")),
+            (String::from("app_2-1"), String::from("container-3")),
+            (String::from("app_2-2"), String::from("container-4")),
+        ]);
+        let reserved = instance.reserved();
+        let can_fit_again = instance.can_fit(&app_2);
+
+        user_state.remove_service("app_2");
+
+        // Assert
+        assert!(can_fit);
+        assert_eq!(reserved, (1750, 256));
+        assert!(!can_fit_again);
+        assert_eq!(
+            user_state.instances["localhost"].container_ids,
+            HashMap::from([(String::from("app_1"), String::from("container-1"))])
+        );
+    }
+
+    #[test]
+    fn test_instance_available_overcommitted() {
+        // Arrange
+        let mut user_state = get_user_state();
+        let instance = user_state
+            .instances
+            .get_mut("localhost")
+            .expect("Instance is missing");
+        instance.cpus = 0;
+        instance.memory = 0;
+
+        // Act & Assert
+        assert_eq!(instance.reserved(), (250, 64));
+        assert_eq!(instance.available(), (0, 0));
+    }
 }
@@ -0,0 +1,541 @@
+use std::collections::{BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use notify::{RecursiveMode, Watcher};
+use oct_config::{Config, Service};
+use tokio::sync::mpsc;
+
+use crate::build::{self, ImageBuilder};
+use crate::{ApplyOptions, OrchestratorWithGraph, get_config_dir};
+
+/// Quiet period after the last file change before the services are redeployed
+pub(crate) const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Collects changed paths and releases them once no new change arrived
+/// during the `delay`, so a burst of editor writes triggers a single redeploy
+#[derive(Debug)]
+pub(crate) struct Debouncer {
+    delay: Duration,
+    changed_paths: BTreeSet<PathBuf>,
+    last_change_at: Option<Instant>,
+}
+
+impl Debouncer {
+    pub(crate) fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            changed_paths: BTreeSet::new(),
+            last_change_at: None,
+        }
+    }
+
+    pub(crate) fn push(&mut self, path: PathBuf, at: Instant) {
+        self.changed_paths.insert(path);
+        self.last_change_at = Some(at);
+    }
+
+    /// Time left until the pending changes are released,
+    /// `None` when there are no pending changes
+    pub(crate) fn time_left(&self, now: Instant) -> Option<Duration> {
+        let last_change_at = self.last_change_at?;
+
+        Some(
+            self.delay
+                .saturating_sub(now.duration_since(last_change_at)),
+        )
+    }
+
+    /// Returns the pending changed paths once the quiet period is over
+    pub(crate) fn take_ready(&mut self, now: Instant) -> Option<Vec<PathBuf>> {
+        if self.time_left(now)? > Duration::ZERO {
+            return None;
+        }
+
+        self.last_change_at = None;
+
+        Some(
+            std::mem::take(&mut self.changed_paths)
+                .into_iter()
+                .collect(),
+        )
+    }
+}
+
+/// Directory with the service Dockerfile, `dockerfile_path` is relative
+/// to the config directory
+fn get_service_dir(config_dir: &Path, service: &Service) -> Option<PathBuf> {
+    let dockerfile_path = config_dir.join(service.dockerfile_path.as_ref()?);
+
+    Some(dockerfile_path.parent().unwrap_or(config_dir).to_path_buf())
+}
+
+/// Service Dockerfile directories to watch recursively
+pub(crate) fn get_service_dirs(config_dir: &Path, config: &Config) -> Vec<PathBuf> {
+    config
+        .project
+        .services
+        .iter()
+        .filter_map(|service| get_service_dir(config_dir, service))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Names of the services to redeploy after the `changed_paths` changes
+///
+/// A config change affects the added, changed and removed services,
+/// a change inside a Dockerfile directory affects the service built from it
+pub(crate) fn get_affected_services(
+    changed_paths: &[PathBuf],
+    config_paths: &[PathBuf],
+    previous_config: &Config,
+    config: &Config,
+) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut affected = BTreeSet::new();
+
+    if changed_paths.iter().any(|path| config_paths.contains(path)) {
+        let previous_services = previous_config.services_by_name()?;
+        let services = config.services_by_name()?;
+
+        for (name, service) in &services {
+            if previous_services.get(name) != Some(service) {
+                affected.insert(name.clone());
+            }
+        }
+
+        for name in previous_services.keys() {
+            if !services.contains_key(name) {
+                affected.insert(name.clone());
+            }
+        }
+    }
+
+    let config_dir = get_config_dir(config_paths);
+
+    for service in &config.project.services {
+        let Some(service_dir) = get_service_dir(config_dir, service) else {
+            continue;
+        };
+
+        let is_changed = changed_paths
+            .iter()
+            .any(|path| !config_paths.contains(path) && path.starts_with(&service_dir));
+
+        if is_changed {
+            affected.insert(service.name.clone());
+        }
+    }
+
+    Ok(affected.into_iter().collect())
+}
+
+/// Config with only the `affected_services` and the services they depend on,
+/// applied with `partial` so the other running services are kept
+///
+/// `None` when an affected service is removed from the config, stopping it
+/// needs a full apply
+pub(crate) fn get_partial_config(
+    config: &Config,
+    affected_services: &[String],
+) -> Result<Option<Config>, Box<dyn std::error::Error + Send + Sync>> {
+    let services = config.services_by_name()?;
+
+    let mut partial_services = BTreeSet::new();
+    for affected_service in affected_services {
+        if !services.contains_key(affected_service) {
+            return Ok(None);
+        }
+
+        partial_services.extend(config.get_target_services(affected_service)?);
+    }
+
+    let mut partial_config = config.clone();
+    partial_config
+        .project
+        .services
+        .retain(|service| partial_services.contains(&service.name));
+
+    Ok(Some(partial_config))
+}
+
+/// Rebuilds the images of the affected services built from a Dockerfile,
+/// then applies the affected services
+async fn redeploy(
+    orchestrator: &OrchestratorWithGraph,
+    config_dir: &Path,
+    config: &Config,
+    affected_services: &[String],
+    options: ApplyOptions,
+    image_builder: &dyn ImageBuilder,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let rebuilt_services = config
+        .project
+        .services
+        .iter()
+        .filter(|service| affected_services.contains(&service.name))
+        .cloned()
+        .collect::<Vec<_>>();
+    build::check_builds(&rebuilt_services, config_dir, image_builder)?;
+
+    match get_partial_config(config, affected_services)? {
+        Some(partial_config) => {
+            let options = ApplyOptions {
+                partial: true,
+                ..options
+            };
+
+            orchestrator
+                .apply_with_options(&partial_config, options)
+                .await
+        }
+        None => orchestrator.apply_with_options(config, options).await,
+    }
+}
+
+/// Applies the services, then re-applies them on every debounced change
+/// affecting at least one service until the watcher stops
+///
+/// `load_config` is called after each change, a config failing to load or
+/// a failed redeploy is only logged so the next change can fix it
+pub(crate) async fn watch_apply(
+    orchestrator: &OrchestratorWithGraph,
+    config_paths: &[String],
+    options: ApplyOptions,
+    load_config: impl Fn() -> Result<Config, Box<dyn std::error::Error + Send + Sync>>,
+    image_builder: &dyn ImageBuilder,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if config_paths.iter().any(|config_path| config_path == "-") {
+        return Err("Watching requires a config file, not stdin".into());
+    }
+
+    let config_paths = config_paths
+        .iter()
+        .map(std::fs::canonicalize)
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut config = load_config()?;
+
+    if let Err(e) = orchestrator.apply_with_options(&config, options).await {
+        log::error!("Failed to apply services: {e}");
+    }
+
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if let Ok(event) = event {
+            for path in event.paths {
+                let _ = sender.send(path);
+            }
+        }
+    })?;
+
+    // The config directories are watched instead of the config files
+    // themselves, many editors replace the file on save
+    let config_dirs = config_paths
+        .iter()
+        .filter_map(|config_path| config_path.parent())
+        .collect::<BTreeSet<_>>();
+    for config_dir in config_dirs {
+        watcher.watch(config_dir, RecursiveMode::NonRecursive)?;
+    }
+
+    let mut watched_dirs = HashSet::new();
+    let mut debouncer = Debouncer::new(DEFAULT_DEBOUNCE);
+
+    loop {
+        for dir in get_service_dirs(get_config_dir(&config_paths), &config) {
+            if watched_dirs.contains(&dir) {
+                continue;
+            }
+
+            match watcher.watch(&dir, RecursiveMode::Recursive) {
+                Ok(()) => {
+                    log::info!("Watching {}", dir.display());
+
+                    watched_dirs.insert(dir);
+                }
+                Err(e) => log::warn!("Failed to watch {}: {e}", dir.display()),
+            }
+        }
+
+        let received = match debouncer.time_left(Instant::now()) {
+            Some(time_left) => tokio::time::timeout(time_left, receiver.recv()).await,
+            None => Ok(receiver.recv().await),
+        };
+
+        match received {
+            Ok(Some(path)) => {
+                debouncer.push(path, Instant::now());
+
+                continue;
+            }
+            Ok(None) => return Err("File watcher stopped".into()),
+            Err(_elapsed) => {}
+        }
+
+        let Some(changed_paths) = debouncer.take_ready(Instant::now()) else {
+            continue;
+        };
+
+        let new_config = match load_config() {
+            Ok(new_config) => new_config,
+            Err(e) => {
+                log::error!("Failed to load config, waiting for the next change: {e}");
+
+                continue;
+            }
+        };
+
+        let affected_services =
+            match get_affected_services(&changed_paths, &config_paths, &config, &new_config) {
+                Ok(affected_services) => affected_services,
+                Err(e) => {
+                    log::error!(
+                        "Failed to get affected services, waiting for the next change: {e}"
+                    );
+
+                    continue;
+                }
+            };
+        config = new_config;
+
+        if affected_services.is_empty() {
+            continue;
+        }
+
+        log::info!("Redeploying services: {}", affected_services.join(", "));
+
+        if let Err(e) = redeploy(
+            orchestrator,
+            get_config_dir(&config_paths),
+            &config,
+            &affected_services,
+            options,
+            image_builder,
+        )
+        .await
+        {
+            log::error!("Failed to redeploy services: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_config(services: &str) -> Config {
+        let config_content = format!(
+            r#"
+[project]
+name = "example"
+
+[project.state_backend.local]
+path = "./state.json"
+
+[project.user_state_backend.local]
+path = "./user_state.json"
+
+{services}
+"#
+        );
+
+        Config::from_reader(std::io::Cursor::new(config_content))
+            .expect("Failed to create a new config")
+    }
+
+    const SERVICES: &str = r#"
+[[project.services]]
+name = "api"
+image = "api:latest"
+dockerfile_path = "api/Dockerfile"
+cpus = 250
+memory = 64
+
+[[project.services]]
+name = "web"
+image = "web:latest"
+dockerfile_path = "web/Dockerfile"
+cpus = 250
+memory = 64
+
+[[project.services]]
+name = "db"
+image = "postgres:latest"
+cpus = 250
+memory = 64
+"#;
+
+    #[test]
+    fn test_debouncer_releases_changes_after_quiet_period() {
+        // Arrange
+        let started_at = Instant::now();
+        let mut debouncer = Debouncer::new(Duration::from_millis(500));
+
+        debouncer.push(PathBuf::from("/project/oct.toml"), started_at);
+        debouncer.push(
+            PathBuf::from("/project/api/main.py"),
+            started_at + Duration::from_millis(300),
+        );
+        debouncer.push(
+            PathBuf::from("/project/oct.toml"),
+            started_at + Duration::from_millis(400),
+        );
+
+        // Act
+        let early = debouncer.take_ready(started_at + Duration::from_millis(800));
+        let ready = debouncer.take_ready(started_at + Duration::from_millis(900));
+
+        // Assert
+        assert_eq!(early, None);
+        assert_eq!(
+            ready,
+            Some(vec![
+                PathBuf::from("/project/api/main.py"),
+                PathBuf::from("/project/oct.toml"),
+            ])
+        );
+        assert_eq!(
+            debouncer.time_left(started_at + Duration::from_millis(900)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_debouncer_without_changes() {
+        // Arrange
+        let mut debouncer = Debouncer::new(DEFAULT_DEBOUNCE);
+
+        // Act
+        let ready = debouncer.take_ready(Instant::now());
+
+        // Assert
+        assert_eq!(ready, None);
+    }
+
+    #[test]
+    fn test_get_affected_services_dockerfile_dir_change() {
+        // Arrange
+        let config = get_config(SERVICES);
+
+        // Act
+        let affected_services = get_affected_services(
+            &[PathBuf::from("/project/web/src/index.html")],
+            &[PathBuf::from("/project/oct.toml")],
+            &config,
+            &config,
+        )
+        .expect("Failed to get affected services");
+
+        // Assert
+        assert_eq!(affected_services, vec![String::from("web")]);
+    }
+
+    #[test]
+    fn test_get_affected_services_config_change() {
+        // Arrange
+        let previous_config = get_config(SERVICES);
+        let config = get_config(
+            &SERVICES
+                .replace("api:latest", "api:v2")
+                .replace("name = \"db\"", "name = \"cache\""),
+        );
+
+        // Act
+        let affected_services = get_affected_services(
+            &[PathBuf::from("/project/prod.toml")],
+            &[
+                PathBuf::from("/project/oct.toml"),
+                PathBuf::from("/project/prod.toml"),
+            ],
+            &previous_config,
+            &config,
+        )
+        .expect("Failed to get affected services");
+
+        // Assert
+        assert_eq!(
+            affected_services,
+            vec![
+                String::from("api"),
+                String::from("cache"),
+                String::from("db"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_affected_services_unrelated_change() {
+        // Arrange
+        let config = get_config(SERVICES);
+
+        // Act
+        let affected_services = get_affected_services(
+            &[
+                PathBuf::from("/project/oct.toml"),
+                PathBuf::from("/project/README.md"),
+            ],
+            &[PathBuf::from("/project/oct.toml")],
+            &config,
+            &config,
+        )
+        .expect("Failed to get affected services");
+
+        // Assert
+        assert!(affected_services.is_empty());
+    }
+
+    #[test]
+    fn test_get_partial_config() {
+        // Arrange
+        let config = get_config(&SERVICES.replace(
+            "dockerfile_path = \"web/Dockerfile\"",
+            "dockerfile_path = \"web/Dockerfile\"\ndepends_on = [\"db\"]",
+        ));
+
+        // Act
+        let partial_config =
+            get_partial_config(&config, &[String::from("web")]).expect("Failed to get config");
+
+        // Assert
+        let partial_services = partial_config
+            .expect("Expected partial config")
+            .project
+            .services
+            .into_iter()
+            .map(|service| service.name)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            partial_services,
+            vec![String::from("web"), String::from("db")]
+        );
+    }
+
+    #[test]
+    fn test_get_partial_config_removed_service() {
+        // Arrange
+        let config = get_config(SERVICES);
+
+        // Act
+        let partial_config =
+            get_partial_config(&config, &[String::from("api"), String::from("cache")])
+                .expect("Failed to get config");
+
+        // Assert
+        assert!(partial_config.is_none());
+    }
+
+    #[test]
+    fn test_get_service_dirs() {
+        // Arrange
+        let config = get_config(SERVICES);
+
+        // Act
+        let service_dirs = get_service_dirs(Path::new("/project"), &config);
+
+        // Assert
+        assert_eq!(
+            service_dirs,
+            vec![PathBuf::from("/project/api"), PathBuf::from("/project/web"),]
+        );
+    }
+}
@@ -23,6 +23,9 @@ pub trait ConfigManager: Send + Sync {
     // Project Management
     fn list_projects(&self) -> Vec<ProjectSummary>;
     fn create_project(&self, name: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+    /// Whether the config storage can be read, used by the readiness check
+    fn is_available(&self) -> bool;
 }
 
 pub struct FileConfigManager {
@@ -47,8 +50,7 @@ impl FileConfigManager {
                     user_state_backend: StateBackend::Local {
                         path: "./user_state.json".to_string(),
                     },
-                    services: vec![],
-                    domain: None,
+                    ..Default::default()
                 },
             });
         }
@@ -100,6 +102,10 @@ impl ConfigManager for FileConfigManager {
     fn create_project(&self, _name: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         Err("Project creation not supported in single-file mode".into())
     }
+
+    fn is_available(&self) -> bool {
+        self.load().is_ok()
+    }
 }
 
 pub struct WorkspaceConfigManager {
@@ -188,8 +194,7 @@ impl ConfigManager for WorkspaceConfigManager {
                 user_state_backend: StateBackend::Local {
                     path: path.join("user_state.json").to_string_lossy().to_string(),
                 },
-                services: vec![],
-                domain: None,
+                ..Default::default()
             },
         };
 
@@ -239,6 +244,10 @@ impl ConfigManager for WorkspaceConfigManager {
         fs::write(config_path, toml_str)?;
         Ok(())
     }
+
+    fn is_available(&self) -> bool {
+        self.root_path.is_dir()
+    }
 }
 
 #[cfg(test)]
@@ -307,8 +316,7 @@ path = "./user_state.json"
                 user_state_backend: StateBackend::Local {
                     path: "user_state.json".to_string(),
                 },
-                services: vec![],
-                domain: None,
+                ..Default::default()
             },
         };
 
@@ -0,0 +1,418 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use oct_config::{Config, StateBackend};
+
+pub const GITHUB_API_URL: &str = "https://api.github.com";
+
+/// Name of the config file fetched from the root of the repository
+const CONFIG_FILE_NAME: &str = "oct.toml";
+
+/// GitHub API rate limit is exceeded, requests can be retried after
+/// `retry_after`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimitError {
+    pub retry_after: Duration,
+}
+
+impl std::fmt::Display for RateLimitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "GitHub API rate limit exceeded, retry in {} seconds",
+            self.retry_after.as_secs()
+        )
+    }
+}
+
+impl std::error::Error for RateLimitError {}
+
+/// GitHub API is unreachable or responded with an unexpected status, the
+/// failure is not caused by the requested repository config
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpstreamError {
+    pub message: String,
+}
+
+impl std::fmt::Display for UpstreamError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for UpstreamError {}
+
+/// Detects a rate limited response
+///
+/// The primary rate limit responds with `X-RateLimit-Remaining: 0` and the
+/// `X-RateLimit-Reset` epoch time, the secondary one with `Retry-After`
+/// seconds
+fn get_rate_limit_error(response: &reqwest::Response, now: SystemTime) -> Option<RateLimitError> {
+    if !matches!(
+        response.status(),
+        reqwest::StatusCode::FORBIDDEN | reqwest::StatusCode::TOO_MANY_REQUESTS
+    ) {
+        return None;
+    }
+
+    let get_header =
+        |name: &str| -> Option<u64> { response.headers().get(name)?.to_str().ok()?.parse().ok() };
+
+    if let Some(retry_after) = get_header("retry-after") {
+        return Some(RateLimitError {
+            retry_after: Duration::from_secs(retry_after),
+        });
+    }
+
+    if get_header("x-ratelimit-remaining") != Some(0) {
+        return None;
+    }
+
+    let reset_at = UNIX_EPOCH + Duration::from_secs(get_header("x-ratelimit-reset")?);
+
+    Some(RateLimitError {
+        retry_after: reset_at.duration_since(now).unwrap_or(Duration::ZERO),
+    })
+}
+
+/// Rejects local state backends of a repository config, their paths would
+/// read and write the files of the platform host
+fn validate_state_backends(
+    config: &Config,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let has_local_backend = [
+        &config.project.state_backend,
+        &config.project.user_state_backend,
+    ]
+    .into_iter()
+    .any(|state_backend| matches!(state_backend, StateBackend::Local { .. }));
+
+    if has_local_backend {
+        return Err("state_backend and user_state_backend must be s3".into());
+    }
+
+    Ok(())
+}
+
+/// Fetches `oct.toml` of the `repo` (`owner/name`) with the GitHub contents
+/// API and validates it, `git_ref` defaults to the repository default branch
+///
+/// Only the S3 state backends are accepted. A rate limited request fails
+/// with [`RateLimitError`], a network failure or an unexpected GitHub status
+/// with [`UpstreamError`]
+pub async fn fetch_repo_config(
+    api_url: &str,
+    repo: &str,
+    git_ref: Option<&str>,
+) -> Result<Config, Box<dyn std::error::Error + Send + Sync>> {
+    let is_valid_repo = matches!(
+        repo.split_once('/'),
+        Some((owner, name)) if !owner.is_empty() && !name.is_empty() && !name.contains('/')
+    );
+    if !is_valid_repo {
+        return Err(format!("Invalid repository '{repo}', expected 'owner/name'").into());
+    }
+
+    let mut url = reqwest::Url::parse(&format!(
+        "{api_url}/repos/{repo}/contents/{CONFIG_FILE_NAME}"
+    ))?;
+    if let Some(git_ref) = git_ref {
+        url.query_pairs_mut().append_pair("ref", git_ref);
+    }
+
+    let upstream_error = |e: &dyn std::fmt::Display| UpstreamError {
+        message: format!("Failed to fetch {CONFIG_FILE_NAME} from {repo}: {e}"),
+    };
+
+    let response = reqwest::Client::new()
+        .get(url)
+        .header(reqwest::header::ACCEPT, "application/vnd.github.raw")
+        .header(reqwest::header::USER_AGENT, "oct-platform")
+        .send()
+        .await
+        .map_err(|e| upstream_error(&e))?;
+
+    if let Some(rate_limit_error) = get_rate_limit_error(&response, SystemTime::now()) {
+        return Err(Box::new(rate_limit_error));
+    }
+
+    match response.status() {
+        reqwest::StatusCode::OK => {}
+        reqwest::StatusCode::NOT_FOUND => {
+            return Err(format!("{CONFIG_FILE_NAME} is not found in {repo}").into());
+        }
+        status => {
+            return Err(Box::new(upstream_error(&status)));
+        }
+    }
+
+    let content = response.text().await.map_err(|e| upstream_error(&e))?;
+
+    // The repository is not trusted, its config must not read the host envs
+    Config::from_untrusted_str(&content)
+        .and_then(|config| {
+            validate_state_backends(&config)?;
+
+            Ok(config)
+        })
+        .map_err(|e| format!("Invalid {CONFIG_FILE_NAME} in {repo}: {e}").into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+[project]
+name = "example"
+
+[project.state_backend.s3]
+region = "us-west-2"
+bucket = "example"
+key = "state.json"
+
+[project.user_state_backend.s3]
+region = "us-west-2"
+bucket = "example"
+key = "user_state.json"
+
+[[project.services]]
+name = "app_1"
+image = "nginx:latest"
+cpus = 250
+memory = 64
+"#;
+
+    #[tokio::test]
+    async fn test_fetch_repo_config() {
+        // Arrange
+        let mut server = mockito::Server::new_async().await;
+        let contents_mock = server
+            .mock("GET", "/repos/owner/app/contents/oct.toml")
+            .match_query(mockito::Matcher::UrlEncoded("ref".into(), "main".into()))
+            .match_header("accept", "application/vnd.github.raw")
+            .with_status(200)
+            .with_body(CONFIG)
+            .create_async()
+            .await;
+
+        // Act
+        let config = fetch_repo_config(&server.url(), "owner/app", Some("main"))
+            .await
+            .expect("Failed to fetch config");
+
+        // Assert
+        contents_mock.assert_async().await;
+        assert_eq!(config.project.name, "example");
+        assert_eq!(config.project.services[0].name, "app_1");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_repo_config_invalid_config() {
+        // Arrange
+        let mut server = mockito::Server::new_async().await;
+        let _contents_mock = server
+            .mock("GET", "/repos/owner/app/contents/oct.toml")
+            .with_status(200)
+            .with_body("[project]\nname = \"example\"\n")
+            .create_async()
+            .await;
+
+        // Act
+        let result = fetch_repo_config(&server.url(), "owner/app", None).await;
+
+        // Assert
+        let error = result.expect_err("Expected validation error").to_string();
+        assert!(error.starts_with("Invalid oct.toml in owner/app: "));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_repo_config_local_state_backend() {
+        // Arrange
+        let mut server = mockito::Server::new_async().await;
+        let _contents_mock = server
+            .mock("GET", "/repos/owner/app/contents/oct.toml")
+            .with_status(200)
+            .with_body(CONFIG.replacen(
+                "[project.state_backend.s3]\nregion = \"us-west-2\"\nbucket = \"example\"\nkey",
+                "[project.state_backend.local]\npath",
+                1,
+            ))
+            .create_async()
+            .await;
+
+        // Act
+        let result = fetch_repo_config(&server.url(), "owner/app", None).await;
+
+        // Assert
+        assert_eq!(
+            result
+                .expect_err("Expected state backend error")
+                .to_string(),
+            "Invalid oct.toml in owner/app: state_backend and user_state_backend must be s3"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_repo_config_not_found() {
+        // Arrange
+        let mut server = mockito::Server::new_async().await;
+        let _contents_mock = server
+            .mock("GET", "/repos/owner/app/contents/oct.toml")
+            .with_status(404)
+            .create_async()
+            .await;
+
+        // Act
+        let result = fetch_repo_config(&server.url(), "owner/app", None).await;
+
+        // Assert
+        assert_eq!(
+            result.expect_err("Expected not found error").to_string(),
+            "oct.toml is not found in owner/app"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_repo_config_rate_limited() {
+        // Arrange
+        let reset_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Failed to get current time")
+            + Duration::from_secs(120);
+
+        let mut server = mockito::Server::new_async().await;
+        let _contents_mock = server
+            .mock("GET", "/repos/owner/app/contents/oct.toml")
+            .with_status(403)
+            .with_header("x-ratelimit-remaining", "0")
+            .with_header("x-ratelimit-reset", &reset_at.as_secs().to_string())
+            .with_body(r#"{"message": "API rate limit exceeded"}"#)
+            .create_async()
+            .await;
+
+        // Act
+        let result = fetch_repo_config(&server.url(), "owner/app", None).await;
+
+        // Assert
+        let error = result.expect_err("Expected rate limit error");
+        let rate_limit_error = error
+            .downcast_ref::<RateLimitError>()
+            .expect("Expected RateLimitError");
+        assert!(rate_limit_error.retry_after <= Duration::from_secs(120));
+        assert!(rate_limit_error.retry_after > Duration::from_secs(100));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_repo_config_secondary_rate_limited() {
+        // Arrange
+        let mut server = mockito::Server::new_async().await;
+        let _contents_mock = server
+            .mock("GET", "/repos/owner/app/contents/oct.toml")
+            .with_status(429)
+            .with_header("retry-after", "30")
+            .create_async()
+            .await;
+
+        // Act
+        let result = fetch_repo_config(&server.url(), "owner/app", None).await;
+
+        // Assert
+        let error = result.expect_err("Expected rate limit error");
+        assert_eq!(
+            error.downcast_ref::<RateLimitError>(),
+            Some(&RateLimitError {
+                retry_after: Duration::from_secs(30),
+            })
+        );
+        assert_eq!(
+            error.to_string(),
+            "GitHub API rate limit exceeded, retry in 30 seconds"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_repo_config_forbidden_without_rate_limit() {
+        // Arrange
+        let mut server = mockito::Server::new_async().await;
+        let _contents_mock = server
+            .mock("GET", "/repos/owner/app/contents/oct.toml")
+            .with_status(403)
+            .with_header("x-ratelimit-remaining", "59")
+            .create_async()
+            .await;
+
+        // Act
+        let result = fetch_repo_config(&server.url(), "owner/app", None).await;
+
+        // Assert
+        assert_eq!(
+            result
+                .expect_err("Expected forbidden error")
+                .downcast_ref::<UpstreamError>()
+                .map(ToString::to_string),
+            Some(String::from(
+                "Failed to fetch oct.toml from owner/app: 403 Forbidden"
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_repo_config_server_error() {
+        // Arrange
+        let mut server = mockito::Server::new_async().await;
+        let _contents_mock = server
+            .mock("GET", "/repos/owner/app/contents/oct.toml")
+            .with_status(502)
+            .create_async()
+            .await;
+
+        // Act
+        let result = fetch_repo_config(&server.url(), "owner/app", None).await;
+
+        // Assert
+        assert_eq!(
+            result
+                .expect_err("Expected server error")
+                .downcast_ref::<UpstreamError>()
+                .map(ToString::to_string),
+            Some(String::from(
+                "Failed to fetch oct.toml from owner/app: 502 Bad Gateway"
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_repo_config_unreachable() {
+        // Arrange
+        let listener =
+            std::net::TcpListener::bind("127.0.0.1:0").expect("Failed to bind a free port");
+        let api_url = format!(
+            "http://{}",
+            listener.local_addr().expect("Failed to get local address")
+        );
+        drop(listener);
+
+        // Act
+        let result = fetch_repo_config(&api_url, "owner/app", None).await;
+
+        // Assert
+        assert!(
+            result
+                .expect_err("Expected network error")
+                .downcast_ref::<UpstreamError>()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_repo_config_invalid_repo() {
+        // Act
+        let result = fetch_repo_config(GITHUB_API_URL, "owner/app/extra", None).await;
+
+        // Assert
+        assert_eq!(
+            result.expect_err("Expected invalid repo error").to_string(),
+            "Invalid repository 'owner/app/extra', expected 'owner/name'"
+        );
+    }
+}
@@ -4,18 +4,21 @@ use std::fmt::Write;
 use std::sync::Arc;
 
 use askama::Template;
-use axum::extract::{Json, Path, Query, State};
-use axum::http::StatusCode;
+use axum::extract::{Json, Path, Query, Request, State};
+use axum::http::{StatusCode, header};
+use axum::middleware::Next;
 use axum::response::sse::{Event, Sse};
-use axum::response::{Html, IntoResponse};
+use axum::response::{Html, IntoResponse, Response};
 use futures::stream::Stream;
 use oct_cloud::infra::state::State as InfraState;
 use oct_config::{Project, Service};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tokio_stream::StreamExt;
 use tokio_stream::wrappers::BroadcastStream;
 
 use crate::config_manager::{ConfigManager, ProjectSummary};
+use crate::github::{RateLimitError, UpstreamError, fetch_repo_config};
+use crate::jobs::JobQueue;
 use crate::orchestrator::Orchestrator;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -25,6 +28,10 @@ pub struct AppState {
     pub orchestrator: Arc<dyn Orchestrator>,
     pub config_manager: Arc<dyn ConfigManager>,
     pub log_sender: tokio::sync::broadcast::Sender<String>,
+    pub github_api_url: String,
+    pub jobs: JobQueue,
+    /// Bearer token required by the `/deploy` endpoints, `None` disables them
+    pub deploy_token: Option<String>,
 }
 
 // --- Templates ---
@@ -79,6 +86,36 @@ pub async fn root_redirect() -> impl IntoResponse {
     axum::response::Redirect::to("/projects")
 }
 
+/// Liveness endpoint, responds while the server is able to handle requests
+pub async fn healthz() -> impl IntoResponse {
+    StatusCode::OK
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadinessStatus {
+    pub config: bool,
+}
+
+/// Readiness endpoint, responds with 503 until the config storage
+/// can be read
+///
+/// The platform reads no GitHub app credentials, e.g. `GITHUB_CLIENT_ID`,
+/// so their presence says nothing about whether requests can be served,
+/// the config storage every page and deploy depends on does
+pub async fn readyz(State(state): State<AppState>) -> impl IntoResponse {
+    let readiness_status = ReadinessStatus {
+        config: state.config_manager.is_available(),
+    };
+
+    let status_code = if readiness_status.config {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (status_code, Json(readiness_status))
+}
+
 pub async fn list_projects(State(state): State<AppState>) -> impl IntoResponse {
     let projects = state.config_manager.list_projects();
     let template = ProjectsTemplate {
@@ -214,10 +251,18 @@ fn form_to_services(
                 cpus: s.cpus.parse().unwrap_or(250),
                 memory: s.memory.parse().unwrap_or(64),
                 dockerfile_path: existing.and_then(|e| e.dockerfile_path.clone()),
+                entrypoint: existing.and_then(|e| e.entrypoint.clone()),
                 command: existing.and_then(|e| e.command.clone()),
                 internal_port: existing.and_then(|e| e.internal_port),
                 external_port: existing.and_then(|e| e.external_port),
+                ports: existing.map(|e| e.ports.clone()).unwrap_or_default(),
+                network_mode: existing.and_then(|e| e.network_mode),
+                pull_policy: existing.and_then(|e| e.pull_policy),
+                replicas: existing.map_or(1, |e| e.replicas),
                 depends_on: existing.map(|e| e.depends_on.clone()).unwrap_or_default(),
+                health_check: existing.and_then(|e| e.health_check.clone()),
+                description: existing.and_then(|e| e.description.clone()),
+                labels: existing.map(|e| e.labels.clone()).unwrap_or_default(),
                 envs,
             }
         })
@@ -354,12 +399,7 @@ pub async fn add_service_to_config(
         image: "nginx:latest".to_string(),
         cpus: 250,
         memory: 64,
-        dockerfile_path: None,
-        command: None,
-        internal_port: None,
-        external_port: None,
-        depends_on: vec![],
-        envs: HashMap::new(),
+        ..Default::default()
     });
 
     config.project.services = new_services;
@@ -507,6 +547,129 @@ pub async fn remove_env_var_from_config(
     render_template(template).into_response()
 }
 
+/// Compares the tokens in time independent of where they differ,
+/// so the deploy token can't be guessed byte by byte from response times
+fn tokens_eq(left: &str, right: &str) -> bool {
+    left.len() == right.len()
+        && left
+            .bytes()
+            .zip(right.bytes())
+            .fold(0, |diff, (left_byte, right_byte)| {
+                diff | (left_byte ^ right_byte)
+            })
+            == 0
+}
+
+/// Rejects requests without the `Authorization: Bearer <token>` header
+/// matching the deploy token, all of them when no token is configured
+pub async fn check_deploy_token(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(token) = &state.deploy_token else {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({
+                "error": "Deploy API is disabled, set OCT_PLATFORM_DEPLOY_TOKEN to enable it",
+            })),
+        )
+            .into_response();
+    };
+
+    let request_token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    if !request_token.is_some_and(|request_token| tokens_eq(request_token, token)) {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(serde_json::json!({ "error": "Missing or invalid bearer token" })),
+        )
+            .into_response();
+    }
+
+    next.run(request).await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeployFromRepoPayload {
+    /// GitHub repository in the `owner/name` format
+    pub repo: String,
+    #[serde(rename = "ref")]
+    pub git_ref: Option<String>,
+}
+
+/// Fetches and validates `oct.toml` of the repository and applies it in
+/// a background job, validation errors are returned as
+/// `422 Unprocessable Entity`, GitHub rate limiting as
+/// `429 Too Many Requests` with the `retry_after` seconds and network
+/// failures or unexpected GitHub statuses as `502 Bad Gateway`
+pub async fn deploy_from_repo(
+    State(state): State<AppState>,
+    Json(payload): Json<DeployFromRepoPayload>,
+) -> impl IntoResponse {
+    let result = fetch_repo_config(
+        &state.github_api_url,
+        &payload.repo,
+        payload.git_ref.as_deref(),
+    )
+    .await;
+
+    match result {
+        Ok(config) => {
+            let orchestrator = state.orchestrator.clone();
+
+            let job_id = state
+                .jobs
+                .spawn(async move { orchestrator.apply(&config).await })
+                .await;
+
+            (
+                StatusCode::ACCEPTED,
+                Json(serde_json::json!({ "job_id": job_id })),
+            )
+        }
+        Err(e) => {
+            if let Some(rate_limit_error) = e.downcast_ref::<RateLimitError>() {
+                return (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    Json(serde_json::json!({
+                        "error": e.to_string(),
+                        "retry_after": rate_limit_error.retry_after.as_secs(),
+                    })),
+                );
+            }
+
+            let status_code = if e.is::<UpstreamError>() {
+                StatusCode::BAD_GATEWAY
+            } else {
+                StatusCode::UNPROCESSABLE_ENTITY
+            };
+
+            (
+                status_code,
+                Json(serde_json::json!({ "error": e.to_string() })),
+            )
+        }
+    }
+}
+
+pub async fn deploy_status(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match state.jobs.status(&id).await {
+        Some(status) => (StatusCode::OK, Json(serde_json::json!(status))),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({ "error": format!("Job {id} not found") })),
+        ),
+    }
+}
+
 pub async fn run_genesis(
     State(state): State<AppState>,
     Path(name): Path<String>,
@@ -623,11 +786,8 @@ mod tests {
             cpus: 100,
             memory: 128,
             dockerfile_path: Some("Dockerfile".to_string()),
-            command: None,
             internal_port: Some(80),
-            external_port: None,
-            depends_on: vec![],
-            envs: HashMap::new(),
+            ..Default::default()
         }];
 
         let updates = vec![ServiceUpdate {
@@ -0,0 +1,250 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use serde::Serialize;
+use tokio::sync::{Mutex, Semaphore};
+
+pub type JobId = String;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed { message: String },
+}
+
+/// Jobs running at the same time with [`JobQueue::default`]
+const DEFAULT_MAX_RUNNING_JOBS: usize = 4;
+
+/// Finished jobs kept by [`JobQueue::default`]
+const DEFAULT_MAX_FINISHED_JOBS: usize = 1000;
+
+#[derive(Default)]
+struct Jobs {
+    statuses: HashMap<JobId, JobStatus>,
+    /// Finished job ids, the oldest first
+    finished: VecDeque<JobId>,
+}
+
+impl Jobs {
+    /// Records the final status of the job and evicts the oldest finished
+    /// jobs beyond `max_finished`
+    fn finish(&mut self, id: JobId, status: JobStatus, max_finished: usize) {
+        self.statuses.insert(id.clone(), status);
+        self.finished.push_back(id);
+
+        while self.finished.len() > max_finished {
+            if let Some(evicted_id) = self.finished.pop_front() {
+                self.statuses.remove(&evicted_id);
+            }
+        }
+    }
+}
+
+/// In-memory registry of the background jobs, the statuses are lost
+/// on restart
+///
+/// At most `max_running` jobs run at the same time, the others wait
+/// `Queued` for a free slot. Only the last `max_finished` finished jobs are
+/// kept, the status of an older one is no longer found
+#[derive(Clone)]
+pub struct JobQueue {
+    jobs: Arc<Mutex<Jobs>>,
+    running: Arc<Semaphore>,
+    max_finished: usize,
+}
+
+impl Default for JobQueue {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_RUNNING_JOBS, DEFAULT_MAX_FINISHED_JOBS)
+    }
+}
+
+impl JobQueue {
+    pub fn new(max_running: usize, max_finished: usize) -> Self {
+        Self {
+            jobs: Arc::default(),
+            running: Arc::new(Semaphore::new(max_running)),
+            max_finished,
+        }
+    }
+
+    /// Runs the `job` in a background task and returns its id right away,
+    /// the job is `Queued` until the task starts and a running slot is free
+    pub async fn spawn<F>(&self, job: F) -> JobId
+    where
+        F: Future<Output = Result<(), Box<dyn std::error::Error + Send + Sync>>> + Send + 'static,
+    {
+        let id = uuid::Uuid::new_v4().to_string();
+
+        self.jobs
+            .lock()
+            .await
+            .statuses
+            .insert(id.clone(), JobStatus::Queued);
+
+        let jobs = self.jobs.clone();
+        let running = self.running.clone();
+        let max_finished = self.max_finished;
+        let job_id = id.clone();
+        tokio::spawn(async move {
+            let _running_permit = running
+                .acquire_owned()
+                .await
+                .expect("Job semaphore is never closed");
+
+            jobs.lock()
+                .await
+                .statuses
+                .insert(job_id.clone(), JobStatus::Running);
+
+            let status = match job.await {
+                Ok(()) => JobStatus::Succeeded,
+                Err(e) => {
+                    tracing::error!("Job {job_id} failed: {e}");
+
+                    JobStatus::Failed {
+                        message: e.to_string(),
+                    }
+                }
+            };
+
+            jobs.lock().await.finish(job_id, status, max_finished);
+        });
+
+        id
+    }
+
+    pub async fn status(&self, id: &str) -> Option<JobStatus> {
+        self.jobs.lock().await.statuses.get(id).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::oneshot;
+
+    use super::*;
+
+    /// Yields to the spawned job until it leaves the `Running` status
+    async fn wait_for_finish(queue: &JobQueue, id: &str) -> Option<JobStatus> {
+        loop {
+            let status = queue.status(id).await;
+            if status != Some(JobStatus::Queued) && status != Some(JobStatus::Running) {
+                return status;
+            }
+
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_job_queue_success_lifecycle() {
+        // Arrange
+        let queue = JobQueue::default();
+        let (finish_sender, finish_receiver) = oneshot::channel::<()>();
+
+        // Act
+        let id = queue
+            .spawn(async move {
+                finish_receiver.await?;
+
+                Ok(())
+            })
+            .await;
+        let queued_status = queue.status(&id).await;
+
+        tokio::task::yield_now().await;
+        let running_status = queue.status(&id).await;
+
+        finish_sender.send(()).expect("Failed to finish job");
+        let finished_status = wait_for_finish(&queue, &id).await;
+
+        // Assert
+        assert_eq!(queued_status, Some(JobStatus::Queued));
+        assert_eq!(running_status, Some(JobStatus::Running));
+        assert_eq!(finished_status, Some(JobStatus::Succeeded));
+    }
+
+    #[tokio::test]
+    async fn test_job_queue_failure_lifecycle() {
+        // Arrange
+        let queue = JobQueue::default();
+
+        // Act
+        let id = queue.spawn(async { Err("No VMs available".into()) }).await;
+        let finished_status = wait_for_finish(&queue, &id).await;
+
+        // Assert
+        assert_eq!(
+            finished_status,
+            Some(JobStatus::Failed {
+                message: String::from("No VMs available"),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_job_queue_evicts_oldest_finished_job() {
+        // Arrange
+        let queue = JobQueue::new(DEFAULT_MAX_RUNNING_JOBS, 1);
+
+        // Act
+        let first_id = queue.spawn(async { Ok(()) }).await;
+        let first_status = wait_for_finish(&queue, &first_id).await;
+
+        let second_id = queue.spawn(async { Ok(()) }).await;
+        let second_status = wait_for_finish(&queue, &second_id).await;
+
+        // Assert
+        assert_eq!(first_status, Some(JobStatus::Succeeded));
+        assert_eq!(second_status, Some(JobStatus::Succeeded));
+        assert_eq!(queue.status(&first_id).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_job_queue_limits_running_jobs() {
+        // Arrange
+        let queue = JobQueue::new(1, DEFAULT_MAX_FINISHED_JOBS);
+        let (finish_sender, finish_receiver) = oneshot::channel::<()>();
+
+        // Act
+        let first_id = queue
+            .spawn(async move {
+                finish_receiver.await?;
+
+                Ok(())
+            })
+            .await;
+        let second_id = queue.spawn(async { Ok(()) }).await;
+
+        tokio::task::yield_now().await;
+        tokio::task::yield_now().await;
+        let first_running_status = queue.status(&first_id).await;
+        let second_waiting_status = queue.status(&second_id).await;
+
+        finish_sender.send(()).expect("Failed to finish job");
+        let first_status = wait_for_finish(&queue, &first_id).await;
+        let second_status = wait_for_finish(&queue, &second_id).await;
+
+        // Assert
+        assert_eq!(first_running_status, Some(JobStatus::Running));
+        assert_eq!(second_waiting_status, Some(JobStatus::Queued));
+        assert_eq!(first_status, Some(JobStatus::Succeeded));
+        assert_eq!(second_status, Some(JobStatus::Succeeded));
+    }
+
+    #[tokio::test]
+    async fn test_job_queue_unknown_job() {
+        // Arrange
+        let queue = JobQueue::default();
+
+        // Act
+        let status = queue.status("unknown").await;
+
+        // Assert
+        assert_eq!(status, None);
+    }
+}
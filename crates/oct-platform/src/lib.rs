@@ -1,5 +1,7 @@
 pub mod config_manager;
+pub mod github;
 pub mod handlers;
+pub mod jobs;
 pub mod logging;
 pub mod orchestrator;
 pub mod routes;
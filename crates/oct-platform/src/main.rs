@@ -1,7 +1,9 @@
 use std::sync::Arc;
 
 use oct_platform::config_manager::{ConfigManager, FileConfigManager, WorkspaceConfigManager};
+use oct_platform::github::GITHUB_API_URL;
 use oct_platform::handlers::AppState;
+use oct_platform::jobs::JobQueue;
 use oct_platform::logging::LogLayer;
 use oct_platform::orchestrator::{MockOrchestrator, Orchestrator, RealOrchestrator};
 use oct_platform::routes::router;
@@ -62,10 +64,20 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         .and_then(|p| p.parse::<u16>().ok())
         .unwrap_or(3000);
 
+    let deploy_token = std::env::var("OCT_PLATFORM_DEPLOY_TOKEN")
+        .ok()
+        .filter(|token| !token.is_empty());
+    if deploy_token.is_none() {
+        tracing::warn!("OCT_PLATFORM_DEPLOY_TOKEN is not set, the deploy API is disabled");
+    }
+
     let state = AppState {
         orchestrator,
         config_manager,
         log_sender,
+        github_api_url: GITHUB_API_URL.to_string(),
+        jobs: JobQueue::default(),
+        deploy_token,
     };
 
     let app = router(state).layer(TraceLayer::new_for_http());
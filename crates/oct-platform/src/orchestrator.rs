@@ -102,8 +102,7 @@ mod tests {
                 user_state_backend: StateBackend::Local {
                     path: "user_state.json".to_string(),
                 },
-                services: vec![],
-                domain: None,
+                ..Default::default()
             },
         }
     }
@@ -1,15 +1,24 @@
-use axum::Router;
 use axum::routing::{get, post, put};
+use axum::{Router, middleware};
 
 use crate::handlers::{
-    AppState, add_env_var_to_config, add_service_to_config, create_project_action, edit_config,
-    list_projects, project_dashboard, remove_env_var_from_config, remove_service_from_config,
+    AppState, add_env_var_to_config, add_service_to_config, check_deploy_token,
+    create_project_action, deploy_from_repo, deploy_status, edit_config, healthz, list_projects,
+    project_dashboard, readyz, remove_env_var_from_config, remove_service_from_config,
     root_redirect, run_apply, run_destroy, run_genesis, update_config, view_state,
 };
 
 pub fn router(state: AppState) -> Router {
     Router::new()
+        .route("/deploy", post(deploy_from_repo))
+        .route("/deploy/{id}", get(deploy_status))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            check_deploy_token,
+        ))
         .route("/", get(root_redirect))
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
         .route("/projects", get(list_projects).post(create_project_action))
         .route("/projects/{name}", get(project_dashboard))
         .route("/projects/{name}/edit", get(edit_config))
@@ -36,3 +45,188 @@ pub fn router(state: AppState) -> Router {
         .route("/projects/{name}/action/destroy", get(run_destroy))
         .with_state(state)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::config_manager::{ConfigManager, FileConfigManager, WorkspaceConfigManager};
+    use crate::github::GITHUB_API_URL;
+    use crate::jobs::JobQueue;
+    use crate::orchestrator::MockOrchestrator;
+
+    fn get_state(config_manager: Arc<dyn ConfigManager>) -> AppState {
+        let (log_sender, _log_receiver) = tokio::sync::broadcast::channel(1);
+
+        AppState {
+            orchestrator: Arc::new(MockOrchestrator::default()),
+            config_manager,
+            log_sender,
+            github_api_url: GITHUB_API_URL.to_string(),
+            jobs: JobQueue::default(),
+            deploy_token: Some(String::from("secret-token")),
+        }
+    }
+
+    async fn get_status(state: AppState, uri: &str) -> StatusCode {
+        router(state)
+            .oneshot(
+                Request::get(uri)
+                    .body(Body::empty())
+                    .expect("Failed to prepare body"),
+            )
+            .await
+            .expect("Failed to get response")
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_healthz() {
+        // Arrange
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let config_path = dir.path().join("oct.toml");
+        std::fs::write(&config_path, "invalid").expect("Failed to write config");
+
+        let state = get_state(Arc::new(FileConfigManager::new(
+            &config_path.to_string_lossy(),
+        )));
+
+        // Act
+        let status = get_status(state, "/healthz").await;
+
+        // Assert
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_readyz_config_available() {
+        // Arrange
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let state = get_state(Arc::new(
+            WorkspaceConfigManager::with_root(dir.path().to_path_buf())
+                .expect("Failed to create config manager"),
+        ));
+
+        // Act
+        let status = get_status(state, "/readyz").await;
+
+        // Assert
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_deploy_requires_token() {
+        // Arrange
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let state = get_state(Arc::new(
+            WorkspaceConfigManager::with_root(dir.path().to_path_buf())
+                .expect("Failed to create config manager"),
+        ));
+        let disabled_state = AppState {
+            deploy_token: None,
+            ..state.clone()
+        };
+
+        let get_deploy_status = |state: AppState, authorization: &str| {
+            router(state).oneshot(
+                Request::get("/deploy/unknown")
+                    .header("authorization", authorization)
+                    .body(Body::empty())
+                    .expect("Failed to prepare body"),
+            )
+        };
+
+        // Act
+        let missing_token_status = get_status(state.clone(), "/deploy/unknown").await;
+        let invalid_token_status = get_deploy_status(state.clone(), "Bearer wrong-token")
+            .await
+            .expect("Failed to get response")
+            .status();
+        let valid_token_status = get_deploy_status(state, "Bearer secret-token")
+            .await
+            .expect("Failed to get response")
+            .status();
+        let disabled_status = get_deploy_status(disabled_state, "Bearer secret-token")
+            .await
+            .expect("Failed to get response")
+            .status();
+
+        // Assert
+        assert_eq!(missing_token_status, StatusCode::UNAUTHORIZED);
+        assert_eq!(invalid_token_status, StatusCode::UNAUTHORIZED);
+        assert_eq!(valid_token_status, StatusCode::NOT_FOUND);
+        assert_eq!(disabled_status, StatusCode::FORBIDDEN);
+    }
+
+    async fn get_deploy_status_code(github_status: usize, github_body: &str) -> StatusCode {
+        let mut server = mockito::Server::new_async().await;
+        let _contents_mock = server
+            .mock("GET", "/repos/owner/app/contents/oct.toml")
+            .with_status(github_status)
+            .with_body(github_body)
+            .create_async()
+            .await;
+
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let state = AppState {
+            github_api_url: server.url(),
+            ..get_state(Arc::new(
+                WorkspaceConfigManager::with_root(dir.path().to_path_buf())
+                    .expect("Failed to create config manager"),
+            ))
+        };
+
+        router(state)
+            .oneshot(
+                Request::post("/deploy")
+                    .header("authorization", "Bearer secret-token")
+                    .header("content-type", "application/json")
+                    .body(Body::from(r#"{"repo": "owner/app"}"#))
+                    .expect("Failed to prepare body"),
+            )
+            .await
+            .expect("Failed to get response")
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_deploy_github_server_error() {
+        // Act
+        let status = get_deploy_status_code(500, "").await;
+
+        // Assert
+        assert_eq!(status, StatusCode::BAD_GATEWAY);
+    }
+
+    #[tokio::test]
+    async fn test_deploy_invalid_config() {
+        // Act
+        let status = get_deploy_status_code(200, "[project]\nname = \"example\"\n").await;
+
+        // Assert
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_readyz_config_unavailable() {
+        // Arrange
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let config_path = dir.path().join("oct.toml");
+        std::fs::write(&config_path, "invalid").expect("Failed to write config");
+
+        let state = get_state(Arc::new(FileConfigManager::new(
+            &config_path.to_string_lossy(),
+        )));
+
+        // Act
+        let status = get_status(state, "/readyz").await;
+
+        // Assert
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    }
+}